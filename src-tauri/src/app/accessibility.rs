@@ -0,0 +1,115 @@
+use serde::Serialize;
+
+///
+/// Accessibility preferences of the operating system, so the frontend doesn't have to probe for them itself
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessibilityInfo {
+    /// Animations are turned off in the system settings
+    #[serde(rename = "reducedMotion")]
+    pub reduced_motion: bool,
+    #[serde(rename = "highContrast")]
+    pub high_contrast: bool,
+    /// Scale of the display the launcher window is on, 1.0 is 96 dpi
+    #[serde(rename = "scaleFactor")]
+    pub scale_factor: f64,
+    /// Additional scale of text only, 1.0 if the system doesn't support it
+    #[serde(rename = "textScale")]
+    pub text_scale: f64,
+}
+
+/// Reads the accessibility preferences, preferences that can't be read fall back to their defaults
+pub async fn system_info(scale_factor: f64) -> AccessibilityInfo {
+    let mut info = AccessibilityInfo { reduced_motion: false, high_contrast: false, scale_factor, text_scale: 1.0 };
+    platform::read(&mut info).await;
+    info
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use crate::utils::user_registry_value as registry_value;
+
+    use super::AccessibilityInfo;
+
+    /// `HCF_HIGHCONTRASTON` of the high contrast flags
+    const HIGH_CONTRAST_ON: u32 = 0x1;
+
+    fn parse_number(value: &str) -> Option<u32> {
+        match value.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => value.parse().ok(),
+        }
+    }
+
+    pub async fn read(info: &mut AccessibilityInfo) {
+        if let Some(min_animate) = registry_value("Control Panel\\Desktop\\WindowMetrics", "MinAnimate").await {
+            info.reduced_motion = min_animate == "0";
+        }
+        if let Some(flags) = registry_value("Control Panel\\Accessibility\\HighContrast", "Flags").await.as_deref().and_then(parse_number) {
+            info.high_contrast = flags & HIGH_CONTRAST_ON != 0;
+        }
+        if let Some(percent) = registry_value("Software\\Microsoft\\Accessibility", "TextScaleFactor").await.as_deref().and_then(parse_number) {
+            info.text_scale = percent as f64 / 100.0;
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use tokio::process::Command;
+
+    use super::AccessibilityInfo;
+
+    async fn universal_access_enabled(name: &str) -> Option<bool> {
+        let output = Command::new("defaults").args(["read", "com.apple.universalaccess", name]).output().await.ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).trim() == "1")
+    }
+
+    pub async fn read(info: &mut AccessibilityInfo) {
+        info.reduced_motion = universal_access_enabled("reduceMotion").await.unwrap_or_default();
+        info.high_contrast = universal_access_enabled("increaseContrast").await.unwrap_or_default();
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use ashpd::desktop::settings::Settings;
+    use log::debug;
+
+    use super::AccessibilityInfo;
+
+    /// `org.freedesktop.appearance.contrast` value for higher contrast
+    const CONTRAST_HIGH: u32 = 1;
+
+    pub async fn read(info: &mut AccessibilityInfo) {
+        // the settings portal also exposes the gnome settings, which most other desktops mirror
+        let settings = match Settings::new().await {
+            Ok(settings) => settings,
+            Err(err) => {
+                debug!("Settings portal is not available: {:?}", err);
+                return;
+            }
+        };
+
+        if let Ok(enable_animations) = settings.read::<bool>("org.gnome.desktop.interface", "enable-animations").await {
+            info.reduced_motion = !enable_animations;
+        }
+        info.high_contrast = match settings.read::<u32>("org.freedesktop.appearance", "contrast").await {
+            Ok(contrast) => contrast == CONTRAST_HIGH,
+            Err(_) => settings.read::<bool>("org.gnome.desktop.a11y.interface", "high-contrast").await.unwrap_or_default(),
+        };
+        if let Ok(text_scale) = settings.read::<f64>("org.gnome.desktop.interface", "text-scaling-factor").await {
+            info.text_scale = text_scale;
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+mod platform {
+    use super::AccessibilityInfo;
+
+    pub async fn read(_info: &mut AccessibilityInfo) {}
+}
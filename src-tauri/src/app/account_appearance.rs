@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use log::info;
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::app::api::LoginData;
+use crate::app::cape_api::CapeApiEndpoints;
+use crate::utils::JsonStore;
+use crate::{HTTP_CLIENT, LAUNCHER_DIRECTORY};
+
+///
+/// Skin and cape an account should wear, re-applied when switching to the account
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AppearancePreferences {
+    /// Skin file in the local skin folder
+    #[serde(rename = "skinFile", default)]
+    pub skin_file: Option<String>,
+    #[serde(default)]
+    pub slim: bool,
+    #[serde(rename = "capeHash", default)]
+    pub cape_hash: Option<String>,
+    #[serde(rename = "applyOnSwitch", default)]
+    pub apply_on_switch: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct AppearanceStore {
+    pub accounts: HashMap<String, AppearancePreferences>,
+}
+
+impl JsonStore for AppearanceStore {
+    const FILE_NAME: &'static str = "account_appearance.json";
+}
+
+impl AppearanceStore {
+    pub fn get(&self, uuid: &str) -> AppearancePreferences {
+        self.accounts.get(uuid).cloned().unwrap_or_default()
+    }
+}
+
+/// Local skin folder, skins are copied into it so the preferences don't depend on the picked file staying where it is
+fn skins_dir() -> PathBuf {
+    LAUNCHER_DIRECTORY.config_dir().join("skins")
+}
+
+/// Stores the preferences of an account. A skin outside of the local skin folder is copied into it first.
+pub async fn set_preferences(uuid: &str, mut preferences: AppearancePreferences) -> Result<AppearancePreferences> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    if let Some(skin) = preferences.skin_file.clone() {
+        let skin = Path::new(&skin);
+        if skin.is_absolute() {
+            let file_name = format!("{}.png", uuid);
+            fs::create_dir_all(skins_dir()).await?;
+            fs::copy(skin, skins_dir().join(&file_name)).await?;
+            preferences.skin_file = Some(file_name);
+        }
+    }
+
+    let mut store = AppearanceStore::load(config_dir).await.unwrap_or_default();
+    store.accounts.insert(uuid.to_string(), preferences.clone());
+    store.store(config_dir).await?;
+    Ok(preferences)
+}
+
+/// Uploads a skin to the Minecraft profile of the access token
+pub async fn upload_skin(location: &Path, slim: bool, access_token: &str) -> Result<()> {
+    let part = Part::bytes(fs::read(location).await?)
+        .file_name("skin.png");
+
+    let response = HTTP_CLIENT.post("https://api.minecraftservices.com/minecraft/profile/skins")
+        .bearer_auth(access_token)
+        .multipart(Form::new().text("variant", if slim { "slim" } else { "classic" }).part("file", part))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        bail!("Failed to save the new skin. Status code: {}", response.status());
+    }
+    info!("Skin {:?} saved successfully.", location);
+    Ok(())
+}
+
+/// Applies the preferred skin and cape of an account, if it wants them applied on switching to it
+pub async fn apply_preferences(login_data: &LoginData, experimental_mode: bool) -> Result<()> {
+    let store = AppearanceStore::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let preferences = store.get(&login_data.uuid);
+    if !preferences.apply_on_switch {
+        return Ok(());
+    }
+
+    if let Some(skin_file) = &preferences.skin_file {
+        upload_skin(&skins_dir().join(skin_file), preferences.slim, &login_data.mc_token).await?;
+    }
+    if let Some(cape_hash) = &preferences.cape_hash {
+        let norisk_token = if experimental_mode { login_data.experimental_token.clone().unwrap_or_default() } else { login_data.norisk_token.clone() };
+        CapeApiEndpoints::equip_cape(&norisk_token, &login_data.uuid, cape_hash).await
+            .map_err(|e| anyhow::anyhow!("unable to equip cape: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use directories::BaseDirs;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+///
+/// Account of another launcher that can be imported. Its tokens were issued to the other launcher,
+/// so the frontend re-authenticates it through the regular sign-in.
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ImportableAccount {
+    /// `vanilla` or `prism`
+    pub source: String,
+    pub uuid: String,
+    pub username: String,
+}
+
+#[derive(Deserialize)]
+struct VanillaAccounts {
+    #[serde(default)]
+    accounts: HashMap<String, VanillaAccount>,
+}
+
+#[derive(Deserialize)]
+struct VanillaAccount {
+    #[serde(rename = "minecraftProfile")]
+    minecraft_profile: Option<MinecraftProfile>,
+}
+
+#[derive(Deserialize)]
+struct PrismAccounts {
+    #[serde(default)]
+    accounts: Vec<PrismAccount>,
+}
+
+#[derive(Deserialize)]
+struct PrismAccount {
+    #[serde(rename = "type")]
+    kind: String,
+    profile: Option<MinecraftProfile>,
+}
+
+#[derive(Deserialize)]
+struct MinecraftProfile {
+    id: String,
+    name: String,
+}
+
+/// Profile ids are stored without dashes by other launchers
+fn hyphenated(id: &str) -> String {
+    uuid::Uuid::parse_str(id).map(|uuid| uuid.hyphenated().to_string()).unwrap_or_else(|_| id.to_string())
+}
+
+fn vanilla_accounts_file(base_dirs: &BaseDirs) -> PathBuf {
+    #[cfg(target_os = "linux")]
+    return base_dirs.home_dir().join(".minecraft").join("launcher_accounts.json");
+    #[cfg(target_os = "macos")]
+    return base_dirs.data_dir().join("minecraft").join("launcher_accounts.json");
+    #[cfg(target_os = "windows")]
+    return base_dirs.data_dir().join(".minecraft").join("launcher_accounts.json");
+}
+
+fn prism_accounts_file(base_dirs: &BaseDirs) -> PathBuf {
+    base_dirs.data_dir().join("PrismLauncher").join("accounts.json")
+}
+
+async fn vanilla_accounts(base_dirs: &BaseDirs) -> Result<Vec<ImportableAccount>> {
+    let file = vanilla_accounts_file(base_dirs);
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+    let accounts: VanillaAccounts = serde_json::from_slice(&fs::read(file).await?)?;
+    Ok(accounts.accounts.into_values()
+        .filter_map(|account| account.minecraft_profile)
+        .map(|profile| ImportableAccount {
+            source: "vanilla".to_string(),
+            uuid: hyphenated(&profile.id),
+            username: profile.name,
+        })
+        .collect())
+}
+
+async fn prism_accounts(base_dirs: &BaseDirs) -> Result<Vec<ImportableAccount>> {
+    let file = prism_accounts_file(base_dirs);
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+    let accounts: PrismAccounts = serde_json::from_slice(&fs::read(file).await?)?;
+    Ok(accounts.accounts.into_iter()
+        .filter(|account| account.kind == "MSA")
+        .filter_map(|account| account.profile)
+        .map(|profile| ImportableAccount {
+            source: "prism".to_string(),
+            uuid: hyphenated(&profile.id),
+            username: profile.name,
+        })
+        .collect())
+}
+
+/// Accounts of the vanilla launcher and Prism Launcher, an account known to both is listed once
+pub async fn find_importable_accounts() -> Vec<ImportableAccount> {
+    let Some(base_dirs) = BaseDirs::new() else { return Vec::new() };
+
+    let mut accounts: Vec<ImportableAccount> = Vec::new();
+    for found in [prism_accounts(&base_dirs).await, vanilla_accounts(&base_dirs).await] {
+        match found {
+            Ok(found) => {
+                for account in found {
+                    if !accounts.iter().any(|known| known.uuid == account.uuid) {
+                        accounts.push(account);
+                    }
+                }
+            }
+            Err(err) => warn!("Failed to read accounts of another launcher: {:?}", err),
+        }
+    }
+    debug!("Found {} importable accounts", accounts.len());
+    accounts
+}
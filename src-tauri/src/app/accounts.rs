@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use chrono::Utc;
+use futures::future::join_all;
+use log::{debug, error};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::app::api::{is_access_denied, ApiEndpoints, LoginData};
+use crate::app::app_data::LauncherOptions;
+use crate::utils::JsonStore;
+use crate::{HTTP_CLIENT, LAUNCHER_DIRECTORY};
+
+/// Whitelist changes are rare, the account list shouldn't ask NoRisk every time it is opened
+const WHITELIST_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Avatars are downloaded again when older than this, so skin changes show up eventually
+const AVATAR_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Whitelist status of every account and when it was checked
+static WHITELIST_CACHE: Lazy<Mutex<HashMap<String, (Instant, bool)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+///
+/// Everything the account list shows about an account, without its tokens
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct AccountSummary {
+    pub uuid: String,
+    pub username: String,
+    /// Head of the skin as data url, cached on disk
+    pub avatar: Option<String>,
+    /// Unix timestamp the Minecraft token expires at
+    #[serde(rename = "tokenExpiresAt")]
+    pub token_expires_at: Option<i64>,
+    /// Unknown if NoRisk couldn't be reached
+    #[serde(rename = "noriskWhitelisted")]
+    pub norisk_whitelisted: Option<bool>,
+    /// Unix timestamp of the last launch with the account
+    #[serde(rename = "lastUsed")]
+    pub last_used: Option<i64>,
+    pub active: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AccountPage {
+    pub total: usize,
+    pub accounts: Vec<AccountSummary>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct AccountUsageStore {
+    #[serde(rename = "lastUsed")]
+    pub last_used: HashMap<String, i64>,
+}
+
+impl JsonStore for AccountUsageStore {
+    const FILE_NAME: &'static str = "account_usage.json";
+}
+
+/// Remembers that the account was just used to launch the game
+pub async fn record_usage(uuid: &str) {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut store = AccountUsageStore::load(config_dir).await.unwrap_or_default();
+    store.last_used.insert(uuid.to_string(), Utc::now().timestamp());
+    if let Err(err) = store.store(config_dir).await {
+        error!("Failed to store account usage: {:?}", err);
+    }
+}
+
+/// Reads the expiry of a JWT without verifying it
+fn token_expiry(token: &str) -> Option<i64> {
+    #[derive(Deserialize)]
+    struct Claims {
+        exp: i64,
+    }
+
+    let payload = URL_SAFE_NO_PAD.decode(token.split('.').nth(1)?.trim_end_matches('=')).ok()?;
+    serde_json::from_slice::<Claims>(&payload).ok().map(|claims| claims.exp)
+}
+
+/// Whether the account may use NoRisk, cached for a while
+async fn norisk_whitelisted(account: &LoginData, experimental_mode: bool) -> Option<bool> {
+    if let Some((checked, whitelisted)) = WHITELIST_CACHE.lock().unwrap().get(&account.uuid) {
+        if checked.elapsed() < WHITELIST_CACHE_TTL {
+            return Some(*whitelisted);
+        }
+    }
+
+    let norisk_token = if experimental_mode { account.experimental_token.clone().unwrap_or_default() } else { account.norisk_token.clone() };
+    let whitelisted = if norisk_token.is_empty() {
+        false
+    } else {
+        match ApiEndpoints::norisk_branches(&norisk_token, &account.uuid).await {
+            Ok(branches) => !branches.is_empty(),
+            Err(err) if is_access_denied(&err) => false,
+            Err(err) => {
+                debug!("Failed to check whitelist of {}: {:?}", account.uuid, err);
+                return None;
+            }
+        }
+    };
+    WHITELIST_CACHE.lock().unwrap().insert(account.uuid.clone(), (Instant::now(), whitelisted));
+    Some(whitelisted)
+}
+
+/// Cached avatar as data url, downloading it when it is missing or outdated
+async fn avatar(uuid: &str) -> Option<String> {
+    let path = cached_avatar(uuid).await?;
+    let data = fs::read(path).await.ok()?;
+    Some(format!("data:image/png;base64,{}", STANDARD.encode(data)))
+}
+
+async fn cached_avatar(uuid: &str) -> Option<PathBuf> {
+    let path = LAUNCHER_DIRECTORY.config_dir().join("avatars").join(format!("{}.png", uuid));
+    let age = fs::metadata(&path).await.ok()
+        .and_then(|metadata| metadata.modified().ok())
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    if age.map_or(false, |age| age < AVATAR_MAX_AGE) {
+        return Some(path);
+    }
+
+    let download = async {
+        let bytes = HTTP_CLIENT.get(format!("https://mineskin.eu/helm/{}/100.png", uuid))
+            .send().await?
+            .error_for_status()?
+            .bytes().await?;
+        fs::create_dir_all(path.parent().unwrap()).await?;
+        fs::write(&path, bytes).await?;
+        Ok::<(), anyhow::Error>(())
+    };
+    match download.await {
+        Ok(()) => Some(path),
+        Err(err) => {
+            debug!("Failed to download avatar of {}: {:?}", uuid, err);
+            // an outdated avatar is better than none
+            age.map(|_| path)
+        }
+    }
+}
+
+/// Lists a page of the accounts with their metadata, the accounts of a page are resolved in parallel
+pub async fn list(options: &LauncherOptions, offset: usize, limit: usize) -> AccountPage {
+    let usage = AccountUsageStore::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let accounts = join_all(options.accounts.iter().skip(offset).take(limit).map(|account| {
+        let last_used = usage.last_used.get(&account.uuid).copied();
+        async move {
+            let (avatar, norisk_whitelisted) = tokio::join!(avatar(&account.uuid), norisk_whitelisted(account, options.experimental_mode));
+            AccountSummary {
+                uuid: account.uuid.clone(),
+                username: account.username.clone(),
+                avatar,
+                token_expires_at: token_expiry(&account.mc_token),
+                norisk_whitelisted,
+                last_used,
+                active: options.current_uuid.as_ref() == Some(&account.uuid),
+            }
+        }
+    })).await;
+
+    AccountPage { total: options.accounts.len(), accounts }
+}
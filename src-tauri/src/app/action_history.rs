@@ -0,0 +1,239 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use log::{debug, info, warn};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::fs;
+
+use crate::app::app_data::LauncherProfile;
+
+/// Actions that can be undone, older ones are forgotten
+const MAX_ACTIONS: usize = 50;
+
+/// Deleted files are kept here until the launcher starts the next time
+const TRASH_FOLDER: &str = "trash";
+
+/// Fields of a profile that change without the user changing the profile, they are never undone
+const UNTRACKED_FIELDS: [&str; 2] = ["revision", "lastPlayed"];
+
+#[derive(Clone, Debug)]
+pub enum ReversibleAction {
+    /// The fields of a profile the change touched, the rest of the profile is left alone by the undo
+    ProfileChanged { profile_id: String, changes: Vec<FieldChange> },
+    /// A file that was moved to the trash instead of being deleted
+    FileTrashed { original: PathBuf, trashed: PathBuf },
+}
+
+///
+/// Action as listed by `get_action_history`
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ActionSummary {
+    pub id: String,
+    pub description: String,
+    /// `profile` or `file`
+    pub kind: String,
+    /// Unix timestamp in milliseconds
+    pub time: i64,
+}
+
+///
+/// A field of a profile as it was before and after a change, `null` if it wasn't there
+///
+#[derive(Clone, Debug)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedAction {
+    pub summary: ActionSummary,
+    pub action: ReversibleAction,
+}
+
+///
+/// Reversible operations of this launcher session, newest last
+///
+#[derive(Default)]
+pub struct ActionHistory {
+    actions: Mutex<Vec<RecordedAction>>,
+}
+
+impl ActionHistory {
+    pub fn record(&self, description: String, action: ReversibleAction) {
+        let kind = match &action {
+            ReversibleAction::ProfileChanged { .. } => "profile",
+            ReversibleAction::FileTrashed { .. } => "file",
+        };
+        let summary = ActionSummary {
+            id: uuid::Uuid::new_v4().to_string(),
+            description,
+            kind: kind.to_string(),
+            time: Utc::now().timestamp_millis(),
+        };
+        debug!("Recorded action {}", summary.description);
+
+        let mut actions = self.actions.lock().unwrap();
+        actions.push(RecordedAction { summary, action });
+        if actions.len() > MAX_ACTIONS {
+            actions.remove(0);
+        }
+    }
+
+    /// The action an undo reverts, it stays recorded until `remove` is called after the undo
+    pub fn last(&self) -> Option<RecordedAction> {
+        self.actions.lock().unwrap().last().cloned()
+    }
+
+    pub fn remove(&self, id: &str) {
+        self.actions.lock().unwrap().retain(|action| action.summary.id != id);
+    }
+
+    /// Newest first
+    pub fn list(&self) -> Vec<ActionSummary> {
+        self.actions.lock().unwrap().iter().rev().map(|action| action.summary.clone()).collect()
+    }
+}
+
+fn profile_fields(profile: &LauncherProfile) -> Result<serde_json::Map<String, Value>> {
+    match serde_json::to_value(profile)? {
+        Value::Object(mut fields) => {
+            for field in UNTRACKED_FIELDS {
+                fields.remove(field);
+            }
+            Ok(fields)
+        }
+        _ => bail!("profile {} is not an object", profile.id),
+    }
+}
+
+/// Fields that differ between a profile before and after a change
+pub fn profile_changes(before: &LauncherProfile, after: &LauncherProfile) -> Result<Vec<FieldChange>> {
+    let before = profile_fields(before)?;
+    let after = profile_fields(after)?;
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    Ok(fields.into_iter()
+        .filter(|field| before.get(*field) != after.get(*field))
+        .map(|field| FieldChange {
+            field: field.clone(),
+            before: before.get(field).cloned().unwrap_or(Value::Null),
+            after: after.get(field).cloned().unwrap_or(Value::Null),
+        })
+        .collect())
+}
+
+/// Sets the changed fields back to their values before the change.
+/// Fails without touching the profile if one of them was changed again since, the undo would lose that change.
+pub fn revert_changes(profile: &mut LauncherProfile, changes: &[FieldChange]) -> Result<()> {
+    let mut fields = profile_fields(profile)?;
+    for change in changes {
+        if fields.get(&change.field).unwrap_or(&Value::Null) != &change.after {
+            bail!("{} of profile {} was changed again", change.field, profile.id);
+        }
+        if change.before.is_null() {
+            fields.remove(&change.field);
+        } else {
+            fields.insert(change.field.clone(), change.before.clone());
+        }
+    }
+
+    let revision = profile.revision;
+    let last_played = profile.last_played;
+    *profile = serde_json::from_value(Value::Object(fields))?;
+    profile.revision = revision;
+    profile.last_played = last_played;
+    Ok(())
+}
+
+/// Moves a file into the trash folder of the data dir, only files inside the data dir can be trashed
+pub async fn trash_file(data_path: &Path, path: &Path) -> Result<ReversibleAction> {
+    let path = path.canonicalize()?;
+    if !path.starts_with(data_path.canonicalize()?) {
+        bail!("{:?} is not part of the launcher data", path);
+    }
+    if !path.is_file() {
+        bail!("{:?} is not a file", path);
+    }
+
+    let trash_dir = data_path.join(TRASH_FOLDER).join(uuid::Uuid::new_v4().to_string());
+    fs::create_dir_all(&trash_dir).await?;
+    let trashed = trash_dir.join(path.file_name().unwrap_or_default());
+    fs::rename(&path, &trashed).await?;
+    info!("Moved {:?} to the trash", path);
+    Ok(ReversibleAction::FileTrashed { original: path, trashed })
+}
+
+/// Moves a trashed file back, unless a file of the same name took its place
+pub async fn restore_file(original: &Path, trashed: &Path) -> Result<()> {
+    if original.exists() {
+        bail!("{:?} exists again", original);
+    }
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs::rename(trashed, original).await?;
+    if let Some(trash_dir) = trashed.parent() {
+        let _ = fs::remove_dir(trash_dir).await;
+    }
+    info!("Restored {:?} from the trash", original);
+    Ok(())
+}
+
+/// The history only lives as long as the launcher, so files trashed by earlier sessions can't be restored anymore
+pub async fn empty_trash(data_path: &Path) {
+    let trash = data_path.join(TRASH_FOLDER);
+    if trash.exists() {
+        if let Err(err) = fs::remove_dir_all(&trash).await {
+            warn!("Failed to empty the trash: {:?}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn profile() -> LauncherProfile {
+        serde_json::from_value(json!({ "id": "profile", "branch": "prod", "name": "Profile", "mods": [], "notes": "before" })).unwrap()
+    }
+
+    #[test]
+    fn undo_only_reverts_the_recorded_fields() {
+        let before = profile();
+        let mut after = before.clone();
+        after.notes = "after".to_string();
+        after.revision = 1;
+        let changes = profile_changes(&before, &after).unwrap();
+        assert_eq!(changes.iter().map(|change| change.field.as_str()).collect::<Vec<_>>(), ["notes"]);
+
+        // changed later without being recorded
+        after.tags = vec!["later".to_string()];
+        after.revision = 2;
+        revert_changes(&mut after, &changes).unwrap();
+        assert_eq!(after.notes, "before");
+        assert_eq!(after.tags, ["later"]);
+        assert_eq!(after.revision, 2);
+    }
+
+    #[test]
+    fn undo_refuses_fields_changed_again() {
+        let before = profile();
+        let mut after = before.clone();
+        after.notes = "after".to_string();
+        let changes = profile_changes(&before, &after).unwrap();
+
+        after.notes = "again".to_string();
+        assert!(revert_changes(&mut after, &changes).is_err());
+        assert_eq!(after.notes, "again");
+    }
+}
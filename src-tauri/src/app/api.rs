@@ -1,5 +1,9 @@
 use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::RwLock;
 use anyhow::Result;
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde::de::DeserializeOwned;
@@ -8,6 +12,8 @@ use log::{debug, info};
 use crate::custom_servers::models::CustomServer;
 use crate::{HTTP_CLIENT, LAUNCHER_DIRECTORY};
 use crate::app::app_data::LauncherOptions;
+use crate::app::content_check::ContentType;
+use crate::app::modrinth_api::ModrinthApiEndpoints;
 use crate::minecraft::version::AssetObject;
 use crate::utils::get_maven_artifact_path;
 
@@ -17,6 +23,10 @@ pub const CONTENT_FOLDER: &str = "NoRiskClient";
 pub struct ApiEndpoints;
 
 pub fn get_api_base(is_experimental: bool) -> String {
+    #[cfg(feature = "testing")]
+    if let Some(base) = crate::testing::api_base_override() {
+        return format!("{}/norisk/api/v1", base);
+    }
     return if is_experimental {
         String::from("https://api-staging.norisk.gg/api/v1")
     } else {
@@ -65,11 +75,26 @@ impl ApiEndpoints {
         Self::request_from_norisk_endpoint(&*format!("launcher/featured/{}/datapacks", branch), "", "").await
     }
     
+    /// Request mod versions that are known to be broken or malicious
+    pub async fn norisk_content_blacklist() -> Result<Vec<BlockedContent>> {
+        Self::request_from_norisk_endpoint("launcher/content-blacklist", "", "").await
+    }
+
+    /// Request the latest translations of a language
+    pub async fn norisk_translations(language: &str) -> Result<HashMap<String, String>> {
+        Self::request_from_norisk_endpoint(&*format!("launcher/translations/{}", language), "", "").await
+    }
+
     /// Request featured servers
     pub async fn norisk_featured_servers(branch: &str) -> Result<Vec<FeaturedServer>> {
         Self::request_from_norisk_endpoint(&*format!("launcher/featured/{}/servers", branch), "", "").await
     }
     
+    /// Request the signed launch hotfixes of a branch
+    pub async fn norisk_launch_hotfixes(branch: &str) -> Result<String> {
+        Self::request_from_norisk_endpoint(&*format!("launcher/hotfixes/{}", branch), "", "").await
+    }
+
     /// Request custom servers
     pub async fn norisk_custom_servers(token: &str, request_uuid: &str) -> Result<CustomServersResponse> {
         Self::request_from_norisk_endpoint("launcher/custom-servers", token, request_uuid).await
@@ -124,11 +149,6 @@ impl ApiEndpoints {
         Self::request_from_norisk_endpoint(&format!("launcher/version/jre/{}/{}/{}", os_name, os_arch, jre_version), "", "").await
     }
 
-    /// Request norisk assets json for specific branch
-    pub async fn norisk_assets(branch: String, norisk_token: &str, request_uuid: &str) -> Result<NoriskAssets> {
-        Self::request_from_norisk_endpoint(&format!("launcher/assets/{}", branch), norisk_token, request_uuid).await
-    }
-    
     /// Request mcreal app token
     pub async fn get_mcreal_app_token(norisk_token: &str, request_uuid: &str) -> Result<String> {
         Self::request_from_norisk_endpoint("mcreal/user/mobileAppToken", norisk_token, request_uuid).await
@@ -274,6 +294,83 @@ pub struct Changelog {
     pub changelog: String,
 }
 
+///
+/// A Minecraft version the launch card offers, e.g. 1.21, with the branches launching it as variants
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StandardVersion {
+    pub id: String,
+    pub name: String,
+    pub variants: Vec<StandardVersionVariant>,
+}
+
+///
+/// One flavor of a standard version, like Fabric or Forge, launched through its own branch
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StandardVersionVariant {
+    pub id: String,
+    pub name: String,
+    pub branch: String,
+    #[serde(rename = "mcVersion", default)]
+    pub mc_version: Option<String>,
+    #[serde(default)]
+    pub loader: Option<String>,
+    /// Version of the loader the branch launches
+    #[serde(rename = "loaderVersion", default)]
+    pub loader_version: Option<String>,
+    #[serde(default)]
+    pub default: bool,
+}
+
+impl StandardVersion {
+    /// Groups the branches by the Minecraft version of their launch manifest, in the order of the branches.
+    /// Each branch is a variant with the branch as its id, the first one of a version is its default.
+    /// A branch without a launch manifest becomes a version of its own.
+    pub fn from_branches(branches: Vec<(String, Option<NoRiskLaunchManifest>)>) -> Vec<StandardVersion> {
+        let mut versions: Vec<StandardVersion> = Vec::new();
+        for (branch, launch_manifest) in branches {
+            let mc_version = launch_manifest.as_ref().map(|launch_manifest| launch_manifest.build.mc_version.clone());
+            let version_id = mc_version.clone().unwrap_or_else(|| branch.clone());
+            let variant = StandardVersionVariant {
+                id: branch.clone(),
+                name: branch.clone(),
+                branch,
+                mc_version,
+                loader: launch_manifest.as_ref().map(|launch_manifest| launch_manifest.subsystem.loader().to_string()),
+                loader_version: launch_manifest.as_ref().map(|launch_manifest| launch_manifest.build.fabric_loader_version.clone()),
+                default: false,
+            };
+
+            match versions.iter_mut().find(|version| version.id == version_id) {
+                Some(version) => version.variants.push(variant),
+                None => versions.push(StandardVersion {
+                    id: version_id.clone(),
+                    name: version_id,
+                    variants: vec![StandardVersionVariant { default: true, ..variant }],
+                }),
+            }
+        }
+        versions
+    }
+}
+
+///
+/// Mod version blocked by NoRisk, matched by the sha1 of its file or by project and (optionally) version
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockedContent {
+    #[serde(default)]
+    pub sha1: Option<String>,
+    /// Modrinth project id or slug
+    #[serde(rename = "projectId", default)]
+    pub project_id: Option<String>,
+    /// Modrinth version id or version number, every version of the project is blocked if missing
+    #[serde(rename = "versionId", default)]
+    pub version_id: Option<String>,
+    pub reason: String,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct FeaturedServer {
     pub name: String,
@@ -345,7 +442,43 @@ pub struct LoginData {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LoginDataMinimal {
     pub uuid: String,
-    pub username: String
+    pub username: String,
+    // tokens written to options.json by older launchers, only read to migrate them into the secret store
+    #[serde(rename = "mcToken", default, skip_serializing)]
+    legacy_mc_token: Option<String>,
+    #[serde(rename = "accessToken", default, skip_serializing)]
+    legacy_access_token: Option<String>,
+    #[serde(rename = "refreshToken", default, skip_serializing)]
+    legacy_refresh_token: Option<String>,
+    #[serde(rename = "noriskToken", default, skip_serializing)]
+    legacy_norisk_token: Option<String>,
+    #[serde(rename = "experimentalToken", default, skip_serializing)]
+    legacy_experimental_token: Option<String>,
+}
+
+impl LoginDataMinimal {
+    pub fn new(uuid: String, username: String) -> Self {
+        LoginDataMinimal {
+            uuid,
+            username,
+            legacy_mc_token: None,
+            legacy_access_token: None,
+            legacy_refresh_token: None,
+            legacy_norisk_token: None,
+            legacy_experimental_token: None,
+        }
+    }
+
+    /// Legacy tokens in the order mc, access, refresh, norisk, experimental token
+    pub fn legacy_tokens(&self) -> [Option<&String>; 5] {
+        [
+            self.legacy_mc_token.as_ref(),
+            self.legacy_access_token.as_ref(),
+            self.legacy_refresh_token.as_ref(),
+            self.legacy_norisk_token.as_ref(),
+            self.legacy_experimental_token.as_ref(),
+        ]
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -382,6 +515,120 @@ impl LoginData {
     }
 }
 
+/// Whether a request failed because the NoRisk token was rejected
+pub fn is_unauthorized(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>().and_then(reqwest::Error::status) == Some(StatusCode::UNAUTHORIZED)
+}
+
+/// Whether a request failed because NoRisk couldn't be reached at all, not because it answered with an error
+pub fn is_unreachable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<reqwest::Error>().map_or(false, |err| err.is_connect() || err.is_timeout())
+}
+
+/// Whether NoRisk refused a request, because the token was rejected or the account may not access the content
+pub fn is_access_denied(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref::<reqwest::Error>().and_then(reqwest::Error::status), Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN))
+}
+
+///
+/// Result of checking before a launch whether the NoRisk pack of a branch can be downloaded
+///
+#[derive(Debug, Serialize)]
+#[serde(tag = "status")]
+pub enum NoRiskAccessCheck {
+    #[serde(rename = "granted")]
+    Granted,
+    /// The account has no valid NoRisk token or isn't whitelisted, the launch can only continue without the pack
+    #[serde(rename = "denied")]
+    Denied { reason: String },
+    /// NoRisk couldn't be reached, the launch falls back to the installed pack
+    #[serde(rename = "unavailable")]
+    Unavailable { reason: String },
+}
+
+///
+/// NoRisk token shared by parallel requests of a launch. When the token expires, the first request that notices it
+/// refreshes the account and every other request continues with the new token.
+///
+pub struct SharedNoRiskToken {
+    token: RwLock<String>,
+    uuid: String,
+    experimental: bool,
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl SharedNoRiskToken {
+    pub fn new(norisk_token: &str, uuid: &str, experimental: bool) -> Self {
+        SharedNoRiskToken {
+            token: RwLock::new(norisk_token.to_string()),
+            uuid: uuid.to_string(),
+            experimental,
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn current(&self) -> String {
+        self.token.read().unwrap().clone()
+    }
+
+    pub fn uuid(&self) -> &str {
+        &self.uuid
+    }
+
+    pub fn is_experimental(&self) -> bool {
+        self.experimental
+    }
+
+    /// Refreshes the account after `rejected` was rejected, unless another request already did
+    pub async fn refresh(&self, rejected: &str) -> Result<String> {
+        let _refreshing = self.refreshing.lock().await;
+        let current = self.current();
+        if current != rejected {
+            return Ok(current);
+        }
+
+        info!("NoRisk token expired, refreshing account {}", self.uuid);
+        let mut options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await?;
+        let account = options.accounts.iter_mut().find(|account| account.uuid == self.uuid)
+            .ok_or_else(|| anyhow::anyhow!("account {} not found", self.uuid))?;
+        *account = account.clone().refresh_maybe_fixed().await?;
+        let token = if self.experimental { account.experimental_token.clone().unwrap_or_default() } else { account.norisk_token.clone() };
+        options.store(LAUNCHER_DIRECTORY.config_dir()).await?;
+
+        *self.token.write().unwrap() = token.clone();
+        Ok(token)
+    }
+
+    /// Requests a NoRisk endpoint, refreshing the token once if it was rejected
+    pub async fn request<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        let norisk_token = self.current();
+        match ApiEndpoints::request_from_norisk_endpoint(endpoint, &norisk_token, &self.uuid).await {
+            Err(err) if is_unauthorized(&err) => {
+                let norisk_token = self.refresh(&norisk_token).await?;
+                ApiEndpoints::request_from_norisk_endpoint(endpoint, &norisk_token, &self.uuid).await
+            }
+            result => result,
+        }
+    }
+
+    /// Request norisk assets json for specific branch
+    pub async fn norisk_assets(&self, branch: &str) -> Result<NoriskAssets> {
+        self.request(&format!("launcher/assets/{}", branch)).await
+    }
+
+    /// Checks whether the NoRisk assets of a branch can be requested with this token, refreshing it once if needed
+    pub async fn check_assets_access(&self, branch: &str) -> NoRiskAccessCheck {
+        if self.current().is_empty() {
+            return NoRiskAccessCheck::Denied { reason: "not logged in to NoRisk".to_string() };
+        }
+        match self.norisk_assets(branch).await {
+            Ok(_) => NoRiskAccessCheck::Granted,
+            Err(err) if is_access_denied(&err) => NoRiskAccessCheck::Denied { reason: err.to_string() },
+            Err(err) => NoRiskAccessCheck::Unavailable { reason: err.to_string() },
+        }
+    }
+}
+
 ///
 /// JSON struct of Build
 ///
@@ -474,6 +721,52 @@ pub struct LoaderMod {
     pub enabled: bool,
     pub name: String,
     pub source: ModSource,
+    /// Pack metadata explaining why the mod is part of the pack, only provided by norisk launch manifests
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub recommended: bool,
+    #[serde(rename = "sourceUrl", default)]
+    pub source_url: Option<String>,
+    /// Which versions the update check may suggest for this mod
+    #[serde(rename = "updateChannel", default)]
+    pub update_channel: UpdateChannel,
+    /// Sha1 of the mod file if the manifest or the provider knows it, a cached file with the same hash is reused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+}
+
+///
+/// Modrinth version types a mod accepts as update
+///
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum UpdateChannel {
+    #[serde(rename = "release")]
+    Release,
+    #[serde(rename = "beta")]
+    Beta,
+    /// Keeps the installed version, no updates are suggested
+    #[serde(rename = "pinned")]
+    Pinned,
+}
+
+impl Default for UpdateChannel {
+    fn default() -> Self {
+        UpdateChannel::Release
+    }
+}
+
+impl UpdateChannel {
+    /// Whether a version of the given modrinth `version_type` may be suggested
+    pub fn allows(&self, version_type: &str) -> bool {
+        match self {
+            UpdateChannel::Release => version_type == "release",
+            UpdateChannel::Beta => version_type == "release" || version_type == "beta",
+            UpdateChannel::Pinned => false,
+        }
+    }
 }
 
 impl LoaderMod {
@@ -520,6 +813,19 @@ impl ModSource {
         }
     }
 
+    pub fn get_version(&self) -> String {
+        match self {
+            ModSource::Repository { repository: _repository, artifact, url: _ } => {
+                let parts: Vec<&str> = artifact.split(":").collect();
+                if parts.len() > 2 {
+                    parts[2].to_string()
+                } else {
+                    "".to_string()
+                }
+            }
+        }
+    }
+
     pub fn get_path(&self) -> Result<String> {
         Ok(
             match self {
@@ -541,6 +847,16 @@ pub enum LoaderSubsystem {
     Forge { manifest: String, mod_directory: String },
 }
 
+impl LoaderSubsystem {
+    /// Name of the mod loader as used by modrinth
+    pub fn loader(&self) -> &'static str {
+        match self {
+            LoaderSubsystem::Fabric { .. } => "fabric",
+            LoaderSubsystem::Forge { .. } => "forge",
+        }
+    }
+}
+
 ///
 /// JSON struct of JRE source
 ///
@@ -567,4 +883,217 @@ pub struct WhitelistSlots {
     pub available_slots: i32,
     #[serde(rename = "previousInvites")]
     pub previous_invites: u32,
-}
\ No newline at end of file
+}
+
+///
+/// Search for contents of one type, optionally limited to a game version and loader
+///
+#[derive(Clone, Debug, Deserialize)]
+pub struct ContentSearch {
+    pub query: String,
+    #[serde(rename = "contentType")]
+    pub content_type: ContentType,
+    #[serde(rename = "gameVersion", default)]
+    pub game_version: Option<String>,
+    #[serde(default)]
+    pub loader: Option<String>,
+    pub limit: u32,
+    #[serde(default)]
+    pub offset: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ContentSearchResult {
+    #[serde(rename = "totalHits")]
+    pub total_hits: u32,
+    pub hits: Vec<ContentProject>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ContentProject {
+    pub id: String,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub author: Option<String>,
+    #[serde(rename = "iconUrl")]
+    pub icon_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ContentVersion {
+    pub id: String,
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub name: String,
+    #[serde(rename = "versionNumber")]
+    pub version_number: String,
+    #[serde(rename = "gameVersions")]
+    pub game_versions: Vec<String>,
+    pub loaders: Vec<String>,
+    pub files: Vec<ContentFile>,
+    /// Projects this version can't run without
+    #[serde(rename = "requiredProjects")]
+    pub required_projects: Vec<String>,
+}
+
+impl ContentVersion {
+    /// The file to install, providers may list additional files like sources
+    pub fn primary_file(&self) -> Option<&ContentFile> {
+        self.files.iter().find(|file| file.primary).or_else(|| self.files.first())
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ContentFile {
+    pub url: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub sha1: Option<String>,
+    pub size: Option<u64>,
+    pub primary: bool,
+}
+
+///
+/// Filters of the modpack browser, every filter left empty matches all modpacks
+///
+#[derive(Debug, Deserialize)]
+pub struct ModpackSearchFilters {
+    #[serde(default)]
+    pub query: String,
+    #[serde(rename = "gameVersion", default)]
+    pub game_version: Option<String>,
+    /// Like `fabric` or `forge`
+    #[serde(default)]
+    pub loader: Option<String>,
+    /// Largest size of the modpack file in bytes
+    #[serde(rename = "maxPackSize", default)]
+    pub max_pack_size: Option<u64>,
+    /// Sort order, `relevance`, `downloads`, `follows`, `newest` or `updated`. Featured lists sort by `follows`.
+    #[serde(default)]
+    pub index: Option<String>,
+    pub limit: u32,
+    #[serde(default)]
+    pub offset: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModpackSearchResult {
+    pub hits: Vec<ModpackSummary>,
+    pub offset: u32,
+    /// Offset the next page starts at, further than `offset + limit` when hits were filtered out
+    #[serde(rename = "nextOffset")]
+    pub next_offset: u32,
+    pub limit: u32,
+    #[serde(rename = "totalHits")]
+    pub total_hits: u32,
+}
+
+///
+/// A modpack as shown in the list of the modpack browser
+///
+#[derive(Debug, Serialize)]
+pub struct ModpackSummary {
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    pub author: Option<String>,
+    #[serde(rename = "iconUrl")]
+    pub icon_url: Option<String>,
+    pub downloads: u64,
+    pub follows: u64,
+    /// Loaders and categories of the modpack
+    pub categories: Vec<String>,
+    #[serde(rename = "gameVersions")]
+    pub game_versions: Vec<String>,
+    #[serde(rename = "latestVersionId")]
+    pub latest_version_id: Option<String>,
+    /// Size of the latest modpack file in bytes, only known when filtered by size
+    #[serde(rename = "packSize")]
+    pub pack_size: Option<u64>,
+}
+
+///
+/// Everything the details page of the modpack browser shows. A version can be installed by downloading its file
+/// and handing it to `import_mrpack_as_profile` together with the project and version id.
+///
+#[derive(Debug, Serialize)]
+pub struct ModpackDetails {
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub slug: String,
+    pub title: String,
+    pub description: String,
+    /// Markdown description
+    pub body: String,
+    #[serde(rename = "iconUrl")]
+    pub icon_url: Option<String>,
+    pub downloads: u64,
+    pub follows: u64,
+    pub categories: Vec<String>,
+    #[serde(rename = "gameVersions")]
+    pub game_versions: Vec<String>,
+    pub loaders: Vec<String>,
+    /// Image urls
+    pub gallery: Vec<String>,
+    pub versions: Vec<ModpackVersionSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModpackVersionSummary {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "versionNumber")]
+    pub version_number: String,
+    /// `release`, `beta` or `alpha`
+    #[serde(rename = "versionType")]
+    pub version_type: String,
+    #[serde(rename = "datePublished")]
+    pub date_published: String,
+    #[serde(rename = "gameVersions")]
+    pub game_versions: Vec<String>,
+    pub loaders: Vec<String>,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    pub url: String,
+    /// Size of the modpack file in bytes
+    #[serde(rename = "packSize")]
+    pub pack_size: u64,
+}
+
+///
+/// A platform contents can be installed from. Everything the launcher needs to know about a platform
+/// is behind this trait, so new platforms don't need changes to the install code or the commands.
+///
+pub trait ContentProvider: Send + Sync {
+    /// Name of the provider as used by the frontend and in the repository of installed mods
+    fn id(&self) -> &'static str;
+
+    fn search<'a>(&'a self, search: &'a ContentSearch) -> BoxFuture<'a, Result<ContentSearchResult>>;
+
+    /// Versions of a project, newest first
+    fn get_versions<'a>(&'a self, project: &'a str, game_version: Option<&'a str>, loader: Option<&'a str>) -> BoxFuture<'a, Result<Vec<ContentVersion>>>;
+
+    /// Finds the version a file belongs to by its sha1, `None` if the provider doesn't know the file
+    fn resolve_by_hash<'a>(&'a self, sha1: &'a str) -> BoxFuture<'a, Result<Option<ContentVersion>>>;
+
+    /// Downloads a file of a version to `target`, verifying its hash if the provider knows it
+    fn download<'a>(&'a self, file: &'a ContentFile, target: &'a Path) -> BoxFuture<'a, Result<()>>;
+
+    /// Searches modpacks for the modpack browser
+    fn search_modpacks<'a>(&'a self, filters: &'a ModpackSearchFilters) -> BoxFuture<'a, Result<ModpackSearchResult>>;
+
+    /// Loads a modpack with all of its versions, newest first
+    fn get_modpack_details<'a>(&'a self, project: &'a str) -> BoxFuture<'a, Result<ModpackDetails>>;
+}
+
+/// All registered content providers
+pub fn content_providers() -> [&'static dyn ContentProvider; 1] {
+    [&ModrinthApiEndpoints]
+}
+
+pub fn content_provider(id: &str) -> Option<&'static dyn ContentProvider> {
+    content_providers().into_iter().find(|provider| provider.id() == id)
+}
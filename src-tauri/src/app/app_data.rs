@@ -3,12 +3,18 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::vec;
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
-use keyring::Entry as KeyringEntry;
+use log::{error, info};
 
-use crate::app::api::{LoginData, LoginDataMinimal};
+use crate::app::api::{LoginData, LoginDataMinimal, StandardVersion, StandardVersionVariant};
+use crate::app::notifications::NotificationCategory;
+use crate::app::profile_migrations;
+use crate::launcher_directory::overridden_data_dir;
+use crate::minecraft::auth::secret_store;
 use crate::LAUNCHER_DIRECTORY;
 
 use super::modrinth_api::CustomMod;
@@ -20,6 +26,260 @@ fn default_concurrent_downloads() -> i32 {
     10
 }
 
+fn default_preferred_gpu() -> String {
+    "DEFAULT".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_install_concurrency() -> u32 {
+    1
+}
+
+///
+/// Categories of native notifications the user wants to receive
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    #[serde(default = "default_true")]
+    pub operations: bool,
+    #[serde(default = "default_true")]
+    pub crashes: bool,
+    #[serde(default = "default_true")]
+    pub updates: bool,
+    #[serde(default = "default_true")]
+    pub capes: bool,
+}
+
+impl NotificationSettings {
+    pub fn is_enabled(&self, category: NotificationCategory) -> bool {
+        match category {
+            NotificationCategory::Operations => self.operations,
+            NotificationCategory::Crashes => self.crashes,
+            NotificationCategory::Updates => self.updates,
+            NotificationCategory::Capes => self.capes,
+        }
+    }
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            operations: true,
+            crashes: true,
+            updates: true,
+            capes: true,
+        }
+    }
+}
+
+///
+/// What is removed from game output before it is shown or uploaded
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogPrivacySettings {
+    /// Access tokens, session ids and the tokens of the launching account
+    #[serde(rename = "redactTokens", default = "default_true")]
+    pub redact_tokens: bool,
+    #[serde(rename = "redactIpAddresses", default)]
+    pub redact_ip_addresses: bool,
+    /// The user name in home folder paths
+    #[serde(rename = "redactUsernames", default = "default_true")]
+    pub redact_usernames: bool,
+}
+
+impl Default for LogPrivacySettings {
+    fn default() -> Self {
+        Self {
+            redact_tokens: true,
+            redact_ip_addresses: false,
+            redact_usernames: true,
+        }
+    }
+}
+
+fn default_daily_upload_cap() -> u32 {
+    5
+}
+
+///
+/// Uploading the logs to mclo.gs when the game crashes
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CrashUploadSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Crashes per day whose logs are uploaded, a crash loop shouldn't upload dozens of logs
+    #[serde(rename = "dailyCap", default = "default_daily_upload_cap")]
+    pub daily_cap: u32,
+}
+
+impl Default for CrashUploadSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            daily_cap: default_daily_upload_cap(),
+        }
+    }
+}
+
+///
+/// Argon2 hash of a PIN in PHC format, contains its salt. Used by the restricted mode and the settings lock.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PinHash(String);
+
+impl PinHash {
+    pub fn new(pin: &str) -> Result<Self> {
+        if pin.len() < 4 {
+            bail!("the PIN needs at least 4 characters");
+        }
+        let salt = SaltString::encode_b64(uuid::Uuid::new_v4().as_bytes()).map_err(|err| anyhow!("unable to create salt: {}", err))?;
+        let hash = Argon2::default().hash_password(pin.as_bytes(), &salt)
+            .map_err(|err| anyhow!("unable to hash PIN: {}", err))?;
+        Ok(PinHash(hash.to_string()))
+    }
+
+    pub fn verify(&self, pin: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(&self.0) else { return false };
+        Argon2::default().verify_password(pin.as_bytes(), &hash).is_ok()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+///
+/// Restricted mode for players on servers with strict mod rules or children, only the mods of the NoRisk packs can be used
+/// while it is enabled. It can only be turned off again with the PIN it was enabled with.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RestrictedMode {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(rename = "pinHash", default, skip_serializing_if = "PinHash::is_empty")]
+    pin_hash: PinHash,
+}
+
+impl RestrictedMode {
+    pub fn enable(&mut self, pin: &str) -> Result<()> {
+        if self.enabled {
+            bail!("restricted mode is already enabled");
+        }
+        self.pin_hash = PinHash::new(pin)?;
+        self.enabled = true;
+        Ok(())
+    }
+
+    pub fn disable(&mut self, pin: &str) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !self.pin_hash.verify(pin) {
+            bail!("wrong PIN");
+        }
+        *self = RestrictedMode::default();
+        Ok(())
+    }
+}
+
+///
+/// Which Minecraft versions featured mods may be offered for when they don't support the exact version yet
+///
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum ModVersionFallback {
+    /// Only mods that support the exact version, the fallback has to be enabled in the settings
+    #[default]
+    #[serde(rename = "exact")]
+    Exact,
+    /// The nearest supported version of the same major version, like 1.21.1 for 1.21.3
+    #[serde(rename = "nearestInMajor")]
+    NearestInMajor,
+}
+
+///
+/// How many of the existing Minecraft assets are hashed before a launch, missing assets are always downloaded
+///
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AssetVerificationMode {
+    /// Only checks that the assets exist
+    #[serde(rename = "none")]
+    None,
+    /// Hashes a random share of the assets, all of them once one doesn't match
+    #[serde(rename = "sample")]
+    Sample,
+    /// Hashes every asset
+    #[serde(rename = "full")]
+    Full,
+}
+
+impl Default for AssetVerificationMode {
+    fn default() -> Self {
+        AssetVerificationMode::Sample
+    }
+}
+
+fn default_asset_sample_percent() -> u8 {
+    5
+}
+
+///
+/// Verification of the Minecraft assets on every launch, see `AssetVerificationMode`
+///
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct AssetVerification {
+    #[serde(default)]
+    pub mode: AssetVerificationMode,
+    /// Share of the assets hashed in sample mode, 1 to 100
+    #[serde(rename = "samplePercent", default = "default_asset_sample_percent")]
+    pub sample_percent: u8,
+}
+
+impl Default for AssetVerification {
+    fn default() -> Self {
+        Self {
+            mode: AssetVerificationMode::default(),
+            sample_percent: default_asset_sample_percent(),
+        }
+    }
+}
+
+///
+/// PIN that has to be entered before settings can be changed, accounts removed or experimental mode toggled.
+/// Entering it unlocks these for a short session, see `settings_lock`.
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SettingsLock {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(rename = "pinHash", default, skip_serializing_if = "PinHash::is_empty")]
+    pin_hash: PinHash,
+}
+
+impl SettingsLock {
+    pub fn verify(&self, pin: &str) -> bool {
+        self.pin_hash.verify(pin)
+    }
+
+    /// Sets, changes or removes (`None`) the PIN, changing an existing PIN requires the current one
+    pub fn set_pin(&mut self, current_pin: Option<&str>, new_pin: Option<&str>) -> Result<()> {
+        if self.enabled && !current_pin.map_or(false, |pin| self.verify(pin)) {
+            bail!("wrong PIN");
+        }
+        let Some(new_pin) = new_pin else {
+            *self = SettingsLock::default();
+            return Ok(());
+        };
+        self.pin_hash = PinHash::new(new_pin)?;
+        self.enabled = true;
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Addons {
     pub shaders: Vec<Shader>,
@@ -34,6 +294,234 @@ pub struct LauncherProfile {
     pub branch: String,
     pub name: String,
     pub mods: Vec<CustomMod>,
+    #[serde(rename = "lastPlayed", default)]
+    pub last_played: Option<i64>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub memory: Option<ProfileMemory>,
+    /// Either `builtin:<name>` or the file name of an icon in the profile_icons folder
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Modpack the profile was created from, used to detect importing the same pack twice
+    #[serde(rename = "packSource", default)]
+    pub pack_source: Option<PackSource>,
+    /// Default profile of the branch this profile was created from
+    #[serde(rename = "sourceStandardProfileId", default)]
+    pub source_standard_profile_id: Option<String>,
+    /// Variant of the standard version this profile was created from
+    #[serde(rename = "standardVariant", default)]
+    pub standard_variant: Option<StandardVariantSource>,
+    /// Loader version launched instead of the one of the branch's launch manifest, set by standard versions
+    #[serde(rename = "loaderVersion", default)]
+    pub loader_version: Option<String>,
+    /// Where the profile came from, profiles of older launchers don't know it
+    #[serde(default)]
+    pub provenance: Option<ProfileProvenance>,
+    /// Keeps mods from writing to the user's home and the shared `.minecraft`, see `isolated_home`
+    #[serde(default)]
+    pub isolated: bool,
+    /// Mod updates the user doesn't want to be notified about, see `IgnoredUpdate`
+    #[serde(rename = "ignoredUpdates", default)]
+    pub ignored_updates: Vec<IgnoredUpdate>,
+    /// Raised by every stored change, changes based on an older revision are conflicts, see `profile_state::PROFILE_CONFLICT`
+    #[serde(default)]
+    pub revision: u64,
+    /// Fields of newer launchers, kept so storing the profile doesn't remove them
+    #[serde(flatten)]
+    pub unknown_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StandardVariantSource {
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+    #[serde(rename = "variantId")]
+    pub variant_id: String,
+    /// Minecraft version and loader of the variant when the profile was created or last synced
+    #[serde(rename = "mcVersion", default)]
+    pub mc_version: Option<String>,
+    #[serde(default)]
+    pub loader: Option<String>,
+}
+
+///
+/// Default of a standard profile that differs in a profile derived from it
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ProfileFieldChange {
+    pub field: String,
+    pub current: serde_json::Value,
+    pub standard: serde_json::Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ProfileOrigin {
+    #[serde(rename = "modrinth")]
+    Modrinth,
+    #[serde(rename = "noriskpack")]
+    Noriskpack,
+    #[serde(rename = "manual")]
+    Manual,
+}
+
+///
+/// Which pack, in which version, a profile was imported from. The Modrinth project and version ids are in `packSource`.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProfileProvenance {
+    pub source: ProfileOrigin,
+    /// Name of the pack as it was imported, the profile itself might have been renamed since
+    #[serde(rename = "packName", default)]
+    pub pack_name: Option<String>,
+    /// Version of the pack as its author named it
+    #[serde(rename = "packVersion", default)]
+    pub pack_version: Option<String>,
+    /// Unix timestamp of the import, or of the creation for manual profiles
+    #[serde(rename = "importedAt")]
+    pub imported_at: i64,
+    /// File the pack was imported from
+    #[serde(rename = "fileName", default)]
+    pub file_name: Option<String>,
+    /// Location of that file on the host, files chosen through the Flatpak document portal are translated back
+    #[serde(rename = "filePath", default)]
+    pub file_path: Option<String>,
+}
+
+impl ProfileProvenance {
+    pub fn new(source: ProfileOrigin) -> Self {
+        ProfileProvenance {
+            source,
+            pack_name: None,
+            pack_version: None,
+            imported_at: chrono::Utc::now().timestamp(),
+            file_name: None,
+            file_path: None,
+        }
+    }
+}
+
+///
+/// Available version of a mod the update check skips. Once a newer version is released, it is suggested again.
+///
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct IgnoredUpdate {
+    pub slug: String,
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PackSource {
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+}
+
+///
+/// Result of checking whether a modpack was already imported, lets the frontend offer update-in-place or create-copy
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status")]
+pub enum PackImportCheck {
+    #[serde(rename = "new")]
+    New,
+    #[serde(rename = "sameVersion")]
+    SameVersion {
+        #[serde(rename = "profileId")]
+        profile_id: String,
+    },
+    #[serde(rename = "otherVersion")]
+    OtherVersion {
+        #[serde(rename = "profileId")]
+        profile_id: String,
+        #[serde(rename = "installedVersionId")]
+        installed_version_id: String,
+    },
+}
+
+impl PackImportCheck {
+    /// Profile that was already created from the modpack
+    pub fn profile_id(&self) -> Option<&str> {
+        match self {
+            PackImportCheck::New => None,
+            PackImportCheck::SameVersion { profile_id } | PackImportCheck::OtherVersion { profile_id, .. } => Some(profile_id),
+        }
+    }
+}
+
+///
+/// What to do when a modpack is imported that a profile was already created from
+///
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum DuplicatePackImport {
+    /// Replaces the mods and pack files of the existing profile, its name and settings are kept
+    #[serde(rename = "update")]
+    Update,
+    /// Creates another profile from the modpack
+    #[serde(rename = "copy")]
+    Copy,
+}
+
+///
+/// Result of importing a modpack. Importing a modpack a second time waits for the user to choose a `DuplicatePackImport`.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status")]
+pub enum PackImportResult {
+    #[serde(rename = "imported")]
+    Imported {
+        profile: LauncherProfile,
+    },
+    #[serde(rename = "duplicate")]
+    Duplicate {
+        check: PackImportCheck,
+    },
+}
+
+///
+/// Lightweight view of a profile without its mods, used for listing many profiles
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ProfileSummary {
+    pub id: String,
+    pub branch: String,
+    pub name: String,
+    #[serde(rename = "modCount")]
+    pub mod_count: usize,
+    #[serde(rename = "lastPlayed")]
+    pub last_played: Option<i64>,
+    pub pinned: bool,
+    pub icon: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl From<&LauncherProfile> for ProfileSummary {
+    fn from(profile: &LauncherProfile) -> Self {
+        ProfileSummary {
+            id: profile.id.clone(),
+            branch: profile.branch.clone(),
+            name: profile.name.clone(),
+            mod_count: profile.mods.len(),
+            last_played: profile.last_played,
+            pinned: profile.pinned,
+            icon: profile.icon.clone(),
+            tags: profile.tags.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProfileMemory {
+    #[serde(rename = "minMb")]
+    pub min_mb: u64,
+    #[serde(rename = "maxMb")]
+    pub max_mb: u64,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -46,7 +534,12 @@ pub(crate) struct LauncherProfiles {
     pub experimental_profiles: Vec<LauncherProfile>,
     #[serde(rename = "selectedExperimentalProfiles")]
     pub selected_experimental_profiles: HashMap<String, String>,
-    pub addons: HashMap<String, Addons>
+    pub addons: HashMap<String, Addons>,
+    #[serde(rename = "schemaVersion", default)]
+    pub schema_version: u32,
+    /// Fields of newer launchers, kept so storing the profiles doesn't remove them
+    #[serde(flatten)]
+    pub unknown_fields: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -76,7 +569,47 @@ pub struct LauncherOptions {
     #[serde(rename = "accounts")]
     pub accounts: Vec<LoginData>,
     #[serde(rename = "concurrentDownloads", default = "default_concurrent_downloads")]
-    pub concurrent_downloads: i32
+    pub concurrent_downloads: i32,
+    #[serde(rename = "preferredGpu", default = "default_preferred_gpu")]
+    pub preferred_gpu: String,
+    #[serde(rename = "notifications", default)]
+    pub notifications: NotificationSettings,
+    #[serde(rename = "logPrivacy", default)]
+    pub log_privacy: LogPrivacySettings,
+    #[serde(rename = "crashUpload", default)]
+    pub crash_upload: CrashUploadSettings,
+    /// Commits the `config/` folder to a git repository before launches and mod changes
+    #[serde(rename = "configHistory", default)]
+    pub config_history: bool,
+    #[serde(rename = "queueInstalls", default = "default_true")]
+    pub queue_installs: bool,
+    #[serde(rename = "installConcurrency", default = "default_install_concurrency")]
+    pub install_concurrency: u32,
+    #[serde(rename = "linkMods", default = "default_true")]
+    pub link_mods: bool,
+    #[serde(rename = "ignoreContentBlacklist", default)]
+    pub ignore_content_blacklist: bool,
+    #[serde(rename = "restrictedMode", default)]
+    pub restricted_mode: RestrictedMode,
+    #[serde(rename = "settingsLock", default)]
+    pub settings_lock: SettingsLock,
+    #[serde(rename = "fullIntegrityCheck", default)]
+    pub full_integrity_check: bool,
+    #[serde(rename = "adaptiveDownloads", default)]
+    pub adaptive_downloads: bool,
+    #[serde(rename = "modVersionFallback", default)]
+    pub mod_version_fallback: ModVersionFallback,
+    #[serde(rename = "assetVerification", default)]
+    pub asset_verification: AssetVerification,
+    /// Names export files after the ASCII version of the profile name, see `find_unique_profile_segment`
+    #[serde(rename = "transliterateProfileNames", default = "default_true")]
+    pub transliterate_profile_names: bool,
+    /// Locale of the translations, the system locale is used if none is selected
+    #[serde(rename = "language", default)]
+    pub language: Option<String>,
+    /// Folder of the last export, the save dialog of the next export starts there
+    #[serde(rename = "lastExportDirectory", default)]
+    pub last_export_directory: Option<PathBuf>
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -106,30 +639,86 @@ pub(crate) struct LauncherOptionsMinimal {
     #[serde(rename = "accounts")]
     pub accounts: Vec<LoginDataMinimal>,
     #[serde(rename = "concurrentDownloads", default = "default_concurrent_downloads")]
-    pub concurrent_downloads: i32
+    pub concurrent_downloads: i32,
+    #[serde(rename = "preferredGpu", default = "default_preferred_gpu")]
+    pub preferred_gpu: String,
+    #[serde(rename = "notifications", default)]
+    pub notifications: NotificationSettings,
+    #[serde(rename = "logPrivacy", default)]
+    pub log_privacy: LogPrivacySettings,
+    #[serde(rename = "crashUpload", default)]
+    pub crash_upload: CrashUploadSettings,
+    /// Commits the `config/` folder to a git repository before launches and mod changes
+    #[serde(rename = "configHistory", default)]
+    pub config_history: bool,
+    #[serde(rename = "queueInstalls", default = "default_true")]
+    pub queue_installs: bool,
+    #[serde(rename = "installConcurrency", default = "default_install_concurrency")]
+    pub install_concurrency: u32,
+    #[serde(rename = "linkMods", default = "default_true")]
+    pub link_mods: bool,
+    #[serde(rename = "ignoreContentBlacklist", default)]
+    pub ignore_content_blacklist: bool,
+    #[serde(rename = "restrictedMode", default)]
+    pub restricted_mode: RestrictedMode,
+    #[serde(rename = "settingsLock", default)]
+    pub settings_lock: SettingsLock,
+    #[serde(rename = "fullIntegrityCheck", default)]
+    pub full_integrity_check: bool,
+    #[serde(rename = "adaptiveDownloads", default)]
+    pub adaptive_downloads: bool,
+    #[serde(rename = "modVersionFallback", default)]
+    pub mod_version_fallback: ModVersionFallback,
+    #[serde(rename = "assetVerification", default)]
+    pub asset_verification: AssetVerification,
+    /// Names export files after the ASCII version of the profile name, see `find_unique_profile_segment`
+    #[serde(rename = "transliterateProfileNames", default = "default_true")]
+    pub transliterate_profile_names: bool,
+    #[serde(rename = "language", default)]
+    pub language: Option<String>,
+    /// Folder of the last export, the save dialog of the next export starts there
+    #[serde(rename = "lastExportDirectory", default)]
+    pub last_export_directory: Option<PathBuf>
 }
 
 impl LauncherOptions {
     pub async fn load(app_data: &Path) -> Result<Self> {
         // load the options from the file
         let options = serde_json::from_slice::<LauncherOptionsMinimal>(&fs::read(app_data.join("options.json")).await?).map_err(|err| -> String { format!("Failed to write options.json: {}", err.to_string()).into() }).unwrap_or_else(|_| LauncherOptionsMinimal::default());
-        Ok(
-            LauncherOptions {
-                keep_launcher_open: options.keep_launcher_open,
-                experimental_mode: options.experimental_mode,
-                experimental_mode_token: options.experimental_mode_token,
-                data_path: options.data_path,
-                memory_percentage: options.memory_percentage,
-                custom_java_path: options.custom_java_path,
-                custom_java_args: options.custom_java_args,
-                theme: options.theme,
-                latest_branch: options.latest_branch,
-                latest_dev_branch: options.latest_dev_branch,
-                current_uuid: options.current_uuid,
-                accounts: options.accounts.iter().map(|account| TokenManager{}.load_tokens(account.clone()).into()).collect(),
-                concurrent_downloads: options.concurrent_downloads
-            }
-        )
+        let launcher_options = LauncherOptions {
+            keep_launcher_open: options.keep_launcher_open,
+            experimental_mode: options.experimental_mode,
+            experimental_mode_token: options.experimental_mode_token,
+            data_path: options.data_path,
+            memory_percentage: options.memory_percentage,
+            custom_java_path: options.custom_java_path,
+            custom_java_args: options.custom_java_args,
+            theme: options.theme,
+            latest_branch: options.latest_branch,
+            latest_dev_branch: options.latest_dev_branch,
+            current_uuid: options.current_uuid,
+            accounts: options.accounts.iter().map(|account| TokenManager{}.load_tokens(account.clone()).into()).collect(),
+            concurrent_downloads: options.concurrent_downloads,
+            preferred_gpu: options.preferred_gpu,
+            notifications: options.notifications,
+            log_privacy: options.log_privacy,
+            crash_upload: options.crash_upload,
+            config_history: options.config_history,
+            queue_installs: options.queue_installs,
+            install_concurrency: options.install_concurrency,
+            link_mods: options.link_mods,
+            ignore_content_blacklist: options.ignore_content_blacklist,
+            restricted_mode: options.restricted_mode,
+            settings_lock: options.settings_lock,
+            full_integrity_check: options.full_integrity_check,
+            adaptive_downloads: options.adaptive_downloads,
+            mod_version_fallback: options.mod_version_fallback,
+            asset_verification: options.asset_verification,
+            transliterate_profile_names: options.transliterate_profile_names,
+            language: options.language,
+            last_export_directory: options.last_export_directory
+        };
+        Ok(launcher_options)
     }
 
     pub async fn store(&self, app_data: &Path) -> Result<()> {
@@ -147,7 +736,25 @@ impl LauncherOptions {
             latest_dev_branch: self.latest_dev_branch.clone(),
             current_uuid: self.current_uuid.clone(),
             accounts: self.accounts.iter().map(|account| TokenManager{}.store_tokens(account.clone()).into()).collect(),
-            concurrent_downloads: self.concurrent_downloads
+            concurrent_downloads: self.concurrent_downloads,
+            preferred_gpu: self.preferred_gpu.clone(),
+            notifications: self.notifications.clone(),
+            log_privacy: self.log_privacy.clone(),
+            crash_upload: self.crash_upload.clone(),
+            config_history: self.config_history,
+            queue_installs: self.queue_installs,
+            install_concurrency: self.install_concurrency,
+            link_mods: self.link_mods,
+            ignore_content_blacklist: self.ignore_content_blacklist,
+            restricted_mode: self.restricted_mode.clone(),
+            settings_lock: self.settings_lock.clone(),
+            full_integrity_check: self.full_integrity_check,
+            adaptive_downloads: self.adaptive_downloads,
+            mod_version_fallback: self.mod_version_fallback,
+            asset_verification: self.asset_verification,
+            transliterate_profile_names: self.transliterate_profile_names,
+            language: self.language.clone(),
+            last_export_directory: self.last_export_directory.clone()
         };
 
         let _ = fs::write(app_data.join("options.json"), serde_json::to_string_pretty(&options_minimal)?).await.map_err(|err| -> String { format!("Failed to write options.json: {}", err).into() });
@@ -155,6 +762,11 @@ impl LauncherOptions {
     }
 
     pub fn data_path_buf(&self) -> PathBuf {
+        // the drive letter of a USB stick changes, so portable installs never use the stored absolute path.
+        // The data folder of the environment already is the launcher's data folder.
+        if LAUNCHER_DIRECTORY.is_portable() || overridden_data_dir().is_some() {
+            return LAUNCHER_DIRECTORY.data_dir().to_path_buf();
+        }
         if self.data_path.is_empty() {
             return LAUNCHER_DIRECTORY.data_dir().to_path_buf();
         }
@@ -165,55 +777,71 @@ impl LauncherOptions {
 pub struct TokenManager {}
 
 impl TokenManager {
-    const SERVICE: &'static str = "noriskclient-launcher";
+    const TOKEN_TYPES: [&'static str; 5] = ["mcToken", "accessToken", "refreshToken", "noriskToken", "experimentalToken"];
 
-    fn get_keyring_entry(uuid: &str, token_type: &str) -> KeyringEntry {
-        KeyringEntry::new(Self::SERVICE, &*format!("{}-{}", uuid, token_type)).map_err(|e| format!("Failed to fetch keyring-{}-token: {}", token_type, e.to_string())).unwrap()
+    fn key(uuid: &str, token_type: &str) -> String {
+        format!("{}-{}", uuid, token_type)
+    }
+
+    fn load_token(uuid: &str, token_type: &str) -> Option<String> {
+        secret_store().get(&Self::key(uuid, token_type))
+    }
+
+    fn store_token(uuid: &str, token_type: &str, token: &str) -> Result<()> {
+        secret_store().set(&Self::key(uuid, token_type), token)
+            .map_err(|err| anyhow!("unable to store {} of {}: {:?}", token_type, uuid, err))
     }
 
     pub fn load_tokens(&self, login_data: LoginDataMinimal) -> LoginData {
-        // logic for loading tokens
         let uuid = login_data.uuid.clone();
-        let mc_token = Self::get_keyring_entry(&uuid, "mcToken").get_password().unwrap_or(String::new());
-        let access_token = Self::get_keyring_entry(&uuid, "accessToken").get_password().unwrap_or(String::new());
-        let refresh_token = Self::get_keyring_entry(&uuid, "refreshToken").get_password().unwrap_or(String::new());
-        let norisk_token = Self::get_keyring_entry(&uuid, "noriskToken").get_password().unwrap_or(String::new());
-        let experimental_token = Self::get_keyring_entry(&uuid, "experimentalToken").get_password().unwrap_or(String::new());
+
+        // older launchers kept the tokens in options.json, they are moved into the secret store once.
+        // Tokens the secret store doesn't have are taken from the file, so they still work while it is unavailable.
+        let mut tokens = Self::TOKEN_TYPES.iter().zip(login_data.legacy_tokens()).map(|(token_type, legacy_token)| {
+            if let Some(token) = Self::load_token(&uuid, token_type) {
+                return token;
+            }
+            let token = legacy_token.cloned().unwrap_or_default();
+            if !token.is_empty() {
+                info!("Migrating {} of {} into the secret store", token_type, uuid);
+                if let Err(err) = Self::store_token(&uuid, token_type, &token) {
+                    error!("Failed to migrate token: {:?}", err);
+                }
+            }
+            token
+        });
 
         return LoginData {
             uuid: login_data.uuid,
             username: login_data.username,
-            mc_token: mc_token,
-            access_token: access_token,
-            refresh_token: refresh_token,
-            norisk_token: norisk_token,
-            experimental_token: Some(experimental_token)
+            mc_token: tokens.next().unwrap_or_default(),
+            access_token: tokens.next().unwrap_or_default(),
+            refresh_token: tokens.next().unwrap_or_default(),
+            norisk_token: tokens.next().unwrap_or_default(),
+            experimental_token: tokens.next()
         };
     }
 
+    /// Stores the tokens in the secret store. Tokens are never written to options.json, if the secret store is unavailable
+    /// they are only kept until the launcher is closed and the account has to sign in again.
     pub fn store_tokens(&mut self, login_data: LoginData) -> LoginDataMinimal {
-        // logic for storing tokens
-        let uuid = login_data.uuid.clone();
-        let _mc_token = Self::get_keyring_entry(&uuid, "mcToken").set_password(&login_data.mc_token).unwrap();
-        let _access_token = Self::get_keyring_entry(&uuid, "accessToken").set_password(&login_data.access_token).unwrap();
-        let _refresh_token = Self::get_keyring_entry(&uuid, "refreshToken").set_password(&login_data.refresh_token).unwrap();
-        let _norisk_token = Self::get_keyring_entry(&uuid, "noriskToken").set_password(&login_data.norisk_token).unwrap();
-        let _experimental_token = Self::get_keyring_entry(&uuid, "experimentalToken").set_password(&login_data.experimental_token.unwrap()).unwrap();
+        let uuid = &login_data.uuid;
+        let stored = Self::store_token(uuid, "mcToken", &login_data.mc_token)
+            .and_then(|_| Self::store_token(uuid, "accessToken", &login_data.access_token))
+            .and_then(|_| Self::store_token(uuid, "refreshToken", &login_data.refresh_token))
+            .and_then(|_| Self::store_token(uuid, "noriskToken", &login_data.norisk_token))
+            .and_then(|_| Self::store_token(uuid, "experimentalToken", login_data.experimental_token.as_deref().unwrap_or_default()));
 
-        return LoginDataMinimal {
-            uuid: login_data.uuid,
-            username: login_data.username
-        };
+        if let Err(err) = stored {
+            error!("Failed to store the tokens of {}, they won't be kept: {:?}", uuid, err);
+        }
+        LoginDataMinimal::new(login_data.uuid, login_data.username)
     }
 
     pub fn delete_tokens(&mut self, login_data: LoginData) {
-        // logic for deleting tokens
-        let uuid = login_data.uuid.clone();
-        let _ = Self::get_keyring_entry(&uuid, "mcToken").delete_password();
-        let _ = Self::get_keyring_entry(&uuid, "accessToken").delete_password();
-        let _ = Self::get_keyring_entry(&uuid, "refreshToken").delete_password();
-        let _ = Self::get_keyring_entry(&uuid, "noriskToken").delete_password();
-        let _ = Self::get_keyring_entry(&uuid, "experimentalToken").delete_password();
+        for token_type in Self::TOKEN_TYPES {
+            secret_store().delete(&Self::key(&login_data.uuid, token_type));
+        }
     }
 }
 
@@ -248,7 +876,25 @@ impl Default for LauncherOptions {
             latest_dev_branch: None,
             current_uuid: None,
             accounts: Vec::new(),
-            concurrent_downloads: 10
+            concurrent_downloads: 10,
+            preferred_gpu: default_preferred_gpu(),
+            notifications: NotificationSettings::default(),
+            log_privacy: LogPrivacySettings::default(),
+            crash_upload: CrashUploadSettings::default(),
+            config_history: false,
+            queue_installs: true,
+            install_concurrency: default_install_concurrency(),
+            link_mods: true,
+            ignore_content_blacklist: false,
+            restricted_mode: RestrictedMode::default(),
+            settings_lock: SettingsLock::default(),
+            full_integrity_check: false,
+            adaptive_downloads: false,
+            mod_version_fallback: ModVersionFallback::default(),
+            asset_verification: AssetVerification::default(),
+            transliterate_profile_names: true,
+            language: None,
+            last_export_directory: None
         }
     }
 }
@@ -284,7 +930,25 @@ impl Default for LauncherOptionsMinimal {
             latest_dev_branch: None,
             current_uuid: None,
             accounts: Vec::new(),
-            concurrent_downloads: 10
+            concurrent_downloads: 10,
+            preferred_gpu: default_preferred_gpu(),
+            notifications: NotificationSettings::default(),
+            log_privacy: LogPrivacySettings::default(),
+            crash_upload: CrashUploadSettings::default(),
+            config_history: false,
+            queue_installs: true,
+            install_concurrency: default_install_concurrency(),
+            link_mods: true,
+            ignore_content_blacklist: false,
+            restricted_mode: RestrictedMode::default(),
+            settings_lock: SettingsLock::default(),
+            full_integrity_check: false,
+            adaptive_downloads: false,
+            mod_version_fallback: ModVersionFallback::default(),
+            asset_verification: AssetVerification::default(),
+            transliterate_profile_names: true,
+            language: None,
+            last_export_directory: None
         }
     }
 }
@@ -292,8 +956,24 @@ impl Default for LauncherOptionsMinimal {
 impl LauncherProfiles {
     pub async fn load(app_data: &Path) -> Result<Self> {
         // load the launcher_profiles from the file
-        let launcher_profiles = serde_json::from_slice::<LauncherProfiles>(&fs::read(app_data.join("launcher_profiles.json")).await?).map_err(|err| -> String { format!("Failed to write launcher_profiles.json: {}", err.to_string()).into() }).unwrap_or_else(|_| LauncherProfiles::default());
-        Ok(launcher_profiles)
+        let path = app_data.join("launcher_profiles.json");
+        let data = fs::read(&path).await?;
+        let launcher_profiles = serde_json::from_slice::<serde_json::Value>(&data)
+            .and_then(|mut value| {
+                profile_migrations::migrate(&mut value);
+                serde_json::from_value::<LauncherProfiles>(value)
+            });
+
+        match launcher_profiles {
+            Ok(launcher_profiles) => Ok(launcher_profiles),
+            Err(err) => {
+                // keep the unreadable file, the defaults are going to overwrite it
+                let backup = app_data.join(format!("launcher_profiles.{}.broken.json", chrono::Utc::now().timestamp()));
+                error!("Failed to read launcher_profiles.json, keeping it as {:?}: {}", backup, err);
+                fs::write(&backup, &data).await?;
+                Ok(LauncherProfiles::default())
+            }
+        }
     }
 
     pub async fn store(&self, app_data: &Path) -> Result<()> {
@@ -301,6 +981,182 @@ impl LauncherProfiles {
         let _ = fs::write(app_data.join("launcher_profiles.json"), serde_json::to_string_pretty(&self)?).await.map_err(|err| -> String { format!("Failed to write launcher_profiles.json: {}", err).into() });
         Ok(())
     }
+
+    pub fn profiles(&self, experimental_mode: bool) -> &Vec<LauncherProfile> {
+        if experimental_mode { &self.experimental_profiles } else { &self.main_profiles }
+    }
+
+    pub fn profiles_mut(&mut self, experimental_mode: bool) -> &mut Vec<LauncherProfile> {
+        if experimental_mode { &mut self.experimental_profiles } else { &mut self.main_profiles }
+    }
+
+    pub fn find_profile_mut(&mut self, experimental_mode: bool, profile_id: &str) -> Option<&mut LauncherProfile> {
+        self.profiles_mut(experimental_mode).iter_mut().find(|profile| profile.id == profile_id)
+    }
+
+    /// Returns the profile that is currently selected for the given branch
+    pub fn selected_profile_mut(&mut self, experimental_mode: bool, branch: &str) -> Option<&mut LauncherProfile> {
+        let selected = if experimental_mode { &self.selected_experimental_profiles } else { &self.selected_main_profiles };
+        let profile_id = selected.get(branch)?.clone();
+        self.find_profile_mut(experimental_mode, &profile_id)
+    }
+
+    /// Profiles whose name or one of its tags contains the query, ignoring case
+    pub fn search_profiles(&self, experimental_mode: bool, query: &str) -> Vec<&LauncherProfile> {
+        let query = query.trim().to_lowercase();
+        self.profiles(experimental_mode)
+            .iter()
+            .filter(|profile| query.is_empty() || profile.name.to_lowercase().contains(&query) || profile.tags.iter().any(|tag| tag.to_lowercase().contains(&query)))
+            .collect()
+    }
+
+    /// Checks whether a profile was already created from the given modpack, preferring one with the same version
+    pub fn check_pack_import(&self, experimental_mode: bool, pack: &PackSource) -> PackImportCheck {
+        let from_pack = self.profiles(experimental_mode)
+            .iter()
+            .filter_map(|profile| profile.pack_source.as_ref().map(|source| (profile, source)))
+            .filter(|(_, source)| source.project_id == pack.project_id)
+            .collect::<Vec<_>>();
+
+        if let Some((profile, _)) = from_pack.iter().find(|(_, source)| *source == pack) {
+            return PackImportCheck::SameVersion { profile_id: profile.id.clone() };
+        }
+        match from_pack.first() {
+            Some((profile, source)) => PackImportCheck::OtherVersion { profile_id: profile.id.clone(), installed_version_id: source.version_id.clone() },
+            None => PackImportCheck::New,
+        }
+    }
+
+    /// Compares a profile with the current standard version variant it was created from and optionally applies the changed
+    /// defaults, the branch (pack id) and loader version the profile launches. Variants are identified by their branch, so
+    /// profiles created before variants existed are matched with the variant of their branch and record it on the first sync.
+    /// Mods and user settings like name, notes, tags, icon and memory are never touched.
+    pub fn sync_with_standard(&mut self, experimental_mode: bool, profile_id: &str, versions: &[StandardVersion], apply: bool) -> Result<Vec<ProfileFieldChange>> {
+        let profile = self.profiles(experimental_mode).iter().find(|profile| profile.id == profile_id).cloned().ok_or_else(|| anyhow!("unable to find profile {}", profile_id))?;
+        if profile.standard_variant.is_none() && profile.source_standard_profile_id.is_none() {
+            bail!("profile {} was not created from a standard profile", profile_id);
+        }
+        let variant_id = profile.standard_variant.as_ref().map(|source| source.variant_id.clone()).unwrap_or_else(|| profile.branch.clone());
+
+        // variants are branches, which move to another version when their Minecraft version changes
+        let (version, variant) = versions.iter()
+            .flat_map(|version| version.variants.iter().map(move |variant| (version, variant)))
+            .find(|(_, variant)| variant.id == variant_id)
+            .ok_or_else(|| anyhow!("variant {} no longer exists in the standard versions", variant_id))?;
+
+        let mut changes = Vec::new();
+        let mut compare = |field: &str, current: serde_json::Value, standard: serde_json::Value| {
+            if current != standard {
+                changes.push(ProfileFieldChange { field: field.to_string(), current, standard });
+            }
+        };
+        compare("branch", serde_json::json!(profile.branch), serde_json::json!(variant.branch));
+        compare("loaderVersion", serde_json::json!(profile.loader_version), serde_json::json!(variant.loader_version));
+
+        if apply {
+            if profile.branch != variant.branch {
+                // the profile stays selected, but for its new branch
+                let selected = if experimental_mode { &mut self.selected_experimental_profiles } else { &mut self.selected_main_profiles };
+                if selected.get(&profile.branch).map_or(false, |selected_id| selected_id == profile_id) {
+                    selected.remove(&profile.branch);
+                    selected.insert(variant.branch.clone(), profile.id.clone());
+                }
+                self.addons.entry(variant.branch.clone()).or_default();
+            }
+            if let Some(profile) = self.find_profile_mut(experimental_mode, profile_id) {
+                profile.branch = variant.branch.clone();
+                profile.loader_version = variant.loader_version.clone();
+                profile.standard_variant = Some(StandardVariantSource {
+                    version_id: version.id.clone(),
+                    variant_id: variant.id.clone(),
+                    mc_version: variant.mc_version.clone(),
+                    loader: variant.loader.clone(),
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Creates a profile for a variant of a standard version and selects it for the variant's branch
+    pub fn instantiate_standard_profile(&mut self, experimental_mode: bool, version: &StandardVersion, variant: &StandardVersionVariant) -> LauncherProfile {
+        let standard_profile_id = self.profiles(experimental_mode)
+            .iter()
+            .find(|profile| profile.branch == variant.branch && profile.name == format!("{} - Default", variant.branch))
+            .map(|profile| profile.id.clone());
+
+        let profile = LauncherProfile {
+            id: uuid::Uuid::new_v4().to_string(),
+            branch: variant.branch.clone(),
+            name: format!("{} - {}", version.name, variant.name),
+            mods: Vec::new(),
+            last_played: None,
+            pinned: false,
+            memory: None,
+            icon: None,
+            notes: String::new(),
+            tags: Vec::new(),
+            pack_source: None,
+            source_standard_profile_id: standard_profile_id,
+            standard_variant: Some(StandardVariantSource {
+                version_id: version.id.clone(),
+                variant_id: variant.id.clone(),
+                mc_version: variant.mc_version.clone(),
+                loader: variant.loader.clone(),
+            }),
+            loader_version: variant.loader_version.clone(),
+            provenance: Some(ProfileProvenance::new(ProfileOrigin::Manual)),
+            isolated: false,
+            ignored_updates: Vec::new(),
+            revision: 0,
+            unknown_fields: serde_json::Map::new(),
+        };
+
+        self.profiles_mut(experimental_mode).push(profile.clone());
+        let selected = if experimental_mode { &mut self.selected_experimental_profiles } else { &mut self.selected_main_profiles };
+        selected.insert(variant.branch.clone(), profile.id.clone());
+        self.addons.entry(variant.branch.clone()).or_default();
+        profile
+    }
+
+    /// Keeps the newest `last_played` value of every profile that also exists in `other`
+    pub fn keep_last_played(&mut self, other: &LauncherProfiles) {
+        for profile in self.main_profiles.iter_mut().chain(self.experimental_profiles.iter_mut()) {
+            let stored = other.main_profiles.iter().chain(other.experimental_profiles.iter()).find(|p| p.id == profile.id);
+            if let Some(stored) = stored {
+                profile.last_played = profile.last_played.max(stored.last_played);
+            }
+        }
+    }
+
+    /// Takes over the revisions of `other`, the profiles stored before. Profiles that are older than their stored revision are
+    /// replaced by the stored ones, so a stale copy doesn't undo newer changes. Changed profiles keep the stored revision,
+    /// reporting them through `profile_state::update` gives them their next one.
+    /// Returns the ids of the replaced profiles and of the changed profiles.
+    pub fn keep_newer_revisions(&mut self, other: &LauncherProfiles) -> (Vec<String>, Vec<String>) {
+        let mut replaced = Vec::new();
+        let mut changed = Vec::new();
+        for profile in self.main_profiles.iter_mut().chain(self.experimental_profiles.iter_mut()) {
+            let Some(stored) = other.main_profiles.iter().chain(other.experimental_profiles.iter()).find(|p| p.id == profile.id) else { continue };
+            if profile.revision < stored.revision {
+                replaced.push(profile.id.clone());
+                *profile = stored.clone();
+                continue;
+            }
+            profile.revision = stored.revision;
+            if serde_json::to_value(&*profile).ok() != serde_json::to_value(stored).ok() {
+                changed.push(profile.id.clone());
+            }
+        }
+        (replaced, changed)
+    }
+
+    /// Pinned profiles first, then the remaining profiles ordered by the last time they were played
+    pub fn recent_profiles(&self, experimental_mode: bool, limit: usize) -> Vec<LauncherProfile> {
+        let mut profiles = self.profiles(experimental_mode).clone();
+        profiles.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_played.cmp(&a.last_played)));
+        profiles.into_iter().filter(|profile| profile.pinned || profile.last_played.is_some()).take(limit).collect()
+    }
 }
 
 impl Default for LauncherProfiles {
@@ -310,7 +1166,9 @@ impl Default for LauncherProfiles {
             selected_main_profiles: HashMap::new(),
             experimental_profiles: vec![],
             selected_experimental_profiles: HashMap::new(),
-            addons: HashMap::new()
+            addons: HashMap::new(),
+            schema_version: profile_migrations::CURRENT_SCHEMA_VERSION,
+            unknown_fields: serde_json::Map::new(),
         }
     }
 }
@@ -324,3 +1182,98 @@ impl Default for Addons {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn profile_keeps_unknown_fields() {
+        // profiles of older launchers lack the newer fields, those of newer launchers have fields this one doesn't know
+        let old: LauncherProfile = serde_json::from_value(json!({ "id": "old", "branch": "prod", "name": "Old", "mods": [] })).unwrap();
+        assert_eq!(old.revision, 0);
+        assert!(old.provenance.is_none());
+
+        let stored = json!({
+            "id": "new",
+            "branch": "prod",
+            "name": "New",
+            "mods": [],
+            "provenance": { "source": "modrinth", "importedAt": 1700000000, "filePath": "C:\\Users\\Steve\\pack.mrpack" },
+            "revision": 7,
+            "accountUuid": "625dd22b-bad2-4b82-a0bc-e43ba1c1a7fd",
+        });
+        let profile: LauncherProfile = serde_json::from_value(stored).unwrap();
+        assert_eq!(profile.unknown_fields["accountUuid"], "625dd22b-bad2-4b82-a0bc-e43ba1c1a7fd");
+        let reloaded: LauncherProfile = serde_json::from_value(serde_json::to_value(&profile).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(reloaded).unwrap(), serde_json::to_value(profile).unwrap());
+    }
+
+    #[test]
+    fn sync_with_standard_keeps_mods_and_settings() {
+        let profile: LauncherProfile = serde_json::from_value(json!({
+            "id": "copy",
+            "branch": "prod",
+            "name": "My Copy",
+            "mods": [{
+                "title": "sodium",
+                "image_url": "https://cdn.modrinth.com/sodium.png",
+                "value": {
+                    "enabled": true,
+                    "name": "sodium",
+                    "source": { "type": "repository", "repository": "modrinth", "artifact": "maven.modrinth:sodium:1.0.0", "url": null },
+                },
+                "dependencies": [],
+            }],
+            "memory": { "minMb": 2048, "maxMb": 4096 },
+            "notes": "notes",
+            "tags": ["pvp"],
+            "isolated": true,
+            "sourceStandardProfileId": "prod-default",
+            "standardVariant": { "versionId": "1.20.4", "variantId": "prod", "mcVersion": "1.20.4", "loader": "fabric" },
+            "loaderVersion": "0.15.0",
+        })).unwrap();
+        let mut launcher_profiles = LauncherProfiles::default();
+        launcher_profiles.main_profiles.push(profile.clone());
+        launcher_profiles.selected_main_profiles.insert("prod".to_string(), "copy".to_string());
+        let versions = vec![StandardVersion {
+            id: "1.21".to_string(),
+            name: "1.21".to_string(),
+            variants: vec![StandardVersionVariant {
+                id: "prod".to_string(),
+                name: "prod".to_string(),
+                branch: "prod-1.21".to_string(),
+                mc_version: Some("1.21".to_string()),
+                loader: Some("fabric".to_string()),
+                loader_version: Some("0.16.0".to_string()),
+                default: true,
+            }],
+        }];
+
+        let preview = launcher_profiles.sync_with_standard(false, "copy", &versions, false).unwrap();
+        assert_eq!(preview.iter().map(|change| change.field.as_str()).collect::<Vec<_>>(), ["branch", "loaderVersion"]);
+        assert_eq!(launcher_profiles.main_profiles[0].loader_version.as_deref(), Some("0.15.0"));
+
+        let applied = launcher_profiles.sync_with_standard(false, "copy", &versions, true).unwrap();
+        assert_eq!(applied.len(), 2);
+        let synced = &launcher_profiles.main_profiles[0];
+        assert_eq!(synced.branch, "prod-1.21");
+        assert_eq!(synced.loader_version.as_deref(), Some("0.16.0"));
+        assert_eq!(synced.standard_variant.as_ref().and_then(|source| source.mc_version.as_deref()), Some("1.21"));
+        assert_eq!(launcher_profiles.selected_main_profiles.get("prod-1.21").map(String::as_str), Some("copy"));
+        assert!(!launcher_profiles.selected_main_profiles.contains_key("prod"));
+
+        // everything the user chose is left alone
+        let synced = &launcher_profiles.main_profiles[0];
+        assert_eq!(serde_json::to_value(&synced.mods).unwrap(), serde_json::to_value(&profile.mods).unwrap());
+        assert_eq!(synced.name, profile.name);
+        assert_eq!(synced.notes, profile.notes);
+        assert_eq!(synced.tags, profile.tags);
+        assert_eq!(synced.isolated, profile.isolated);
+        assert_eq!(serde_json::to_value(&synced.memory).unwrap(), serde_json::to_value(&profile.memory).unwrap());
+
+        assert!(launcher_profiles.sync_with_standard(false, "copy", &versions, false).unwrap().is_empty());
+    }
+}
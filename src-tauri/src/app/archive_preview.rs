@@ -0,0 +1,73 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use async_zip::read::seek::ZipFileReader;
+use serde::Serialize;
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+
+/// Files describing the content of an archive, shown as text in import dialogs
+const PREVIEW_FILES: [&str; 4] = ["pack.mcmeta", "profile.json", "modrinth.index.json", "fabric.mod.json"];
+/// Text previews are cut off after this many bytes
+const MAX_PREVIEW_BYTES: u64 = 16 * 1024;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+    #[serde(rename = "compressedSize")]
+    pub compressed_size: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ArchiveFilePreview {
+    pub name: String,
+    pub text: String,
+    pub truncated: bool,
+}
+
+///
+/// What is inside a `.zip`, `.mrpack` or `.noriskpack` before it gets installed
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ArchivePreview {
+    #[serde(rename = "totalEntries")]
+    pub total_entries: usize,
+    #[serde(rename = "totalSize")]
+    pub total_size: u64,
+    /// The first `max_entries` entries of the archive
+    pub entries: Vec<ArchiveEntry>,
+    pub previews: Vec<ArchiveFilePreview>,
+}
+
+pub async fn preview(path: &Path, max_entries: usize) -> Result<ArchivePreview> {
+    let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if !["zip", "mrpack", "noriskpack", "jar"].contains(&extension.as_str()) {
+        bail!("{:?} is not a supported archive", path);
+    }
+
+    let mut reader = ZipFileReader::new(fs::File::open(path).await?).await?;
+    let entries: Vec<ArchiveEntry> = reader.file().entries().iter().map(|entry| {
+        let entry = entry.entry();
+        ArchiveEntry { name: entry.filename().to_string(), size: entry.uncompressed_size(), compressed_size: entry.compressed_size() }
+    }).collect();
+
+    let mut previews = Vec::new();
+    for name in PREVIEW_FILES {
+        let Some(index) = entries.iter().position(|entry| entry.name == name) else { continue };
+        let mut data = Vec::new();
+        reader.entry(index).await?.take(MAX_PREVIEW_BYTES).read_to_end(&mut data).await?;
+        previews.push(ArchiveFilePreview {
+            name: name.to_string(),
+            text: String::from_utf8_lossy(&data).to_string(),
+            truncated: entries[index].size > MAX_PREVIEW_BYTES,
+        });
+    }
+
+    Ok(ArchivePreview {
+        total_entries: entries.len(),
+        total_size: entries.iter().map(|entry| entry.size).sum(),
+        entries: entries.into_iter().take(max_entries).collect(),
+        previews,
+    })
+}
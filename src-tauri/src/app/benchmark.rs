@@ -0,0 +1,181 @@
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use log::{info, warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::JsonStore;
+use crate::HTTP_CLIENT;
+
+/// Size of the file written sequentially
+const SEQUENTIAL_SIZE: usize = 64 * 1024 * 1024;
+const SEQUENTIAL_CHUNK: usize = 1024 * 1024;
+
+/// Size of the file written at random offsets and the size of every write
+const RANDOM_FILE_SIZE: u64 = 16 * 1024 * 1024;
+const RANDOM_BLOCK: usize = 4096;
+const RANDOM_WRITES: usize = 2048;
+
+/// Every CDN download stops after this many bytes or this long
+const DOWNLOAD_LIMIT: u64 = 16 * 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Files the game and the launcher download the most from. They have to be several megabytes,
+/// small files only measure the latency. The NoRisk CDN has no public file that big, its speed is informative only,
+/// the recommendation goes by the fastest CDN.
+const CDNS: [(&str, &str); 3] = [
+    ("Mojang Libraries", "https://libraries.minecraft.net/com/ibm/icu/icu4j/73.2/icu4j-73.2.jar"),
+    ("Forge Maven", "https://maven.minecraftforge.net/net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-installer.jar"),
+    ("NoRisk", "https://dl.norisk.gg/capes/prod/template.png"),
+];
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DiskBenchmark {
+    #[serde(rename = "sequentialWriteMbps")]
+    pub sequential_write_mbps: f64,
+    #[serde(rename = "randomWriteMbps")]
+    pub random_write_mbps: f64,
+    #[serde(rename = "randomWriteIops")]
+    pub random_write_iops: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CdnThroughput {
+    pub name: String,
+    pub url: String,
+    /// Megabits per second, `None` if the download failed
+    pub mbps: Option<f64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+///
+/// Disk and network speed of this computer, kept in `benchmark.json` for diagnostics
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    #[serde(rename = "measuredAt")]
+    pub measured_at: i64,
+    pub disk: Option<DiskBenchmark>,
+    #[serde(rename = "diskError", default)]
+    pub disk_error: Option<String>,
+    pub network: Vec<CdnThroughput>,
+    /// Concurrent downloads that suit the measured speeds
+    #[serde(rename = "recommendedConcurrentDownloads")]
+    pub recommended_concurrent_downloads: i32,
+}
+
+impl JsonStore for BenchmarkResult {
+    const FILE_NAME: &'static str = "benchmark.json";
+}
+
+/// Writes a big file in large chunks and a smaller one in small blocks at random offsets, both synced to the disk
+fn benchmark_disk(folder: &Path) -> Result<DiskBenchmark> {
+    std::fs::create_dir_all(folder)?;
+    let sequential_path = folder.join(".benchmark-sequential");
+    let random_path = folder.join(".benchmark-random");
+    let result = (|| -> Result<DiskBenchmark> {
+        let chunk = vec![0x5au8; SEQUENTIAL_CHUNK];
+        let started = Instant::now();
+        let mut file = std::fs::File::create(&sequential_path)?;
+        for _ in 0..SEQUENTIAL_SIZE / SEQUENTIAL_CHUNK {
+            file.write_all(&chunk)?;
+        }
+        file.sync_all()?;
+        let sequential_secs = started.elapsed().as_secs_f64().max(f64::EPSILON);
+
+        let block = vec![0xa5u8; RANDOM_BLOCK];
+        let mut file = std::fs::File::create(&random_path)?;
+        file.set_len(RANDOM_FILE_SIZE)?;
+        file.sync_all()?;
+        let mut rng = rand::thread_rng();
+        let started = Instant::now();
+        for _ in 0..RANDOM_WRITES {
+            let offset = rng.gen_range(0..RANDOM_FILE_SIZE / RANDOM_BLOCK as u64) * RANDOM_BLOCK as u64;
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&block)?;
+        }
+        file.sync_all()?;
+        let random_secs = started.elapsed().as_secs_f64().max(f64::EPSILON);
+
+        Ok(DiskBenchmark {
+            sequential_write_mbps: SEQUENTIAL_SIZE as f64 / 1_000_000.0 / sequential_secs,
+            random_write_mbps: (RANDOM_WRITES * RANDOM_BLOCK) as f64 / 1_000_000.0 / random_secs,
+            random_write_iops: RANDOM_WRITES as f64 / random_secs,
+        })
+    })();
+
+    let _ = std::fs::remove_file(&sequential_path);
+    let _ = std::fs::remove_file(&random_path);
+    result
+}
+
+async fn download_throughput(url: &str) -> Result<f64> {
+    let started = Instant::now();
+    let mut response = HTTP_CLIENT.get(url).send().await?.error_for_status()?;
+    let mut received = 0u64;
+    while received < DOWNLOAD_LIMIT && started.elapsed() < DOWNLOAD_TIMEOUT {
+        match tokio::time::timeout(DOWNLOAD_TIMEOUT, response.chunk()).await?? {
+            Some(chunk) => received += chunk.len() as u64,
+            None => break,
+        }
+    }
+    Ok(received as f64 * 8.0 / 1_000_000.0 / started.elapsed().as_secs_f64().max(f64::EPSILON))
+}
+
+/// Many parallel downloads only help fast connections, slow disks can't keep up with them anyway
+fn recommended_concurrency(disk: Option<&DiskBenchmark>, network: &[CdnThroughput]) -> i32 {
+    let mbps = network.iter().filter_map(|cdn| cdn.mbps).fold(0.0, f64::max);
+    let mut concurrency = match mbps {
+        mbps if mbps <= 0.0 => 10,
+        mbps if mbps < 10.0 => 4,
+        mbps if mbps < 50.0 => 8,
+        mbps if mbps < 200.0 => 16,
+        _ => 32,
+    };
+    if disk.map_or(false, |disk| disk.sequential_write_mbps < 30.0 || disk.random_write_iops < 200.0) {
+        concurrency = concurrency.min(6);
+    }
+    concurrency
+}
+
+/// Measures the disk the data folder is on and the download speed from the main CDNs
+pub async fn run(data_dir: PathBuf) -> BenchmarkResult {
+    let disk = tokio::task::spawn_blocking(move || benchmark_disk(&data_dir)).await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    let (disk, disk_error) = match disk {
+        Ok(disk) => (Some(disk), None),
+        Err(err) => {
+            warn!("Disk benchmark failed: {:?}", err);
+            (None, Some(err.to_string()))
+        }
+    };
+
+    let mut network = Vec::new();
+    for (name, url) in CDNS {
+        let throughput = download_throughput(url).await;
+        if let Err(err) = &throughput {
+            warn!("Download benchmark of {} failed: {:?}", name, err);
+        }
+        network.push(CdnThroughput {
+            name: name.to_string(),
+            url: url.to_string(),
+            mbps: throughput.as_ref().ok().copied(),
+            error: throughput.err().map(|err| err.to_string()),
+        });
+    }
+
+    let recommended_concurrent_downloads = recommended_concurrency(disk.as_ref(), &network);
+    info!("Benchmark: disk {:?}, network {:?}, recommending {} concurrent downloads", disk, network, recommended_concurrent_downloads);
+    BenchmarkResult {
+        measured_at: chrono::Utc::now().timestamp(),
+        disk,
+        disk_error,
+        network,
+        recommended_concurrent_downloads,
+    }
+}
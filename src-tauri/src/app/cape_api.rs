@@ -1,7 +1,5 @@
 use std::error::Error;
 use std::fs::File;
-#[cfg(target_os = "linux")]
-use std::fs::metadata;
 use std::io::Read;
 use std::path::PathBuf;
 use std::process::Command;
@@ -13,6 +11,10 @@ use log::debug;
 use crate::{HTTP_CLIENT, LAUNCHER_DIRECTORY};
 use crate::app::api::get_api_base;
 use crate::app::app_data::LauncherOptions;
+use crate::utils::file_utils;
+
+/// High enough to include every cape of an account, a pending cape missing from the owned capes counts as rejected
+const OWNED_CAPES_LIMIT: u32 = 1000;
 
 /// Placeholder struct for API endpoints implementation
 pub struct CapeApiEndpoints;
@@ -187,6 +189,14 @@ impl CapeApiEndpoints {
         Ok(owned_capes)
     }
 
+    /// The uploaded capes with their review status and the moderator's message, taken from the owned capes which include the
+    /// ones that weren't accepted yet.
+    pub async fn request_cape_submissions(norisk_token: &str, uuid: &str) -> Result<Vec<CapeSubmission>, Box<dyn Error>> {
+        debug!("Requesting Cape Submissions...");
+        let owned_capes = Self::request_owned_capes(norisk_token, uuid, OWNED_CAPES_LIMIT).await?;
+        Ok(owned_capes.into_iter().map(CapeSubmission::from).collect())
+    }
+
     pub fn show_in_folder(path: &str) {
         debug!("Spawning Path {}",path);
         #[cfg(target_os = "windows")]
@@ -197,35 +207,16 @@ impl CapeApiEndpoints {
                 .unwrap();
         }
 
-        /* TODO Später
         #[cfg(target_os = "linux")]
         {
-            if path.contains(",") {
-                // see https://gitlab.freedesktop.org/dbus/dbus/-/issues/76
-                let new_path = match metadata(&path).unwrap().is_dir() {
-                    true => path,
-                    false => {
-                        let mut path2 = PathBuf::from(path);
-                        path2.pop();
-                        &path2.into_os_string().into_string().unwrap()
-                    }
-                };
-                Command::new("xdg-open")
-                    .arg(&new_path)
-                    .spawn()
-                    .unwrap();
-            } else {
-                if let Ok(Fork::Child) = daemon(false, false) {
-                    Command::new("dbus-send")
-                        .args(["--session", "--dest=org.freedesktop.FileManager1", "--type=method_call",
-                            "/org/freedesktop/FileManager1", "org.freedesktop.FileManager1.ShowItems",
-                            format!("array:string:\"file://{path}\"").as_str(), "string:\"\""])
-                        .spawn()
-                        .unwrap();
+            // file managers can't select a file via xdg-open, so open the containing folder instead
+            let folder = PathBuf::from(path).parent().map(|parent| parent.to_path_buf()).unwrap_or_default();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = file_utils::open_path(&folder).await {
+                    debug!("Failed to open folder {:?}: {:?}", folder, err);
                 }
-            }
+            });
         }
-        */
 
         #[cfg(target_os = "macos")]
         {
@@ -252,4 +243,46 @@ pub struct Cape {
     pub first_seen: String,
     #[serde(rename = "creationDate")]
     pub creation_date: i64,
+    /// Review status of a cape the user uploaded, capes of older API versions only know `accepted`
+    #[serde(default)]
+    pub status: Option<CapeSubmissionStatus>,
+    /// Message of the moderator who reviewed the cape
+    #[serde(rename = "moderatorMessage", default)]
+    pub moderator_message: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CapeSubmissionStatus {
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "accepted")]
+    Accepted,
+    #[serde(rename = "rejected")]
+    Rejected,
+}
+
+///
+/// A cape the user uploaded and its review
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CapeSubmission {
+    #[serde(rename = "_id")]
+    pub hash: String,
+    pub status: CapeSubmissionStatus,
+    #[serde(rename = "creationDate")]
+    pub creation_date: i64,
+    #[serde(rename = "moderatorMessage", default)]
+    pub moderator_message: Option<String>,
+}
+
+impl From<Cape> for CapeSubmission {
+    fn from(cape: Cape) -> Self {
+        let accepted = if cape.accepted { CapeSubmissionStatus::Accepted } else { CapeSubmissionStatus::Pending };
+        CapeSubmission {
+            hash: cape.hash,
+            status: cape.status.unwrap_or(accepted),
+            creation_date: cape.creation_date,
+            moderator_message: cape.moderator_message,
+        }
+    }
 }
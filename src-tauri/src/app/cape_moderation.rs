@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{debug, error, info, warn};
+use once_cell::sync::Lazy;
+use tauri::{AppHandle, Manager};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, Notify};
+
+use crate::app::app_data::LauncherOptions;
+use crate::app::cape_api::{CapeApiEndpoints, CapeSubmission, CapeSubmissionStatus};
+use crate::app::notifications::{self, NotificationCategory};
+use crate::utils::JsonStore;
+use crate::LAUNCHER_DIRECTORY;
+
+/// Reviews take a while, there is no need to ask more often
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Last known status of every submission by account and cape hash.
+/// Kept on disk so a review that happened while the launcher was closed is still notified.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+struct CapeSubmissionStore {
+    accounts: HashMap<String, HashMap<String, CapeSubmissionStatus>>,
+}
+
+impl JsonStore for CapeSubmissionStore {
+    const FILE_NAME: &'static str = "cape_submissions.json";
+}
+
+/// Loaded on the first poll
+static KNOWN_STATUSES: Lazy<Mutex<Option<CapeSubmissionStore>>> = Lazy::new(|| Mutex::new(None));
+
+/// Wakes the poll loop before the poll interval is over
+static POLL_NOW: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Polls the cape submissions of the active account in the background
+pub fn init(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(err) = poll(&app_handle).await {
+                debug!("Failed to poll cape submissions: {:?}", err);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = POLL_NOW.notified() => {}
+            }
+        }
+    });
+}
+
+/// Polls right away, e.g. after an upload so the new cape is known as pending and its review gets notified
+/// even if it happens before the next regular poll
+pub fn poll_now() {
+    POLL_NOW.notify_one();
+}
+
+/// Emits `cape-submission-updated` and notifies for every submission that was reviewed since the last poll
+async fn poll(app_handle: &AppHandle) -> Result<()> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await?;
+    let Some(account) = options.current_uuid.as_ref().and_then(|uuid| options.accounts.iter().find(|account| account.uuid == *uuid)) else { return Ok(()) };
+    let norisk_token = if options.experimental_mode { account.experimental_token.clone().unwrap_or_default() } else { account.norisk_token.clone() };
+    if norisk_token.is_empty() {
+        return Ok(());
+    }
+
+    let submissions = CapeApiEndpoints::request_cape_submissions(&norisk_token, &account.uuid).await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+    for submission in reviewed_since_last_poll(&account.uuid, submissions).await {
+        info!("Cape {} was {:?}", submission.hash, submission.status);
+        if let Err(err) = app_handle.emit_all("cape-submission-updated", &submission) {
+            error!("Failed to emit cape submission update: {:?}", err);
+        }
+
+        let (title, body) = if submission.status == CapeSubmissionStatus::Accepted {
+            ("Cape accepted", "Your cape was accepted and can be equipped now.")
+        } else {
+            ("Cape rejected", "Your cape was rejected.")
+        };
+        let body = match submission.moderator_message.as_deref().map(str::trim).filter(|message| !message.is_empty()) {
+            Some(message) => format!("{}\nModerator: {}", body, message),
+            None => body.to_string(),
+        };
+        notifications::notify(NotificationCategory::Capes, title, &body).await;
+    }
+    Ok(())
+}
+
+/// Submissions that were pending at the last poll and were reviewed since then.
+/// Submissions that are gone, e.g. because the cape was deleted, are forgotten without a notification, the API reports
+/// the review of a cape as long as it exists. The first poll of an account only remembers the statuses.
+async fn reviewed_since_last_poll(uuid: &str, submissions: Vec<CapeSubmission>) -> Vec<CapeSubmission> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut known = KNOWN_STATUSES.lock().await;
+    if known.is_none() {
+        *known = Some(CapeSubmissionStore::load(config_dir).await.unwrap_or_default());
+    }
+    let known = known.get_or_insert_with(CapeSubmissionStore::default);
+
+    let account = known.accounts.entry(uuid.to_string()).or_default();
+    account.retain(|hash, _| submissions.iter().any(|submission| submission.hash == *hash));
+    let reviewed: Vec<CapeSubmission> = submissions.into_iter()
+        .filter(|submission| {
+            let previous = account.insert(submission.hash.clone(), submission.status);
+            previous == Some(CapeSubmissionStatus::Pending) && submission.status != CapeSubmissionStatus::Pending
+        })
+        .collect();
+
+    if let Err(err) = known.store(config_dir).await {
+        warn!("Failed to store cape submission statuses: {:?}", err);
+    }
+    reviewed
+}
@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use log::{error, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::sync::Mutex;
+
+use crate::app::api::{ApiEndpoints, BlockedContent};
+use crate::LAUNCHER_DIRECTORY;
+
+/// The blacklist is requested again once it is older than this
+const MAX_AGE: Duration = Duration::from_secs(60 * 60);
+/// Wait after the first failed request, doubled with every further failure up to `MAX_AGE`
+const MIN_BACKOFF: Duration = Duration::from_secs(30);
+
+static BLACKLIST: Lazy<Mutex<Option<ContentBlacklist>>> = Lazy::new(|| Mutex::new(None));
+/// Failed requests in a row and when the next one may be made, so installs and launches don't hit an unreachable api every time
+static BACKOFF: Lazy<Mutex<(u32, Option<Instant>)>> = Lazy::new(|| Mutex::new((0, None)));
+
+/// Wait after the given number of failed requests in a row
+fn backoff_delay(failures: u32) -> Duration {
+    MIN_BACKOFF.saturating_mul(2u32.saturating_pow(failures.saturating_sub(1))).min(MAX_AGE)
+}
+
+///
+/// Known broken or malicious mod versions, cached in `content_blacklist.json` so they are also blocked while offline
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ContentBlacklist {
+    #[serde(rename = "fetchedAt")]
+    pub fetched_at: i64,
+    pub entries: Vec<BlockedContent>,
+}
+
+impl ContentBlacklist {
+    /// Returns the cached blacklist, requesting a new one from the api if it is outdated.
+    /// The request happens without holding the lock, so a slow api doesn't block other callers.
+    pub async fn get() -> ContentBlacklist {
+        let path = LAUNCHER_DIRECTORY.config_dir().join("content_blacklist.json");
+
+        let cached = {
+            let mut blacklist = BLACKLIST.lock().await;
+            if blacklist.is_none() {
+                *blacklist = match fs::read(&path).await {
+                    Ok(data) => serde_json::from_slice(&data).ok(),
+                    Err(_) => None,
+                };
+            }
+            blacklist.clone()
+        };
+
+        let outdated = cached.as_ref().map_or(true, |blacklist| Utc::now().timestamp() - blacklist.fetched_at > MAX_AGE.as_secs() as i64);
+        if !outdated {
+            return cached.unwrap_or_default();
+        }
+        if BACKOFF.lock().await.1.map_or(false, |retry_at| Instant::now() < retry_at) {
+            return cached.unwrap_or_default();
+        }
+
+        match ApiEndpoints::norisk_content_blacklist().await {
+            Ok(entries) => {
+                *BACKOFF.lock().await = (0, None);
+                let fetched = ContentBlacklist { fetched_at: Utc::now().timestamp(), entries };
+                let mut blacklist = BLACKLIST.lock().await;
+                // another caller might have stored a newer one in the meantime
+                if blacklist.as_ref().map_or(true, |blacklist| blacklist.fetched_at < fetched.fetched_at) {
+                    if let Ok(data) = serde_json::to_vec(&fetched) {
+                        let _ = fs::write(&path, data).await;
+                    }
+                    *blacklist = Some(fetched);
+                }
+                blacklist.clone().unwrap_or_default()
+            }
+            Err(err) => {
+                let mut backoff = BACKOFF.lock().await;
+                backoff.0 += 1;
+                let delay = backoff_delay(backoff.0);
+                backoff.1 = Some(Instant::now() + delay);
+                error!("Failed to request content blacklist, using cached one and retrying in {:?}: {:?}", delay, err);
+                cached.unwrap_or_default()
+            }
+        }
+    }
+
+    /// Finds the entry blocking a mod by its file hash or by one of its project ids (id or slug) and versions (id or number)
+    pub fn find(&self, sha1: Option<&str>, projects: &[&str], versions: &[&str]) -> Option<&BlockedContent> {
+        self.entries.iter().find(|entry| {
+            if let (Some(blocked_sha1), Some(sha1)) = (&entry.sha1, sha1) {
+                if blocked_sha1.eq_ignore_ascii_case(sha1) {
+                    return true;
+                }
+            }
+
+            let Some(project_id) = &entry.project_id else { return false };
+            if !projects.iter().any(|project| project.eq_ignore_ascii_case(project_id)) {
+                return false;
+            }
+            match &entry.version_id {
+                Some(version_id) => versions.iter().any(|version| version == version_id),
+                None => true,
+            }
+        })
+    }
+}
+
+/// Error for blocked content, or `None` if it's allowed or the user overrides the blacklist
+pub fn refuse(blocked: Option<&BlockedContent>, name: &str, allow_blocked_content: bool) -> Option<String> {
+    let blocked = blocked?;
+    if allow_blocked_content {
+        warn!("{} is blocked ({}) but the blacklist is overridden", name, blocked.reason);
+        return None;
+    }
+    Some(format!("{} is blocked by NoRisk: {}", name, blocked.reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_until_max_age() {
+        assert_eq!(backoff_delay(1), MIN_BACKOFF);
+        assert_eq!(backoff_delay(2), MIN_BACKOFF * 2);
+        assert_eq!(backoff_delay(4), MIN_BACKOFF * 8);
+        assert_eq!(backoff_delay(100), MAX_AGE);
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::api::LoaderMod;
+use crate::app::app_data::{Addons, LauncherProfile};
+use crate::app::installed_content::{installed_contents, InstalledContent};
+use crate::utils::file_utils;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ContentType {
+    #[serde(rename = "mod")]
+    Mod,
+    #[serde(rename = "shader")]
+    Shader,
+    #[serde(rename = "resourcepack")]
+    ResourcePack,
+    #[serde(rename = "datapack")]
+    Datapack,
+}
+
+///
+/// Content the frontend wants to know about, matched by its slug or by the name of its file
+///
+#[derive(Clone, Debug, Deserialize)]
+pub struct CheckContentParams {
+    #[serde(rename = "contentType")]
+    pub content_type: ContentType,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(rename = "fileName", default)]
+    pub file_name: Option<String>,
+    /// World of a datapack
+    #[serde(default)]
+    pub world: Option<String>,
+}
+
+///
+/// Answers whether contents are installed in a profile, every folder is only read once no matter how many contents are checked
+///
+pub struct ContentCheck {
+    contents: Vec<InstalledContent>,
+    game_dir: PathBuf,
+    folders: HashMap<PathBuf, HashSet<String>>,
+}
+
+impl ContentCheck {
+    pub fn new(profile: &LauncherProfile, pack_mods: &[LoaderMod], addons: Option<&Addons>, game_dir: PathBuf) -> Self {
+        ContentCheck { contents: installed_contents(profile, pack_mods, addons), game_dir, folders: HashMap::new() }
+    }
+
+    async fn folder_contains(&mut self, folder: &Path, file_name: &str) -> bool {
+        if !self.folders.contains_key(folder) {
+            let files = file_utils::list_file_names(folder).await.unwrap_or_default();
+            self.folders.insert(folder.to_path_buf(), files.into_iter().collect());
+        }
+        self.folders[folder].contains(file_name)
+    }
+
+    pub async fn is_installed(&mut self, params: &CheckContentParams) -> bool {
+        let in_profile = self.contents.iter()
+            .any(|content| content.matches(params.content_type, params.slug.as_deref(), params.file_name.as_deref(), params.world.as_deref()));
+        if in_profile {
+            return true;
+        }
+
+        // files the user put into the folders themselves
+        let Some(file_name) = &params.file_name else { return false };
+        let folder = match (params.content_type, &params.world) {
+            (ContentType::Mod, _) => self.game_dir.join("mods"),
+            (ContentType::Shader, _) => self.game_dir.join("shaderpacks"),
+            (ContentType::ResourcePack, _) => self.game_dir.join("resourcepacks"),
+            (ContentType::Datapack, Some(world)) => self.game_dir.join("saves").join(world).join("datapacks"),
+            (ContentType::Datapack, None) => return false,
+        };
+        self.folder_contains(&folder, file_name).await
+    }
+}
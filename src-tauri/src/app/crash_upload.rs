@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::Local;
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::app::app_data::CrashUploadSettings;
+use crate::app::log_privacy::LogRedactor;
+use crate::app::mclogs_api::McLogsApiEndpoints;
+use crate::utils::JsonStore;
+use crate::LAUNCHER_DIRECTORY;
+
+///
+/// Log uploaded after a crash
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct UploadedLog {
+    /// `latest.log` or the file name of the crash report
+    pub name: String,
+    pub url: String,
+}
+
+///
+/// Payload of `client-exited`
+///
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ClientExit {
+    pub branch: String,
+    pub crashed: bool,
+    pub uploads: Vec<UploadedLog>,
+}
+
+/// Uploads of the current day, to respect the daily cap
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct UploadCounter {
+    pub day: String,
+    pub uploads: u32,
+}
+
+impl JsonStore for UploadCounter {
+    const FILE_NAME: &'static str = "crash_uploads.json";
+}
+
+/// Whether the file was written after the given time, so it belongs to the crashed launch
+async fn written_since(path: &Path, since: SystemTime) -> bool {
+    fs::metadata(path).await.ok()
+        .and_then(|metadata| metadata.modified().ok())
+        .map_or(false, |modified| modified >= since)
+}
+
+/// Newest crash report written since the launch
+async fn crash_report(game_dir: &Path, since: SystemTime) -> Option<PathBuf> {
+    let mut entries = fs::read_dir(game_dir.join("crash-reports")).await.ok()?;
+    let mut newest: Option<(SystemTime, PathBuf)> = None;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Ok(modified) = entry.metadata().await.and_then(|metadata| metadata.modified()) else { continue };
+        if modified >= since && newest.as_ref().map_or(true, |(newest, _)| modified > *newest) {
+            newest = Some((modified, entry.path()));
+        }
+    }
+    newest.map(|(_, path)| path)
+}
+
+/// Uploads `latest.log` and the crash report of a crashed launch, unless the daily cap is reached
+pub async fn upload_crash_logs(settings: &CrashUploadSettings, game_dir: &Path, launch_started: SystemTime, redactor: &LogRedactor) -> Vec<UploadedLog> {
+    if !settings.enabled {
+        return Vec::new();
+    }
+
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let mut counter = UploadCounter::load(config_dir).await.unwrap_or_default();
+    if counter.day != today {
+        counter = UploadCounter { day: today, uploads: 0 };
+    }
+    if counter.uploads >= settings.daily_cap {
+        info!("Not uploading crash logs, the daily cap of {} is reached", settings.daily_cap);
+        return Vec::new();
+    }
+
+    let mut files = Vec::new();
+    let latest_log = game_dir.join("logs").join("latest.log");
+    if written_since(&latest_log, launch_started).await {
+        files.push(latest_log);
+    }
+    files.extend(crash_report(game_dir, launch_started).await);
+
+    let mut uploads = Vec::new();
+    for file in files {
+        let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let upload = async {
+            let content = String::from_utf8_lossy(&fs::read(&file).await?).to_string();
+            McLogsApiEndpoints::upload_logs(redactor.redact(&content)).await
+        };
+        match upload.await {
+            Ok(response) => {
+                debug!("Uploaded {} to {}", name, response.url);
+                uploads.push(UploadedLog { name, url: response.url });
+            }
+            Err(err) => error!("Failed to upload {}: {:?}", name, err),
+        }
+    }
+
+    if !uploads.is_empty() {
+        counter.uploads += 1;
+        if let Err(err) = counter.store(config_dir).await {
+            error!("Failed to store crash upload counter: {:?}", err);
+        }
+    }
+    uploads
+}
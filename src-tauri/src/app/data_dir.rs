@@ -0,0 +1,287 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::app::app_data::LauncherOptions;
+use crate::launcher_directory::overridden_data_dir;
+use crate::LAUNCHER_DIRECTORY;
+
+/// OS user the data folder belongs to, its id and its name on separate lines
+const OWNER_FILE: &str = ".launcher-user";
+
+///
+/// Problem with the data folder, mostly caused by several OS users sharing one folder
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct DataDirIssue {
+    /// `notWritable`, `ownedByOtherUser` or `sharedWithOtherUser`
+    pub kind: String,
+    pub message: String,
+    /// The other user of a `sharedWithOtherUser` folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+///
+/// Result of the data folder check that runs on startup
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct DataDirStatus {
+    pub path: PathBuf,
+    /// Whether the folder comes from the `NORISKCLIENT_DATA_DIR` environment variable
+    pub overridden: bool,
+    pub issues: Vec<DataDirIssue>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum DataDirRepair {
+    /// Makes the files writable again and claims the folder for the current user.
+    /// Folders owned by another user can't be claimed, only that user or an administrator can hand them over.
+    #[serde(rename = "claim")]
+    Claim,
+    /// Switches to the default data folder of the current user
+    #[serde(rename = "useDefault")]
+    UseDefault,
+}
+
+/// OS user the launcher runs as
+struct OsUser {
+    /// SID on Windows and uid elsewhere, taken from the process because the user name of the environment can be changed by anyone
+    id: String,
+    /// Only shown to the other users of the folder
+    name: String,
+}
+
+impl OsUser {
+    /// Owner files of older launcher versions only hold a user name, which says nothing about the user
+    fn parse(owner_file: &str) -> Option<OsUser> {
+        let (id, name) = owner_file.split_once('\n')?;
+        if id.trim().is_empty() {
+            return None;
+        }
+        Some(OsUser { id: id.trim().to_string(), name: name.trim().to_string() })
+    }
+
+    fn owner_file(&self) -> String {
+        format!("{}\n{}", self.id, self.name)
+    }
+
+    fn display_name(&self) -> &str {
+        if self.name.is_empty() { &self.id } else { &self.name }
+    }
+}
+
+#[cfg(unix)]
+fn current_user() -> Option<OsUser> {
+    let uid = unsafe { libc::geteuid() };
+    // the passwd entry is overwritten by the next lookup, so the name is copied right away
+    let name = unsafe {
+        let passwd = libc::getpwuid(uid);
+        if passwd.is_null() { String::new() } else { std::ffi::CStr::from_ptr((*passwd).pw_name).to_string_lossy().to_string() }
+    };
+    Some(OsUser { id: uid.to_string(), name })
+}
+
+#[cfg(windows)]
+fn current_user() -> Option<OsUser> {
+    windows_security::current_user()
+}
+
+/// Id of the OS user owning a file, comparable with `OsUser::id`
+#[cfg(unix)]
+fn file_owner(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|metadata| metadata.uid().to_string())
+}
+
+#[cfg(windows)]
+fn file_owner(path: &Path) -> Option<String> {
+    windows_security::file_owner(path)
+}
+
+/// Creates and deletes a file to find out whether the folder is writable, returns the owner of the created file
+async fn probe(data_dir: &Path) -> io::Result<Option<String>> {
+    fs::create_dir_all(data_dir).await?;
+    let probe = data_dir.join(".write-test");
+    fs::write(&probe, []).await?;
+    let owner = file_owner(&probe);
+    fs::remove_file(&probe).await?;
+    Ok(owner)
+}
+
+/// Compares the owner of the folder with the owner of a file created in it rather than with the current user,
+/// so a folder of an elevated Windows administrator, owned by the Administrators group, isn't taken for another user's
+fn owned_by_other_user(data_dir: &Path, created_owner: Option<&str>) -> bool {
+    match (file_owner(data_dir), created_owner) {
+        (Some(owner), Some(created_owner)) => owner != created_owner,
+        _ => false,
+    }
+}
+
+#[cfg(windows)]
+mod windows_security {
+    use std::path::Path;
+
+    use windows::core::{HSTRING, PCWSTR, PWSTR};
+    use windows::Win32::Foundation::{CloseHandle, LocalFree, HANDLE, HLOCAL};
+    use windows::Win32::Security::Authorization::{ConvertSidToStringSidW, GetNamedSecurityInfoW, SE_FILE_OBJECT};
+    use windows::Win32::Security::{GetTokenInformation, LookupAccountSidW, TokenUser, OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, PSID, SID_NAME_USE, TOKEN_QUERY, TOKEN_USER};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    use super::OsUser;
+
+    /// User of the process token
+    pub fn current_user() -> Option<OsUser> {
+        unsafe {
+            let mut token = HANDLE::default();
+            OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).ok()?;
+            let mut length = 0u32;
+            let _ = GetTokenInformation(token, TokenUser, None, 0, &mut length);
+            // u64s keep the SID pointer at the start of the buffer aligned
+            let mut buffer = vec![0u64; (length as usize).div_ceil(8)];
+            let result = GetTokenInformation(token, TokenUser, Some(buffer.as_mut_ptr().cast()), length, &mut length);
+            let _ = CloseHandle(token);
+            result.ok()?;
+
+            let sid = (*buffer.as_ptr().cast::<TOKEN_USER>()).User.Sid;
+            Some(OsUser { id: sid_string(sid)?, name: account_name(sid) })
+        }
+    }
+
+    /// SID of the owner of a file or folder
+    pub fn file_owner(path: &Path) -> Option<String> {
+        unsafe {
+            let mut owner = PSID::default();
+            let mut descriptor = PSECURITY_DESCRIPTOR::default();
+            let result = GetNamedSecurityInfoW(&HSTRING::from(path.as_os_str()), SE_FILE_OBJECT, OWNER_SECURITY_INFORMATION, Some(&mut owner), None, None, None, &mut descriptor);
+            if result.is_err() {
+                return None;
+            }
+            // the owner points into the descriptor
+            let owner = sid_string(owner);
+            let _ = LocalFree(HLOCAL(descriptor.0));
+            owner
+        }
+    }
+
+    unsafe fn sid_string(sid: PSID) -> Option<String> {
+        let mut string_sid = PWSTR::null();
+        ConvertSidToStringSidW(sid, &mut string_sid).ok()?;
+        let result = string_sid.to_string().ok();
+        let _ = LocalFree(HLOCAL(string_sid.0.cast()));
+        result
+    }
+
+    /// `DOMAIN\name` of the account, empty if it can't be looked up
+    unsafe fn account_name(sid: PSID) -> String {
+        let mut name = [0u16; 256];
+        let mut domain = [0u16; 256];
+        let (mut name_length, mut domain_length) = (name.len() as u32, domain.len() as u32);
+        let mut name_use = SID_NAME_USE::default();
+        if LookupAccountSidW(PCWSTR::null(), sid, PWSTR(name.as_mut_ptr()), &mut name_length, PWSTR(domain.as_mut_ptr()), &mut domain_length, &mut name_use).is_err() {
+            return String::new();
+        }
+        let name = String::from_utf16_lossy(&name[..name_length as usize]);
+        match String::from_utf16_lossy(&domain[..domain_length as usize]) {
+            domain if domain.is_empty() => name,
+            domain => format!("{}\\{}", domain, name),
+        }
+    }
+}
+
+pub async fn check(options: &LauncherOptions) -> DataDirStatus {
+    let data_dir = options.data_path_buf();
+    let mut issues = Vec::new();
+
+    match probe(&data_dir).await {
+        Ok(created_owner) => {
+            if owned_by_other_user(&data_dir, created_owner.as_deref()) {
+                issues.push(DataDirIssue {
+                    kind: "ownedByOtherUser".to_string(),
+                    message: format!("{} belongs to another user of this computer.", data_dir.display()),
+                    owner: None,
+                });
+            }
+        }
+        Err(err) => {
+            warn!("Data folder {:?} is not writable: {:?}", data_dir, err);
+            issues.push(DataDirIssue {
+                kind: "notWritable".to_string(),
+                message: format!("The launcher can't write to {}: {}", data_dir.display(), err),
+                owner: None,
+            });
+        }
+    }
+
+    let owner = fs::read_to_string(data_dir.join(OWNER_FILE)).await.ok().and_then(|owner| OsUser::parse(&owner));
+    match (owner, current_user()) {
+        (Some(owner), Some(user)) if owner.id != user.id => {
+            issues.push(DataDirIssue {
+                kind: "sharedWithOtherUser".to_string(),
+                message: format!("{} is also used by {}. Sharing a data folder breaks accounts and running games of both users.", data_dir.display(), owner.display_name()),
+                owner: Some(owner.display_name().to_string()),
+            });
+        }
+        // the owner file of an older launcher version is replaced like a missing one
+        (None, Some(user)) if issues.is_empty() => {
+            let _ = fs::write(data_dir.join(OWNER_FILE), user.owner_file()).await;
+        }
+        _ => {}
+    }
+
+    DataDirStatus { path: data_dir, overridden: overridden_data_dir().is_some(), issues }
+}
+
+fn make_writable(path: &Path) -> io::Result<()> {
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    if !permissions.readonly() {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    std::fs::set_permissions(path, permissions)
+}
+
+pub async fn repair(repair: DataDirRepair) -> Result<DataDirStatus> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+
+    match repair {
+        DataDirRepair::Claim => {
+            let data_dir = options.data_path_buf();
+            if check(&options).await.issues.iter().any(|issue| issue.kind == "ownedByOtherUser") {
+                bail!("{:?} belongs to another user, the launcher can't take it over", data_dir);
+            }
+            let walked = data_dir.clone();
+            let failed = tokio::task::spawn_blocking(move || {
+                walkdir::WalkDir::new(&walked).into_iter()
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| make_writable(entry.path()).is_err())
+                    .count()
+            }).await?;
+            if failed > 0 {
+                warn!("{} files in {:?} could not be made writable", failed, data_dir);
+            }
+            let user = current_user().ok_or_else(|| anyhow!("unable to find out the current user"))?;
+            fs::write(data_dir.join(OWNER_FILE), user.owner_file()).await?;
+            info!("Claimed data folder {:?}", data_dir);
+        }
+        DataDirRepair::UseDefault => {
+            options.data_path = LAUNCHER_DIRECTORY.data_dir().to_string_lossy().to_string();
+            options.store(config_dir).await?;
+            info!("Switched to the default data folder {:?}", options.data_path);
+        }
+    }
+
+    Ok(check(&options).await)
+}
@@ -1,31 +1,85 @@
-use std::{path::PathBuf, sync::{Arc, Mutex}, thread};
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc, Mutex}, thread};
 
+use chrono::Utc;
 use directories::UserDirs;
-use log::{debug, error, info};
-use reqwest::multipart::{Form, Part};
+use futures::stream::{self, StreamExt};
+use log::{debug, error, info, warn};
 use tauri::{LogicalSize, Manager, Window, WindowEvent};
-use tauri::api::dialog::blocking::message;
+use tauri::api::dialog::blocking::{message, FileDialogBuilder};
 use tokio::{fs, io::{AsyncReadExt, AsyncWriteExt}, process::Child};
 
-use crate::{custom_servers::{manager::CustomServerManager, models::CustomServer, providers::{bukkit::BukkitProvider, fabric::{FabricLoaderVersion, FabricProvider, FabricVersion}, folia::{FoliaBuilds, FoliaManifest, FoliaProvider}, forge::{ForgeManifest, ForgeProvider}, neoforge::{NeoForgeManifest, NeoForgeProvider}, paper::{PaperBuilds, PaperManifest, PaperProvider}, purpur::{PurpurProvider, PurpurVersions}, quilt::{QuiltManifest, QuiltProvider}, spigot::SpigotProvider, vanilla::{VanillaManifest, VanillaProvider, VanillaVersions}}}, minecraft::{launcher::{LauncherData, LaunchingParameter}, prelauncher, progress::ProgressUpdate}, HTTP_CLIENT, LAUNCHER_DIRECTORY};
-use crate::app::api::{LoginData, NoRiskLaunchManifest};
+use crate::{custom_servers::{loader_install, manager::CustomServerManager, models::CustomServer, providers::{bukkit::BukkitProvider, fabric::{FabricLoaderVersion, FabricProvider, FabricVersion}, folia::{FoliaBuilds, FoliaManifest, FoliaProvider}, forge::{ForgeManifest, ForgeProvider}, neoforge::{NeoForgeManifest, NeoForgeProvider}, paper::{PaperBuilds, PaperManifest, PaperProvider}, purpur::{PurpurProvider, PurpurVersions}, quilt::{QuiltManifest, QuiltProvider}, spigot::SpigotProvider, vanilla::{VanillaManifest, VanillaProvider, VanillaVersions}}}, minecraft::{config_history::{self, ConfigRevision}, launcher::{self, LaunchArguments, LaunchCommandPreview, LauncherData, LaunchingParameter}, mod_configs::{self, ConfigFileDiff, ModConfig}, mod_sync::{self, ModSyncReport}, prelauncher, progress::ProgressUpdate, version::VersionProfile}, HTTP_CLIENT, LAUNCHER_DIRECTORY};
+use crate::app::account_appearance::{self, AppearancePreferences, AppearanceStore};
+use crate::app::account_import::{self, ImportableAccount};
+use crate::app::accounts::{self, AccountPage};
+use crate::app::api::{self, ContentProvider, ContentSearch, ContentSearchResult, ContentVersion, LoginData, ModSource, ModpackDetails, ModpackSearchFilters, ModpackSearchResult, NoRiskAccessCheck, NoRiskLaunchManifest, SharedNoRiskToken};
 use crate::app::app_data::TokenManager;
-use crate::app::cape_api::{Cape, CapeApiEndpoints};
+use crate::app::cape_api::{Cape, CapeApiEndpoints, CapeSubmission};
+use crate::app::cape_moderation;
+use crate::app::crash_upload::{self, ClientExit};
+use crate::app::data_dir::{self, DataDirRepair, DataDirStatus};
+use crate::app::log_privacy::LogRedactor;
+use crate::app::accessibility::{self, AccessibilityInfo};
+use crate::app::action_history::{self, ActionHistory, ActionSummary, ReversibleAction};
+use crate::app::archive_preview::{self, ArchivePreview};
+use crate::app::benchmark::{self, BenchmarkResult};
+use crate::app::content_check::{CheckContentParams, ContentCheck, ContentType};
+use crate::app::installed_content::{self, InstalledContent};
+use crate::app::{content_blacklist::{self, ContentBlacklist}, i18n, jump_list, log_windows, progress_overlay, single_instance, steam, thumbnails};
+use crate::app::profile_export::{self, ExportedProfile};
+use crate::app::profile_import::{self, ImportStaging};
+use crate::app::profile_names;
+use crate::app::startup::{self, StartupPhase, StateReady};
+use crate::app::memory::{self, MemoryRecommendation};
+use crate::app::notifications::{self, NotificationCategory};
+use crate::app::profile_icons;
+use crate::app::update_changelogs::{self, PendingChangelogs};
+use crate::app::version_fallback::{self, FallbackMod, FeaturedModsFiltered};
+use crate::app::task_manager::{TaskHandle, TaskInfo, TaskKind, TaskManager, TaskPhase};
+use crate::app::profile_state::{self, ProfileChange};
+use crate::app::profile_statistics::{self, ProfileStatistics, ProfileStatisticsStore};
+use crate::app::settings_lock::{self, PinAttempts, SettingsSessions};
+use crate::app::mod_dependencies::{self, ModRemovalResult, OrphanCandidate};
+use crate::app::mod_list::{self, ModListFormat};
 use crate::app::mclogs_api::{McLogsApiEndpoints, McLogsUploadResponse};
-use crate::app::modrinth_api::{CustomMod, ModInfo, ModrinthApiEndpoints, ModrinthProject, ModrinthSearchRequestParams, ModrinthModsSearchResponse};
+use crate::app::modrinth_api::{CustomMod, ModInfo, ModInstallResult, ModUpdate, ModrinthApiEndpoints, ModrinthProject, ModrinthSearchRequestParams, ModrinthModsSearchResponse};
 use crate::minecraft::auth;
-use crate::utils::percentage_of_total_memory;
-
-use super::{api::{ApiEndpoints, CustomServersResponse, FeaturedServer, LoaderMod, WhitelistSlots}, app_data::{self, LauncherOptions, LauncherProfiles}, modrinth_api::{Datapack, DatapackInfo, ModrinthDatapacksSearchResponse, ModrinthResourcePacksSearchResponse, ModrinthShadersSearchResponse, ResourcePack, ResourcePackInfo, Shader, ShaderInfo}};
-
+use crate::minecraft::fake_process::{self, FakeScript};
+use crate::minecraft::launch_hooks::{self, LaunchContext};
+use crate::minecraft::java::JavaRuntime;
+use crate::minecraft::install_manifest::InstallManifest;
+use crate::minecraft::install_size::{self, InstallSizeEstimate};
+use crate::minecraft::install_state::{self, InstallState};
+use crate::minecraft::integrity::{self, IntegrityReport, RepairScope};
+use crate::minecraft::launch_hotfixes::HotfixConsentStore;
+use crate::utils::{self, create_zip_archive, file_utils, get_system_ram_mb, percentage_of_total_memory, sha1sum, EndpointMetrics, JsonStore};
+
+use super::{api::{ApiEndpoints, CustomServersResponse, FeaturedServer, LoaderMod, StandardVersion, WhitelistSlots}, app_data::{self, CrashUploadSettings, DuplicatePackImport, IgnoredUpdate, LauncherOptions, LauncherProfile, LauncherProfiles, PackImportCheck, PackImportResult, PackSource, ProfileFieldChange, ProfileOrigin, ProfileProvenance, ProfileSummary}, modrinth_api::{Datapack, DatapackInfo, ModrinthDatapacksSearchResponse, ModrinthResourcePacksSearchResponse, ModrinthShadersSearchResponse, ResourcePack, ResourcePackInfo, Shader, ShaderInfo}};
+
+/// Running game of a branch, it stays registered until its runner thread exits so a relaunch can't overlap with it
 struct RunnerInstance {
-    terminator: tokio::sync::oneshot::Sender<()>,
+    /// Taken once the game was asked to stop
+    terminator: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+#[derive(serde::Serialize)]
+struct ClientStatus {
+    running: bool,
+    /// The first of `branches`
+    branch: Option<String>,
+    /// Branches with a running game, sorted
+    branches: Vec<String>,
 }
 
 struct AppState {
-    runner_instance: Arc<Mutex<Option<RunnerInstance>>>,
+    /// Games that are installing or running by branch, branches share their game dir so each can only run once
+    runner_instances: Arc<Mutex<HashMap<String, RunnerInstance>>>,
+    task_manager: Arc<TaskManager>,
     forwarding_manager_process: Arc<Mutex<Option<Child>>>,
     custom_server_process: Arc<Mutex<Option<Child>>>,
+    action_history: Arc<ActionHistory>,
+    settings_sessions: Arc<SettingsSessions>,
+    pin_attempts: Arc<PinAttempts>,
 }
 
 
@@ -95,20 +149,42 @@ fn open_url(url: &str, size: (u32, u32), handle: tauri::AppHandle) -> Result<(),
     Ok(())
 }
 
+#[tauri::command]
+fn open_log_window(process_id: &str, title: &str, handle: tauri::AppHandle) -> Result<(), String> {
+    log_windows::open(&handle, process_id, title)
+        .map_err(|e| format!("unable to open log window: {:?}", e))
+}
+
+#[tauri::command]
+fn open_progress_overlay(profile_id: &str, handle: tauri::AppHandle) -> Result<(), String> {
+    progress_overlay::open(&handle, profile_id)
+        .map_err(|e| format!("unable to open progress overlay: {:?}", e))
+}
+
+#[tauri::command]
+fn close_progress_overlay(handle: tauri::AppHandle) -> Result<(), String> {
+    progress_overlay::close(&handle)
+        .map_err(|e| format!("unable to close progress overlay: {:?}", e))
+}
+
+#[tauri::command]
+async fn send_notification(category: NotificationCategory, title: &str, body: &str) -> Result<(), String> {
+    notifications::notify(category, title, body).await;
+    Ok(())
+}
+
 #[tauri::command]
 async fn upload_cape(norisk_token: &str, uuid: &str, window: tauri::Window) -> Result<(), String> {
     debug!("Uploading Cape...");
-    use tauri::api::dialog::blocking::FileDialogBuilder; // Note the updated import
 
-    let dialog_result = FileDialogBuilder::new()
-        .set_title("Select Cape")
-        .add_filter("Pictures", &["png"])
-        .pick_file();
-
-    // dialog_result will be of type Option<PathBuf> now.
+    let Some(cape_path) = file_utils::pick_file("Select Cape", "Pictures", &["png"]).await else {
+        return Ok(());
+    };
 
-    match CapeApiEndpoints::upload_cape(norisk_token, uuid, dialog_result.unwrap()).await {
+    match CapeApiEndpoints::upload_cape(norisk_token, uuid, cape_path).await {
         Ok(result) => {
+            // the review of the cape is notified by the cape moderation poll
+            cape_moderation::poll_now();
             message(Some(&window), "Cape Upload", result);
         }
         Err(err) => {
@@ -137,20 +213,28 @@ async fn equip_cape(norisk_token: &str, uuid: &str, hash: &str, window: tauri::W
 async fn get_featured_mods(branch: &str, mc_version: &str, window: tauri::Window) -> Result<Vec<ModInfo>, String> {
     debug!("Getting Featured Mods...");
 
+    let fallback_rule = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default().mod_version_fallback;
     match ApiEndpoints::norisk_featured_mods(&branch).await {
         Ok(result) => {
             // fetch mod info for each mod
             let mut mod_infos: Vec<ModInfo> = Vec::new();
+            let mut filtered = FeaturedModsFiltered { mc_version: mc_version.to_string(), ..Default::default() };
             for mod_id in result {
                 match ModrinthApiEndpoints::get_mod_info(&*mod_id).await {
-                    Ok(mod_info) => {
+                    Ok(mut mod_info) => {
                         // Filter featured mods based on mc version
                         match &mod_info.game_versions {
                             Some(versions) => {
                                 if versions.contains(&mc_version.to_string()) {
                                     mod_infos.push(mod_info);
+                                } else if let Some(game_version) = version_fallback::nearest_compatible(fallback_rule, mc_version, versions) {
+                                    debug!("Featured mod {} does not support version {}, offering it for {}", mod_info.title, mc_version, game_version);
+                                    filtered.fallback.push(FallbackMod { title: mod_info.title.clone(), game_version: game_version.clone() });
+                                    mod_info.fallback_game_version = Some(game_version);
+                                    mod_infos.push(mod_info);
                                 } else {
                                     debug!("Featured mod {} does not support version {}", mod_info.title, mc_version);
+                                    filtered.dropped.push(mod_info.title);
                                 }
                             }
                             _ => {
@@ -163,6 +247,12 @@ async fn get_featured_mods(branch: &str, mc_version: &str, window: tauri::Window
                     }
                 }
             }
+            if !filtered.is_empty() {
+                warn!("Featured mods of {} filtered for {}: {:?}", branch, mc_version, filtered);
+                if let Err(err) = window.emit("featured-mods-filtered", filtered) {
+                    error!("Failed to emit filtered featured mods: {:?}", err);
+                }
+            }
             Ok(mod_infos)
         }
         Err(err) => {
@@ -329,20 +419,255 @@ async fn get_mod_info(slug: String, window: Window) -> Result<ModInfo, String> {
     }
 }
 
+/// Fails if restricted mode is enabled, for everything that would allow mods outside of the NoRisk packs
+async fn ensure_unrestricted(action: &str) -> Result<(), String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    if options.restricted_mode.enabled {
+        return Err(format!("{} is not allowed in restricted mode", action));
+    }
+    Ok(())
+}
+
+/// Mods the pack requires can't be replaced by another version of them, `pack_mods` are the mods of the launch manifest
+fn ensure_not_required(custom_mod: &CustomMod, pack_mods: &[LoaderMod]) -> Result<(), String> {
+    match pack_mods.iter().find(|pack_mod| pack_mod.required && pack_mod.is_same_slug(&custom_mod.value)) {
+        Some(pack_mod) => Err(format!("{} is required by the pack and can't be replaced", pack_mod.name)),
+        None => Ok(()),
+    }
+}
+
 #[tauri::command]
-async fn install_mod_and_dependencies(slug: &str, params: &str, required_mods: Vec<LoaderMod>, window: Window) -> Result<CustomMod, String> {
+async fn install_mod_and_dependencies(slug: &str, params: &str, profile_id: &str, required_mods: Vec<LoaderMod>, window: Window, app_state: tauri::State<'_, AppState>) -> Result<CustomMod, String> {
     info!("Installing Mod And Dependencies...");
-    match ModrinthApiEndpoints::install_mod_and_dependencies(slug, params, &required_mods).await {
-        Ok(installed_mod) => {
-            Ok(installed_mod)
+    ensure_unrestricted("Installing mods").await?;
+    // the required mods of the frontend only decide which dependencies are skipped, the pack's own list decides what may be replaced
+    let launch_manifest = profile_launch_manifest(profile_id).await?;
+    app_state.task_manager.run(TaskKind::Install, format!("Installing mod {}", slug), |_| async move {
+        match ModrinthApiEndpoints::install_mod_and_dependencies(slug, params, &required_mods).await {
+            Ok(installed_mod) => {
+                ensure_not_required(&installed_mod, &launch_manifest.mods)?;
+                check_content_blacklist(&installed_mod).await?;
+                Ok(installed_mod)
+            }
+            Err(err) => {
+                message(Some(&window), "Modrinth Error", err.to_string());
+                Err(err.to_string())
+            }
         }
-        Err(err) => {
-            message(Some(&window), "Modrinth Error", err.to_string());
-            Err(err.to_string())
+    }).await
+}
+
+/// Refuses an installed mod or one of its dependencies if NoRisk blocked it, unless the user overrides the blacklist
+async fn check_content_blacklist(custom_mod: &CustomMod) -> Result<(), String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let blacklist = ContentBlacklist::get().await;
+
+    let mut mods = vec![custom_mod];
+    while let Some(current) = mods.pop() {
+        let source = &current.value.source;
+        let blocked = blacklist.find(None, &[&source.get_slug()], &[&source.get_version()]);
+        if let Some(err) = content_blacklist::refuse(blocked, &current.title, options.ignore_content_blacklist) {
+            return Err(err);
+        }
+        mods.extend(current.dependencies.iter());
+    }
+    Ok(())
+}
+
+/// Update checks running against modrinth at the same time
+const UPDATE_CHECK_REQUESTS: usize = 4;
+
+/// Newer versions of the profile's modrinth mods, respecting the update channel of every mod
+#[tauri::command]
+async fn check_modrinth_updates(profile_id: &str) -> Result<Vec<ModUpdate>, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode)
+        .iter()
+        .find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+    let (game_version, loader) = profile_game_target(profile_id).await?;
+    let (game_version, loader) = (game_version.as_str(), loader.as_str());
+
+    let checks = profile.mods.iter()
+        .filter(|custom_mod| matches!(&custom_mod.value.source, ModSource::Repository { repository, .. } if repository == "modrinth"))
+        .map(|custom_mod| async move {
+            match ModrinthApiEndpoints::check_mod_update(custom_mod, game_version, loader).await {
+                Ok(update) => update,
+                Err(e) => {
+                    warn!("Failed to check {} for updates: {:?}", custom_mod.title, e);
+                    None
+                }
+            }
+        });
+    let updates: Vec<ModUpdate> = stream::iter(checks).buffer_unordered(UPDATE_CHECK_REQUESTS).collect::<Vec<_>>().await.into_iter()
+        .flatten()
+        .filter(|update| !profile.ignored_updates.iter().any(|ignored| ignored.slug.eq_ignore_ascii_case(&update.slug) && ignored.version_id == update.version_id))
+        .collect();
+
+    update_changelogs::cache(profile_id, &updates).await;
+
+    debug!("Found {} mod updates for profile {}", updates.len(), profile_id);
+    Ok(updates)
+}
+
+/// Changelogs of the updates found by the last update check of the profile
+#[tauri::command]
+async fn get_pending_update_changelogs(profile_id: &str) -> Result<PendingChangelogs, String> {
+    Ok(update_changelogs::pending(profile_id).await)
+}
+
+/// Stops suggesting the given version of a mod, a newer version is suggested again
+#[tauri::command]
+async fn ignore_mod_update(profile_id: &str, slug: String, version_id: String) -> Result<(), String> {
+    update_profile(profile_id, |profile| {
+        // only the newest ignored version of a mod matters
+        profile.ignored_updates.retain(|ignored| !ignored.slug.eq_ignore_ascii_case(&slug));
+        profile.ignored_updates.push(IgnoredUpdate { slug, version_id });
+    }).await
+}
+
+/// Removes a mod from a profile. Dependencies only that mod required are returned for confirmation first,
+/// `delete_orphans` then decides whether they are deleted too or kept as mods of their own.
+#[tauri::command]
+async fn remove_mod_with_orphans(profile_id: &str, mod_id: &str, delete_orphans: Option<bool>, app_state: tauri::State<'_, AppState>) -> Result<ModRemovalResult, String> {
+    let delete_orphans = match delete_orphans {
+        Some(delete_orphans) => delete_orphans,
+        None => {
+            let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+            let launcher_profiles = profile_state::profiles().await;
+            let profile = launcher_profiles.profiles(options.experimental_mode)
+                .iter()
+                .find(|profile| profile.id == profile_id)
+                .ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+            let orphans: Vec<OrphanCandidate> = mod_dependencies::orphans(&profile.mods, mod_id).iter()
+                .map(|orphan| OrphanCandidate { slug: orphan.value.source.get_slug(), title: orphan.title.clone() })
+                .collect();
+            if !orphans.is_empty() {
+                return Ok(ModRemovalResult::RequiresConfirmation { orphans });
+            }
+            false
         }
+    };
+
+    snapshot_configs(&profile_game_dir(profile_id).await?, format!("Before removing {}", mod_id)).await;
+    let mut removed = Vec::new();
+    update_profile_undoable(&app_state.action_history, profile_id, None, format!("Removed {}", mod_id), |profile| removed = mod_dependencies::remove(&mut profile.mods, mod_id, delete_orphans)).await?;
+    info!("Removed {:?} from profile {}", removed, profile_id);
+    Ok(ModRemovalResult::Removed { removed })
+}
+
+/// Enables or disables an optional mod of the pack for the profile, mods the pack requires can't be disabled
+#[tauri::command]
+async fn set_norisk_mod_status(profile_id: &str, slug: &str, enabled: bool) -> Result<(), String> {
+    let launch_manifest = profile_launch_manifest(profile_id).await?;
+    let pack_mod = launch_manifest.mods.iter()
+        .find(|pack_mod| pack_mod.source.get_slug().eq_ignore_ascii_case(slug))
+        .ok_or_else(|| format!("{} is not part of the pack", slug))?;
+    if pack_mod.required && !enabled {
+        return Err(format!("{} is required by the pack and can't be disabled", pack_mod.name));
+    }
+
+    update_profile(profile_id, |profile| {
+        profile.mods.retain(|custom_mod| !custom_mod.is_disabled_pack_mod(slug));
+        if !enabled {
+            profile.mods.push(CustomMod::disabled_pack_mod(slug));
+        }
+    }).await
+}
+
+/// Launch manifest of the profile's branch with the loader version the profile launches
+async fn profile_launch_manifest(profile_id: &str) -> Result<NoRiskLaunchManifest, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode).iter().find(|profile| profile.id == profile_id).ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+    let mut launch_manifest = branch_launch_manifest(&options, &profile.branch).await?;
+    apply_profile_loader(&mut launch_manifest, profile);
+    Ok(launch_manifest)
+}
+
+/// Replaces the loader version of the branch with the one the profile was synced to
+fn apply_profile_loader(launch_manifest: &mut NoRiskLaunchManifest, profile: &LauncherProfile) {
+    if let Some(loader_version) = &profile.loader_version {
+        launch_manifest.build.fabric_loader_version = loader_version.clone();
+    }
+}
+
+/// Launch manifest of a branch for the selected account, or the one of the branch's last install without a connection
+async fn branch_launch_manifest(options: &LauncherOptions, branch: &str) -> Result<NoRiskLaunchManifest, String> {
+    let (token, uuid) = selected_account_token(options)?;
+    match ApiEndpoints::launch_manifest(branch, &token, &uuid).await {
+        Ok(launch_manifest) => Ok(launch_manifest),
+        // a profile prepared for offline play still knows what it was installed with, but a rejected token
+        // or a revoked access must not be worked around
+        Err(err) if api::is_unreachable(&err) => InstallManifest::load_launch_manifest(&options.data_path_buf().join("gameDir").join(branch)).await
+            .ok_or_else(|| format!("unable to request launch manifest: {:?}", err))
+            .map(|launch_manifest| {
+                warn!("Using the launch manifest of the last install of {}: {:?}", branch, err);
+                launch_manifest
+            }),
+        Err(err) => Err(format!("unable to request launch manifest: {:?}", err)),
     }
 }
 
+/// NoRisk token of the selected account for the current mode and the account's uuid
+fn selected_account_token(options: &LauncherOptions) -> Result<(String, String), String> {
+    let login_data = options.accounts.iter().find(|account| Some(&account.uuid) == options.current_uuid.as_ref()).ok_or_else(|| "no account selected".to_string())?;
+    let token = if options.experimental_mode { login_data.experimental_token.clone().unwrap_or_default() } else { login_data.norisk_token.clone() };
+    Ok((token, login_data.uuid.clone()))
+}
+
+/// Minecraft version and mod loader of the profile's branch, taken from its launch manifest
+async fn profile_game_target(profile_id: &str) -> Result<(String, String), String> {
+    Ok(game_target(&profile_launch_manifest(profile_id).await?))
+}
+
+/// Minecraft version and mod loader of a launch manifest
+fn game_target(launch_manifest: &NoRiskLaunchManifest) -> (String, String) {
+    (launch_manifest.build.mc_version.clone(), launch_manifest.subsystem.loader().to_string())
+}
+
+/// Adds a specific modrinth version to a profile, unless it doesn't fit the profile's game version or loader
+/// and the user hasn't confirmed to install it anyway
+#[tauri::command]
+async fn add_modrinth_mod_to_profile(profile_id: &str, version_id: &str, confirm_incompatible: bool, app_state: tauri::State<'_, AppState>) -> Result<ModInstallResult, String> {
+    ensure_unrestricted("Installing mods").await?;
+    let version = ModrinthApiEndpoints::get_version(version_id)
+        .await
+        .map_err(|e| format!("unable to load mod version: {:?}", e))?;
+
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let blacklist = ContentBlacklist::get().await;
+    let sha1 = version.files.first().map(|file| file.hashes.sha1.as_str());
+    let blocked = blacklist.find(sha1, &[&version.project_id], &[&version.id, &version.version_number]);
+    if let Some(err) = content_blacklist::refuse(blocked, &version.name, options.ignore_content_blacklist) {
+        return Err(err);
+    }
+
+    let launch_manifest = profile_launch_manifest(profile_id).await?;
+    let (game_version, loader) = game_target(&launch_manifest);
+    let warnings = version.compatibility_warnings(&game_version, &loader);
+    if !warnings.is_empty() && !confirm_incompatible {
+        return Ok(ModInstallResult::RequiresConfirmation { warnings });
+    }
+
+    let installed_mod = app_state.task_manager.run(TaskKind::Install, format!("Installing mod {}", version.name), |_| async {
+        ModrinthApiEndpoints::install_mod_version(&version, &game_version, &loader, &launch_manifest.mods)
+            .await
+            .map_err(|e| format!("unable to install mod: {:?}", e))
+    }).await?;
+    ensure_not_required(&installed_mod, &launch_manifest.mods)?;
+
+    snapshot_configs(&profile_game_dir(profile_id).await?, format!("Before adding {}", installed_mod.title)).await;
+    let profile_mod = installed_mod.clone();
+    update_profile(profile_id, |profile| {
+        profile.mods.retain(|existing| existing.value.name != profile_mod.value.name);
+        profile.mods.push(profile_mod);
+    }).await?;
+
+    Ok(ModInstallResult::Installed { installed_mod })
+}
+
 #[tauri::command]
 async fn get_project_version(slug: &str, params: &str, window: Window) -> Result<Vec<ModrinthProject>, String> {
     info!("Searching Project Version...");
@@ -358,6 +683,40 @@ async fn get_project_version(slug: &str, params: &str, window: Window) -> Result
     }
 }
 
+fn content_provider(provider: &str) -> Result<&'static dyn ContentProvider, String> {
+    api::content_provider(provider).ok_or_else(|| format!("unknown content provider {}", provider))
+}
+
+/// Searches contents of any type on one of the content providers
+#[tauri::command]
+async fn search_content(provider: &str, search: ContentSearch) -> Result<ContentSearchResult, String> {
+    content_provider(provider)?.search(&search)
+        .await
+        .map_err(|e| format!("unable to search contents: {:?}", e))
+}
+
+#[tauri::command]
+async fn get_content_versions(provider: &str, project: &str, game_version: Option<String>, loader: Option<String>) -> Result<Vec<ContentVersion>, String> {
+    content_provider(provider)?.get_versions(project, game_version.as_deref(), loader.as_deref())
+        .await
+        .map_err(|e| format!("unable to load content versions: {:?}", e))
+}
+
+/// Finds out where a local file comes from by asking every provider for its hash.
+/// Returns the id of the provider and the version, or `None` if no provider knows the file.
+#[tauri::command]
+async fn resolve_content_file(path: PathBuf) -> Result<Option<(String, ContentVersion)>, String> {
+    let sha1 = sha1sum(&path).map_err(|e| format!("unable to hash file: {:?}", e))?;
+    for provider in api::content_providers() {
+        match provider.resolve_by_hash(&sha1).await {
+            Ok(Some(version)) => return Ok(Some((provider.id().to_string(), version))),
+            Ok(None) => {}
+            Err(err) => warn!("Failed to resolve {:?} on {}: {:?}", path, provider.id(), err),
+        }
+    }
+    Ok(None)
+}
+
 #[tauri::command]
 async fn search_shaders(params: ModrinthSearchRequestParams, window: Window) -> Result<ModrinthShadersSearchResponse, String> {
     debug!("Searching Shaders...");
@@ -388,18 +747,35 @@ async fn get_shader_info(slug: String, window: Window) -> Result<ShaderInfo, Str
     }
 }
 
+/// Searches modpacks for the modpack browser on one of the content providers
+#[tauri::command]
+async fn search_modpacks(provider: &str, filters: ModpackSearchFilters) -> Result<ModpackSearchResult, String> {
+    content_provider(provider)?.search_modpacks(&filters)
+        .await
+        .map_err(|e| format!("unable to search modpacks: {:?}", e))
+}
+
+#[tauri::command]
+async fn get_modpack_details(provider: &str, project: &str) -> Result<ModpackDetails, String> {
+    content_provider(provider)?.get_modpack_details(project)
+        .await
+        .map_err(|e| format!("unable to load modpack: {:?}", e))
+}
+
 #[tauri::command]
-async fn install_shader(slug: &str, params: &str, window: Window) -> Result<Shader, String> {
+async fn install_shader(slug: &str, params: &str, window: Window, app_state: tauri::State<'_, AppState>) -> Result<Shader, String> {
     info!("Installing Shader...");
-    match ModrinthApiEndpoints::install_shader(slug, params).await {
-        Ok(installed_shader) => {
-            Ok(installed_shader)
-        }
-        Err(err) => {
-            message(Some(&window), "Modrinth Error", err.to_string());
-            Err(err.to_string())
+    app_state.task_manager.run(TaskKind::Install, format!("Installing shader {}", slug), |_| async move {
+        match ModrinthApiEndpoints::install_shader(slug, params).await {
+            Ok(installed_shader) => {
+                Ok(installed_shader)
+            }
+            Err(err) => {
+                message(Some(&window), "Modrinth Error", err.to_string());
+                Err(err.to_string())
+            }
         }
-    }
+    }).await
 }
 
 #[tauri::command]
@@ -433,17 +809,19 @@ async fn get_resourcepack_info(slug: String, window: Window) -> Result<ResourceP
 }
 
 #[tauri::command]
-async fn install_resourcepack(slug: &str, params: &str, window: Window) -> Result<ResourcePack, String> {
+async fn install_resourcepack(slug: &str, params: &str, window: Window, app_state: tauri::State<'_, AppState>) -> Result<ResourcePack, String> {
     info!("Installing ResourcePack...");
-    match ModrinthApiEndpoints::install_resourcepack(slug, params).await {
-        Ok(installed_resourcepack) => {
-            Ok(installed_resourcepack)
-        }
-        Err(err) => {
-            message(Some(&window), "Modrinth Error", err.to_string());
-            Err(err.to_string())
+    app_state.task_manager.run(TaskKind::Install, format!("Installing resourcepack {}", slug), |_| async move {
+        match ModrinthApiEndpoints::install_resourcepack(slug, params).await {
+            Ok(installed_resourcepack) => {
+                Ok(installed_resourcepack)
+            }
+            Err(err) => {
+                message(Some(&window), "Modrinth Error", err.to_string());
+                Err(err.to_string())
+            }
         }
-    }
+    }).await
 }
 
 #[tauri::command]
@@ -477,17 +855,19 @@ async fn get_datapack_info(slug: String, window: Window) -> Result<DatapackInfo,
 }
 
 #[tauri::command]
-async fn install_datapack(slug: &str, params: &str, world: &str, window: Window) -> Result<Datapack, String> {
+async fn install_datapack(slug: &str, params: &str, world: &str, window: Window, app_state: tauri::State<'_, AppState>) -> Result<Datapack, String> {
     info!("Installing Datapack...");
-    match ModrinthApiEndpoints::install_datapack(slug, params, world).await {
-        Ok(installed_datapack) => {
-            Ok(installed_datapack)
-        }
-        Err(err) => {
-            message(Some(&window), "Modrinth Error", err.to_string());
-            Err(err.to_string())
+    app_state.task_manager.run(TaskKind::Install, format!("Installing datapack {}", slug), |_| async move {
+        match ModrinthApiEndpoints::install_datapack(slug, params, world).await {
+            Ok(installed_datapack) => {
+                Ok(installed_datapack)
+            }
+            Err(err) => {
+                message(Some(&window), "Modrinth Error", err.to_string());
+                Err(err.to_string())
+            }
         }
-    }
+    }).await
 }
 
 #[tauri::command]
@@ -532,6 +912,12 @@ async fn request_trending_capes(norisk_token: &str, uuid: &str, alltime: u32, li
     }
 }
 
+#[tauri::command]
+async fn get_my_cape_submissions(norisk_token: &str, uuid: &str) -> Result<Vec<CapeSubmission>, String> {
+    CapeApiEndpoints::request_cape_submissions(norisk_token, uuid).await
+        .map_err(|e| format!("unable to request cape submissions: {:?}", e))
+}
+
 #[tauri::command]
 async fn request_owned_capes(norisk_token: &str, uuid: &str, limit: u32) -> Result<Vec<Cape>, String> {
     match CapeApiEndpoints::request_owned_capes(norisk_token, uuid, limit).await {
@@ -602,8 +988,7 @@ async fn get_options() -> Result<LauncherOptions, String> {
 
 #[tauri::command]
 async fn get_launcher_profiles() -> Result<LauncherProfiles, String> {
-    let config_dir = LAUNCHER_DIRECTORY.config_dir();
-    let launcher_profiles = LauncherProfiles::load(config_dir).await.unwrap_or_default(); // default to basic launcher_profiles defaults if unable to load
+    let launcher_profiles = profile_state::profiles().await; // default to basic launcher_profiles defaults if unable to load
 
     Ok(launcher_profiles)
 }
@@ -623,13 +1008,17 @@ async fn get_custom_mods_folder(options: LauncherOptions, branch: &str, mc_versi
 
 #[tauri::command]
 async fn save_custom_mods_to_folder(options: LauncherOptions, branch: &str, mc_version: &str, file: FileData) -> Result<(), String> {
+    ensure_unrestricted("Importing custom mods").await?;
     let file_path = options.data_path_buf().join("custom_mods").join(format!("{}-{}", branch, mc_version)).join(file.name.clone());
 
-    info!("Saving {} to {}-{} custom mods folder.", file.name.clone(), branch, mc_version);
+    info!("Saving {} ({:?}) to {}-{} custom mods folder.", file.name.clone(), file_utils::document_host_path(&PathBuf::from(&file.location)), branch, mc_version);
 
     if let Err(err) = fs::copy(PathBuf::from(file.location), &file_path).await {
         return Err(format!("Error saving custom mod {}: {}", file.name, err));
     }
+    if let Some(folder) = file_path.parent() {
+        file_utils::invalidate_folder_scan(folder);
+    }
 
     Ok(())
 }
@@ -656,6 +1045,9 @@ async fn save_custom_shaders_to_folder(options: LauncherOptions, branch: &str, f
     if let Err(err) = fs::copy(PathBuf::from(file.location), &file_path).await {
         return Err(format!("Error saving custom shader {}: {}", file.name, err));
     }
+    if let Some(folder) = file_path.parent() {
+        file_utils::invalidate_folder_scan(folder);
+    }
 
     Ok(())
 }
@@ -682,6 +1074,9 @@ async fn save_custom_resourcepacks_to_folder(options: LauncherOptions, branch: &
     if let Err(err) = fs::copy(PathBuf::from(file.location), &file_path).await {
         return Err(format!("Error saving custom resourcepack {}: {}", file.name, err));
     }
+    if let Some(folder) = file_path.parent() {
+        file_utils::invalidate_folder_scan(folder);
+    }
 
     Ok(())
 }
@@ -708,6 +1103,9 @@ async fn save_custom_datapacks_to_folder(options: LauncherOptions, branch: &str,
     if let Err(err) = fs::copy(PathBuf::from(file.location), &file_path).await {
         return Err(format!("Error saving custom datapack {}: {}", file.name, err));
     }
+    if let Some(folder) = file_path.parent() {
+        file_utils::invalidate_folder_scan(folder);
+    }
 
     Ok(())
 }
@@ -738,27 +1136,29 @@ async fn get_player_skins(uuid: String) -> Result<Vec<String>, String> {
 
 #[tauri::command]
 async fn save_player_skin(location: String, slim: bool, access_token: String) -> Result<(), String> {
-    let file_data = match tokio::fs::read(&location).await {
-        Ok(data) => data,
-        Err(e) => return Err(e.to_string()),
-    };
+    account_appearance::upload_skin(Path::new(&location), slim, &access_token).await
+        .map_err(|e| e.to_string())
+}
 
-    let part = Part::bytes(file_data)
-        .file_name("skin.png");
+#[tauri::command]
+async fn get_account_appearance_preferences(uuid: &str) -> Result<AppearancePreferences, String> {
+    let store = AppearanceStore::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    Ok(store.get(uuid))
+}
 
-    let response = HTTP_CLIENT.post("https://api.minecraftservices.com/minecraft/profile/skins")
-        .bearer_auth(access_token)
-        .multipart(Form::new().text("variant", if slim { "slim" } else { "classic" }).part("file", part))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+/// Stores the skin and cape an account should wear, `applyOnSwitch` makes switching to the account apply them
+#[tauri::command]
+async fn set_account_appearance_preferences(uuid: &str, preferences: AppearancePreferences) -> Result<AppearancePreferences, String> {
+    account_appearance::set_preferences(uuid, preferences).await
+        .map_err(|e| format!("unable to store appearance preferences: {:?}", e))
+}
 
-    if response.status().is_success() {
-        info!("Skin {} saved successfully.", &location);
-        Ok(())
-    } else {
-        Err(format!("Failed to save the new skin. Status code: {}", response.status()))
-    }
+/// Called after switching the active account
+#[tauri::command]
+async fn apply_account_appearance(login_data: LoginData) -> Result<(), String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    account_appearance::apply_preferences(&login_data, options.experimental_mode).await
+        .map_err(|e| format!("unable to apply appearance preferences: {:?}", e))
 }
 
 #[tauri::command]
@@ -796,8 +1196,21 @@ async fn read_remote_image_file(location: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-async fn store_options(options: LauncherOptions) -> Result<(), String> {
+async fn store_options(mut options: LauncherOptions, session_token: Option<String>, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
     let config_dir = LAUNCHER_DIRECTORY.config_dir();
+
+    // restricted mode and the settings PIN can only be changed with their PIN, never by storing the options
+    let stored_options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    options.restricted_mode = stored_options.restricted_mode.clone();
+    options.settings_lock = stored_options.settings_lock.clone();
+    if settings_lock::changes_settings(&stored_options, &options) {
+        app_state.settings_sessions.ensure_unlocked(&stored_options, session_token.as_deref(), "Changing settings")?;
+    }
+    if options.restricted_mode.enabled {
+        options.experimental_mode = false;
+        options.ignore_content_blacklist = false;
+    }
+
     options.store(config_dir)
         .await
         .map_err(|e| format!("unable to store config data: {:?}", e))?;
@@ -805,151 +1218,931 @@ async fn store_options(options: LauncherOptions) -> Result<(), String> {
     Ok(())
 }
 
+/// Enables restricted mode, it can only be disabled again with the same PIN
 #[tauri::command]
-async fn store_launcher_profiles(launcher_profiles: LauncherProfiles) -> Result<(), String> {
+async fn enable_restricted_mode(pin: &str) -> Result<(), String> {
     let config_dir = LAUNCHER_DIRECTORY.config_dir();
-    launcher_profiles.store(config_dir)
+    let mut options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    options.restricted_mode.enable(pin).map_err(|e| format!("unable to enable restricted mode: {}", e))?;
+    options.experimental_mode = false;
+    options.ignore_content_blacklist = false;
+    options.store(config_dir)
         .await
-        .map_err(|e| format!("unable to store launcher_profiles data: {:?}", e))?;
-
-    Ok(())
+        .map_err(|e| format!("unable to store config data: {:?}", e))
 }
 
 #[tauri::command]
-async fn check_maintenance_mode() -> Result<bool, String> {
-    let maintenance_mode = ApiEndpoints::norisk_maintenance_mode()
+async fn disable_restricted_mode(pin: &str, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    app_state.pin_attempts.ensure_allowed()?;
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    if let Err(e) = options.restricted_mode.disable(pin) {
+        app_state.pin_attempts.failed();
+        return Err(format!("unable to disable restricted mode: {}", e));
+    }
+    app_state.pin_attempts.succeeded();
+    options.store(config_dir)
         .await
-        .map_err(|e| format!("unable to request maintenance mode: {:?}", e))?;
-    Ok(maintenance_mode)
+        .map_err(|e| format!("unable to store config data: {:?}", e))
 }
 
+/// Whether the launcher keeps everything beside its executable, the data folder can't be changed then
 #[tauri::command]
-async fn request_norisk_branches(norisk_token: &str, uuid: &str) -> Result<Vec<String>, String> {
-    let branches = ApiEndpoints::norisk_branches(norisk_token, uuid)
-        .await
-        .map_err(|e| format!("unable to request branches: {:?}", e))?;
-    Ok(branches)
+fn is_portable_mode() -> bool {
+    LAUNCHER_DIRECTORY.is_portable()
 }
 
 #[tauri::command]
-async fn enable_experimental_mode(experimental_token: &str) -> Result<bool, String> {
-    return ApiEndpoints::enable_experimental_mode(experimental_token)
-        .await
-        .map_err(|e| format!("unable to validate experimental token: {:?}", e));
+async fn check_data_directory() -> Result<DataDirStatus, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    Ok(data_dir::check(&options).await)
 }
 
 #[tauri::command]
-async fn get_launch_manifest(branch: &str, norisk_token: &str, uuid: &str) -> Result<NoRiskLaunchManifest, String> {
-    let manifest = ApiEndpoints::launch_manifest(branch, norisk_token, uuid).await
-        .map_err(|e| format!("unable to request launch manifest: {:?}", e))?;
-    Ok(manifest)
+async fn repair_data_directory(repair: DataDirRepair) -> Result<DataDirStatus, String> {
+    data_dir::repair(repair).await.map_err(|e| format!("unable to repair data folder: {:?}", e))
 }
 
+/// Measures disk and download speed, optionally sets the concurrent downloads to the recommended value
 #[tauri::command]
-async fn upload_logs(log: String) -> Result<McLogsUploadResponse, String> {
-    let log_response = McLogsApiEndpoints::upload_logs(log).await
-        .map_err(|e| format!("unable to upload logs: {:?}", e))?;
-    Ok(log_response)
+async fn run_benchmark(apply_concurrency: bool, session_token: Option<String>, app_state: tauri::State<'_, AppState>) -> Result<BenchmarkResult, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    if apply_concurrency {
+        app_state.settings_sessions.ensure_unlocked(&options, session_token.as_deref(), "Changing settings")?;
+    }
+
+    let result = benchmark::run(options.data_path_buf()).await;
+    if let Err(err) = result.store(config_dir).await {
+        error!("Failed to store benchmark: {:?}", err);
+    }
+    if apply_concurrency {
+        // the benchmark takes a while, settings changed in the meantime must not be overwritten
+        let mut options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+        options.concurrent_downloads = result.recommended_concurrent_downloads;
+        options.store(config_dir)
+            .await
+            .map_err(|e| format!("unable to store config data: {:?}", e))?;
+    }
+    Ok(result)
 }
 
+/// Timing and status codes per endpoint since the launcher started, the slowest first
 #[tauri::command]
-async fn connect_discord_intigration(options: LauncherOptions, login_data: LoginData, handle: tauri::AppHandle) -> Result<(), String> {
-    let url = format!("https://api{}.norisk.gg/api/v1/core/oauth/discord?token={}", if options.experimental_mode.clone() { "-staging" } else { "" }, if options.experimental_mode.clone() { login_data.experimental_token.unwrap() } else { login_data.norisk_token });
-    let _ = open_url(url.as_str(), (1200, 900), handle);
-
-    Ok(())
+fn get_network_metrics() -> Result<Vec<EndpointMetrics>, String> {
+    Ok(utils::network_metrics())
 }
 
 #[tauri::command]
-async fn check_discord_intigration(norisk_token: &str, uuid: &str) -> Result<bool, String> {
-    let status = ApiEndpoints::discord_link_status(norisk_token, uuid).await
-        .map_err(|e| format!("unable to check discord intigration: {:?}", e))?;
-    Ok(status)
+async fn get_last_benchmark() -> Result<Option<BenchmarkResult>, String> {
+    Ok(BenchmarkResult::load(LAUNCHER_DIRECTORY.config_dir()).await.ok())
 }
 
+/// Hotfixes waiting for a decision and the decisions made so far
 #[tauri::command]
-async fn unlink_discord_intigration(norisk_token: &str, uuid: &str) -> Result<(), String> {
-    ApiEndpoints::unlink_discord(norisk_token, uuid).await
-        .map_err(|e| format!("unable to unlink discord: {:?}", e))?;
-    Ok(())
+async fn get_launch_hotfixes() -> Result<HotfixConsentStore, String> {
+    Ok(HotfixConsentStore::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default())
 }
 
+/// Agrees to or declines a pending hotfix, hotfixes change the JVM arguments so they count as settings
 #[tauri::command]
-async fn login_norisk_microsoft(options: LauncherOptions, handle: tauri::AppHandle) -> Result<LoginData, String> {
-    let auth_prepare_response = ApiEndpoints::auth_prepare_response().await;
-    match auth_prepare_response {
-        Ok(response) => {
-            // Hier kannst du auf die Daten von 'response' zugreifen
-            let url = response.url;
-            let id = response.id;
-            let _ = open_url(url.as_str(), (1200, 800), handle);
+async fn set_hotfix_consent(id: &str, accepted: bool, session_token: Option<String>, app_state: tauri::State<'_, AppState>) -> Result<HotfixConsentStore, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    app_state.settings_sessions.ensure_unlocked(&options, session_token.as_deref(), "Deciding about hotfixes")?;
 
-            let login_data = ApiEndpoints::await_auth_response(id).await;
-            match login_data {
-                Ok(response) => {
-                    info!("Received NoRisk Auth Response");
-                    Ok(LoginData {
-                        norisk_token: if options.experimental_mode { String::from("") } else { response.norisk_token.clone() },
-                        experimental_token: Some(if options.experimental_mode { response.norisk_token.clone() } else { String::from("") }),
-                        ..response
-                    })
-                }
-                Err(err) => {
-                    Err(format!("await auth error: {:?}", err))
-                }
-            }
-        }
-        Err(err) => {
-            Err(format!("await prepare response error: {:?}", err))
+    let mut store = HotfixConsentStore::load(config_dir).await.unwrap_or_default();
+    store.decide(id, accepted).map_err(|e| format!("unable to record hotfix consent: {}", e))?;
+    store.store(config_dir)
+        .await
+        .map_err(|e| format!("unable to store hotfix consent: {:?}", e))?;
+    Ok(store)
+}
+
+/// Sets, changes or removes (`new_pin: None`) the settings PIN
+#[tauri::command]
+async fn set_settings_pin(current_pin: Option<String>, new_pin: Option<String>, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    app_state.pin_attempts.ensure_allowed()?;
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    if options.settings_lock.enabled {
+        if !current_pin.as_deref().map_or(false, |pin| options.settings_lock.verify(pin)) {
+            app_state.pin_attempts.failed();
+            return Err("unable to set settings PIN: wrong PIN".to_string());
         }
+        app_state.pin_attempts.succeeded();
     }
+    options.settings_lock.set_pin(current_pin.as_deref(), new_pin.as_deref()).map_err(|e| format!("unable to set settings PIN: {}", e))?;
+    options.store(config_dir)
+        .await
+        .map_err(|e| format!("unable to store config data: {:?}", e))
 }
 
+/// Returns a session token for the commands protected by the settings PIN
 #[tauri::command]
-async fn remove_account(login_data: LoginData) -> Result<(), String> {
-    TokenManager {}.delete_tokens(login_data);
-    Ok(())
+async fn unlock_settings(pin: &str, app_state: tauri::State<'_, AppState>) -> Result<String, String> {
+    app_state.pin_attempts.ensure_allowed()?;
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    if options.settings_lock.enabled && !options.settings_lock.verify(pin) {
+        app_state.pin_attempts.failed();
+        return Err("wrong PIN".to_string());
+    }
+    app_state.pin_attempts.succeeded();
+    Ok(app_state.settings_sessions.issue())
 }
 
-fn handle_stdout(window: &Arc<Mutex<Window>>, data: &[u8]) -> anyhow::Result<()> {
-    let data = String::from_utf8(data.to_vec())?;
-    if data.is_empty() {
-        return Ok(()); // ignore empty lines
+#[tauri::command]
+async fn store_launcher_profiles(mut launcher_profiles: LauncherProfiles) -> Result<(), String> {
+    // keep single profile stores and installs out until all profiles are replaced
+    let profile_ids: Vec<String> = launcher_profiles.main_profiles.iter().chain(launcher_profiles.experimental_profiles.iter())
+        .map(|profile| profile.id.clone())
+        .collect();
+    let _profile_locks = profile_state::lock_profiles(profile_ids.iter().map(String::as_str)).await;
+
+    // the frontend may still hold an older copy, don't lose play times recorded by the backend
+    let stored_profiles = profile_state::profiles().await;
+    launcher_profiles.keep_last_played(&stored_profiles);
+
+    // mods are mostly changed by the frontend storing all profiles, commit the configs of the affected branches first
+    let changed_branches: Vec<String> = launcher_profiles.main_profiles.iter().chain(launcher_profiles.experimental_profiles.iter())
+        .filter(|profile| stored_profiles.main_profiles.iter().chain(stored_profiles.experimental_profiles.iter())
+            .find(|stored| stored.id == profile.id)
+            .map_or(false, |stored| serde_json::to_value(&stored.mods).ok() != serde_json::to_value(&profile.mods).ok()))
+        .map(|profile| profile.branch.clone())
+        .collect();
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    for branch in changed_branches {
+        snapshot_configs(&options.data_path_buf().join("gameDir").join(&branch), "Before changing mods".to_string()).await;
     }
 
+    profile_state::update(move |profiles| {
+        let (replaced, changed) = launcher_profiles.keep_newer_revisions(profiles);
+        *profiles = launcher_profiles;
+        if !replaced.is_empty() {
+            warn!("Kept the newer stored revisions of profiles {:?}", replaced);
+        }
+        // every changed profile gets its next revision, the frontend has to know it or its next store is a conflict
+        profiles.main_profiles.iter().chain(profiles.experimental_profiles.iter())
+            .filter(|profile| replaced.contains(&profile.id) || changed.contains(&profile.id))
+            .map(|profile| ProfileChange::Profile { profile: profile.clone() })
+            .collect()
+    }).await;
+    jump_list::refresh().await;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_profile_summaries(branch: Option<String>, offset: Option<usize>, limit: Option<usize>) -> Result<Vec<ProfileSummary>, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+
+    Ok(launcher_profiles.profiles(options.experimental_mode)
+        .iter()
+        .filter(|profile| branch.as_ref().map_or(true, |branch| &profile.branch == branch))
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .map(ProfileSummary::from)
+        .collect())
+}
+
+#[tauri::command]
+async fn get_launcher_profile(profile_id: &str) -> Result<LauncherProfile, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+
+    launcher_profiles.profiles(options.experimental_mode)
+        .iter()
+        .find(|profile| profile.id == profile_id)
+        .cloned()
+        .ok_or_else(|| format!("unable to find profile {}", profile_id))
+}
+
+#[tauri::command]
+async fn get_last_sync_report(profile_id: &str) -> Result<Option<ModSyncReport>, String> {
+    ModSyncReport::load(&profile_game_dir(profile_id).await?)
+        .await
+        .map_err(|e| format!("unable to load mod sync report: {:?}", e))
+}
+
+async fn profile_game_dir(profile_id: &str) -> Result<PathBuf, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let profile = get_launcher_profile(profile_id).await?;
+    Ok(options.data_path_buf().join("gameDir").join(&profile.branch))
+}
+
+/// Answers whether contents are installed in a profile in one pass, instead of loading the profile for every content.
+/// The results are in the same order as the queries.
+#[tauri::command]
+async fn check_contents_installed(profile_id: &str, queries: Vec<CheckContentParams>) -> Result<Vec<bool>, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode)
+        .iter()
+        .find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+    let launch_manifest = profile_launch_manifest(profile_id).await?;
+
+    let game_dir = options.data_path_buf().join("gameDir").join(&profile.branch);
+    let mut check = ContentCheck::new(profile, &launch_manifest.mods, launcher_profiles.addons.get(&profile.branch), game_dir);
+    let mut installed = Vec::with_capacity(queries.len());
+    for query in &queries {
+        installed.push(check.is_installed(query).await);
+    }
+    Ok(installed)
+}
+
+/// Mods, shaders, resource packs and datapacks of a profile in one list
+#[tauri::command]
+async fn get_installed_contents(profile_id: &str) -> Result<Vec<InstalledContent>, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode)
+        .iter()
+        .find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+    let launch_manifest = profile_launch_manifest(profile_id).await?;
+
+    Ok(installed_content::installed_contents(profile, &launch_manifest.mods, launcher_profiles.addons.get(&profile.branch)))
+}
+
+#[tauri::command]
+async fn set_content_enabled(profile_id: &str, content_type: ContentType, slug: &str, enabled: bool, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if content_type != ContentType::Mod {
+        return Err("only mods can be disabled".to_string());
+    }
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let in_profile = profile_state::profiles().await.profiles(options.experimental_mode)
+        .iter()
+        .find(|profile| profile.id == profile_id)
+        .map_or(false, |profile| installed_content::has_mod(profile, slug));
+    if !in_profile {
+        // mods of the pack aren't part of the profile
+        return set_norisk_mod_status(profile_id, slug, enabled).await;
+    }
+
+    let mut found = false;
+    let description = format!("{} {}", if enabled { "Enabled" } else { "Disabled" }, slug);
+    update_profile_undoable(&app_state.action_history, profile_id, None, description, |profile| found = installed_content::set_mod_enabled(profile, slug, enabled)).await?;
+    if !found {
+        return Err(format!("unable to find mod {} in profile {}", slug, profile_id));
+    }
+    Ok(())
+}
+
+/// Lists the entries of an archive and shows the files describing it, so import dialogs can show what gets installed
+#[tauri::command]
+async fn preview_archive(path: PathBuf, max_entries: usize) -> Result<ArchivePreview, String> {
+    archive_preview::preview(&path, max_entries)
+        .await
+        .map_err(|e| format!("unable to preview archive: {:?}", e))
+}
+
+/// Thumbnails of the icons of mod jars and packs, by the path of their archive.
+/// Archives without an icon or that can't be read are mapped to `None`.
+#[tauri::command]
+async fn get_thumbnails(paths: Vec<String>, size: u32) -> Result<HashMap<String, Option<PathBuf>>, String> {
+    let size = size.clamp(16, 512);
+    let mut thumbnails = HashMap::new();
+    for path in paths {
+        let thumbnail = match thumbnails::thumbnail(Path::new(&path), size).await {
+            Ok(thumbnail) => thumbnail,
+            Err(err) => {
+                debug!("Unable to create thumbnail of {}: {:?}", path, err);
+                None
+            }
+        };
+        thumbnails.insert(path, thumbnail);
+    }
+    Ok(thumbnails)
+}
+
+#[tauri::command]
+async fn list_profile_configs(profile_id: &str) -> Result<Vec<ModConfig>, String> {
+    mod_configs::list(&profile_game_dir(profile_id).await?)
+        .await
+        .map_err(|e| format!("unable to list configs: {:?}", e))
+}
+
+#[tauri::command]
+async fn diff_config_against_default(profile_id: &str, r#mod: &str) -> Result<Vec<ConfigFileDiff>, String> {
+    mod_configs::diff(&profile_game_dir(profile_id).await?, r#mod)
+        .await
+        .map_err(|e| format!("unable to diff configs: {:?}", e))
+}
+
+/// Resets the configs of a mod to their defaults, the current configs are backed up first
+#[tauri::command]
+async fn reset_mod_config(profile_id: &str, r#mod: &str) -> Result<PathBuf, String> {
+    mod_configs::reset(&profile_game_dir(profile_id).await?, r#mod)
+        .await
+        .map_err(|e| format!("unable to reset configs: {:?}", e))
+}
+
+/// Commits the configs of the game directory if the config history is enabled, failures never block the caller
+async fn snapshot_configs(game_dir: &Path, message: String) {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    if !options.config_history {
+        return;
+    }
+    if let Err(err) = config_history::snapshot(game_dir, message).await {
+        error!("Failed to commit configs of {:?}: {:?}", game_dir, err);
+    }
+}
+
+#[tauri::command]
+async fn list_config_revisions(profile_id: &str) -> Result<Vec<ConfigRevision>, String> {
+    config_history::revisions(&profile_game_dir(profile_id).await?)
+        .await
+        .map_err(|e| format!("unable to list config revisions: {:?}", e))
+}
+
+/// Restores the configs of a profile to a revision, the current configs are committed first
+#[tauri::command]
+async fn restore_config_revision(profile_id: &str, revision: &str) -> Result<(), String> {
+    config_history::restore(&profile_game_dir(profile_id).await?, revision)
+        .await
+        .map_err(|e| format!("unable to restore config revision: {:?}", e))
+}
+
+/// Moves jars the user put into the mods folder of a profile into its custom mods folder
+#[tauri::command]
+async fn adopt_unknown_mods(profile_id: &str) -> Result<Vec<String>, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let profile = get_launcher_profile(profile_id).await?;
+    let game_dir = options.data_path_buf().join("gameDir").join(&profile.branch);
+
+    let report = ModSyncReport::load(&game_dir)
+        .await
+        .map_err(|e| format!("unable to load mod sync report: {:?}", e))?
+        .ok_or_else(|| "the profile has to be launched once before its mods can be adopted".to_string())?;
+    let custom_mods_dir = options.data_path_buf().join("custom_mods").join(format!("{}-{}", profile.branch, report.mc_version));
+
+    mod_sync::adopt_unknown_mods(&report, &game_dir, &custom_mods_dir)
+        .await
+        .map_err(|e| format!("unable to adopt unknown mods: {:?}", e))
+}
+
+/// Sets the icon of a profile, either a `builtin:<name>` icon or an image file that gets copied into the launcher
+#[tauri::command]
+async fn set_profile_icon(profile_id: &str, path_or_builtin: Option<String>) -> Result<Option<String>, String> {
+    let icon = match path_or_builtin {
+        Some(builtin) if builtin.starts_with(profile_icons::BUILTIN_PREFIX) => Some(builtin),
+        Some(path) => Some(profile_icons::store_icon(profile_id, &PathBuf::from(path)).await.map_err(|e| format!("unable to store profile icon: {:?}", e))?),
+        None => None,
+    };
+
+    let mut previous_icon = None;
+    update_profile(profile_id, |profile| previous_icon = std::mem::replace(&mut profile.icon, icon.clone())).await?;
+
+    if let Some(previous_icon) = previous_icon.filter(|previous_icon| Some(previous_icon) != icon.as_ref()) {
+        profile_icons::delete_icon(&previous_icon).await;
+    }
+
+    Ok(icon)
+}
+
+#[tauri::command]
+async fn resolve_image_path(reference: &str) -> Result<String, String> {
+    profile_icons::resolve(reference).await.map_err(|e| format!("unable to resolve image {}: {:?}", reference, e))
+}
+
+/// Stores a single profile, the whole profile list doesn't have to be sent for small changes like toggling a mod.
+/// Returns the new revision of the profile, storing a profile whose revision is outdated fails with a `PROFILE_CONFLICT`.
+/// Changes of existing profiles are recorded so they can be undone, like toggling a mod in the mods screen.
+#[tauri::command]
+async fn store_launcher_profile(mut profile: LauncherProfile, app_state: tauri::State<'_, AppState>) -> Result<u64, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let _lock = profile_state::lock_profile(&profile.id).await;
+    let profile_id = profile.id.clone();
+
+    let mut result = Ok(0);
+    let mut recorded = None;
+    profile_state::update(|launcher_profiles| {
+        let profiles = launcher_profiles.profiles_mut(options.experimental_mode);
+        match profiles.iter_mut().find(|existing| existing.id == profile.id) {
+            Some(existing) => {
+                if let Err(err) = profile_state::check_revision(existing, Some(profile.revision)) {
+                    result = Err(err);
+                    return Vec::new();
+                }
+                profile.last_played = existing.last_played.max(profile.last_played);
+                // reporting the profile gives it its next revision
+                profile.revision = existing.revision;
+                result = Ok(profile.revision + 1);
+                recorded = Some((describe_profile_change(existing, &profile), action_history::profile_changes(existing, &profile)));
+                *existing = profile.clone();
+            }
+            None => {
+                result = Ok(profile.revision + 1);
+                profiles.push(profile.clone());
+            }
+        }
+        vec![ProfileChange::Profile { profile }]
+    }).await;
+
+    match recorded {
+        Some((description, Ok(changes))) if !changes.is_empty() => app_state.action_history.record(description, ReversibleAction::ProfileChanged { profile_id, changes }),
+        Some((description, Err(err))) => warn!("Unable to record {} for undo: {:?}", description, err),
+        _ => {}
+    }
+    result
+}
+
+/// Description of a profile stored by the frontend in the action history
+fn describe_profile_change(before: &LauncherProfile, after: &LauncherProfile) -> String {
+    let toggled = installed_content::toggled_mods(before, after);
+    match toggled.as_slice() {
+        [] => format!("Changed profile {}", after.name),
+        [(slug, enabled)] => format!("{} {}", if *enabled { "Enabled" } else { "Disabled" }, slug),
+        _ => format!("Toggled {} mods", toggled.len()),
+    }
+}
+
+#[tauri::command]
+async fn get_profile_statistics(profile_id: &str) -> Result<ProfileStatistics, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let store = ProfileStatisticsStore::load(config_dir).await.unwrap_or_default();
+
+    Ok(store.get(profile_id))
+}
+
+#[tauri::command]
+async fn get_recent_profiles(limit: usize) -> Result<Vec<LauncherProfile>, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+
+    Ok(launcher_profiles.recent_profiles(options.experimental_mode, limit))
+}
+
+/// Applies a change to a single profile of the current mode and notifies the frontend
+async fn update_profile<F: FnOnce(&mut LauncherProfile)>(profile_id: &str, updater: F) -> Result<(), String> {
+    update_profile_revision(profile_id, None, updater).await
+}
+
+/// Like `update_profile`, refusing the change with a `PROFILE_CONFLICT` if the profile isn't at the expected revision anymore
+async fn update_profile_revision<F: FnOnce(&mut LauncherProfile)>(profile_id: &str, expected_revision: Option<u64>, updater: F) -> Result<(), String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let _lock = profile_state::lock_profile(profile_id).await;
+    let mut result = Err(format!("unable to find profile {}", profile_id));
+
+    profile_state::update(|launcher_profiles| {
+        let Some(profile) = launcher_profiles.find_profile_mut(options.experimental_mode, profile_id) else { return Vec::new() };
+        result = profile_state::check_revision(profile, expected_revision);
+        if result.is_err() {
+            return Vec::new();
+        }
+        updater(profile);
+        vec![ProfileChange::Profile { profile: profile.clone() }]
+    }).await;
+
+    result
+}
+
+/// Like `update_profile_revision`, remembering the fields it changed so the change can be undone
+async fn update_profile_undoable<F: FnOnce(&mut LauncherProfile)>(history: &ActionHistory, profile_id: &str, expected_revision: Option<u64>, description: String, updater: F) -> Result<(), String> {
+    let mut changes = None;
+    update_profile_revision(profile_id, expected_revision, |profile| {
+        let previous = profile.clone();
+        updater(profile);
+        changes = Some(action_history::profile_changes(&previous, profile));
+    }).await?;
+    match changes {
+        Some(Ok(changes)) if !changes.is_empty() => history.record(description, ReversibleAction::ProfileChanged { profile_id: profile_id.to_string(), changes }),
+        Some(Err(err)) => warn!("Unable to record {} for undo: {:?}", description, err),
+        _ => {}
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_profile_notes(profile_id: &str, notes: String, revision: Option<u64>, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    update_profile_undoable(&app_state.action_history, profile_id, revision, "Changed profile notes".to_string(), |profile| profile.notes = notes).await
+}
+
+#[tauri::command]
+async fn set_profile_tags(profile_id: &str, tags: Vec<String>, revision: Option<u64>, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let mut unique_tags: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+        if !unique_tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+            unique_tags.push(tag.to_string());
+        }
+    }
+    update_profile_undoable(&app_state.action_history, profile_id, revision, "Changed profile tags".to_string(), |profile| profile.tags = unique_tags).await
+}
+
+/// Moves a file of the launcher data to the trash, so deleting it can be undone
+#[tauri::command]
+async fn trash_file(path: PathBuf, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let action = action_history::trash_file(&options.data_path_buf(), &path)
+        .await
+        .map_err(|e| format!("unable to delete file: {:?}", e))?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    app_state.action_history.record(format!("Deleted {}", name), action);
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_action_history(app_state: tauri::State<'_, AppState>) -> Result<Vec<ActionSummary>, String> {
+    Ok(app_state.action_history.list())
+}
+
+/// Reverts the newest recorded action, returns `None` if there is nothing to undo
+#[tauri::command]
+async fn undo_last_action(app_state: tauri::State<'_, AppState>) -> Result<Option<ActionSummary>, String> {
+    let Some(recorded) = app_state.action_history.last() else { return Ok(None) };
+
+    let undone = match &recorded.action {
+        ReversibleAction::ProfileChanged { profile_id, changes } => {
+            let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+            let _lock = profile_state::lock_profile(profile_id).await;
+            let mut result = Err(format!("unable to find profile {}", profile_id));
+            profile_state::update(|launcher_profiles| {
+                let Some(profile) = launcher_profiles.find_profile_mut(options.experimental_mode, profile_id) else { return Vec::new() };
+                result = action_history::revert_changes(profile, changes).map_err(|e| format!("unable to undo: {:?}", e));
+                if result.is_err() {
+                    return Vec::new();
+                }
+                vec![ProfileChange::Profile { profile: profile.clone() }]
+            }).await;
+            result
+        }
+        ReversibleAction::FileTrashed { original, trashed } => {
+            action_history::restore_file(original, trashed).await.map_err(|e| format!("unable to restore file: {:?}", e))
+        }
+    };
+
+    // an action that can never be undone, e.g. because its profile was deleted or changed again, is dropped so older actions can still be undone.
+    // Other failures keep it for another try.
+    let permanent = match &recorded.action {
+        ReversibleAction::ProfileChanged { .. } => true,
+        ReversibleAction::FileTrashed { original, .. } => original.exists(),
+    };
+    if undone.is_ok() || permanent {
+        app_state.action_history.remove(&recorded.summary.id);
+    }
+    undone?;
+    info!("Undid {}", recorded.summary.description);
+    Ok(Some(recorded.summary))
+}
+
+#[tauri::command]
+async fn search_profiles(query: &str) -> Result<Vec<ProfileSummary>, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+
+    Ok(launcher_profiles.search_profiles(options.experimental_mode, query).into_iter().map(ProfileSummary::from).collect())
+}
+
+/// Returns the defaults that changed in the standard version of the profile, applying them when `apply` is set
+#[tauri::command]
+async fn sync_profile_with_standard(profile_id: &str, apply: bool, norisk_token: &str, uuid: &str) -> Result<Vec<ProfileFieldChange>, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let versions = get_standard_versions(norisk_token, uuid).await?;
+
+    if !apply {
+        let mut launcher_profiles = profile_state::profiles().await;
+        return launcher_profiles.sync_with_standard(options.experimental_mode, profile_id, &versions, false)
+            .map_err(|e| format!("unable to compare profile with standard profile: {:?}", e));
+    }
+
+    let mut result = Ok(Vec::new());
+    profile_state::update(|launcher_profiles| {
+        result = launcher_profiles.sync_with_standard(options.experimental_mode, profile_id, &versions, true);
+        launcher_profiles.profiles(options.experimental_mode).iter()
+            .find(|profile| profile.id == profile_id)
+            .map(|profile| vec![ProfileChange::Profile { profile: profile.clone() }])
+            .unwrap_or_default()
+    }).await;
+
+    result.map_err(|e| format!("unable to sync profile with standard profile: {:?}", e))
+}
+
+/// Lets the frontend decide between updating an existing profile and creating a copy before importing a modpack
+#[tauri::command]
+async fn check_pack_import(project_id: String, version_id: String) -> Result<PackImportCheck, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+
+    Ok(launcher_profiles.check_pack_import(options.experimental_mode, &PackSource { project_id, version_id }))
+}
+
+#[tauri::command]
+async fn pin_profile(profile_id: &str, pinned: bool) -> Result<(), String> {
+    update_profile(profile_id, |profile| profile.pinned = pinned).await?;
+    jump_list::refresh().await;
+
+    Ok(())
+}
+
+/// Returns the profile the launcher was asked to launch via a jump list or dock menu entry, only once
+#[tauri::command]
+async fn take_pending_launch_profile() -> Result<Option<String>, String> {
+    Ok(jump_list::take_pending_launch())
+}
+
+#[tauri::command]
+async fn create_steam_shortcut(profile_id: &str) -> Result<Vec<String>, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+
+    let profile = launcher_profiles.profiles(options.experimental_mode).iter().find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+
+    let written = steam::create_shortcut(profile).await
+        .map_err(|e| format!("unable to create steam shortcut: {:?}", e))?;
+    Ok(written.iter().map(|path| path.to_string_lossy().to_string()).collect())
+}
+
+/// Lightweight status for controller / Big Picture overlays polling the launcher
+#[tauri::command]
+async fn get_client_status(app_state: tauri::State<'_, AppState>) -> Result<ClientStatus, String> {
+    let mut branches: Vec<String> = app_state.runner_instances.lock()
+        .map_err(|e| format!("unable to lock runner instance: {:?}", e))?
+        .keys().cloned().collect();
+    branches.sort();
+
+    Ok(ClientStatus {
+        running: !branches.is_empty(),
+        branch: branches.first().cloned(),
+        branches,
+    })
+}
+
+#[tauri::command]
+async fn check_maintenance_mode() -> Result<bool, String> {
+    let maintenance_mode = ApiEndpoints::norisk_maintenance_mode()
+        .await
+        .map_err(|e| format!("unable to request maintenance mode: {:?}", e))?;
+    Ok(maintenance_mode)
+}
+
+#[tauri::command]
+async fn request_norisk_branches(norisk_token: &str, uuid: &str) -> Result<Vec<String>, String> {
+    // the branches are refreshed when the settings are closed, versions might have changed since then
+    prelauncher::invalidate_version_profiles();
+    let branches = ApiEndpoints::norisk_branches(norisk_token, uuid)
+        .await
+        .map_err(|e| format!("unable to request branches: {:?}", e))?;
+    Ok(branches)
+}
+
+/// Versions the launch card offers, the branches grouped by the Minecraft version of their launch manifests
+#[tauri::command]
+async fn get_standard_versions(norisk_token: &str, uuid: &str) -> Result<Vec<StandardVersion>, String> {
+    let branches = ApiEndpoints::norisk_branches(norisk_token, uuid)
+        .await
+        .map_err(|e| format!("unable to request branches: {:?}", e))?;
+
+    let branches = futures::future::join_all(branches.into_iter().map(|branch| async move {
+        let launch_manifest = match ApiEndpoints::launch_manifest(&branch, norisk_token, uuid).await {
+            Ok(launch_manifest) => Some(launch_manifest),
+            Err(err) => {
+                warn!("Failed to request launch manifest of {}: {:?}", branch, err);
+                None
+            }
+        };
+        (branch, launch_manifest)
+    })).await;
+    Ok(StandardVersion::from_branches(branches))
+}
+
+/// Creates and selects a profile for a variant of a standard version, `variant` defaults to the version's default variant
+#[tauri::command]
+async fn instantiate_standard_profile(version_id: &str, variant: Option<String>, norisk_token: &str, uuid: &str) -> Result<LauncherProfile, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let versions = get_standard_versions(norisk_token, uuid).await?;
+    let version = versions.iter().find(|version| version.id == version_id).ok_or_else(|| format!("unknown version {}", version_id))?;
+    let variant = match &variant {
+        Some(variant_id) => version.variants.iter().find(|variant| variant.id == *variant_id),
+        None => version.variants.iter().find(|variant| variant.default).or_else(|| version.variants.first()),
+    }.ok_or_else(|| format!("unknown variant {:?} of version {}", variant, version_id))?;
+
+    let mut profile = None;
+    profile_state::update(|launcher_profiles| {
+        let created = launcher_profiles.instantiate_standard_profile(options.experimental_mode, version, variant);
+        profile = Some(created.clone());
+        vec![ProfileChange::Profile { profile: created }]
+    }).await;
+
+    let profile = profile.unwrap();
+    info!("Created profile {} for variant {} of {}", profile.id, variant.id, version.id);
+    Ok(profile)
+}
+
+#[tauri::command]
+async fn enable_experimental_mode(experimental_token: &str, session_token: Option<String>, app_state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    ensure_unrestricted("Experimental mode").await?;
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    app_state.settings_sessions.ensure_unlocked(&options, session_token.as_deref(), "Experimental mode")?;
+    return ApiEndpoints::enable_experimental_mode(experimental_token)
+        .await
+        .map_err(|e| format!("unable to validate experimental token: {:?}", e));
+}
+
+#[tauri::command]
+async fn get_launch_manifest(branch: &str, norisk_token: &str, uuid: &str) -> Result<NoRiskLaunchManifest, String> {
+    let manifest = ApiEndpoints::launch_manifest(branch, norisk_token, uuid).await
+        .map_err(|e| format!("unable to request launch manifest: {:?}", e))?;
+    Ok(manifest)
+}
+
+/// Checks before a launch whether the NoRisk pack can be downloaded, so the frontend can offer launching without it
+#[tauri::command]
+async fn check_norisk_access(branch: &str, norisk_token: &str, uuid: &str) -> Result<NoRiskAccessCheck, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let check = SharedNoRiskToken::new(norisk_token, uuid, options.experimental_mode).check_assets_access(branch).await;
+    debug!("NoRisk access for {}: {:?}", branch, check);
+    Ok(check)
+}
+
+#[tauri::command]
+async fn upload_logs(log: String) -> Result<McLogsUploadResponse, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let login_data = options.current_uuid.as_ref().and_then(|uuid| options.accounts.iter().find(|account| account.uuid == *uuid));
+    let log = LogRedactor::new(options.log_privacy.clone(), login_data).redact(&log);
+    let log_response = McLogsApiEndpoints::upload_logs(log).await
+        .map_err(|e| format!("unable to upload logs: {:?}", e))?;
+    Ok(log_response)
+}
+
+#[tauri::command]
+async fn connect_discord_intigration(options: LauncherOptions, login_data: LoginData, handle: tauri::AppHandle) -> Result<(), String> {
+    let url = format!("https://api{}.norisk.gg/api/v1/core/oauth/discord?token={}", if options.experimental_mode.clone() { "-staging" } else { "" }, if options.experimental_mode.clone() { login_data.experimental_token.unwrap() } else { login_data.norisk_token });
+    let _ = open_url(url.as_str(), (1200, 900), handle);
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn check_discord_intigration(norisk_token: &str, uuid: &str) -> Result<bool, String> {
+    let status = ApiEndpoints::discord_link_status(norisk_token, uuid).await
+        .map_err(|e| format!("unable to check discord intigration: {:?}", e))?;
+    Ok(status)
+}
+
+#[tauri::command]
+async fn unlink_discord_intigration(norisk_token: &str, uuid: &str) -> Result<(), String> {
+    ApiEndpoints::unlink_discord(norisk_token, uuid).await
+        .map_err(|e| format!("unable to unlink discord: {:?}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn login_norisk_microsoft(options: LauncherOptions, handle: tauri::AppHandle) -> Result<LoginData, String> {
+    let auth_prepare_response = ApiEndpoints::auth_prepare_response().await;
+    match auth_prepare_response {
+        Ok(response) => {
+            // Hier kannst du auf die Daten von 'response' zugreifen
+            let url = response.url;
+            let id = response.id;
+            let _ = open_url(url.as_str(), (1200, 800), handle);
+
+            let login_data = ApiEndpoints::await_auth_response(id).await;
+            match login_data {
+                Ok(response) => {
+                    info!("Received NoRisk Auth Response");
+                    Ok(LoginData {
+                        norisk_token: if options.experimental_mode { String::from("") } else { response.norisk_token.clone() },
+                        experimental_token: Some(if options.experimental_mode { response.norisk_token.clone() } else { String::from("") }),
+                        ..response
+                    })
+                }
+                Err(err) => {
+                    Err(format!("await auth error: {:?}", err))
+                }
+            }
+        }
+        Err(err) => {
+            Err(format!("await prepare response error: {:?}", err))
+        }
+    }
+}
+
+/// Lists accounts with everything the account list shows, so it doesn't need extra requests per account
+#[tauri::command]
+async fn list_accounts(offset: Option<usize>, limit: Option<usize>) -> Result<AccountPage, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await
+        .map_err(|e| format!("unable to load options: {:?}", e))?;
+    Ok(accounts::list(&options, offset.unwrap_or_default(), limit.unwrap_or(usize::MAX)).await)
+}
+
+/// Accounts of the vanilla launcher and Prism Launcher that could be imported
+#[tauri::command]
+async fn find_importable_accounts() -> Result<Vec<ImportableAccount>, String> {
+    Ok(account_import::find_importable_accounts().await)
+}
+
+#[tauri::command]
+async fn remove_account(login_data: LoginData, session_token: Option<String>, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    app_state.settings_sessions.ensure_unlocked(&options, session_token.as_deref(), "Removing accounts")?;
+    TokenManager {}.delete_tokens(login_data);
+    Ok(())
+}
+
+///
+/// Window and process id of a running client, used to route its output to the main window and its log window
+///
+struct ClientProcess {
+    window: Arc<Mutex<Window>>,
+    process_id: String,
+    task: Option<TaskHandle>,
+    progress_max: AtomicU64,
+    redactor: LogRedactor,
+    /// Set once the game process is running, failures before that happened during the install
+    game_spawned: Arc<AtomicBool>,
+    /// Profile whose statistics the launch counts towards
+    profile_id: Option<String>,
+}
+
+fn handle_stdout(client: &ClientProcess, data: &[u8]) -> anyhow::Result<()> {
+    let data = String::from_utf8(data.to_vec())?;
+    if data.is_empty() {
+        return Ok(()); // ignore empty lines
+    }
+    let data = client.redactor.redact(&data);
+
     info!("{}", data);
-    window.lock().unwrap().emit("process-output", data)?;
+    if let Some(profile_id) = &client.profile_id {
+        profile_statistics::check_game_output(profile_id, &data);
+    }
+    let window = client.window.lock().unwrap();
+    log_windows::forward(&window.app_handle(), &client.process_id, &data);
+    window.emit("process-output", data)?;
     Ok(())
 }
 
-fn handle_stderr(window: &Arc<std::sync::Mutex<Window>>, data: &[u8]) -> anyhow::Result<()> {
+fn handle_stderr(client: &ClientProcess, data: &[u8]) -> anyhow::Result<()> {
     let data = String::from_utf8(data.to_vec())?;
     if data.is_empty() {
         return Ok(()); // ignore empty lines
     }
+    let data = client.redactor.redact(&data);
 
     error!("{}", data);
-    window.lock().unwrap().emit("process-output", data)?;
+    let window = client.window.lock().unwrap();
+    log_windows::forward(&window.app_handle(), &client.process_id, &data);
+    window.emit("process-output", data)?;
     Ok(())
 }
 
-fn handle_progress(window: &Arc<std::sync::Mutex<Window>>, progress_update: ProgressUpdate) -> anyhow::Result<()> {
-    window.lock().unwrap().emit("progress-update", progress_update)?;
+fn handle_progress(client: &ClientProcess, progress_update: ProgressUpdate) -> anyhow::Result<()> {
+    if let Some(task) = &client.task {
+        match progress_update {
+            ProgressUpdate::SetMax(max) => client.progress_max.store(max, Ordering::Relaxed),
+            ProgressUpdate::SetProgress(progress) => task.set_progress(progress as f64 / client.progress_max.load(Ordering::Relaxed).max(1) as f64),
+            ProgressUpdate::SetLabel(_) | ProgressUpdate::SetBytes(_) => {}
+        }
+    }
+
+    let window = client.window.lock().unwrap();
+    progress_overlay::forward(&window.app_handle(), &progress_update);
+    window.emit("progress-update", progress_update)?;
     Ok(())
 }
 
-#[tauri::command]
-async fn run_client(branch: String, login_data: LoginData, options: LauncherOptions, force_server: Option<String>, mods: Vec<LoaderMod>, shaders: Vec<Shader>, resourcepacks: Vec<ResourcePack>, datapacks: Vec<Datapack>, window: Window, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
-    info!("Starting Client with branch {}",branch);
-    let window_mutex = Arc::new(std::sync::Mutex::new(window));
+fn handle_installed(client: &ClientProcess) {
+    // the game is downloaded, so queued installs don't have to wait until it is closed
+    if let Some(task) = &client.task {
+        task.release_install_slot();
+    }
+    // the overlay only shows the install progress, the game window takes over from here
+    let window = client.window.lock().unwrap();
+    if let Err(err) = progress_overlay::close(&window.app_handle()) {
+        error!("Failed to close progress overlay: {:?}", err);
+    }
+    if let Err(err) = window.emit("client-installed", ()) {
+        error!("Failed to emit installed client: {:?}", err);
+    }
+}
+
+fn handle_spawned(client: &ClientProcess) {
+    client.game_spawned.store(true, Ordering::Relaxed);
+    if let Some(profile_id) = &client.profile_id {
+        profile_statistics::game_spawned(profile_id);
+    }
+}
 
-    let parameters = LaunchingParameter {
+fn launching_parameter(options: &LauncherOptions, login_data: LoginData, force_server: Option<String>) -> LaunchingParameter {
+    LaunchingParameter {
         dev_mode: options.experimental_mode,
         force_server: force_server,
         memory: percentage_of_total_memory(options.memory_percentage),
+        min_memory: None,
         data_path: options.data_path_buf(),
-        custom_java_path: if !options.custom_java_path.is_empty() { Some(options.custom_java_path) } else { None },
-        custom_java_args: options.custom_java_args,
+        custom_java_path: if !options.custom_java_path.is_empty() { Some(options.custom_java_path.clone()) } else { None },
+        custom_java_args: options.custom_java_args.clone(),
         auth_player_name: login_data.username,
         auth_uuid: login_data.uuid,
         auth_access_token: login_data.mc_token,
@@ -958,29 +2151,692 @@ async fn run_client(branch: String, login_data: LoginData, options: LauncherOpti
         user_type: "msa".to_string(),
         keep_launcher_open: options.keep_launcher_open,
         concurrent_downloads: options.concurrent_downloads,
+        adaptive_downloads: options.adaptive_downloads,
+        preferred_gpu: options.preferred_gpu.clone(),
+        link_mods: options.link_mods,
+        ignore_content_blacklist: options.ignore_content_blacklist && !options.restricted_mode.enabled,
+        restricted_mode: options.restricted_mode.enabled,
+        experimental_mode: options.experimental_mode,
+        quick_launch: false,
+        skip_norisk_assets: false,
+        prepare_only: false,
+        isolated: false,
+        profile_id: None,
+        asset_verification: options.asset_verification,
+    }
+}
+
+fn apply_profile_settings(parameters: &mut LaunchingParameter, profile: &LauncherProfile) {
+    if let Some(memory) = &profile.memory {
+        parameters.memory = memory.max_mb as i64;
+        parameters.min_memory = Some(memory.min_mb as i64);
+    }
+    parameters.isolated = profile.isolated;
+    parameters.profile_id = Some(profile.id.clone());
+}
+
+/// Loads everything needed to assemble the launch command of a profile without launching it
+async fn prepare_profile_launch(profile_id: &str) -> Result<(LauncherOptions, NoRiskLaunchManifest, VersionProfile, LaunchingParameter), String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode).iter().find(|profile| profile.id == profile_id).ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+    let login_data = options.accounts.iter().find(|account| Some(&account.uuid) == options.current_uuid.as_ref()).cloned().ok_or_else(|| "no account selected".to_string())?;
+
+    let mut launch_manifest = branch_launch_manifest(&options, &profile.branch).await?;
+    apply_profile_loader(&mut launch_manifest, profile);
+    let version_profile = prelauncher::load_version_profile(&launch_manifest)
+        .await
+        .map_err(|e| format!("unable to load version profile: {:?}", e))?;
+
+    let mut parameters = launching_parameter(&options, login_data, None);
+    apply_profile_settings(&mut parameters, profile);
+
+    Ok((options, launch_manifest, version_profile, parameters))
+}
+
+#[tauri::command]
+async fn get_launch_command_preview(profile_id: &str) -> Result<LaunchCommandPreview, String> {
+    let (options, launch_manifest, version_profile, parameters) = prepare_profile_launch(profile_id).await?;
+
+    launcher::launch_command_preview(&options.data_path_buf(), &launch_manifest, &version_profile, parameters)
+        .await
+        .map_err(|e| format!("unable to assemble launch command: {:?}", e))
+}
+
+/// The profile's mods and all of their dependencies as they are passed to a launch
+fn flatten_mods(mods: &[CustomMod]) -> Vec<LoaderMod> {
+    fn flatten(mods: &[CustomMod], loader_mods: &mut Vec<LoaderMod>) {
+        for custom_mod in mods {
+            loader_mods.push(custom_mod.value.clone());
+            flatten(&custom_mod.dependencies, loader_mods);
+        }
+    }
+    let mut loader_mods = Vec::new();
+    flatten(mods, &mut loader_mods);
+    loader_mods
+}
+
+/// Estimates how much still has to be downloaded before a profile can be launched and whether it fits on the disk
+#[tauri::command]
+async fn estimate_install_size(profile_id: &str) -> Result<InstallSizeEstimate, String> {
+    let (options, launch_manifest, version_profile, _) = prepare_profile_launch(profile_id).await?;
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode)
+        .iter()
+        .find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+
+    let additional_mods = flatten_mods(&profile.mods);
+
+    install_size::estimate(&options.data_path_buf(), &launch_manifest, &version_profile, &additional_mods)
+        .await
+        .map_err(|e| format!("unable to estimate install size: {:?}", e))
+}
+
+/// Repairs the game files of a profile by removing broken ones, the next launch downloads them again.
+/// Without `full` the `fullIntegrityCheck` option decides whether every file is hashed, without `scope` everything is checked.
+#[tauri::command]
+async fn repair_profile_files(profile_id: &str, full: Option<bool>, scope: Option<RepairScope>, app_state: tauri::State<'_, AppState>) -> Result<IntegrityReport, String> {
+    app_state.task_manager.run(TaskKind::Install, "Repairing game files".to_string(), |_task| async move {
+        let (options, launch_manifest, version_profile, _) = prepare_profile_launch(profile_id).await?;
+        let game_dir = options.data_path_buf().join("gameDir").join(&launch_manifest.build.branch);
+        let report = integrity::verify(&options.data_path_buf(), &game_dir, &version_profile, full.unwrap_or(options.full_integrity_check), scope.unwrap_or_default())
+            .await
+            .map_err(|e| format!("unable to verify game files: {:?}", e))?;
+        if !report.broken_files.is_empty() {
+            // the broken files were deleted, the next launch downloads them again
+            install_state::store(&game_dir, InstallState::NeedsRepair { reason: format!("{} files were broken", report.broken_files.len()) });
+        }
+        Ok(report)
+    }).await
+}
+
+/// Names from the frontend, like branches, become folder names. They must not lead out of the folder they are joined to.
+fn ensure_folder_name(kind: &str, name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(format!("invalid {} {:?}", kind, name));
+    }
+    Ok(())
+}
+
+/// Install state of a branch, see `InstallState`
+#[tauri::command]
+async fn get_install_state(branch: String) -> Result<InstallState, String> {
+    ensure_folder_name("branch", &branch)?;
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    Ok(install_state::load(&options.data_path_buf().join("gameDir").join(&branch)).await)
+}
+
+/// Downloads everything the profile needs to launch without starting the game, so it can be played without internet later
+#[tauri::command]
+async fn prepare_profile_offline(profile_id: &str, window: Window, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    // the mods are installed as they are now, changes have to wait
+    let _lock = profile_state::read_profile(profile_id).await;
+    let (options, launch_manifest, _, mut parameters) = prepare_profile_launch(profile_id).await?;
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode)
+        .iter()
+        .find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+    // the mods folder of the branch is shared with the running game
+    if is_branch_running(&app_state, &profile.branch)? {
+        return Err(format!("{} can't be prepared while a game of {} is running", profile.name, profile.branch));
+    }
+    let mods = profile.mods.iter()
+        .flat_map(|custom_mod| std::iter::once(custom_mod.value.clone()).chain(custom_mod.dependencies.iter().map(|dependency| dependency.value.clone())))
+        .collect::<Vec<_>>();
+    let addons = launcher_profiles.addons.get(&profile.branch).cloned();
+    let (shaders, resourcepacks, datapacks) = addons.map(|addons| (addons.shaders, addons.resourcepacks, addons.datapacks)).unwrap_or_default();
+    parameters.prepare_only = true;
+
+    let login_data = options.accounts.iter().find(|account| Some(&account.uuid) == options.current_uuid.as_ref()).cloned().ok_or_else(|| "no account selected".to_string())?;
+    let token = if options.experimental_mode { login_data.experimental_token.clone().unwrap_or_default() } else { login_data.norisk_token.clone() };
+    let uuid = login_data.uuid.clone();
+    let window_mutex = Arc::new(std::sync::Mutex::new(window));
+    let redactor = LogRedactor::new(options.log_privacy.clone(), Some(&login_data));
+
+    app_state.task_manager.run(TaskKind::Install, format!("Preparing {} for offline play", profile.name), |task| async move {
+        let (_terminator_tx, terminator_rx) = tokio::sync::oneshot::channel();
+        prelauncher::launch(
+            &token,
+            &uuid,
+            launch_manifest,
+            parameters,
+            mods,
+            shaders,
+            resourcepacks,
+            datapacks,
+            LauncherData {
+                on_stdout: handle_stdout,
+                on_stderr: handle_stderr,
+                on_progress: handle_progress,
+                on_installed: handle_installed,
+                on_spawned: handle_spawned,
+                data: Box::new(ClientProcess { window: window_mutex.clone(), process_id: profile_id.to_string(), task: Some(task), progress_max: AtomicU64::new(0), redactor, game_spawned: Arc::new(AtomicBool::new(false)), profile_id: None }),
+                terminator: terminator_rx,
+            },
+            window_mutex,
+        ).await.map_err(|e| format!("unable to prepare profile: {:?}", e))
+    }).await
+}
+
+#[tauri::command]
+async fn export_launch_script(profile_id: &str, path: PathBuf, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    app_state.task_manager.run(TaskKind::Export, "Exporting launch script".to_string(), |task| async move {
+        let (options, launch_manifest, version_profile, parameters) = prepare_profile_launch(profile_id).await?;
+        let batch = path.extension().map_or(false, |extension| extension.eq_ignore_ascii_case("bat") || extension.eq_ignore_ascii_case("cmd"));
+        task.set_progress(0.5);
+
+        let script = launcher::launch_script(&options.data_path_buf(), &launch_manifest, &version_profile, parameters, batch)
+            .await
+            .map_err(|e| format!("unable to assemble launch script: {:?}", e))?;
+        fs::write(&path, script).await.map_err(|e| format!("unable to write launch script: {:?}", e))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).await.map_err(|e| format!("unable to make launch script executable: {:?}", e))?;
+        }
+
+        info!("Exported launch script of profile {} to {:?}", profile_id, path);
+        Ok(())
+    }).await
+}
+
+/// Path an export is written to: the given path, or the one the user picks in a save dialog starting in the folder
+/// of the last export and suggesting a file named after the profile. `None` if the user closed the dialog.
+async fn export_destination(output_path: Option<PathBuf>, profile_name: String, filter: &'static str, extension: &'static str) -> Result<Option<PathBuf>, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let path = match output_path {
+        Some(path) => path,
+        None => {
+            let directory = options.last_export_directory.clone()
+                .filter(|directory| directory.is_dir())
+                .unwrap_or_else(|| options.data_path_buf().join("exports"));
+            fs::create_dir_all(&directory).await.map_err(|e| format!("unable to create exports folder: {:?}", e))?;
+            let file_name = profile_names::find_unique_profile_segment(&directory, &profile_name, extension, options.transliterate_profile_names);
+            let chosen = tokio::task::spawn_blocking(move || {
+                FileDialogBuilder::new()
+                    .set_directory(directory)
+                    .set_file_name(&format!("{}.{}", file_name, extension))
+                    .add_filter(filter, &[extension])
+                    .save_file()
+            }).await.map_err(|e| format!("unable to open save dialog: {:?}", e))?;
+            let Some(path) = chosen else { return Ok(None) };
+            path
+        }
+    };
+
+    options.last_export_directory = path.parent().map(Path::to_path_buf);
+    if let Err(err) = options.store(config_dir).await {
+        error!("Failed to remember export folder: {:?}", err);
+    }
+    Ok(Some(path))
+}
+
+/// Writes a profile to a file that can be shared, see `ExportedProfile` for what it contains.
+/// Without a path the user picks one, returns `None` if they didn't.
+#[tauri::command]
+async fn export_profile(profile_id: &str, path: Option<PathBuf>, app_state: tauri::State<'_, AppState>) -> Result<Option<PathBuf>, String> {
+    let profile = get_launcher_profile(profile_id).await?;
+    let Some(path) = export_destination(path, profile.name.clone(), "Profile", "json").await? else { return Ok(None) };
+
+    app_state.task_manager.run(TaskKind::Export, "Exporting profile".to_string(), |task| async move {
+        task.set_phase(TaskPhase::Writing);
+        let exported = serde_json::to_string_pretty(&ExportedProfile::from(&profile))
+            .map_err(|e| format!("unable to serialize profile: {:?}", e))?;
+        fs::write(&path, exported).await.map_err(|e| format!("unable to write profile: {:?}", e))?;
+
+        info!("Exported profile {} to {:?}", profile_id, path);
+        Ok(Some(path))
+    }).await
+}
+
+/// Writes the mods of a profile as markdown table, csv or json and returns the written file.
+/// Without an output path the user picks one, returns `None` if they didn't.
+#[tauri::command]
+async fn export_mod_list(profile_id: &str, format: ModListFormat, output_path: Option<PathBuf>, app_state: tauri::State<'_, AppState>) -> Result<Option<PathBuf>, String> {
+    let profile = get_launcher_profile(profile_id).await?;
+    let Some(path) = export_destination(output_path, format!("{}-mods", profile.name), "Mod List", format.extension()).await? else { return Ok(None) };
+
+    app_state.task_manager.run(TaskKind::Export, "Exporting mod list".to_string(), |_task| async move {
+        let entries = mod_list::entries(&profile);
+        let rendered = mod_list::render(&profile, &entries, format)
+            .map_err(|e| format!("unable to render mod list: {:?}", e))?;
+        fs::write(&path, rendered).await.map_err(|e| format!("unable to write mod list: {:?}", e))?;
+
+        info!("Exported {} mods of profile {} to {:?}", entries.len(), profile_id, path);
+        Ok(Some(path))
+    }).await
+}
+
+/// Exports a profile with the files of its game dir as `.noriskpack`, the progress is reported through the export task.
+/// Without a path the user picks one, returns `None` if they didn't.
+#[tauri::command]
+async fn export_noriskpack(profile_id: &str, path: Option<PathBuf>, include_worlds: bool, app_state: tauri::State<'_, AppState>) -> Result<Option<PathBuf>, String> {
+    let profile = get_launcher_profile(profile_id).await?;
+    let Some(path) = export_destination(path, profile.name.clone(), "NoRisk Pack", "noriskpack").await? else { return Ok(None) };
+
+    app_state.task_manager.run(TaskKind::Export, "Exporting profile".to_string(), |task| async move {
+        let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+
+        task.set_phase(TaskPhase::Scanning);
+        // lets the import refuse the pack once the branch moved on to another version
+        let mc_version = match profile_launch_manifest(profile_id).await {
+            Ok(launch_manifest) => Some(launch_manifest.build.mc_version),
+            Err(err) => {
+                warn!("Exporting without the Minecraft version of {}: {}", profile.branch, err);
+                None
+            }
+        };
+        let game_dir = options.data_path_buf().join("gameDir").join(&profile.branch);
+        let custom_mods_dir = mc_version.as_ref().map(|mc_version| options.data_path_buf().join("custom_mods").join(format!("{}-{}", profile.branch, mc_version)));
+        let entries = profile_export::noriskpack_entries(&profile, mc_version, &game_dir, custom_mods_dir.as_deref(), include_worlds)
+            .map_err(|e| format!("unable to collect files: {:?}", e))?;
+
+        // every chunk that is read is reported, only forward whole permille steps
+        task.set_phase(TaskPhase::Compressing);
+        let reported = AtomicU64::new(0);
+        let compression_task = task.clone();
+        create_zip_archive(&path, entries, move |written, total| {
+            let permille = if total == 0 { 1000 } else { written * 1000 / total };
+            if reported.fetch_max(permille, Ordering::Relaxed) < permille {
+                compression_task.set_progress(permille as f64 / 1000.0);
+            }
+            // the compression outlives a cancelled task, stop it
+            !compression_task.is_cancelled()
+        }).await.map_err(|e| format!("unable to write pack: {:?}", e))?;
+
+        info!("Exported profile {} to {:?}", profile_id, path);
+        Ok(Some(path))
+    }).await
+}
+
+/// Adds an imported profile and selects it for its branch
+async fn add_imported_profile(experimental_mode: bool, profile: LauncherProfile) -> LauncherProfile {
+    let added = profile.clone();
+    profile_state::update(|launcher_profiles| {
+        launcher_profiles.profiles_mut(experimental_mode).push(profile.clone());
+        let selected = if experimental_mode { &mut launcher_profiles.selected_experimental_profiles } else { &mut launcher_profiles.selected_main_profiles };
+        selected.insert(profile.branch.clone(), profile.id.clone());
+        launcher_profiles.addons.entry(profile.branch.clone()).or_default();
+        vec![ProfileChange::Profile { profile }]
+    }).await;
+    info!("Imported profile {} ({})", added.name, added.id);
+    added
+}
+
+/// Whether a profile was already created from the modpack, imports without a known pack source are always new
+async fn check_duplicate_import(experimental_mode: bool, pack_source: Option<&PackSource>) -> PackImportCheck {
+    match pack_source {
+        Some(pack_source) => profile_state::profiles().await.check_pack_import(experimental_mode, pack_source),
+        None => PackImportCheck::New,
+    }
+}
+
+/// Creates a profile from a `.mrpack`. Everything is prepared in a staging folder and the profile is only created at the end,
+/// so a failed or cancelled import leaves neither files nor a profile behind.
+/// A modpack that was already imported is only imported again once the user chose to update the existing profile or to create a copy.
+#[tauri::command]
+async fn import_mrpack_as_profile(path: PathBuf, branch: String, pack_source: Option<PackSource>, duplicate: Option<DuplicatePackImport>, app_state: tauri::State<'_, AppState>) -> Result<PackImportResult, String> {
+    ensure_unrestricted("Importing modpacks").await?;
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let check = check_duplicate_import(options.experimental_mode, pack_source.as_ref()).await;
+    let existing_id = check.profile_id().map(str::to_string);
+    let updated = match (existing_id, duplicate) {
+        (None, _) | (Some(_), Some(DuplicatePackImport::Copy)) => None,
+        (Some(_), None) => return Ok(PackImportResult::Duplicate { check }),
+        (Some(profile_id), Some(DuplicatePackImport::Update)) => Some(
+            profile_state::profiles().await.profiles(options.experimental_mode).iter()
+                .find(|profile| profile.id == profile_id)
+                .cloned()
+                .ok_or_else(|| format!("unable to find profile {}", profile_id))?
+        ),
     };
 
-    let runner_instance = &app_state.runner_instance;
+    let title = format!("Importing {}", path.file_name().unwrap_or_default().to_string_lossy());
+    app_state.task_manager.run(TaskKind::Install, title, |task| async move {
+        // an updated profile stays on its branch
+        let branch = updated.as_ref().map_or(branch, |profile| profile.branch.clone());
+        let staging = ImportStaging::new(&options.data_path_buf()).await
+            .map_err(|e| format!("unable to create import folder: {:?}", e))?;
+        let pack = profile_import::import_mrpack(&path, &staging, &task).await
+            .map_err(|e| format!("unable to import modpack: {:?}", e))?;
+        ensure_pack_branch(&options, &branch, Some(&pack.game_version)).await?;
+        for custom_mod in &pack.mods {
+            check_content_blacklist(custom_mod).await?;
+        }
+        task.set_phase(TaskPhase::Writing);
+
+        let game_dir = options.data_path_buf().join("gameDir").join(&branch);
+        let profile_id = updated.as_ref().map_or_else(|| uuid::Uuid::new_v4().to_string(), |profile| profile.id.clone());
+        if updated.is_some() {
+            profile_import::clear_overrides(&game_dir, &profile_id).await
+                .map_err(|e| format!("unable to remove previous modpack files: {:?}", e))?;
+        }
+        staging.commit(&game_dir, &profile_id).await
+            .map_err(|e| format!("unable to copy modpack files: {:?}", e))?;
+        let provenance = ProfileProvenance {
+            pack_name: Some(pack.name.clone()),
+            pack_version: pack.version,
+            file_name: path.file_name().map(|name| name.to_string_lossy().to_string()),
+            file_path: Some(file_utils::document_host_path(&path).to_string_lossy().to_string()),
+            ..ProfileProvenance::new(ProfileOrigin::Modrinth)
+        };
+
+        // icons the user chose for an updated profile are kept
+        let icon = match staging.icon() {
+            Some(icon) if updated.as_ref().map_or(true, |profile| profile.icon.is_none()) => {
+                Some(profile_icons::store_icon(&profile_id, &icon).await.map_err(|e| format!("unable to store profile icon: {:?}", e))?)
+            }
+            _ => None,
+        };
+
+        if updated.is_some() {
+            update_profile(&profile_id, |profile| {
+                profile.mods = pack.mods;
+                profile.pack_source = pack_source;
+                profile.provenance = Some(provenance);
+                if icon.is_some() {
+                    profile.icon = icon;
+                }
+            }).await?;
+            let profile = profile_state::profiles().await.profiles(options.experimental_mode).iter()
+                .find(|profile| profile.id == profile_id)
+                .cloned()
+                .ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+            info!("Updated profile {} ({}) from its modpack", profile.name, profile.id);
+            return Ok(PackImportResult::Imported { profile });
+        }
+
+        let profile = LauncherProfile {
+            id: profile_id,
+            branch,
+            name: pack.name,
+            mods: pack.mods,
+            last_played: None,
+            pinned: false,
+            memory: None,
+            icon,
+            notes: String::new(),
+            tags: Vec::new(),
+            pack_source,
+            source_standard_profile_id: None,
+            standard_variant: None,
+            loader_version: None,
+            provenance: Some(provenance),
+            isolated: false,
+            ignored_updates: Vec::new(),
+            revision: 0,
+            unknown_fields: serde_json::Map::new(),
+        };
+        Ok(PackImportResult::Imported { profile: add_imported_profile(options.experimental_mode, profile).await })
+    }).await
+}
+
+/// Downloads a modpack version found in the modpack browser and imports it like a `.mrpack`.
+/// The file is looked up again by its project, so the frontend can't make the launcher download anything else.
+#[tauri::command]
+async fn install_modpack_version(provider: &str, project_id: String, version_id: String, branch: String, duplicate: Option<DuplicatePackImport>, app_state: tauri::State<'_, AppState>) -> Result<PackImportResult, String> {
+    let pack_source = PackSource { project_id, version_id };
+    if duplicate.is_none() {
+        let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+        let check = check_duplicate_import(options.experimental_mode, Some(&pack_source)).await;
+        if check.profile_id().is_some() {
+            return Ok(PackImportResult::Duplicate { check });
+        }
+    }
+    let PackSource { project_id, version_id } = pack_source;
+
+    let details = content_provider(provider)?.get_modpack_details(&project_id)
+        .await
+        .map_err(|e| format!("unable to load modpack: {:?}", e))?;
+    let version = details.versions.iter()
+        .find(|version| version.id == version_id)
+        .ok_or_else(|| format!("modpack {} has no version {}", details.title, version_id))?;
+    let file_name = Path::new(&version.file_name).file_name()
+        .ok_or_else(|| format!("modpack version {} has an invalid file name", version_id))?;
+
+    let modpacks_dir = LAUNCHER_DIRECTORY.cache_dir().join("modpacks");
+    tokio::fs::create_dir_all(&modpacks_dir)
+        .await
+        .map_err(|e| format!("unable to create modpacks folder: {:?}", e))?;
+    let path = modpacks_dir.join(file_name);
+    utils::download_file_untracked(&version.url, &path)
+        .await
+        .map_err(|e| format!("unable to download modpack: {:?}", e))?;
+    let result = import_mrpack_as_profile(path.clone(), branch, Some(PackSource { project_id, version_id }), duplicate, app_state).await;
+    if let Err(err) = tokio::fs::remove_file(&path).await {
+        debug!("Unable to remove downloaded modpack {:?}: {:?}", path, err);
+    }
+
+    // most modpacks don't ship an icon, the one of their project is used instead
+    match (result?, details.icon_url) {
+        (PackImportResult::Imported { mut profile }, Some(icon_url)) if profile.icon.is_none() => {
+            match store_modpack_icon(&profile.id, &icon_url).await {
+                Ok(icon) => {
+                    update_profile(&profile.id, |stored| stored.icon = Some(icon.clone())).await?;
+                    profile.icon = Some(icon);
+                }
+                Err(err) => warn!("Unable to store the icon of modpack {}: {:?}", details.title, err),
+            }
+            Ok(PackImportResult::Imported { profile })
+        }
+        (result, _) => Ok(result),
+    }
+}
+
+async fn store_modpack_icon(profile_id: &str, icon_url: &str) -> anyhow::Result<String> {
+    let path = LAUNCHER_DIRECTORY.cache_dir().join("modpacks").join(format!("{}.icon", sanitize_filename::sanitize(profile_id)));
+    utils::download_file_untracked(icon_url, &path).await?;
+    let icon = profile_icons::store_icon(profile_id, &path).await;
+    let _ = tokio::fs::remove_file(&path).await;
+    icon
+}
+
+/// Refuses the branch of an imported pack unless it is one of the branches of the API and, if known, still on the pack's Minecraft version.
+/// The branch names a folder of the data dir, so it must not leave it.
+async fn ensure_pack_branch(options: &LauncherOptions, branch: &str, mc_version: Option<&str>) -> Result<(), String> {
+    ensure_folder_name("branch", branch).map_err(|e| format!("pack has an {}", e))?;
+    let (token, uuid) = selected_account_token(options)?;
+    let branches = ApiEndpoints::norisk_branches(&token, &uuid)
+        .await
+        .map_err(|e| format!("unable to request branches: {:?}", e))?;
+    if !branches.iter().any(|known| known == branch) {
+        return Err(format!("pack is made for the unknown branch {}", branch));
+    }
+
+    if let Some(mc_version) = mc_version {
+        let launch_manifest = branch_launch_manifest(options, branch).await?;
+        if launch_manifest.build.mc_version != mc_version {
+            return Err(format!("pack is made for Minecraft {}, but {} is on {}", mc_version, branch, launch_manifest.build.mc_version));
+        }
+    }
+    Ok(())
+}
+
+/// Creates a profile from a `.noriskpack`, staged like `import_mrpack_as_profile`
+#[tauri::command]
+async fn import_noriskpack_as_profile(path: PathBuf, app_state: tauri::State<'_, AppState>) -> Result<LauncherProfile, String> {
+    ensure_unrestricted("Importing modpacks").await?;
+    let title = format!("Importing {}", path.file_name().unwrap_or_default().to_string_lossy());
+    app_state.task_manager.run(TaskKind::Install, title, |task| async move {
+        let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+        let staging = ImportStaging::new(&options.data_path_buf()).await
+            .map_err(|e| format!("unable to create import folder: {:?}", e))?;
+        let exported = profile_import::import_noriskpack(&path, &staging, &task).await
+            .map_err(|e| format!("unable to import NoRisk pack: {:?}", e))?;
+        ensure_pack_branch(&options, exported.branch(), exported.mc_version()).await?;
+        let custom_mods_dir = exported.mc_version().map(|mc_version| options.data_path_buf().join("custom_mods").join(format!("{}-{}", exported.branch(), mc_version)));
+        let mut profile = exported.into_profile();
+        profile.provenance = Some(ProfileProvenance {
+            pack_name: Some(profile.name.clone()),
+            file_name: path.file_name().map(|name| name.to_string_lossy().to_string()),
+            file_path: Some(file_utils::document_host_path(&path).to_string_lossy().to_string()),
+            ..ProfileProvenance::new(ProfileOrigin::Noriskpack)
+        });
+        task.set_phase(TaskPhase::ResolvingMods);
+        for (mod_idx, custom_mod) in profile.mods.iter().enumerate() {
+            task.set_progress(mod_idx as f64 / profile.mods.len() as f64);
+            check_content_blacklist(custom_mod).await?;
+        }
+        task.set_phase(TaskPhase::Writing);
+
+        staging.commit(&options.data_path_buf().join("gameDir").join(&profile.branch), &profile.id).await
+            .map_err(|e| format!("unable to copy pack files: {:?}", e))?;
+        if let Some(custom_mods_dir) = custom_mods_dir {
+            staging.commit_custom_mods(&custom_mods_dir).await
+                .map_err(|e| format!("unable to copy custom mods: {:?}", e))?;
+        }
+        if let Some(icon) = staging.icon() {
+            profile.icon = Some(profile_icons::store_icon(&profile.id, &icon).await.map_err(|e| format!("unable to store profile icon: {:?}", e))?);
+        }
+        Ok(add_imported_profile(options.experimental_mode, profile).await)
+    }).await
+}
+
+#[tauri::command]
+async fn run_client(branch: String, login_data: LoginData, mut options: LauncherOptions, force_server: Option<String>, mods: Vec<LoaderMod>, shaders: Vec<Shader>, resourcepacks: Vec<ResourcePack>, datapacks: Vec<Datapack>, quick_launch: Option<bool>, skip_norisk_assets: Option<bool>, window: Window, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    info!("Starting Client with branch {}",branch);
+
+    // the restricted mode of the frontend's options can't be trusted
+    options.restricted_mode = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default().restricted_mode;
+    if options.restricted_mode.enabled && options.experimental_mode {
+        return Err("experimental branches can't be launched in restricted mode".to_string());
+    }
+    let window_mutex = Arc::new(std::sync::Mutex::new(window));
+
+    let mut parameters = launching_parameter(&options, login_data.clone(), force_server);
+    parameters.quick_launch = quick_launch.unwrap_or_default();
+    parameters.skip_norisk_assets = skip_norisk_assets.unwrap_or_default();
 
-    if runner_instance.lock().map_err(|e| format!("unable to lock runner instance: {:?}", e))?.is_some() {
-        return Err("client is already running".to_string());
+    // other branches can be launched while a game is running, their installs wait in the install queue
+    if is_branch_running(&app_state, &branch)? {
+        return Err(format!("{} is already running", branch));
     }
 
+    let redactor = LogRedactor::new(options.log_privacy.clone(), Some(&login_data));
     let experimental_token = login_data.experimental_token.unwrap_or_default();
     let norisk_token = login_data.norisk_token;
 
     info!("Loading launch manifest...");
-    let launch_manifest = ApiEndpoints::launch_manifest(&branch, (if options.experimental_mode { experimental_token.clone() } else { norisk_token.clone() }).to_string().as_mut(), options.current_uuid.clone().unwrap().as_str())
+    let mut launch_manifest = ApiEndpoints::launch_manifest(&branch, (if options.experimental_mode { experimental_token.clone() } else { norisk_token.clone() }).to_string().as_mut(), options.current_uuid.clone().unwrap().as_str())
         .await
         .map_err(|e| format!("unable to request launch manifest: {:?}", e))?;
 
-    let (terminator_tx, terminator_rx) = tokio::sync::oneshot::channel();
+    // the play time is recorded like any other profile change, not while a store of the same profile is running
+    let profile_id = profile_state::profiles().await.selected_profile_mut(options.experimental_mode, &branch).map(|profile| profile.id.clone());
+    let profile_lock = match &profile_id {
+        Some(profile_id) => Some(profile_state::lock_profile(profile_id).await),
+        None => None,
+    };
+    profile_state::update(|launcher_profiles| {
+        let Some(profile) = profile_id.as_deref().and_then(|profile_id| launcher_profiles.find_profile_mut(options.experimental_mode, profile_id)) else { return Vec::new() };
+        profile.last_played = Some(Utc::now().timestamp());
+        apply_profile_settings(&mut parameters, profile);
+        apply_profile_loader(&mut launch_manifest, profile);
+        vec![ProfileChange::Profile { profile: profile.clone() }]
+    }).await;
+    drop(profile_lock);
+    jump_list::refresh().await;
+
+    // the heap is never changed behind the user's back, they are only told when it doesn't fit the mods
+    let memory_recommendation = memory::recommend(memory::launched_mod_count(&launch_manifest, &mods), get_system_ram_mb(), parameters.memory as u64);
+    if !memory_recommendation.warnings.is_empty() {
+        if let Err(err) = window_mutex.lock().unwrap().emit("memory-recommendation", &memory_recommendation) {
+            error!("Failed to emit memory recommendation: {:?}", err);
+        }
+    }
+
+    let game_dir = options.data_path_buf().join("gameDir").join(&branch);
+    snapshot_configs(&game_dir, "Before launch".to_string()).await;
+
+    let run = GameRun {
+        window: window_mutex.clone(),
+        process_id: options.current_uuid.clone().unwrap_or_default(),
+        redactor,
+        profile_id,
+        keep_launcher_open: parameters.keep_launcher_open,
+        crash_upload: options.crash_upload.clone(),
+        game_dir,
+    };
+    accounts::record_usage(&login_data.uuid).await;
+
+    start_game_runner(run, format!("Launching {}", branch), branch, &app_state, move |client, terminator| async move {
+        prelauncher::launch(
+            &if options.experimental_mode {
+                experimental_token
+            } else {
+                norisk_token
+            },
+            options.current_uuid.unwrap().as_str(),
+            launch_manifest,
+            parameters,
+            mods,
+            shaders,
+            resourcepacks,
+            datapacks,
+            LauncherData {
+                on_stdout: handle_stdout,
+                on_stderr: handle_stderr,
+                on_progress: handle_progress,
+                on_installed: handle_installed,
+                on_spawned: handle_spawned,
+                data: Box::new(client),
+                terminator,
+            },
+            window_mutex,
+        ).await.map_err(|e| format!("{:?}", e))
+    })
+}
+
+///
+/// Game process that is about to be started. Launches and fake processes run through the same runner,
+/// so the output, crash handling and statistics of both are the same.
+///
+struct GameRun {
+    window: Arc<Mutex<Window>>,
+    process_id: String,
+    redactor: LogRedactor,
+    profile_id: Option<String>,
+    keep_launcher_open: bool,
+    crash_upload: CrashUploadSettings,
+    game_dir: PathBuf,
+}
+
+impl GameRun {
+    fn client(&self, task: Option<TaskHandle>, game_spawned: &Arc<AtomicBool>) -> ClientProcess {
+        ClientProcess { window: self.window.clone(), process_id: self.process_id.clone(), task, progress_max: AtomicU64::new(0), redactor: self.redactor.clone(), game_spawned: game_spawned.clone(), profile_id: self.profile_id.clone() }
+    }
+}
 
-    *runner_instance.lock().map_err(|e| format!("unable to lock runner instance: {:?}", e))?
-        = Some(RunnerInstance { terminator: terminator_tx });
+fn is_branch_running(app_state: &AppState, branch: &str) -> Result<bool, String> {
+    Ok(app_state.runner_instances.lock().map_err(|e| format!("unable to lock runner instance: {:?}", e))?.contains_key(branch))
+}
 
-    let copy_of_runner_instance = runner_instance.clone();
+/// Runs the game on a thread of its own, the branch counts as running until it exited.
+/// `launch` gets the client the output goes to and the receiver that terminates the game.
+fn start_game_runner<F, Fut>(run: GameRun, title: String, branch: String, app_state: &AppState, launch: F) -> Result<(), String>
+    where F: FnOnce(ClientProcess, tokio::sync::oneshot::Receiver<()>) -> Fut + Send + 'static,
+          Fut: std::future::Future<Output = Result<(), String>> {
+    let (terminator_tx, terminator_rx) = tokio::sync::oneshot::channel();
+    {
+        let mut runner_instances = app_state.runner_instances.lock().map_err(|e| format!("unable to lock runner instance: {:?}", e))?;
+        if runner_instances.contains_key(&branch) {
+            return Err(format!("{} is already running", branch));
+        }
+        runner_instances.insert(branch.clone(), RunnerInstance { terminator: Some(terminator_tx) });
+    }
 
+    let runner_instances = app_state.runner_instances.clone();
+    let task_manager = app_state.task_manager.clone();
+    let game_spawned = Arc::new(AtomicBool::new(false));
+    let launch_started = std::time::SystemTime::now();
+    if let Some(profile_id) = &run.profile_id {
+        profile_statistics::launch_started(profile_id);
+    }
 
     thread::spawn(move || {
         tokio::runtime::Builder::new_current_thread()
@@ -988,57 +2844,115 @@ async fn run_client(branch: String, login_data: LoginData, options: LauncherOpti
             .build()
             .unwrap()
             .block_on(async {
-                let keep_launcher_open = parameters.keep_launcher_open;
-
-                if let Err(e) = prelauncher::launch(
-                    &if options.experimental_mode {
-                        experimental_token
-                    } else {
-                        norisk_token
-                    },
-                    options.current_uuid.unwrap().as_str(),
-                    launch_manifest,
-                    parameters,
-                    mods,
-                    shaders,
-                    resourcepacks,
-                    datapacks,
-                    LauncherData {
-                        on_stdout: handle_stdout,
-                        on_stderr: handle_stderr,
-                        on_progress: handle_progress,
-                        data: Box::new(window_mutex.clone()),
-                        terminator: terminator_rx,
-                    },
-                    window_mutex.clone(),
-                ).await {
-                    if !keep_launcher_open {
-                        window_mutex.lock().unwrap().show().unwrap();
+                let result = task_manager.run(TaskKind::Launch, title, |task| launch(run.client(Some(task), &game_spawned), terminator_rx)).await;
+
+                // failed installs aren't crashes, only a game that exited with an error is
+                let crashed = result.is_err() && game_spawned.load(Ordering::Relaxed);
+                if let Some(profile_id) = &run.profile_id {
+                    profile_statistics::launch_finished(profile_id, crashed).await;
+                }
+
+                let mut exit = ClientExit { branch: branch.clone(), crashed: result.is_err(), uploads: Vec::new() };
+                if let Err(e) = result {
+                    if crashed {
+                        notifications::notify(NotificationCategory::Crashes, "Minecraft crashed", &e).await;
+                    }
+                    if !run.keep_launcher_open {
+                        run.window.lock().unwrap().show().unwrap();
                     }
 
-                    window_mutex.lock().unwrap().emit("client-error", format!("Failed to launch client: {:?}", e)).unwrap();
-                    handle_stderr(&window_mutex, format!("Failed to launch client: {:?}", e).as_bytes()).unwrap();
+                    run.window.lock().unwrap().emit("client-error", format!("Failed to launch client: {}", e)).unwrap();
+                    handle_stderr(&run.client(None, &game_spawned), format!("Failed to launch client: {}", e).as_bytes()).unwrap();
+                    exit.uploads = crash_upload::upload_crash_logs(&run.crash_upload, &run.game_dir, launch_started, &run.redactor).await;
                 };
 
-                *copy_of_runner_instance.lock().map_err(|e| format!("unable to lock runner instance: {:?}", e)).unwrap()
-                    = None;
-                window_mutex.lock().unwrap().emit("client-exited", ()).unwrap()
+                runner_instances.lock().map_err(|e| format!("unable to lock runner instance: {:?}", e)).unwrap()
+                    .remove(&branch);
+                let window = run.window.lock().unwrap();
+                log_windows::process_ended(&window.app_handle(), &run.process_id);
+                let _ = progress_overlay::close(&window.app_handle());
+                window.emit("client-exited", exit).unwrap();
             });
     });
 
-
     Ok(())
 }
 
+/// Development builds only: runs a fake game process for the profile that prints the scripted lines and exits with the
+/// scripted code. Its output, launch hooks, exit and crash handling are the same as for the game, without installing Minecraft.
+#[tauri::command]
+async fn launch_fake_process(profile_id: &str, script: FakeScript, window: Window, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if !cfg!(debug_assertions) {
+        return Err("fake processes are only available in development builds".to_string());
+    }
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode).iter().find(|profile| profile.id == profile_id).ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+    let branch = profile.branch.clone();
+
+    if is_branch_running(&app_state, &branch)? {
+        return Err(format!("{} is already running", branch));
+    }
+    // the hooks of a launch run as well, they only need the manifest and the parameters
+    let (_, launch_manifest, _, parameters) = prepare_profile_launch(profile_id).await?;
+
+    let data = options.data_path_buf();
+    let run = GameRun {
+        window: Arc::new(std::sync::Mutex::new(window)),
+        process_id: options.current_uuid.clone().unwrap_or_default(),
+        redactor: LogRedactor::new(options.log_privacy.clone(), None),
+        profile_id: Some(profile_id.to_string()),
+        keep_launcher_open: true,
+        crash_upload: options.crash_upload.clone(),
+        game_dir: data.join("gameDir").join(&branch),
+    };
+
+    start_game_runner(run, format!("Fake process {}", branch), branch, &app_state, move |client, terminator| async move {
+        if let Some(task) = &client.task {
+            task.release_install_slot();
+        }
+        let context = LaunchContext::new(&data, &launch_manifest, &parameters);
+        launch_hooks::pre_install(&context).await.map_err(|e| format!("{:?}", e))?;
+        let mut arguments = LaunchArguments { jvm_args: Vec::new(), main_class: String::new(), game_args: Vec::new() };
+        launch_hooks::pre_launch(&context, &mut arguments).await.map_err(|e| format!("{:?}", e))?;
+
+        let mut child = fake_process::spawn(&script, &data).await.map_err(|e| format!("unable to spawn fake process: {:?}", e))?;
+        handle_spawned(&client);
+        let exit = JavaRuntime::new(std::env::current_exe().unwrap_or_default())
+            .handle_io(&mut child, handle_stdout, handle_stderr, terminator, &client)
+            .await;
+        launch_hooks::post_exit(&context, exit.is_err()).await;
+        exit.map_err(|e| format!("{:?}", e))
+    })
+}
+
+#[tauri::command]
+fn list_tasks(app_state: tauri::State<'_, AppState>) -> Result<Vec<TaskInfo>, String> {
+    Ok(app_state.task_manager.list())
+}
+
+#[tauri::command]
+fn cancel_task(task_id: &str, app_state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    Ok(app_state.task_manager.cancel(task_id))
+}
+
+/// Terminates the game of the branch, or every game without a branch
 #[tauri::command]
-async fn terminate(app_state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let runner_instance = app_state.runner_instance.clone();
-    let mut lck = runner_instance.lock()
+async fn terminate(branch: Option<String>, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let runner_instances = app_state.runner_instances.clone();
+    let mut lck = runner_instances.lock()
         .map_err(|e| format!("unable to lock runner instance: {:?}", e))?;
 
-    if let Some(inst) = lck.take() {
-        info!("Sending sigterm");
-        inst.terminator.send(()).unwrap();
+    let branches: Vec<String> = match branch {
+        Some(branch) => vec![branch],
+        None => lck.keys().cloned().collect(),
+    };
+    for branch in branches {
+        // the runner thread removes the instance once the game exited
+        if let Some(terminator) = lck.get_mut(&branch).and_then(|inst| inst.terminator.take()) {
+            info!("Sending sigterm to {}", branch);
+            let _ = terminator.send(());
+        }
     }
     Ok(())
 }
@@ -1107,6 +3021,55 @@ async fn add_player_to_whitelist(identifier: &str, norisk_token: &str, request_u
     }
 }
 
+/// Recommends a heap for the mods a launch of the profile would load, it's only a suggestion and never applied on its own
+#[tauri::command]
+async fn recommend_memory(profile_id: String) -> Result<MemoryRecommendation, String> {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launch_manifest = profile_launch_manifest(&profile_id).await?;
+    let launcher_profiles = profile_state::profiles().await;
+    let profile = launcher_profiles.profiles(options.experimental_mode).iter().find(|profile| profile.id == profile_id).ok_or_else(|| format!("unable to find profile {}", profile_id))?;
+    let configured_mb = profile.memory.as_ref().map(|memory| memory.max_mb).unwrap_or_else(|| percentage_of_total_memory(options.memory_percentage) as u64);
+
+    Ok(memory::recommend(memory::launched_mod_count(&launch_manifest, &flatten_mods(&profile.mods)), get_system_ram_mb(), configured_mb))
+}
+
+#[tauri::command]
+async fn get_system_accessibility_info(window: Window) -> Result<AccessibilityInfo, String> {
+    let scale_factor = window.scale_factor().map_err(|e| format!("unable to get scale factor: {:?}", e))?;
+    Ok(accessibility::system_info(scale_factor).await)
+}
+
+#[tauri::command]
+async fn get_system_locale() -> Result<String, String> {
+    Ok(i18n::system_locale().await)
+}
+
+/// Locale the translations should be shown in, the one selected in the options or the system locale
+#[tauri::command]
+async fn get_locale() -> Result<String, String> {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    Ok(i18n::selected_locale(&options).await)
+}
+
+/// Translations for a locale, english is used for missing keys and unsupported languages
+#[tauri::command]
+async fn get_translations(locale: &str) -> Result<HashMap<String, String>, String> {
+    Ok(i18n::translations(locale).await)
+}
+
+/// Durations of the startup phases, to track down regressions of the startup time
+#[tauri::command]
+fn get_startup_timings() -> Result<Vec<StartupPhase>, String> {
+    Ok(startup::timings())
+}
+
+/// Subsystems that sent their `state-ready` already
+#[tauri::command]
+fn get_ready_subsystems() -> Result<Vec<StateReady>, String> {
+    Ok(startup::ready_subsystems())
+}
+
 #[tauri::command]
 async fn mem_percentage(memory_percentage: i32) -> i64 {
     percentage_of_total_memory(memory_percentage)
@@ -1123,10 +3086,12 @@ async fn default_data_folder_path() -> Result<String, String> {
 }
 
 #[tauri::command]
-async fn clear_data(options: LauncherOptions) -> Result<(), String> {
+async fn clear_data(options: LauncherOptions, session_token: Option<String>, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let stored_options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    app_state.settings_sessions.ensure_unlocked(&stored_options, session_token.as_deref(), "Clearing data")?;
     let _ = options.accounts.iter().map(|account| TokenManager {}.delete_tokens(account.clone()));
 
-    let _ = store_options(LauncherOptions::default()).await;
+    let _ = store_options(LauncherOptions::default(), session_token, app_state).await;
 
     ["assets", "gameDir", "libraries", "mod_cache", "natives", "runtimes", "versions"]
         .iter()
@@ -1202,16 +3167,27 @@ async fn create_custom_server(mc_version: &str, loader_version: Option<&str>, r#
 }
 
 #[tauri::command]
-async fn initialize_custom_server(custom_server: CustomServer, additional_data: Option<&str>, window: Window) -> Result<(), String> {
+async fn initialize_custom_server(custom_server: CustomServer, additional_data: Option<&str>, window: Window, app_state: tauri::State<'_, AppState>) -> Result<(), String> {
     let window_mutex = Arc::new(std::sync::Mutex::new(window));
-    match CustomServerManager::initialize_server(&window_mutex, custom_server, additional_data).await {
-        Ok(_) => {
-            Ok(())
-        }
-        Err(err) => {
-            Err(err.to_string())
+    let subdomain = custom_server.subdomain.clone();
+    app_state.task_manager.run(TaskKind::Install, format!("Installing server {}", subdomain), |_| async move {
+        match CustomServerManager::initialize_server(&window_mutex, custom_server, additional_data).await {
+            Ok(_) => {
+                notifications::notify(NotificationCategory::Operations, "Server installed", &format!("{} is ready to start.", subdomain)).await;
+                Ok(())
+            }
+            Err(err) => {
+                Err(err.to_string())
+            }
         }
-    }
+    }).await
+}
+
+/// Output of the last Forge install of a custom server, `None` if none ran yet
+#[tauri::command]
+async fn get_loader_install_log(server_id: &str) -> Result<Option<String>, String> {
+    ensure_folder_name("server id", server_id)?;
+    loader_install::read_log(server_id).await.map_err(|e| format!("unable to read loader install log: {:?}", e))
 }
 
 #[tauri::command]
@@ -1225,6 +3201,7 @@ async fn run_custom_server(custom_server: CustomServer, options: LauncherOptions
             .build()
             .unwrap()
             .block_on(async {
+                let server_id = custom_server.id.clone();
                 let custom_server_process = CustomServerManager::run_server(custom_server, options, token, window_mutex.clone()).await.unwrap();    
                 // if let Err(e) = custom_server_process {
                 //     window_mutex.lock().unwrap().emit("s-error", format!("Failed to launch server: {:?}", e)).unwrap();
@@ -1233,7 +3210,9 @@ async fn run_custom_server(custom_server: CustomServer, options: LauncherOptions
 
                 custom_server_process_mutex.lock().unwrap().replace(custom_server_process);
 
-                window_mutex.lock().unwrap().emit("server-exited", ()).unwrap()
+                let window = window_mutex.lock().unwrap();
+                log_windows::process_ended(&window.app_handle(), &server_id);
+                window.emit("server-exited", ()).unwrap();
             });
     });
     Ok(())
@@ -1417,36 +3396,86 @@ pub fn gui_main() {
         .on_window_event(move |event| match event.event() {
             WindowEvent::Destroyed => {
                 info!("Window destroyed, quitting application");
+                single_instance::release();
+                tauri::async_runtime::block_on(profile_state::flush());
             }
             _ => {}
         })
         .plugin(tauri_plugin_fs_watch::init())
         .setup(|app| {
             let _window = app.get_window("main").unwrap();
+            startup::measure("setup", || {
+                profile_state::init(app.handle());
+                install_state::init(app.handle());
+                notifications::init(&app.handle());
+                thumbnails::init(&app.handle());
+                jump_list::init(&app.handle());
+                single_instance::listen(&app.handle());
+                app.state::<AppState>().task_manager.init(app.handle());
+            });
+            cape_moderation::init(app.handle());
+            tauri::async_runtime::spawn(async {
+                let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+                action_history::empty_trash(&options.data_path_buf()).await;
+            });
+            startup::warm_up(app.handle());
             Ok(())
         })
         .manage(AppState {
-            runner_instance: Arc::new(Mutex::new(None)),
+            runner_instances: Arc::new(Mutex::new(HashMap::new())),
+            task_manager: Arc::new(TaskManager::default()),
             forwarding_manager_process: Arc::new(Mutex::new(None)),
             custom_server_process: Arc::new(Mutex::new(None)),
+            action_history: Arc::new(ActionHistory::default()),
+            settings_sessions: Arc::new(SettingsSessions::default()),
+            pin_attempts: Arc::new(PinAttempts::open(LAUNCHER_DIRECTORY.config_dir().join("pin_attempts.json"))),
         })
         .invoke_handler(tauri::generate_handler![
             open_url,
+            open_log_window,
+            open_progress_overlay,
+            close_progress_overlay,
+            send_notification,
+            launch_fake_process,
+            list_tasks,
+            cancel_task,
+            get_last_sync_report,
+            adopt_unknown_mods,
+            list_profile_configs,
+            check_contents_installed,
+            get_installed_contents,
+            set_content_enabled,
+            trash_file,
+            get_action_history,
+            undo_last_action,
+            get_thumbnails,
+            preview_archive,
+            diff_config_against_default,
+            reset_mod_config,
+            list_config_revisions,
+            restore_config_revision,
             check_online_status,
             get_options,
             store_options,
             check_maintenance_mode,
             request_norisk_branches,
+            get_standard_versions,
+            instantiate_standard_profile,
             connect_discord_intigration,
             check_discord_intigration,
             unlink_discord_intigration,
             login_norisk_microsoft,
             remove_account,
+            list_accounts,
+            find_importable_accounts,
             upload_cape,
             equip_cape,
             get_player_skins,
             save_player_skin,
             read_local_skin_file,
+            get_account_appearance_preferences,
+            set_account_appearance_preferences,
+            apply_account_appearance,
             read_remote_image_file,
             get_cape_hash_by_uuid,
             mc_name_by_uuid,
@@ -1459,10 +3488,35 @@ pub fn gui_main() {
             get_whitelist_slots,
             add_player_to_whitelist,
             run_client,
+            get_launch_command_preview,
+            estimate_install_size,
+            repair_profile_files,
+            get_install_state,
+            export_launch_script,
+            prepare_profile_offline,
+            export_profile,
+            export_mod_list,
+            export_noriskpack,
+            import_mrpack_as_profile,
+            install_modpack_version,
+            import_noriskpack_as_profile,
             enable_experimental_mode,
+            enable_restricted_mode,
+            disable_restricted_mode,
+            set_settings_pin,
+            is_portable_mode,
+            check_data_directory,
+            run_benchmark,
+            get_last_benchmark,
+            get_network_metrics,
+            get_launch_hotfixes,
+            set_hotfix_consent,
+            repair_data_directory,
+            unlock_settings,
             download_template_and_open_explorer,
             request_trending_capes,
             request_owned_capes,
+            get_my_cape_submissions,
             refresh_via_norisk,
             get_mobile_app_token,
             reset_mobile_app_token,
@@ -1472,10 +3526,35 @@ pub fn gui_main() {
             console_log_error,
             get_launcher_profiles,
             store_launcher_profiles,
+            store_launcher_profile,
+            list_profile_summaries,
+            get_launcher_profile,
+            set_profile_icon,
+            resolve_image_path,
+            get_recent_profiles,
+            get_profile_statistics,
+            pin_profile,
+            set_profile_notes,
+            set_profile_tags,
+            search_profiles,
+            check_pack_import,
+            sync_profile_with_standard,
+            take_pending_launch_profile,
+            create_steam_shortcut,
+            get_client_status,
             get_project_version,
+            search_content,
+            get_content_versions,
+            resolve_content_file,
             get_custom_mods_folder,
             save_custom_mods_to_folder,
             install_mod_and_dependencies,
+            add_modrinth_mod_to_profile,
+            check_modrinth_updates,
+            ignore_mod_update,
+            get_pending_update_changelogs,
+            remove_mod_with_orphans,
+            set_norisk_mod_status,
             get_custom_mods_filenames,
             get_custom_shaders_folder,
             save_custom_shaders_to_folder,
@@ -1483,6 +3562,8 @@ pub fn gui_main() {
             search_shaders,
             get_shader_info,
             install_shader,
+            search_modpacks,
+            get_modpack_details,
             get_custom_resourcepacks_folder,
             save_custom_resourcepacks_to_folder,
             get_custom_resourcepacks_filenames,
@@ -1498,7 +3579,15 @@ pub fn gui_main() {
             get_world_folders,
             upload_logs,
             get_launch_manifest,
+            check_norisk_access,
             mem_percentage,
+            recommend_memory,
+            get_system_accessibility_info,
+            get_system_locale,
+            get_locale,
+            get_translations,
+            get_startup_timings,
+            get_ready_subsystems,
             default_data_folder_path,
             terminate,
             get_featured_servers,
@@ -1507,6 +3596,7 @@ pub fn gui_main() {
             get_custom_server_jwt_token,
             create_custom_server,
             initialize_custom_server,
+            get_loader_install_log,
             run_custom_server,
             terminate_custom_server,
             delete_custom_server,
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use log::{debug, error};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::sync::Mutex;
+
+use crate::app::app_data::LauncherOptions;
+use crate::app::api::ApiEndpoints;
+use crate::LAUNCHER_DIRECTORY;
+
+const FALLBACK_LANGUAGE: &str = "en";
+
+/// Translations that ship with the launcher, updated ones are downloaded from the api
+static BUNDLED: &[(&str, &str)] = &[
+    ("en", include_str!("../../translations/en.json")),
+    ("de", include_str!("../../translations/de.json")),
+];
+
+/// Downloaded translations are requested again once they are older than this
+const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+static TRANSLATIONS: Lazy<Mutex<HashMap<String, HashMap<String, String>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+///
+/// Translations downloaded from the api, cached in `translations/<language>.json` of the config directory
+///
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CachedTranslations {
+    #[serde(rename = "fetchedAt")]
+    fetched_at: i64,
+    translations: HashMap<String, String>,
+}
+
+/// Locale of the user as BCP 47 tag, e.g. `de-DE`. Falls back to `en-US` if it can't be detected.
+pub async fn system_locale() -> String {
+    platform::locale().await
+        .map(|locale| normalize(&locale))
+        .filter(|locale| !locale.is_empty())
+        .unwrap_or_else(|| "en-US".to_string())
+}
+
+/// Locale the user selected in the options, or the system locale if they didn't select one
+pub async fn selected_locale(options: &LauncherOptions) -> String {
+    match options.language.as_deref().filter(|language| !language.is_empty()) {
+        Some(language) => language.to_string(),
+        None => system_locale().await,
+    }
+}
+
+/// Turns POSIX locales like `de_DE.UTF-8` into `de-DE`
+fn normalize(locale: &str) -> String {
+    locale.split(|c| c == '.' || c == '@').next().unwrap_or_default().replace('_', "-")
+}
+
+/// Language part of a locale, if there are translations for it
+fn supported_language(locale: &str) -> Option<&'static str> {
+    let language = locale.split('-').next().unwrap_or_default().to_lowercase();
+    BUNDLED.iter().map(|(language, _)| *language).find(|bundled| *bundled == language)
+}
+
+fn bundled(language: &str) -> HashMap<String, String> {
+    BUNDLED.iter()
+        .find(|(bundled, _)| *bundled == language)
+        .and_then(|(_, json)| serde_json::from_str(json).ok())
+        .unwrap_or_default()
+}
+
+/// Downloaded translations of a language, requested again from the api if they are outdated.
+/// The outdated ones are used while the api can't be reached.
+async fn downloaded(language: &str) -> HashMap<String, String> {
+    let path = LAUNCHER_DIRECTORY.config_dir().join("translations").join(format!("{}.json", language));
+    let cached: Option<CachedTranslations> = match fs::read(&path).await {
+        Ok(data) => serde_json::from_slice(&data).ok(),
+        Err(_) => None,
+    };
+
+    if let Some(cached) = &cached {
+        if Utc::now().timestamp() - cached.fetched_at <= MAX_AGE.as_secs() as i64 {
+            return cached.translations.clone();
+        }
+    }
+
+    match ApiEndpoints::norisk_translations(language).await {
+        Ok(translations) => {
+            let fetched = CachedTranslations { fetched_at: Utc::now().timestamp(), translations };
+            if let Err(err) = store(&path, &fetched).await {
+                error!("Failed to cache {} translations: {:?}", language, err);
+            }
+            fetched.translations
+        }
+        Err(err) => {
+            debug!("Unable to request {} translations: {:?}", language, err);
+            cached.map(|cached| cached.translations).unwrap_or_default()
+        }
+    }
+}
+
+async fn store(path: &std::path::Path, translations: &CachedTranslations) -> anyhow::Result<()> {
+    fs::create_dir_all(path.parent().unwrap()).await?;
+    fs::write(path, serde_json::to_vec(translations)?).await?;
+    Ok(())
+}
+
+/// Translations for a locale. Keys that are missing in its language fall back to english,
+/// and downloaded translations take precedence over the bundled ones.
+pub async fn translations(locale: &str) -> HashMap<String, String> {
+    let language = supported_language(locale).unwrap_or(FALLBACK_LANGUAGE);
+
+    let mut cache = TRANSLATIONS.lock().await;
+    if let Some(translations) = cache.get(language) {
+        return translations.clone();
+    }
+
+    let mut translations = bundled(FALLBACK_LANGUAGE);
+    if language != FALLBACK_LANGUAGE {
+        translations.extend(bundled(language));
+    }
+    translations.extend(downloaded(language).await);
+
+    cache.insert(language.to_string(), translations.clone());
+    translations
+}
+
+/// Translation of a key in the selected locale, for texts the backend shows like notifications.
+/// Placeholders like `{name}` are replaced with the values of the same name, like the frontend does.
+pub async fn translate(key: &str, values: &[(&str, &str)]) -> String {
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    let mut translated = translations(&selected_locale(&options).await).await.remove(key).unwrap_or_else(|| key.to_string());
+    for (name, value) in values {
+        translated = translated.replace(&format!("{{{}}}", name), value);
+    }
+    translated
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use crate::utils::user_registry_value;
+
+    pub async fn locale() -> Option<String> {
+        user_registry_value("Control Panel\\International", "LocaleName").await
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use tokio::process::Command;
+
+    pub async fn locale() -> Option<String> {
+        let output = Command::new("defaults").args(["read", "-g", "AppleLocale"]).output().await.ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    pub async fn locale() -> Option<String> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|locale| !locale.is_empty() && locale != "C" && locale != "POSIX")
+    }
+}
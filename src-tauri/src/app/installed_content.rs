@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::app::api::LoaderMod;
+use crate::app::app_data::{Addons, LauncherProfile};
+use crate::app::content_check::ContentType;
+use crate::app::modrinth_api::CustomMod;
+
+///
+/// A mod, shader, resource pack or datapack installed in a profile, no matter where the launcher keeps track of it
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct InstalledContent {
+    #[serde(rename = "contentType")]
+    pub content_type: ContentType,
+    pub slug: String,
+    pub title: String,
+    pub version: Option<String>,
+    /// Packs are always enabled while they are installed
+    pub enabled: bool,
+    /// Mods that are part of the NoRisk pack can't be removed
+    pub required: bool,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    /// World of a datapack
+    pub world: Option<String>,
+    /// Slug of the mod that pulled in this dependency
+    #[serde(rename = "dependencyOf")]
+    pub dependency_of: Option<String>,
+}
+
+impl InstalledContent {
+    pub fn matches(&self, content_type: ContentType, slug: Option<&str>, file_name: Option<&str>, world: Option<&str>) -> bool {
+        self.content_type == content_type
+            && world.map_or(true, |world| self.world.as_deref() == Some(world))
+            && (slug == Some(self.slug.as_str()) || file_name == Some(self.file_name.as_str()))
+    }
+}
+
+/// Placeholders that disable an optional mod of the pack, see `CustomMod::disabled_pack_mod`
+fn is_placeholder(custom_mod: &CustomMod) -> bool {
+    custom_mod.value.source.get_repository() == "PLACEHOLDER"
+}
+
+fn add_mods(mods: &[CustomMod], dependency_of: Option<&str>, contents: &mut Vec<InstalledContent>) {
+    for custom_mod in mods.iter().filter(|custom_mod| !is_placeholder(custom_mod)) {
+        let slug = custom_mod.value.source.get_slug();
+        let version = custom_mod.value.source.get_version();
+        contents.push(InstalledContent {
+            content_type: ContentType::Mod,
+            slug: slug.clone(),
+            title: custom_mod.title.clone(),
+            version: Some(version).filter(|version| !version.is_empty()),
+            enabled: custom_mod.value.enabled,
+            required: custom_mod.value.required,
+            file_name: custom_mod.value.name.clone(),
+            world: None,
+            dependency_of: dependency_of.map(str::to_string),
+        });
+        add_mods(&custom_mod.dependencies, Some(&slug), contents);
+    }
+}
+
+/// Mods of the pack, unless the profile has its own version of them. Optional ones are disabled by a placeholder in the profile.
+fn add_pack_mods(profile: &LauncherProfile, pack_mods: &[LoaderMod], contents: &mut Vec<InstalledContent>) {
+    for pack_mod in pack_mods {
+        let slug = pack_mod.source.get_slug();
+        if contents.iter().any(|content| content.content_type == ContentType::Mod && content.slug.eq_ignore_ascii_case(&slug)) {
+            continue;
+        }
+        let disabled = profile.mods.iter().any(|custom_mod| custom_mod.is_disabled_pack_mod(&slug));
+        let version = pack_mod.source.get_version();
+        contents.push(InstalledContent {
+            content_type: ContentType::Mod,
+            slug,
+            title: pack_mod.name.clone(),
+            version: Some(version).filter(|version| !version.is_empty()),
+            enabled: pack_mod.required || (pack_mod.enabled && !disabled),
+            required: pack_mod.required,
+            file_name: pack_mod.name.clone(),
+            world: None,
+            dependency_of: None,
+        });
+    }
+}
+
+/// Everything installed in a profile. Mods come from the profile and the pack of its branch, packs from the addons of its branch.
+pub fn installed_contents(profile: &LauncherProfile, pack_mods: &[LoaderMod], addons: Option<&Addons>) -> Vec<InstalledContent> {
+    let mut contents = Vec::new();
+    add_mods(&profile.mods, None, &mut contents);
+    add_pack_mods(profile, pack_mods, &mut contents);
+
+    let Some(addons) = addons else { return contents };
+    let packs = addons.shaders.iter().map(|shader| (ContentType::Shader, &shader.slug, &shader.title, &shader.file_name, None))
+        .chain(addons.resourcepacks.iter().map(|pack| (ContentType::ResourcePack, &pack.slug, &pack.title, &pack.file_name, None)))
+        .chain(addons.datapacks.iter().map(|datapack| (ContentType::Datapack, &datapack.slug, &datapack.title, &datapack.file_name, Some(&datapack.world_name))));
+    for (content_type, slug, title, file_name, world) in packs {
+        contents.push(InstalledContent {
+            content_type,
+            slug: slug.clone(),
+            title: title.clone(),
+            version: None,
+            enabled: true,
+            required: false,
+            file_name: file_name.clone(),
+            world: world.cloned(),
+            dependency_of: None,
+        });
+    }
+    contents
+}
+
+/// Whether the profile itself has a mod, as opposed to the mods of its pack
+pub fn has_mod(profile: &LauncherProfile, slug: &str) -> bool {
+    fn find(mods: &[CustomMod], slug: &str) -> bool {
+        mods.iter().filter(|custom_mod| !is_placeholder(custom_mod)).any(|custom_mod| custom_mod.value.source.get_slug() == slug || find(&custom_mod.dependencies, slug))
+    }
+    find(&profile.mods, slug)
+}
+
+/// Mods whose enabled state differs between two versions of a profile, with their new state.
+/// Optional pack mods are disabled through a placeholder entry, adding or removing it toggles them.
+pub fn toggled_mods(before: &LauncherProfile, after: &LauncherProfile) -> Vec<(String, bool)> {
+    fn states(profile: &LauncherProfile) -> HashMap<String, (bool, bool)> {
+        profile.mods.iter().map(|custom_mod| (custom_mod.value.source.get_slug(), (custom_mod.value.enabled, is_placeholder(custom_mod)))).collect()
+    }
+    let before = states(before);
+    let after = states(after);
+    let mut slugs: Vec<&String> = before.keys().chain(after.keys()).collect();
+    slugs.sort();
+    slugs.dedup();
+
+    slugs.into_iter()
+        .filter_map(|slug| match (before.get(slug), after.get(slug)) {
+            (Some((was_enabled, _)), Some((enabled, _))) if was_enabled != enabled => Some((slug.clone(), *enabled)),
+            (None, Some((_, true))) => Some((slug.clone(), false)),
+            (Some((_, true)), None) => Some((slug.clone(), true)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Enables or disables a mod of the profile and its dependencies, returns whether the mod was found.
+/// Mods of the pack aren't part of the profile, packs can't be disabled, they are installed or removed.
+pub fn set_mod_enabled(profile: &mut LauncherProfile, slug: &str, enabled: bool) -> bool {
+    fn set(mods: &mut [CustomMod], slug: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for custom_mod in mods.iter_mut().filter(|custom_mod| !is_placeholder(custom_mod)) {
+            if custom_mod.value.source.get_slug() == slug && !custom_mod.value.required {
+                custom_mod.value.enabled = enabled;
+                for dependency in custom_mod.dependencies.iter_mut().filter(|dependency| !dependency.value.required) {
+                    dependency.value.enabled = enabled;
+                }
+                found = true;
+            } else {
+                found |= set(&mut custom_mod.dependencies, slug, enabled);
+            }
+        }
+        found
+    }
+    set(&mut profile.mods, slug, enabled)
+}
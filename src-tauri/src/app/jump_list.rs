@@ -0,0 +1,209 @@
+use std::sync::Mutex;
+
+use log::{debug, error, info};
+use once_cell::sync::Lazy;
+use tauri::{AppHandle, Manager};
+
+use crate::app::app_data::LauncherOptions;
+use crate::app::profile_state;
+use crate::LAUNCHER_DIRECTORY;
+
+/// Command line argument used by jump list entries to launch a profile directly
+pub const LAUNCH_PROFILE_ARG: &str = "--launch-profile";
+
+/// Max amount of profiles shown in the jump list
+const MAX_ENTRIES: usize = 8;
+
+/// Profile the launcher was asked to launch, until the frontend takes it
+static PENDING_LAUNCH: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(launch_profile_from_args(std::env::args())));
+
+pub struct JumpListEntry {
+    pub profile_id: String,
+    pub title: String,
+}
+
+/// Returns the profile id passed via `--launch-profile <id>` if the launcher was started from a jump list entry
+pub fn launch_profile_from_args(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter().skip_while(|arg| arg != LAUNCH_PROFILE_ARG);
+    args.next()?;
+    args.next()
+}
+
+/// Returns the profile the launcher was asked to launch, only once
+pub fn take_pending_launch() -> Option<String> {
+    PENDING_LAUNCH.lock().unwrap().take()
+}
+
+/// Asks the frontend to launch a profile, for jump list entries started while the launcher runs and the dock menu
+pub fn request_launch(app_handle: &AppHandle, profile_id: String) {
+    info!("Launch of profile {} requested", profile_id);
+    *PENDING_LAUNCH.lock().unwrap() = Some(profile_id);
+    if let Some(window) = app_handle.get_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    let _ = app_handle.emit_all("launch-profile-requested", ());
+}
+
+/// Rebuilds the Windows jump list or the macOS dock menu from the current recent and pinned profiles
+pub async fn refresh() {
+    if !cfg!(any(target_os = "windows", target_os = "macos")) {
+        return;
+    }
+    // portable installs must not leave entries behind on the computers they are plugged into
+    if cfg!(target_os = "windows") && LAUNCHER_DIRECTORY.is_portable() {
+        return;
+    }
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let options = LauncherOptions::load(config_dir).await.unwrap_or_default();
+    let launcher_profiles = profile_state::profiles().await;
+
+    let entries = launcher_profiles.recent_profiles(options.experimental_mode, MAX_ENTRIES)
+        .into_iter()
+        .map(|profile| JumpListEntry {
+            title: format!("Launch {}", profile.name),
+            profile_id: profile.id,
+        })
+        .collect::<Vec<_>>();
+
+    // COM objects have to be created on a thread we own
+    let result = tokio::task::spawn_blocking(move || apply(entries)).await;
+    match result {
+        Ok(Ok(())) => debug!("Updated jump list"),
+        Ok(Err(err)) => error!("Failed to update jump list: {:?}", err),
+        Err(err) => error!("Failed to update jump list: {:?}", err),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn apply(entries: Vec<JumpListEntry>) -> anyhow::Result<()> {
+    use windows::core::{Interface, HSTRING, PROPVARIANT};
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::UI::Shell::{DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink};
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+
+    let executable = std::env::current_exe()?;
+
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let list: ICustomDestinationList = CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        let mut max_slots = 0u32;
+        let _removed: IObjectArray = list.BeginList(&mut max_slots)?;
+
+        let collection: IObjectCollection = CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+        for entry in entries.iter().take(max_slots as usize) {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            link.SetPath(&HSTRING::from(executable.as_os_str()))?;
+            link.SetArguments(&HSTRING::from(format!("{} {}", LAUNCH_PROFILE_ARG, entry.profile_id)))?;
+            link.SetIconLocation(&HSTRING::from(executable.as_os_str()), 0)?;
+
+            let store: IPropertyStore = link.cast()?;
+            store.SetValue(&PKEY_Title, &PROPVARIANT::from(entry.title.as_str()))?;
+            store.Commit()?;
+
+            collection.AddObject(&link)?;
+        }
+
+        let array: IObjectArray = collection.cast()?;
+        list.AppendCategory(&HSTRING::from("Profiles"), &array)?;
+        list.CommitList()?;
+    }
+
+    Ok(())
+}
+
+/// The dock asks for its menu whenever it is opened, so the entries are only kept for then
+#[cfg(target_os = "macos")]
+fn apply(entries: Vec<JumpListEntry>) -> anyhow::Result<()> {
+    *dock_menu::ENTRIES.lock().unwrap() = entries;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn apply(_entries: Vec<JumpListEntry>) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Adds the dock menu to the app delegate of tao, which doesn't offer one itself
+#[cfg(target_os = "macos")]
+pub fn init(app_handle: &AppHandle) {
+    dock_menu::install(app_handle);
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn init(_app_handle: &AppHandle) {}
+
+#[cfg(target_os = "macos")]
+mod dock_menu {
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::sync::Mutex;
+
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSAutoreleasePool, NSString};
+    use log::warn;
+    use objc::declare::ClassDecl;
+    use objc::runtime::{class_addMethod, object_getClass, Class, Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+    use once_cell::sync::{Lazy, OnceCell};
+    use tauri::AppHandle;
+
+    use super::JumpListEntry;
+
+    pub static ENTRIES: Lazy<Mutex<Vec<JumpListEntry>>> = Lazy::new(|| Mutex::new(Vec::new()));
+    static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+    /// Receiver of the clicked menu items, kept as address since `id` isn't `Send`
+    static TARGET: OnceCell<usize> = OnceCell::new();
+
+    pub fn install(app_handle: &AppHandle) {
+        let _ = APP_HANDLE.set(app_handle.clone());
+        unsafe {
+            let mut decl = ClassDecl::new("NoRiskDockMenuTarget", class!(NSObject)).unwrap();
+            decl.add_method(sel!(launchProfile:), launch_profile as extern "C" fn(&Object, Sel, id));
+            let target: id = msg_send![decl.register(), new];
+            let _ = TARGET.set(target as usize);
+
+            let ns_app: id = msg_send![class!(NSApplication), sharedApplication];
+            let delegate: id = msg_send![ns_app, delegate];
+            if delegate == nil {
+                warn!("Unable to add the dock menu, the application has no delegate");
+                return;
+            }
+            let dock_menu: extern "C" fn(&Object, Sel, id) -> id = application_dock_menu;
+            class_addMethod(object_getClass(delegate) as *mut Class, sel!(applicationDockMenu:), std::mem::transmute(dock_menu), b"@@:@\0".as_ptr() as *const c_char);
+        }
+    }
+
+    extern "C" fn application_dock_menu(_this: &Object, _sel: Sel, _sender: id) -> id {
+        unsafe {
+            let menu: id = msg_send![class!(NSMenu), new];
+            let target = TARGET.get().copied().unwrap_or_default() as id;
+            for entry in ENTRIES.lock().unwrap().iter() {
+                let title = NSString::alloc(nil).init_str(&entry.title).autorelease();
+                let key_equivalent = NSString::alloc(nil).init_str("").autorelease();
+                let item: id = msg_send![class!(NSMenuItem), alloc];
+                let item: id = msg_send![item, initWithTitle: title action: sel!(launchProfile:) keyEquivalent: key_equivalent];
+                let profile_id = NSString::alloc(nil).init_str(&entry.profile_id).autorelease();
+                let _: () = msg_send![item, setRepresentedObject: profile_id];
+                let _: () = msg_send![item, setTarget: target];
+                let _: () = msg_send![menu, addItem: item];
+                let _: () = msg_send![item, release];
+            }
+            msg_send![menu, autorelease]
+        }
+    }
+
+    extern "C" fn launch_profile(_this: &Object, _sel: Sel, item: id) {
+        let profile_id = unsafe {
+            let profile_id: id = msg_send![item, representedObject];
+            let utf8: *const c_char = msg_send![profile_id, UTF8String];
+            CStr::from_ptr(utf8).to_string_lossy().to_string()
+        };
+        if let Some(app_handle) = APP_HANDLE.get() {
+            super::request_launch(app_handle, profile_id);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::app::api::LoginData;
+use crate::app::app_data::LogPrivacySettings;
+
+/// JWTs, which Minecraft, Microsoft and NoRisk tokens all are
+static JWT: Lazy<Regex> = Lazy::new(|| Regex::new(r"eyJ[\w-]+\.[\w-]+\.[\w-]*").unwrap());
+/// Tokens passed as arguments or logged as session, like `--accessToken <token>` or `token:<token>:<uuid>`
+static TOKEN_ARGUMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(--accessToken\s+|--session\s+|session(?:id)?[=:]\s*|token:)[^\s:]+").unwrap());
+static IP_ADDRESS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}(?::\d{1,5})?\b").unwrap());
+/// The folder after `Users` or `home` is named after the user of the machine
+static HOME_FOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)([/\\](?:Users|home)[/\\]+)[^/\\\s]+").unwrap());
+
+const REDACTED: &str = "<redacted>";
+
+///
+/// Removes private data from game output according to the log privacy settings
+///
+#[derive(Clone, Debug)]
+pub struct LogRedactor {
+    settings: LogPrivacySettings,
+    /// Tokens of the launching account, removed even if they don't look like tokens
+    secrets: Vec<String>,
+}
+
+impl LogRedactor {
+    pub fn new(settings: LogPrivacySettings, login_data: Option<&LoginData>) -> Self {
+        let secrets = login_data.map(|login_data| {
+            [&login_data.mc_token, &login_data.access_token, &login_data.refresh_token, &login_data.norisk_token]
+                .into_iter()
+                .chain(login_data.experimental_token.as_ref())
+                .filter(|token| token.len() >= 8)
+                .cloned()
+                .collect()
+        }).unwrap_or_default();
+        LogRedactor { settings, secrets }
+    }
+
+    pub fn redact(&self, output: &str) -> String {
+        let mut output = output.to_string();
+        if self.settings.redact_tokens {
+            for secret in &self.secrets {
+                output = output.replace(secret.as_str(), REDACTED);
+            }
+            output = JWT.replace_all(&output, REDACTED).into_owned();
+            output = TOKEN_ARGUMENT.replace_all(&output, format!("${{1}}{}", REDACTED)).into_owned();
+        }
+        if self.settings.redact_ip_addresses {
+            output = IP_ADDRESS.replace_all(&output, REDACTED).into_owned();
+        }
+        if self.settings.redact_usernames {
+            output = HOME_FOLDER.replace_all(&output, "${1}<user>").into_owned();
+        }
+        output
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use log::debug;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::{AppHandle, LogicalSize, Manager, WindowEvent};
+
+/// Open log windows by the id of the process they are showing (player uuid for the client, server id for custom servers)
+static LOG_WINDOWS: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+///
+/// Output line of a process, sent to its log window as `log-window-output`
+///
+#[derive(Clone, Debug, Serialize)]
+struct LogWindowOutput {
+    #[serde(rename = "processId")]
+    process_id: String,
+    data: String,
+}
+
+/// Window labels may only contain alphanumeric characters, `-`, `/`, `:` and `_`
+fn window_label(process_id: &str) -> String {
+    let id: String = process_id.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    format!("log-{}", id)
+}
+
+/// Opens a log window for the given process or focuses it if it is already open
+pub fn open(app_handle: &AppHandle, process_id: &str, title: &str) -> Result<()> {
+    let label = window_label(process_id);
+    if let Some(window) = app_handle.get_window(&label) {
+        LOG_WINDOWS.lock().unwrap().insert(process_id.to_string(), label);
+        window.set_focus()?;
+        return Ok(());
+    }
+
+    let url = format!("index.html?logWindow={}", urlencoding::encode(process_id));
+    let window = tauri::WindowBuilder::new(app_handle, &label, tauri::WindowUrl::App(url.into()))
+        .title(format!("NoRiskClient - {}", title))
+        .build()?;
+    let _ = window.set_size(LogicalSize::new(900, 600));
+
+    LOG_WINDOWS.lock().unwrap().insert(process_id.to_string(), label.clone());
+    debug!("Opened log window {} for process {}", label, process_id);
+
+    let process_id = process_id.to_string();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            LOG_WINDOWS.lock().unwrap().remove(&process_id);
+        }
+    });
+    Ok(())
+}
+
+/// Sends an output line to the log window of the given process, if one is open
+pub fn forward(app_handle: &AppHandle, process_id: &str, data: &str) {
+    let Some(label) = LOG_WINDOWS.lock().unwrap().get(process_id).cloned() else { return };
+    if let Some(window) = app_handle.get_window(&label) {
+        let _ = window.emit("log-window-output", LogWindowOutput { process_id: process_id.to_string(), data: data.to_string() });
+    }
+}
+
+/// Tells the log window of the given process that it has ended so it can stop listening for output.
+/// The window itself stays open so the log can still be read.
+pub fn process_ended(app_handle: &AppHandle, process_id: &str) {
+    let Some(label) = LOG_WINDOWS.lock().unwrap().remove(process_id) else { return };
+    if let Some(window) = app_handle.get_window(&label) {
+        let _ = window.emit("log-window-process-ended", process_id);
+    }
+}
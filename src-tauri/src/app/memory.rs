@@ -0,0 +1,74 @@
+use serde::Serialize;
+
+use crate::app::api::{LoaderMod, NoRiskLaunchManifest};
+
+/// Heap the game needs before any mods are loaded
+const BASE_HEAP_MB: u64 = 2048;
+/// Additional heap per loaded mod
+const HEAP_PER_MOD_MB: u64 = 48;
+/// Heaps above this mostly result in longer garbage collection pauses
+const MAX_USEFUL_HEAP_MB: u64 = 12288;
+/// Memory that should stay available for the operating system and other programs
+const SYSTEM_RESERVED_MB: u64 = 2048;
+const FEW_MODS: usize = 50;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryRecommendation {
+    #[serde(rename = "minMb")]
+    pub min_mb: u64,
+    #[serde(rename = "maxMb")]
+    pub max_mb: u64,
+    #[serde(rename = "systemMb")]
+    pub system_mb: u64,
+    pub warnings: Vec<String>,
+}
+
+fn round_to_512(value: u64) -> u64 {
+    (value + 511) / 512 * 512
+}
+
+/// Amount of mods a launch loads, the enabled pack mods that the profile doesn't disable and the profile's own mods
+pub fn launched_mod_count(launch_manifest: &NoRiskLaunchManifest, additional_mods: &[LoaderMod]) -> usize {
+    let (disabled_pack_mods, profile_mods): (Vec<&LoaderMod>, Vec<&LoaderMod>) = additional_mods.iter()
+        .partition(|loader_mod| loader_mod.source.get_repository() == "PLACEHOLDER");
+    let pack_mods = launch_manifest.mods.iter()
+        .filter(|pack_mod| pack_mod.required || (pack_mod.enabled && !disabled_pack_mods.iter().any(|disabled| disabled.source.get_slug() == pack_mod.source.get_slug())))
+        .count();
+
+    pack_mods + profile_mods.len()
+}
+
+/// Recommends a min/max heap based on the system memory and the amount of mods that are launched.
+/// `configured_mb` is the heap that would currently be used and is only checked for warnings.
+pub fn recommend(mods: usize, system_mb: u64, configured_mb: u64) -> MemoryRecommendation {
+    let mut warnings = Vec::new();
+
+    let available_mb = system_mb.saturating_sub(SYSTEM_RESERVED_MB).max(system_mb / 2);
+    let wanted_mb = round_to_512(BASE_HEAP_MB + mods as u64 * HEAP_PER_MOD_MB).min(MAX_USEFUL_HEAP_MB);
+    let max_mb = wanted_mb.min(available_mb / 512 * 512).max(1024);
+    let min_mb = (max_mb / 2 / 512 * 512).max(512);
+
+    if system_mb < 4096 {
+        warnings.push(format!("Your system only has {} MB of memory, the game might run poorly.", system_mb));
+    }
+    if max_mb < wanted_mb {
+        warnings.push(format!("{} mods would need about {} MB but only {} MB can be spared.", mods, wanted_mb, max_mb));
+    }
+    if configured_mb > MAX_USEFUL_HEAP_MB && mods < FEW_MODS {
+        warnings.push(format!("Allocating more than {} GB with only {} mods is counterproductive and causes longer lag spikes.", MAX_USEFUL_HEAP_MB / 1024, mods));
+    }
+    if configured_mb > available_mb {
+        warnings.push(format!("{} MB leaves too little memory for your operating system.", configured_mb));
+    }
+
+    if configured_mb < min_mb {
+        warnings.push(format!("{} MB is less than the {} MB that {} mods need, the game might run out of memory.", configured_mb, min_mb, mods));
+    }
+
+    MemoryRecommendation {
+        min_mb,
+        max_mb,
+        system_mb,
+        warnings,
+    }
+}
@@ -1,7 +1,44 @@
 pub mod gui;
 
+pub mod accessibility;
+pub mod action_history;
+pub mod account_appearance;
+pub mod account_import;
+pub mod accounts;
 pub mod api;
 pub mod app_data;
+pub mod archive_preview;
+pub mod benchmark;
 pub mod cape_api;
+pub mod cape_moderation;
+pub mod content_blacklist;
+pub mod content_check;
+pub mod crash_upload;
+pub mod data_dir;
+pub mod mod_dependencies;
+pub mod mod_list;
 pub mod modrinth_api;
 pub mod mclogs_api;
+pub mod i18n;
+pub mod installed_content;
+pub mod jump_list;
+pub mod log_privacy;
+pub mod log_windows;
+pub mod memory;
+pub mod notifications;
+pub mod profile_export;
+pub mod profile_import;
+pub mod profile_icons;
+pub mod profile_migrations;
+pub mod profile_names;
+pub mod progress_overlay;
+pub mod profile_state;
+pub mod profile_statistics;
+pub mod settings_lock;
+pub mod single_instance;
+pub mod startup;
+pub mod steam;
+pub mod task_manager;
+pub mod thumbnails;
+pub mod update_changelogs;
+pub mod version_fallback;
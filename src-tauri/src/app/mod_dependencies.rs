@@ -0,0 +1,70 @@
+use serde::Serialize;
+
+use crate::app::modrinth_api::CustomMod;
+
+///
+/// Dependency that no remaining mod of the profile requires after removing a mod
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct OrphanCandidate {
+    pub slug: String,
+    pub title: String,
+}
+
+///
+/// Result of removing a mod. If it leaves dependencies behind that nothing requires anymore,
+/// nothing is removed until the user decided whether to delete them too.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status")]
+pub enum ModRemovalResult {
+    #[serde(rename = "removed")]
+    Removed {
+        /// Slugs of the removed mod and the deleted orphans
+        removed: Vec<String>,
+    },
+    #[serde(rename = "requiresConfirmation")]
+    RequiresConfirmation {
+        orphans: Vec<OrphanCandidate>,
+    },
+}
+
+/// Whether any of the mods or their dependencies is the mod with the given slug
+fn requires(mods: &[&CustomMod], slug: &str) -> bool {
+    mods.iter().any(|custom_mod| {
+        custom_mod.value.source.get_slug().eq_ignore_ascii_case(slug)
+            || custom_mod.dependencies.iter().any(|dependency| dependency.value.source.get_slug().eq_ignore_ascii_case(slug))
+    })
+}
+
+/// Dependencies of the mod that no other mod of the profile requires. Dependencies that NoRiskClient
+/// already ships are disabled in the profile and never orphaned.
+pub fn orphans(mods: &[CustomMod], slug: &str) -> Vec<CustomMod> {
+    let Some(removed) = mods.iter().find(|custom_mod| custom_mod.value.source.get_slug().eq_ignore_ascii_case(slug)) else { return Vec::new() };
+    let remaining: Vec<&CustomMod> = mods.iter()
+        .filter(|custom_mod| !custom_mod.value.source.get_slug().eq_ignore_ascii_case(slug))
+        .collect();
+
+    removed.dependencies.iter()
+        .filter(|dependency| dependency.value.enabled)
+        .filter(|dependency| !requires(&remaining, &dependency.value.source.get_slug()))
+        .cloned()
+        .collect()
+}
+
+/// Removes the mod, deleting its orphaned dependencies or keeping them as mods of their own
+pub fn remove(mods: &mut Vec<CustomMod>, slug: &str, delete_orphans: bool) -> Vec<String> {
+    let orphans = orphans(mods, slug);
+    let mut removed: Vec<String> = mods.iter()
+        .filter(|custom_mod| custom_mod.value.source.get_slug().eq_ignore_ascii_case(slug))
+        .map(|custom_mod| custom_mod.value.source.get_slug())
+        .collect();
+    mods.retain(|custom_mod| !custom_mod.value.source.get_slug().eq_ignore_ascii_case(slug));
+
+    if delete_orphans {
+        removed.extend(orphans.iter().map(|orphan| orphan.value.source.get_slug()));
+    } else {
+        mods.extend(orphans);
+    }
+    removed
+}
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::app::api::ModSource;
+use crate::app::app_data::LauncherProfile;
+use crate::app::modrinth_api::CustomMod;
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum ModListFormat {
+    #[serde(rename = "markdown")]
+    Markdown,
+    #[serde(rename = "csv")]
+    Csv,
+    #[serde(rename = "json")]
+    Json,
+}
+
+impl ModListFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ModListFormat::Markdown => "md",
+            ModListFormat::Csv => "csv",
+            ModListFormat::Json => "json",
+        }
+    }
+}
+
+///
+/// A mod of a profile as listed for credits and documentation
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ModListEntry {
+    pub name: String,
+    pub version: String,
+    #[serde(rename = "sourceUrl")]
+    pub source_url: Option<String>,
+    pub enabled: bool,
+    /// Installed as dependency of another mod
+    pub dependency: bool,
+}
+
+fn source_url(custom_mod: &CustomMod) -> Option<String> {
+    if let Some(url) = &custom_mod.value.source_url {
+        return Some(url.clone());
+    }
+    let ModSource::Repository { repository, url, .. } = &custom_mod.value.source;
+    if repository == "modrinth" {
+        Some(format!("https://modrinth.com/mod/{}", custom_mod.value.source.get_slug()))
+    } else {
+        url.clone()
+    }
+}
+
+fn add_entries(entries: &mut Vec<ModListEntry>, custom_mod: &CustomMod, dependency: bool) {
+    if entries.iter().any(|entry| entry.name == custom_mod.title) {
+        return; // dependencies shared by several mods are listed once
+    }
+    entries.push(ModListEntry {
+        name: custom_mod.title.clone(),
+        version: custom_mod.value.source.get_version(),
+        source_url: source_url(custom_mod),
+        enabled: custom_mod.value.enabled,
+        dependency,
+    });
+    for dependency in &custom_mod.dependencies {
+        add_entries(entries, dependency, true);
+    }
+}
+
+/// Mods of the profile and their dependencies, sorted by name
+pub fn entries(profile: &LauncherProfile) -> Vec<ModListEntry> {
+    let mut entries = Vec::new();
+    for custom_mod in &profile.mods {
+        add_entries(&mut entries, custom_mod, false);
+    }
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+pub fn render(profile: &LauncherProfile, entries: &[ModListEntry], format: ModListFormat) -> Result<String> {
+    Ok(match format {
+        ModListFormat::Markdown => {
+            let mut output = format!("# Mods of {}\n\n| Name | Version | Source | Enabled |\n| --- | --- | --- | --- |\n", markdown_cell(&profile.name));
+            for entry in entries {
+                let name = if entry.dependency { format!("{} (dependency)", entry.name) } else { entry.name.clone() };
+                let source = entry.source_url.as_ref().map_or(String::new(), |url| format!("<{}>", url));
+                output.push_str(&format!("| {} | {} | {} | {} |\n", markdown_cell(&name), markdown_cell(&entry.version), source, if entry.enabled { "yes" } else { "no" }));
+            }
+            output
+        }
+        ModListFormat::Csv => {
+            let mut output = String::from("name,version,source_url,enabled,dependency\n");
+            for entry in entries {
+                output.push_str(&format!("{},{},{},{},{}\n", csv_field(&entry.name), csv_field(&entry.version), csv_field(entry.source_url.as_deref().unwrap_or_default()), entry.enabled, entry.dependency));
+            }
+            output
+        }
+        ModListFormat::Json => serde_json::to_string_pretty(entries)?,
+    })
+}
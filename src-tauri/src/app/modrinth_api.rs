@@ -3,10 +3,28 @@ use std::error::Error;
 use std::fs::metadata;
 use std::path::Path;
 
+use anyhow::bail;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
-use crate::app::api::{LoaderMod, ModSource};
+use crate::app::api::{ContentFile, ContentProject, ContentProvider, ContentSearch, ContentSearchResult, ContentVersion, LoaderMod, ModSource, ModpackDetails, ModpackSearchFilters, ModpackSearchResult, ModpackSummary, ModpackVersionSummary, UpdateChannel};
+use crate::app::content_check::ContentType;
 use crate::HTTP_CLIENT;
+use crate::utils::{download_file_untracked, file_utils, sha1sum};
+
+/// Base url of the Modrinth API, the mock server while testing
+pub fn modrinth_api_base() -> String {
+    #[cfg(feature = "testing")]
+    if let Some(base) = crate::testing::api_base_override() {
+        return format!("{}/modrinth/v2", base);
+    }
+    String::from("https://api.modrinth.com/v2")
+}
+
+/// Pages a search filtered by pack size fetches at most, so a strict filter doesn't page through all of Modrinth
+const MAX_FILTERED_SEARCH_PAGES: usize = 5;
 
 /// Placeholder struct for API endpoints implementation
 pub struct ModrinthApiEndpoints;
@@ -14,7 +32,7 @@ pub struct ModrinthApiEndpoints;
 impl ModrinthApiEndpoints {
     // MODS
     pub async fn search_mods(params: &ModrinthSearchRequestParams) -> Result<ModrinthModsSearchResponse, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/search?facets={}&index={}&limit={}&offset={}&query={}", params.facets, params.index, params.limit, params.offset, params.query);
+        let url = format!("{}/search?facets={}&index={}&limit={}&offset={}&query={}", modrinth_api_base(), params.facets, params.index, params.limit, params.offset, params.query);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -28,20 +46,11 @@ impl ModrinthApiEndpoints {
     pub async fn get_custom_mod_names(mod_cache_path: &Path) -> anyhow::Result<Vec<String>> {
         tokio::fs::create_dir_all(&mod_cache_path).await?;
 
-        // Copy all mods from custom_mods to mods
-        let mut mods_read = tokio::fs::read_dir(&mod_cache_path).await?;
-        let mut files: Vec<String> = Vec::new();
-        while let Some(entry) = mods_read.next_entry().await? {
-            if entry.file_type().await?.is_file() {
-                files.push(entry.file_name().to_str().unwrap().to_string());
-            }
-        }
-
-        Ok(files)
+        file_utils::list_file_names(mod_cache_path).await
     }
 
     pub async fn get_mod_slug(slug_or_id: &str) -> Result<Mod, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}", slug_or_id);
+        let url = format!("{}/project/{}", modrinth_api_base(), slug_or_id);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -53,7 +62,7 @@ impl ModrinthApiEndpoints {
     }
 
     pub async fn get_mod_info(slug_or_id: &str) -> Result<ModInfo, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}", slug_or_id);
+        let url = format!("{}/project/{}", modrinth_api_base(), slug_or_id);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -65,7 +74,7 @@ impl ModrinthApiEndpoints {
     }
 
     pub async fn get_project_version(slug: &str, params: &str) -> Result<Vec<ModrinthProject>, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}/version{}", slug, params);
+        let url = format!("{}/project/{}/version{}", modrinth_api_base(), slug, params);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -112,9 +121,63 @@ impl ModrinthApiEndpoints {
         })
     }
 
+    pub async fn get_version(version_id: &str) -> Result<ModrinthProject, Box<dyn Error>> {
+        let url = format!("{}/version/{}", modrinth_api_base(), version_id);
+        let response = HTTP_CLIENT.get(url)
+            .send()
+            .await
+            .map_err(|e| format!("Modrinth Version Request error: {:?}", e))?;
+        match response.json::<ModrinthProject>().await {
+            Ok(json) => Ok(json),
+            Err(e) => Err(Box::new(e) as Box<dyn Error>),
+        }
+    }
+
+    /// Resolves a specific version of a mod and its dependencies for the given game version and loader
+    pub async fn install_mod_version(version: &ModrinthProject, game_version: &str, loader: &str, required_mods: &Vec<LoaderMod>) -> Result<CustomMod, Box<dyn Error>> {
+        let mod_project = ModrinthApiEndpoints::get_mod_slug(&version.project_id).await?;
+        let params = format!("?game_versions=[\"{}\"]&loaders=[\"{}\"]", game_version, loader);
+        let dependencies = ModrinthApiEndpoints::get_dependencies(&version.dependencies, &params, required_mods).await?;
+
+        Ok(version.to_custom_mod(&mod_project.title, &mod_project.slug, &mod_project.icon_url, dependencies, false, true))
+    }
+
+    /// Newest version of the mod for the game version and loader its update channel allows,
+    /// `None` if the installed version is already the newest or the mod is pinned
+    pub async fn check_mod_update(custom_mod: &CustomMod, game_version: &str, loader: &str) -> Result<Option<ModUpdate>, Box<dyn Error>> {
+        let channel = custom_mod.value.update_channel;
+        if channel == UpdateChannel::Pinned {
+            return Ok(None);
+        }
+
+        let slug = custom_mod.value.source.get_slug();
+        let params = format!("?game_versions=[\"{}\"]&loaders=[\"{}\"]", game_version, loader);
+        let versions = ModrinthApiEndpoints::get_project_version(&slug, &params).await?;
+        let current_version = custom_mod.value.source.get_version();
+        // modrinth lists the newest version first, so every allowed version before the installed one is newer.
+        // without the installed version in the list there is no telling which versions are newer,
+        // suggesting any of them could be a downgrade
+        let Some(installed) = versions.iter().position(|version| version.version_number == current_version) else { return Ok(None) };
+        let candidates: Vec<ModrinthProject> = versions.into_iter()
+            .take(installed)
+            .filter(|version| channel.allows(&version.version_type))
+            .collect();
+        let Some(latest) = candidates.first() else { return Ok(None) };
+
+        Ok(Some(ModUpdate {
+            slug,
+            title: custom_mod.title.clone(),
+            current_version,
+            latest_version: latest.version_number.clone(),
+            version_id: latest.id.clone(),
+            version_type: latest.version_type.clone(),
+            changelogs: candidates.iter().map(VersionChangelog::from).collect(),
+        }))
+    }
+
     // SHADERS
     pub async fn search_shaders(params: &ModrinthSearchRequestParams) -> Result<ModrinthShadersSearchResponse, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/search?facets={}&index={}&limit={}&offset={}&query={}", params.facets, params.index, params.limit, params.offset, params.query);
+        let url = format!("{}/search?facets={}&index={}&limit={}&offset={}&query={}", modrinth_api_base(), params.facets, params.index, params.limit, params.offset, params.query);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -128,21 +191,14 @@ impl ModrinthApiEndpoints {
     pub async fn get_custom_shader_names(shaders_path: &Path, installed_shaders: &Vec<Shader>) -> anyhow::Result<Vec<String>> {
         tokio::fs::create_dir_all(&shaders_path).await?;
 
-        let mut shaders_read = tokio::fs::read_dir(&shaders_path).await?;
-        let mut files: Vec<String> = Vec::new();
-        while let Some(entry) = shaders_read.next_entry().await? {
-            if entry.file_type().await?.is_file() && entry.file_name().to_str().unwrap().ends_with(".zip") {
-                if !installed_shaders.iter().any(|shader| shader.file_name == entry.file_name().to_str().unwrap().to_string()) {
-                    files.push(entry.file_name().to_str().unwrap().to_string());
-                }
-            }
-        }
-
-        Ok(files)
+        Ok(file_utils::list_file_names(shaders_path).await?
+            .into_iter()
+            .filter(|file| file.ends_with(".zip") && !installed_shaders.iter().any(|shader| shader.file_name == *file))
+            .collect())
     }
 
     pub async fn get_shader_slug(slug_or_id: &str) -> Result<Shader, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}", slug_or_id);
+        let url = format!("{}/project/{}", modrinth_api_base(), slug_or_id);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -154,7 +210,7 @@ impl ModrinthApiEndpoints {
     }
 
     pub async fn get_shader_info(slug_or_id: &str) -> Result<ShaderInfo, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}", slug_or_id);
+        let url = format!("{}/project/{}", modrinth_api_base(), slug_or_id);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -181,7 +237,7 @@ impl ModrinthApiEndpoints {
     
     // RESOURCE-PACKS
     pub async fn search_resourcepacks(params: &ModrinthSearchRequestParams) -> Result<ModrinthResourcePacksSearchResponse, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/search?facets={}&index={}&limit={}&offset={}&query={}", params.facets, params.index, params.limit, params.offset, params.query);
+        let url = format!("{}/search?facets={}&index={}&limit={}&offset={}&query={}", modrinth_api_base(), params.facets, params.index, params.limit, params.offset, params.query);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -195,21 +251,14 @@ impl ModrinthApiEndpoints {
     pub async fn get_custom_resourcepack_names(resourcepacks_path: &Path, installed_resourcepacks: &Vec<ResourcePack>) -> anyhow::Result<Vec<String>> {
         tokio::fs::create_dir_all(&resourcepacks_path).await?;
 
-        let mut resourcepacks_read = tokio::fs::read_dir(&resourcepacks_path).await?;
-        let mut files: Vec<String> = Vec::new();
-        while let Some(entry) = resourcepacks_read.next_entry().await? {
-            if entry.file_type().await?.is_file() && entry.file_name().to_str().unwrap().ends_with(".zip") {
-                if !installed_resourcepacks.iter().any(|resourcepack| resourcepack.file_name == entry.file_name().to_str().unwrap().to_string()) {
-                    files.push(entry.file_name().to_str().unwrap().to_string());
-                }
-            }
-        }
-
-        Ok(files)
+        Ok(file_utils::list_file_names(resourcepacks_path).await?
+            .into_iter()
+            .filter(|file| file.ends_with(".zip") && !installed_resourcepacks.iter().any(|resourcepack| resourcepack.file_name == *file))
+            .collect())
     }
 
     pub async fn get_resourcepack_slug(slug_or_id: &str) -> Result<ResourcePack, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}", slug_or_id);
+        let url = format!("{}/project/{}", modrinth_api_base(), slug_or_id);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -221,7 +270,7 @@ impl ModrinthApiEndpoints {
     }
 
     pub async fn get_resourcepack_info(slug_or_id: &str) -> Result<ResourcePackInfo, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}", slug_or_id);
+        let url = format!("{}/project/{}", modrinth_api_base(), slug_or_id);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -248,7 +297,7 @@ impl ModrinthApiEndpoints {
     
     // DATAPACKS
     pub async fn search_datapacks(params: &ModrinthSearchRequestParams) -> Result<ModrinthDatapacksSearchResponse, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/search?facets={}&l=datapack&index={}&limit={}&offset={}&query={}", params.facets, params.index, params.limit, params.offset, params.query);
+        let url = format!("{}/search?facets={}&l=datapack&index={}&limit={}&offset={}&query={}", modrinth_api_base(), params.facets, params.index, params.limit, params.offset, params.query);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -262,21 +311,14 @@ impl ModrinthApiEndpoints {
     pub async fn get_custom_datapack_names(datapacks_path: &Path, installed_resourcepacks: &Vec<Datapack>) -> anyhow::Result<Vec<String>> {
         tokio::fs::create_dir_all(&datapacks_path).await?;
 
-        let mut datapacks_read = tokio::fs::read_dir(&datapacks_path).await?;
-        let mut files: Vec<String> = Vec::new();
-        while let Some(entry) = datapacks_read.next_entry().await? {
-            if entry.file_type().await?.is_file() && entry.file_name().to_str().unwrap().ends_with(".zip") {
-                if !installed_resourcepacks.iter().any(|datapack| datapack.file_name == entry.file_name().to_str().unwrap().to_string()) {
-                    files.push(entry.file_name().to_str().unwrap().to_string());
-                }
-            }
-        }
-
-        Ok(files)
+        Ok(file_utils::list_file_names(datapacks_path).await?
+            .into_iter()
+            .filter(|file| file.ends_with(".zip") && !installed_resourcepacks.iter().any(|datapack| datapack.file_name == *file))
+            .collect())
     }
 
     pub async fn get_datapack_slug(slug_or_id: &str) -> Result<ResourcePack, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}", slug_or_id);
+        let url = format!("{}/project/{}", modrinth_api_base(), slug_or_id);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -288,7 +330,7 @@ impl ModrinthApiEndpoints {
     }
 
     pub async fn get_datapack_info(slug_or_id: &str) -> Result<DatapackInfo, Box<dyn Error>> {
-        let url = format!("https://api.modrinth.com/v2/project/{}", slug_or_id);
+        let url = format!("{}/project/{}", modrinth_api_base(), slug_or_id);
         let response = HTTP_CLIENT.get(url)
             .send()
             .await
@@ -313,6 +355,274 @@ impl ModrinthApiEndpoints {
             url: Some(project_version.files.first().unwrap().url.clone())
         })
     }
+
+    async fn search_modpacks_page(filters: &ModpackSearchFilters, offset: u32) -> anyhow::Result<ModrinthModpacksSearchResponse> {
+        let mut facets = vec![vec!["project_type:modpack".to_string()]];
+        if let Some(game_version) = &filters.game_version {
+            facets.push(vec![format!("versions:{}", game_version)]);
+        }
+        if let Some(loader) = &filters.loader {
+            facets.push(vec![format!("categories:{}", loader)]);
+        }
+
+        Ok(HTTP_CLIENT.get(format!("{}/search", modrinth_api_base()))
+            .query(&[
+                ("facets", serde_json::to_string(&facets)?),
+                ("index", filters.index.clone().unwrap_or_else(|| "relevance".to_string())),
+                ("limit", filters.limit.to_string()),
+                ("offset", offset.to_string()),
+                ("query", filters.query.clone()),
+            ])
+            .send().await?
+            .error_for_status()?
+            .json::<ModrinthModpacksSearchResponse>().await?)
+    }
+
+    /// Loads several versions with a single request
+    async fn get_versions_by_ids(ids: &[String]) -> anyhow::Result<Vec<ModrinthProject>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(HTTP_CLIENT.get(format!("{}/versions", modrinth_api_base()))
+            .query(&[("ids", serde_json::to_string(ids)?)])
+            .send().await?
+            .error_for_status()?
+            .json::<Vec<ModrinthProject>>().await?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ModrinthSearchHit {
+    project_id: String,
+    slug: String,
+    title: String,
+    description: String,
+    author: Option<String>,
+    icon_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModrinthSearchResponse {
+    hits: Vec<ModrinthSearchHit>,
+    total_hits: u32,
+}
+
+impl From<ModrinthProject> for ContentVersion {
+    fn from(project: ModrinthProject) -> Self {
+        ContentVersion {
+            id: project.id,
+            project_id: project.project_id,
+            name: project.name,
+            version_number: project.version_number,
+            game_versions: project.game_versions,
+            loaders: project.loaders,
+            files: project.files.into_iter().map(|file| ContentFile {
+                url: file.url,
+                file_name: file.filename,
+                sha1: Some(file.hashes.sha1),
+                size: Some(file.size as u64),
+                primary: file.primary,
+            }).collect(),
+            required_projects: project.dependencies.into_iter()
+                .filter(|dependency| dependency.dependency_type == "required")
+                .map(|dependency| dependency.project_id)
+                .collect(),
+        }
+    }
+}
+
+impl ContentProvider for ModrinthApiEndpoints {
+    fn id(&self) -> &'static str {
+        "modrinth"
+    }
+
+    fn search<'a>(&'a self, search: &'a ContentSearch) -> BoxFuture<'a, anyhow::Result<ContentSearchResult>> {
+        async move {
+            let project_type = match search.content_type {
+                ContentType::Mod => "mod",
+                ContentType::Shader => "shader",
+                ContentType::ResourcePack => "resourcepack",
+                ContentType::Datapack => "datapack",
+            };
+            let mut facets = vec![format!("[\"project_type:{}\"]", project_type)];
+            if let Some(game_version) = &search.game_version {
+                facets.push(format!("[\"versions:{}\"]", game_version));
+            }
+            if let Some(loader) = &search.loader {
+                facets.push(format!("[\"categories:{}\"]", loader));
+            }
+
+            let response = HTTP_CLIENT.get(format!("{}/search", modrinth_api_base()))
+                .query(&[
+                    ("query", search.query.clone()),
+                    ("facets", format!("[{}]", facets.join(","))),
+                    ("limit", search.limit.to_string()),
+                    ("offset", search.offset.to_string()),
+                ])
+                .send().await?
+                .error_for_status()?
+                .json::<ModrinthSearchResponse>().await?;
+
+            Ok(ContentSearchResult {
+                total_hits: response.total_hits,
+                hits: response.hits.into_iter().map(|hit| ContentProject {
+                    id: hit.project_id,
+                    slug: hit.slug,
+                    title: hit.title,
+                    description: hit.description,
+                    author: hit.author,
+                    icon_url: hit.icon_url.filter(|icon_url| !icon_url.is_empty()),
+                }).collect(),
+            })
+        }.boxed()
+    }
+
+    fn get_versions<'a>(&'a self, project: &'a str, game_version: Option<&'a str>, loader: Option<&'a str>) -> BoxFuture<'a, anyhow::Result<Vec<ContentVersion>>> {
+        async move {
+            let mut query = Vec::new();
+            if let Some(game_version) = game_version {
+                query.push(("game_versions", format!("[\"{}\"]", game_version)));
+            }
+            if let Some(loader) = loader {
+                query.push(("loaders", format!("[\"{}\"]", loader)));
+            }
+
+            let versions = HTTP_CLIENT.get(format!("{}/project/{}/version", modrinth_api_base(), project))
+                .query(&query)
+                .send().await?
+                .error_for_status()?
+                .json::<Vec<ModrinthProject>>().await?;
+            Ok(versions.into_iter().map(ContentVersion::from).collect())
+        }.boxed()
+    }
+
+    fn resolve_by_hash<'a>(&'a self, sha1: &'a str) -> BoxFuture<'a, anyhow::Result<Option<ContentVersion>>> {
+        async move {
+            let response = HTTP_CLIENT.get(format!("{}/version_file/{}?algorithm=sha1", modrinth_api_base(), sha1))
+                .send().await?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            let version = response.error_for_status()?.json::<ModrinthProject>().await?;
+            Ok(Some(version.into()))
+        }.boxed()
+    }
+
+    fn download<'a>(&'a self, file: &'a ContentFile, target: &'a Path) -> BoxFuture<'a, anyhow::Result<()>> {
+        async move {
+            if let Some(parent) = target.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            download_file_untracked(&file.url, target).await?;
+
+            if let Some(sha1) = &file.sha1 {
+                if sha1sum(&target.to_path_buf())? != *sha1 {
+                    tokio::fs::remove_file(target).await?;
+                    bail!("sha1 of {} doesn't match", file.file_name);
+                }
+            }
+            Ok(())
+        }.boxed()
+    }
+
+    /// When a maximum pack size is set the latest version of every hit is looked up, since the search itself
+    /// doesn't know file sizes, and further pages are fetched until the page is full again.
+    fn search_modpacks<'a>(&'a self, filters: &'a ModpackSearchFilters) -> BoxFuture<'a, anyhow::Result<ModpackSearchResult>> {
+        async move {
+            let Some(max_pack_size) = filters.max_pack_size else {
+                let response = ModrinthApiEndpoints::search_modpacks_page(filters, filters.offset).await?;
+                let hits = response.hits.into_iter().map(ModpackSummary::from).collect::<Vec<_>>();
+                let next_offset = filters.offset + hits.len() as u32;
+                return Ok(ModpackSearchResult { hits, offset: filters.offset, next_offset, limit: filters.limit, total_hits: response.total_hits });
+            };
+
+            let mut hits = Vec::new();
+            let mut next_offset = filters.offset;
+            let mut total_hits = 0;
+            for _ in 0..MAX_FILTERED_SEARCH_PAGES {
+                let response = ModrinthApiEndpoints::search_modpacks_page(filters, next_offset).await?;
+                total_hits = response.total_hits;
+                if response.hits.is_empty() {
+                    break;
+                }
+
+                let mut page = response.hits.into_iter().map(ModpackSummary::from).collect::<Vec<_>>();
+                let version_ids = page.iter().filter_map(|hit| hit.latest_version_id.clone()).collect::<Vec<_>>();
+                let versions = ModrinthApiEndpoints::get_versions_by_ids(&version_ids).await?;
+                for hit in &mut page {
+                    hit.pack_size = versions.iter()
+                        .find(|version| Some(&version.id) == hit.latest_version_id.as_ref())
+                        .and_then(|version| version.files.iter().find(|file| file.primary).or(version.files.first()))
+                        .map(|file| file.size as u64);
+                }
+                for hit in page {
+                    // the rest of the page is left to the next search
+                    if hits.len() >= filters.limit as usize {
+                        break;
+                    }
+                    next_offset += 1;
+                    if hit.pack_size.map_or(false, |size| size <= max_pack_size) {
+                        hits.push(hit);
+                    }
+                }
+                if hits.len() >= filters.limit as usize || next_offset >= total_hits {
+                    break;
+                }
+            }
+
+            Ok(ModpackSearchResult { hits, offset: filters.offset, next_offset, limit: filters.limit, total_hits })
+        }.boxed()
+    }
+
+    fn get_modpack_details<'a>(&'a self, project: &'a str) -> BoxFuture<'a, anyhow::Result<ModpackDetails>> {
+        async move {
+            let project = HTTP_CLIENT.get(format!("{}/project/{}", modrinth_api_base(), project))
+                .send().await?
+                .error_for_status()?
+                .json::<ModrinthModpackProject>().await?;
+            if project.project_type != "modpack" {
+                bail!("{} is not a modpack", project.slug);
+            }
+
+            let versions = HTTP_CLIENT.get(format!("{}/project/{}/version", modrinth_api_base(), project.id))
+                .send().await?
+                .error_for_status()?
+                .json::<Vec<ModrinthProject>>().await?
+                .into_iter()
+                .filter_map(|version| {
+                    let file = version.files.iter().find(|file| file.primary).or(version.files.first())?.clone();
+                    Some(ModpackVersionSummary {
+                        id: version.id,
+                        name: version.name,
+                        version_number: version.version_number,
+                        version_type: version.version_type,
+                        date_published: version.date_published,
+                        game_versions: version.game_versions,
+                        loaders: version.loaders,
+                        file_name: file.filename,
+                        url: file.url,
+                        pack_size: file.size as u64,
+                    })
+                })
+                .collect();
+
+            Ok(ModpackDetails {
+                project_id: project.id,
+                slug: project.slug,
+                title: project.title,
+                description: project.description,
+                body: project.body,
+                icon_url: project.icon_url,
+                downloads: project.downloads,
+                follows: project.followers,
+                categories: project.categories,
+                game_versions: project.game_versions,
+                loaders: project.loaders,
+                gallery: project.gallery.into_iter().map(|image| image.url).collect(),
+                versions,
+            })
+        }.boxed()
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -347,6 +657,75 @@ pub struct ModrinthDatapacksSearchResponse {
     total_hits: u32,
 }
 
+#[derive(Debug, Deserialize)]
+struct ModrinthModpacksSearchResponse {
+    hits: Vec<ModrinthModpackHit>,
+    total_hits: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModrinthModpackHit {
+    project_id: String,
+    slug: String,
+    title: String,
+    description: String,
+    author: Option<String>,
+    icon_url: Option<String>,
+    downloads: u64,
+    follows: u64,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    versions: Vec<String>,
+    latest_version: Option<String>,
+}
+
+impl From<ModrinthModpackHit> for ModpackSummary {
+    fn from(hit: ModrinthModpackHit) -> Self {
+        ModpackSummary {
+            project_id: hit.project_id,
+            slug: hit.slug,
+            title: hit.title,
+            description: hit.description,
+            author: hit.author,
+            icon_url: hit.icon_url,
+            downloads: hit.downloads,
+            follows: hit.follows,
+            categories: hit.categories,
+            game_versions: hit.versions,
+            latest_version_id: hit.latest_version,
+            pack_size: None,
+        }
+    }
+}
+
+//Minified response from https://api.modrinth.com/v2/project/{id|slug}
+#[derive(Debug, Deserialize)]
+struct ModrinthModpackProject {
+    id: String,
+    slug: String,
+    project_type: String,
+    title: String,
+    description: String,
+    body: String,
+    icon_url: Option<String>,
+    downloads: u64,
+    followers: u64,
+    #[serde(default)]
+    categories: Vec<String>,
+    #[serde(default)]
+    game_versions: Vec<String>,
+    #[serde(default)]
+    loaders: Vec<String>,
+    #[serde(default)]
+    gallery: Vec<ModrinthGalleryImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModrinthGalleryImage {
+    url: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ModrinthSearchRequestParams {
     pub facets: String,
@@ -364,6 +743,9 @@ pub struct ModInfo {
     pub description: String,
     pub icon_url: String,
     pub game_versions: Option<Vec<String>>,
+    /// Version to install a featured mod for when it doesn't support the branch's version, see `version_fallback`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_game_version: Option<String>,
 }
 
 //Minified response from https://api.modrinth.com/v2/project/{id|slug}
@@ -479,6 +861,12 @@ impl ModrinthProject {
                     artifact: repo_artifact,
                     url: Some(url)
                 },
+                category: None,
+                description: None,
+                recommended: false,
+                source_url: Some(format!("https://modrinth.com/mod/{}", slug)),
+                update_channel: UpdateChannel::default(),
+                sha1: self.files.first().map(|file| file.hashes.sha1.clone()),
             },
             dependencies,
         };
@@ -502,6 +890,12 @@ impl ModrinthProject {
                 artifact: repo_artifact,
                 url: Some(url)
             },
+            category: None,
+            description: None,
+            recommended: false,
+            source_url: Some(format!("https://modrinth.com/mod/{}", slug)),
+            update_channel: UpdateChannel::default(),
+            sha1: self.files.first().map(|file| file.hashes.sha1.clone()),
         };
     }
 
@@ -512,6 +906,18 @@ impl ModrinthProject {
         }).unwrap_or("ERROR-MOD".to_string());
     }
 
+    /// Reasons why this version won't work with the given game version and loader
+    pub fn compatibility_warnings(&self, game_version: &str, loader: &str) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.game_versions.iter().any(|version| version == game_version) {
+            warnings.push(format!("{} is made for Minecraft {} but the profile uses {}", self.name, self.game_versions.join(", "), game_version));
+        }
+        if !self.loaders.iter().any(|version_loader| version_loader.eq_ignore_ascii_case(loader)) {
+            warnings.push(format!("{} is made for {} but the profile uses {}", self.name, self.loaders.join(", "), loader));
+        }
+        warnings
+    }
+
     pub fn is_already_required_by_norisk_client(&self, mods: &Vec<LoaderMod>) -> bool {
         let dependency_slug = self.to_slug();
         return mods.iter().any(|loader_mod| {
@@ -551,4 +957,93 @@ pub struct CustomMod {
     pub image_url: String,
     pub value: LoaderMod,
     pub dependencies: Vec<CustomMod>,
+}
+
+impl CustomMod {
+    /// Entry of the profile that disables the optional pack mod with this slug
+    pub fn disabled_pack_mod(slug: &str) -> Self {
+        CustomMod {
+            title: slug.to_string(),
+            image_url: String::new(),
+            value: LoaderMod {
+                required: false,
+                enabled: false,
+                name: slug.to_string(),
+                source: ModSource::Repository { repository: String::new(), artifact: format!("PLACEHOLDER:{}", slug), url: Some(String::new()) },
+                category: None,
+                description: None,
+                recommended: false,
+                source_url: None,
+                update_channel: UpdateChannel::default(),
+                sha1: None,
+            },
+            dependencies: Vec::new(),
+        }
+    }
+
+    pub fn is_disabled_pack_mod(&self, slug: &str) -> bool {
+        self.value.source.get_repository() == "PLACEHOLDER" && self.value.source.get_slug().eq_ignore_ascii_case(slug)
+    }
+}
+
+///
+/// Newer version of an installed mod that its update channel allows
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ModUpdate {
+    pub slug: String,
+    pub title: String,
+    #[serde(rename = "currentVersion")]
+    pub current_version: String,
+    #[serde(rename = "latestVersion")]
+    pub latest_version: String,
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+    /// `release` or `beta`
+    #[serde(rename = "versionType")]
+    pub version_type: String,
+    /// Changelogs of every version between the installed and the latest one, newest first
+    #[serde(skip)]
+    pub changelogs: Vec<VersionChangelog>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VersionChangelog {
+    #[serde(rename = "versionNumber")]
+    pub version_number: String,
+    #[serde(rename = "versionType")]
+    pub version_type: String,
+    #[serde(rename = "datePublished")]
+    pub date_published: String,
+    /// Markdown as written by the mod author
+    pub changelog: String,
+}
+
+impl From<&ModrinthProject> for VersionChangelog {
+    fn from(version: &ModrinthProject) -> Self {
+        VersionChangelog {
+            version_number: version.version_number.clone(),
+            version_type: version.version_type.clone(),
+            date_published: version.date_published.clone(),
+            changelog: version.changelog.clone(),
+        }
+    }
+}
+
+///
+/// Result of adding a modrinth mod to a profile. Versions that don't match the profile's
+/// game version or loader are only installed after the user confirmed the warnings.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status")]
+pub enum ModInstallResult {
+    #[serde(rename = "installed")]
+    Installed {
+        #[serde(rename = "mod")]
+        installed_mod: CustomMod,
+    },
+    #[serde(rename = "requiresConfirmation")]
+    RequiresConfirmation {
+        warnings: Vec<String>,
+    },
 }
\ No newline at end of file
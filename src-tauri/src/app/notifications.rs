@@ -0,0 +1,43 @@
+use log::{debug, error};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use tauri::api::notification::Notification;
+
+use crate::app::app_data::LauncherOptions;
+use crate::LAUNCHER_DIRECTORY;
+
+static IDENTIFIER: OnceCell<String> = OnceCell::new();
+
+///
+/// Kind of event a native notification is sent for, each can be turned off in the launcher options
+///
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum NotificationCategory {
+    #[serde(rename = "operations")]
+    Operations,
+    #[serde(rename = "crashes")]
+    Crashes,
+    #[serde(rename = "updates")]
+    Updates,
+    #[serde(rename = "capes")]
+    Capes,
+}
+
+pub fn init(app_handle: &tauri::AppHandle) {
+    let _ = IDENTIFIER.set(app_handle.config().tauri.bundle.identifier.clone());
+}
+
+/// Shows a native notification unless its category is disabled in the launcher options
+pub async fn notify(category: NotificationCategory, title: &str, body: &str) {
+    let Some(identifier) = IDENTIFIER.get() else { return };
+
+    let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    if !options.notifications.is_enabled(category) {
+        debug!("Skipping {:?} notification: {}", category, title);
+        return;
+    }
+
+    if let Err(err) = Notification::new(identifier).title(title).body(body).show() {
+        error!("Failed to show notification: {:?}", err);
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::app::api::{LoaderMod, ModSource};
+use crate::app::app_data::{LauncherProfile, PackSource, ProfileMemory};
+use crate::app::modrinth_api::CustomMod;
+use crate::app::{profile_icons, profile_import};
+use crate::utils::{ZipEntry, ZipSource};
+
+/// Version of the export format, raised whenever a field is added or removed
+const EXPORT_VERSION: u32 = 1;
+
+/// File of a `.noriskpack` with the custom icon of the profile
+pub const ICON_FILE: &str = "icon.png";
+
+/// Files and folders of the game dir that are part of a `.noriskpack`, mods are installed from the profile instead
+const PACK_FILES: [&str; 6] = ["config", "options.txt", "optionsof.txt", "servers.dat", "resourcepacks", "shaderpacks"];
+
+///
+/// A profile as it is shared with other players. It can only be built from a profile and copies
+/// the fields that are safe to share, so ids, accounts and local paths never end up in an export.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportedProfile {
+    #[serde(rename = "exportVersion")]
+    export_version: u32,
+    name: String,
+    branch: String,
+    mods: Vec<ExportedMod>,
+    #[serde(default)]
+    memory: Option<ProfileMemory>,
+    /// Only builtin icons, custom icons are files of the local launcher
+    #[serde(default)]
+    icon: Option<String>,
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(rename = "packSource", default)]
+    pack_source: Option<PackSource>,
+    /// Minecraft version of the branch when the pack was exported, older exports don't have it
+    #[serde(rename = "mcVersion", default, skip_serializing_if = "Option::is_none")]
+    mc_version: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ExportedMod {
+    title: String,
+    #[serde(rename = "imageUrl")]
+    image_url: String,
+    value: LoaderMod,
+    #[serde(default)]
+    dependencies: Vec<ExportedMod>,
+}
+
+/// Whether a value points to something on this machine
+fn is_local(value: &str) -> bool {
+    value.starts_with("file:") || Path::new(value).is_absolute() || value.get(1..3) == Some(":\\")
+}
+
+impl ExportedMod {
+    fn from_custom_mod(custom_mod: &CustomMod) -> Option<Self> {
+        let mut value = custom_mod.value.clone();
+        let ModSource::Repository { url, .. } = &mut value.source;
+        if url.as_deref().map_or(false, is_local) {
+            // the file only exists here, the mod can't be installed from an export
+            warn!("Leaving {} out of the export, it is installed from a local file", custom_mod.title);
+            return None;
+        }
+        if value.source_url.as_deref().map_or(false, is_local) {
+            value.source_url = None;
+        }
+
+        Some(ExportedMod {
+            title: custom_mod.title.clone(),
+            image_url: if is_local(&custom_mod.image_url) { String::new() } else { custom_mod.image_url.clone() },
+            value,
+            dependencies: custom_mod.dependencies.iter().filter_map(ExportedMod::from_custom_mod).collect(),
+        })
+    }
+
+    fn into_custom_mod(self) -> CustomMod {
+        CustomMod {
+            title: self.title,
+            image_url: self.image_url,
+            value: self.value,
+            dependencies: self.dependencies.into_iter().map(ExportedMod::into_custom_mod).collect(),
+        }
+    }
+}
+
+impl From<&LauncherProfile> for ExportedProfile {
+    fn from(profile: &LauncherProfile) -> Self {
+        ExportedProfile {
+            export_version: EXPORT_VERSION,
+            name: profile.name.clone(),
+            branch: profile.branch.clone(),
+            mods: profile.mods.iter().filter_map(ExportedMod::from_custom_mod).collect(),
+            memory: profile.memory.clone(),
+            icon: profile.icon.clone().filter(|icon| icon.starts_with("builtin:")),
+            notes: profile.notes.clone(),
+            tags: profile.tags.clone(),
+            pack_source: profile.pack_source.clone(),
+            mc_version: None,
+        }
+    }
+}
+
+impl ExportedProfile {
+    pub fn branch(&self) -> &str {
+        &self.branch
+    }
+
+    pub fn mc_version(&self) -> Option<&str> {
+        self.mc_version.as_deref()
+    }
+
+    /// Creates a new profile from an export, it gets a new id and the branch of the export
+    pub fn into_profile(self) -> LauncherProfile {
+        LauncherProfile {
+            id: uuid::Uuid::new_v4().to_string(),
+            branch: self.branch,
+            name: self.name,
+            mods: self.mods.into_iter().map(ExportedMod::into_custom_mod).collect(),
+            last_played: None,
+            pinned: false,
+            memory: self.memory,
+            icon: self.icon.filter(|icon| icon.starts_with("builtin:")),
+            notes: self.notes,
+            tags: self.tags,
+            pack_source: self.pack_source,
+            source_standard_profile_id: None,
+            standard_variant: None,
+            loader_version: None,
+            provenance: None,
+            isolated: false,
+            ignored_updates: Vec::new(),
+            revision: 0,
+            unknown_fields: serde_json::Map::new(),
+        }
+    }
+}
+
+/// Files of a folder below the root, keyed by their path relative to the root inside the archive
+fn folder_entries(root: &Path, folder: &str, prefix: &str, files: &mut BTreeMap<String, PathBuf>) -> Result<()> {
+    for entry in WalkDir::new(root.join(folder)).into_iter().filter_map(|entry| entry.ok()).filter(|entry| entry.file_type().is_file()) {
+        let relative: Vec<String> = entry.path().strip_prefix(root)?.components().map(|component| component.as_os_str().to_string_lossy().to_string()).collect();
+        files.insert(format!("{}/{}", prefix, relative.join("/")), entry.path().to_path_buf());
+    }
+    Ok(())
+}
+
+/// Entries of a `.noriskpack`, as `import_noriskpack` reads them: the profile, its custom icon, the custom mods of its branch
+/// below `custom_mods/` and the files it launches with below `overrides/`
+pub fn noriskpack_entries(profile: &LauncherProfile, mc_version: Option<String>, game_dir: &Path, custom_mods_dir: Option<&Path>, include_worlds: bool) -> Result<Vec<ZipEntry>> {
+    let exported = ExportedProfile { mc_version, ..ExportedProfile::from(profile) };
+    let mut entries = vec![ZipEntry {
+        name: "profile.json".to_string(),
+        source: ZipSource::Bytes(serde_json::to_vec_pretty(&exported)?),
+    }];
+    if let Some(icon) = profile.icon.as_deref().and_then(profile_icons::icon_path) {
+        entries.push(ZipEntry { name: ICON_FILE.to_string(), source: ZipSource::File(icon) });
+    }
+
+    let mut files = BTreeMap::new();
+    if let Some(custom_mods_dir) = custom_mods_dir {
+        folder_entries(custom_mods_dir, "", "custom_mods", &mut files)?;
+    }
+    let worlds = include_worlds.then_some("saves");
+    for name in PACK_FILES.into_iter().chain(worlds) {
+        folder_entries(game_dir, name, "overrides", &mut files)?;
+    }
+    // the pack files of an imported profile are only copied into the game dir when it is launched, until then the game dir has those of another profile
+    if !profile_import::overrides_applied(game_dir, &profile.id) {
+        folder_entries(&profile_import::profile_overrides(game_dir, &profile.id), "", "overrides", &mut files)?;
+    }
+
+    entries.extend(files.into_iter().map(|(name, path)| ZipEntry { name, source: ZipSource::File(path) }));
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn mod_json(slug: &str, url: Option<&str>) -> Value {
+        json!({
+            "title": slug,
+            "image_url": format!("https://cdn.modrinth.com/{}.png", slug),
+            "value": {
+                "enabled": true,
+                "name": slug,
+                "source": { "type": "repository", "repository": "modrinth", "artifact": format!("maven.modrinth:{}:1.0.0", slug), "url": url },
+            },
+            "dependencies": [],
+        })
+    }
+
+    /// A profile as the current launcher stores it, with everything that must not be exported
+    fn stored_profile() -> LauncherProfile {
+        serde_json::from_value(json!({
+            "id": "4f6e1b9c-0000-4000-8000-000000000000",
+            "branch": "prod",
+            "name": "My Profile",
+            "mods": [mod_json("sodium", None), mod_json("local", Some("file:///C:/Users/Steve/Downloads/local.jar"))],
+            "lastPlayed": 1700000000,
+            "pinned": true,
+            "memory": { "minMb": 2048, "maxMb": 4096 },
+            "icon": "4f6e1b9c.png",
+            "notes": "notes",
+            "tags": ["pvp"],
+            "packSource": { "projectId": "pack", "versionId": "v1" },
+            "sourceStandardProfileId": "prod-default",
+            "provenance": { "source": "modrinth", "importedAt": 1700000000, "filePath": "C:\\Users\\Steve\\pack.mrpack" },
+            "isolated": true,
+            "revision": 7,
+            "accountUuid": "625dd22b-bad2-4b82-a0bc-e43ba1c1a7fd",
+        })).unwrap()
+    }
+
+    #[test]
+    fn export_only_contains_shareable_fields() {
+        let exported = serde_json::to_value(ExportedProfile::from(&stored_profile())).unwrap();
+
+        let mut keys: Vec<&str> = exported.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["branch", "exportVersion", "icon", "memory", "mods", "name", "notes", "packSource", "tags"]);
+        assert_eq!(exported["exportVersion"], EXPORT_VERSION);
+        // custom icons and mods from local files only exist on this machine
+        assert_eq!(exported["icon"], Value::Null);
+        assert_eq!(exported["mods"].as_array().unwrap().len(), 1);
+        assert_eq!(exported["mods"][0]["value"]["name"], "sodium");
+        let serialized = exported.to_string();
+        assert!(!serialized.contains("4f6e1b9c"));
+        assert!(!serialized.contains("625dd22b"));
+        assert!(!serialized.contains("Steve"));
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let exported = ExportedProfile::from(&stored_profile());
+        let json = serde_json::to_string(&exported).unwrap();
+        let imported = serde_json::from_str::<ExportedProfile>(&json).unwrap().into_profile();
+
+        assert_ne!(imported.id, stored_profile().id);
+        assert_eq!(imported.revision, 0);
+        assert!(imported.provenance.is_none());
+        assert!(imported.unknown_fields.is_empty());
+        assert_eq!(serde_json::to_value(ExportedProfile::from(&imported)).unwrap(), serde_json::to_value(&exported).unwrap());
+    }
+
+    #[test]
+    fn imports_minimal_export() {
+        // the optional fields may be missing in exports of other tools
+        let exported: ExportedProfile = serde_json::from_value(json!({
+            "exportVersion": 1,
+            "name": "Old",
+            "branch": "prod",
+            "mods": [{ "title": "sodium", "imageUrl": "", "value": mod_json("sodium", None)["value"] }],
+        })).unwrap();
+        let profile = exported.into_profile();
+
+        assert_eq!(profile.name, "Old");
+        assert_eq!(profile.mods.len(), 1);
+        assert!(profile.memory.is_none());
+        assert!(profile.tags.is_empty());
+    }
+}
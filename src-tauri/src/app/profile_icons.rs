@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::imageops::FilterType;
+use log::{error, info};
+use once_cell::sync::Lazy;
+use tokio::fs;
+
+use crate::LAUNCHER_DIRECTORY;
+
+/// Prefix of icons shipped with the frontend
+pub const BUILTIN_PREFIX: &str = "builtin:";
+/// Icons are scaled down to fit into this size
+const ICON_SIZE: u32 = 128;
+
+/// Icons are data, the folder moves along with the data folder. Icons of older versions were kept in the config folder.
+static ICONS_DIR: Lazy<PathBuf> = Lazy::new(|| {
+    let icons_dir = LAUNCHER_DIRECTORY.data_dir().join("profile_icons");
+    let legacy_dir = LAUNCHER_DIRECTORY.config_dir().join("profile_icons");
+    if legacy_dir.exists() && !icons_dir.exists() {
+        match std::fs::create_dir_all(LAUNCHER_DIRECTORY.data_dir()).and_then(|_| std::fs::rename(&legacy_dir, &icons_dir)) {
+            Ok(()) => info!("Moved profile icons to {:?}", icons_dir),
+            Err(err) => error!("Failed to move profile icons to {:?}: {:?}", icons_dir, err),
+        }
+    }
+    icons_dir
+});
+
+fn icons_dir() -> PathBuf {
+    ICONS_DIR.clone()
+}
+
+/// Copies the image into the profile_icons folder, scaled down and converted to png, and returns its reference
+pub async fn store_icon(profile_id: &str, source: &Path) -> Result<String> {
+    let file_name = format!("{}.png", sanitize_filename::sanitize(profile_id));
+    let target = icons_dir().join(&file_name);
+    fs::create_dir_all(icons_dir()).await?;
+
+    let source = source.to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        // the format is guessed from the content, downloaded icons have no meaningful extension
+        let mut icon = image::io::Reader::open(&source)?.with_guessed_format()?.decode()?;
+        if icon.width() > ICON_SIZE || icon.height() > ICON_SIZE {
+            icon = icon.resize(ICON_SIZE, ICON_SIZE, FilterType::Lanczos3);
+        }
+        icon.save_with_format(&target, image::ImageFormat::Png)?;
+        Ok(())
+    }).await??;
+
+    Ok(file_name)
+}
+
+pub async fn delete_icon(reference: &str) {
+    if !reference.starts_with(BUILTIN_PREFIX) {
+        let _ = fs::remove_file(icons_dir().join(reference)).await;
+    }
+}
+
+/// File of a stored icon, `None` for builtin icons and references that would escape the icons folder
+pub fn icon_path(reference: &str) -> Option<PathBuf> {
+    let stored = !reference.starts_with(BUILTIN_PREFIX) && Path::new(reference).components().count() == 1;
+    stored.then(|| icons_dir().join(reference))
+}
+
+/// Resolves an icon reference to something the frontend can display.
+/// Builtin icons are returned unchanged, stored icons as data url.
+pub async fn resolve(reference: &str) -> Result<String> {
+    if reference.starts_with(BUILTIN_PREFIX) {
+        return Ok(reference.to_string());
+    }
+
+    // references are plain file names, don't allow escaping the icons folder
+    if Path::new(reference).components().count() != 1 {
+        bail!("invalid icon reference {}", reference);
+    }
+
+    let data = fs::read(icons_dir().join(reference)).await?;
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(data)))
+}
@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{anyhow, bail, Result};
+use log::{debug, info, warn};
+use serde::Deserialize;
+use tokio::fs;
+use walkdir::WalkDir;
+
+use crate::app::api::{self, ContentFile};
+use crate::app::modrinth_api::{CustomMod, ModrinthApiEndpoints};
+use crate::app::profile_export::{ExportedProfile, ICON_FILE};
+use crate::app::task_manager::{TaskHandle, TaskPhase};
+use crate::minecraft::{launcher, mod_configs};
+use crate::utils::{long_path, prefixed_path, zip_extract_folder, zip_folder_entries, zip_read_file, MAX_PATH};
+
+/// Folders of a modpack that are copied into the game dir as they are
+const OVERRIDE_FOLDERS: [&str; 2] = ["overrides", "client-overrides"];
+
+/// File below the `profiles` folder of a game dir naming the profile whose pack files the game dir has
+const APPLIED_OVERRIDES_FILE: &str = "applied_overrides";
+
+/// Characters of the staging folder name, unique enough for the imports running at the same time
+const STAGING_ID_LENGTH: usize = 8;
+
+///
+/// Folder an import is prepared in. Nothing outside of it is touched until the import is committed,
+/// so a failed or cancelled import only has to delete this folder, which happens when it is dropped.
+///
+pub struct ImportStaging {
+    dir: PathBuf,
+    /// Longest path of a staged file relative to the overrides, see `estimate_path_lengths`
+    longest_relative: AtomicUsize,
+}
+
+impl ImportStaging {
+    pub async fn new(data: &Path) -> Result<Self> {
+        // a short folder name leaves more of the path limit of Windows to the files of the pack
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let dir = data.join("imports").join(&id[..STAGING_ID_LENGTH]);
+        fs::create_dir_all(&dir).await?;
+        Ok(ImportStaging { dir, longest_relative: AtomicUsize::new(0) })
+    }
+
+    /// Remembers how long the paths of the files about to be staged get, before any of them is written.
+    /// Paths over the limit of Windows are written with the long path prefix instead of failing halfway through the import.
+    fn estimate_path_lengths(&self, relatives: &[PathBuf]) {
+        let longest = relatives.iter().map(|relative| relative.as_os_str().len()).max().unwrap_or_default();
+        self.longest_relative.fetch_max(longest, Ordering::Relaxed);
+        let staged = self.overrides().as_os_str().len() + 1 + longest;
+        if staged >= MAX_PATH {
+            info!("Staged modpack files reach {} characters, writing them with long paths", staged);
+        }
+    }
+
+    /// Files that end up in the game dir
+    fn overrides(&self) -> PathBuf {
+        self.dir.join("overrides")
+    }
+
+    /// Custom mods of a `.noriskpack`, they are shared by all profiles of the branch
+    fn custom_mods(&self) -> PathBuf {
+        self.dir.join("custom_mods")
+    }
+
+    /// Icon of the pack, if it has one
+    pub fn icon(&self) -> Option<PathBuf> {
+        Some(self.dir.join(ICON_FILE)).filter(|icon| icon.is_file())
+    }
+
+    /// Moves the staged custom mods into the custom mods folder of the branch, mods it already has are kept
+    pub async fn commit_custom_mods(&self, custom_mods_dir: &Path) -> Result<()> {
+        let custom_mods = self.custom_mods();
+        if !custom_mods.exists() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(custom_mods_dir).await?;
+        let mut entries = fs::read_dir(&custom_mods).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let target = custom_mods_dir.join(entry.file_name());
+            if target.exists() {
+                debug!("Keeping custom mod {:?}, it is installed already", target);
+                continue;
+            }
+            if fs::rename(entry.path(), &target).await.is_err() {
+                fs::copy(entry.path(), &target).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the staged files to the profile, they are copied into the game dir its branch shares with other profiles
+    /// when the profile is launched, see `apply_overrides`
+    pub async fn commit(&self, game_dir: &Path, profile_id: &str) -> Result<()> {
+        let overrides = self.overrides();
+        if !overrides.exists() {
+            return Ok(());
+        }
+
+        let longest = game_dir.as_os_str().len() + 1 + self.longest_relative.load(Ordering::Relaxed);
+        if longest >= MAX_PATH {
+            warn!("Modpack files reach {} characters in {:?}, mods without long path support may not find them", longest, game_dir);
+        }
+
+        // walked from the prefixed folder so the paths of its files are prefixed as well, however long they get
+        let overrides = prefixed_path(&overrides);
+        // the configs of the pack are what its mods should be reset to
+        if let Err(err) = mod_configs::store_shipped_defaults(&overrides.join("config"), game_dir).await {
+            warn!("Failed to store default configs of the pack: {:?}", err);
+        }
+        let profile_overrides = profile_overrides(game_dir, profile_id);
+        for entry in WalkDir::new(&overrides).into_iter().filter_map(|entry| entry.ok()).filter(|entry| entry.file_type().is_file()) {
+            let source = long_path(entry.path());
+            let relative = entry.path().strip_prefix(&overrides)?;
+            let target = long_path(&profile_overrides.join(relative));
+            fs::create_dir_all(target.parent().unwrap()).await?;
+            if fs::rename(&source, &target).await.is_err() {
+                // the data folder might be on another drive than the game dir
+                fs::copy(&source, &target).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ImportStaging {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_dir_all(&self.dir) {
+            warn!("Failed to remove import staging folder {:?}: {:?}", self.dir, err);
+        }
+    }
+}
+
+/// Files of the pack a profile was imported from
+pub fn profile_overrides(game_dir: &Path, profile_id: &str) -> PathBuf {
+    launcher::profile_dir(game_dir, profile_id).join("overrides")
+}
+
+/// Whether the game dir has the pack files of the profile, because it was the last profile of the branch that was launched
+pub fn overrides_applied(game_dir: &Path, profile_id: &str) -> bool {
+    std::fs::read_to_string(game_dir.join("profiles").join(APPLIED_OVERRIDES_FILE)).map_or(false, |applied| applied == profile_id)
+}
+
+/// Removes the pack files of the profile before the files of another version of its pack are committed.
+/// The game dir has to get the new files on the next launch even if the profile was the last one launched.
+pub async fn clear_overrides(game_dir: &Path, profile_id: &str) -> Result<()> {
+    let overrides = profile_overrides(game_dir, profile_id);
+    if overrides.exists() {
+        fs::remove_dir_all(prefixed_path(&overrides)).await?;
+    }
+    if overrides_applied(game_dir, profile_id) {
+        fs::remove_file(game_dir.join("profiles").join(APPLIED_OVERRIDES_FILE)).await?;
+    }
+    Ok(())
+}
+
+/// Copies the pack files of the profile into the game dir, unless the game dir still has them from the last launch of the profile.
+/// Changes the player made to them since then are kept, they are only replaced once another profile of the branch was launched.
+pub async fn apply_overrides(game_dir: &Path, profile_id: &str) -> Result<()> {
+    if overrides_applied(game_dir, profile_id) {
+        return Ok(());
+    }
+
+    let overrides = profile_overrides(game_dir, profile_id);
+    if overrides.exists() {
+        let overrides = prefixed_path(&overrides);
+        let mut applied = 0;
+        for entry in WalkDir::new(&overrides).into_iter().filter_map(|entry| entry.ok()).filter(|entry| entry.file_type().is_file()) {
+            let relative = entry.path().strip_prefix(&overrides)?;
+            let target = long_path(&game_dir.join(relative));
+            fs::create_dir_all(target.parent().unwrap()).await?;
+            fs::copy(long_path(entry.path()), &target).await?;
+            applied += 1;
+        }
+        info!("Copied {} pack files of profile {} into {:?}", applied, profile_id, game_dir);
+    }
+    let applied_file = game_dir.join("profiles").join(APPLIED_OVERRIDES_FILE);
+    fs::create_dir_all(applied_file.parent().unwrap()).await?;
+    fs::write(&applied_file, profile_id).await?;
+    Ok(())
+}
+
+///
+/// Modpack read into a staging folder, it only becomes a profile once the staged files are committed
+///
+pub struct ImportedPack {
+    pub name: String,
+    /// Version of the pack as its author named it
+    pub version: Option<String>,
+    /// Minecraft version the mods were resolved for
+    pub game_version: String,
+    pub mods: Vec<CustomMod>,
+}
+
+#[derive(Deserialize)]
+struct MrpackIndex {
+    name: String,
+    #[serde(rename = "versionId", default)]
+    version_id: Option<String>,
+    files: Vec<MrpackFile>,
+    dependencies: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct MrpackFile {
+    path: String,
+    hashes: HashMap<String, String>,
+    #[serde(default)]
+    env: Option<HashMap<String, String>>,
+    downloads: Vec<String>,
+    #[serde(rename = "fileSize", default)]
+    file_size: Option<u64>,
+}
+
+impl MrpackIndex {
+    fn game_version(&self) -> Result<&str> {
+        self.dependencies.get("minecraft").map(String::as_str).ok_or_else(|| anyhow!("modpack doesn't specify a minecraft version"))
+    }
+
+    fn loader(&self) -> &str {
+        ["fabric-loader", "quilt-loader", "forge", "neoforge"].into_iter()
+            .find(|loader| self.dependencies.contains_key(*loader))
+            .map_or("fabric", |loader| loader.trim_end_matches("-loader"))
+    }
+}
+
+/// Reads a `.mrpack`. Mods known to Modrinth become mods of the profile, every other file is staged for the game dir.
+pub async fn import_mrpack(path: &Path, staging: &ImportStaging, task: &TaskHandle) -> Result<ImportedPack> {
+    task.set_phase(TaskPhase::Scanning);
+    let index = zip_read_file(fs::File::open(path).await?, "modrinth.index.json").await?
+        .ok_or_else(|| anyhow!("{:?} is not a modrinth modpack", path))?;
+    let index: MrpackIndex = serde_json::from_slice(&index)?;
+    let game_version = index.game_version()?;
+    let loader = index.loader();
+    let provider = api::content_provider("modrinth").unwrap();
+
+    let files = index.files.iter()
+        .filter(|file| file.env.as_ref().and_then(|env| env.get("client")).map_or(true, |client| client != "unsupported"))
+        .collect::<Vec<_>>();
+    let mut relatives = files.iter().map(|file| PathBuf::from(&file.path)).collect::<Vec<_>>();
+    for folder in OVERRIDE_FOLDERS {
+        relatives.extend(zip_folder_entries(fs::File::open(path).await?, folder).await?);
+    }
+    staging.estimate_path_lengths(&relatives);
+
+    let mut mods = Vec::new();
+    task.set_phase(TaskPhase::ResolvingMods);
+    for (file_idx, file) in files.iter().enumerate() {
+        task.set_progress(file_idx as f64 / files.len() as f64);
+        let sha1 = file.hashes.get("sha1").cloned();
+
+        if file.path.starts_with("mods/") {
+            if let Some(sha1) = &sha1 {
+                if let Some(version) = provider.resolve_by_hash(sha1).await? {
+                    let version = ModrinthApiEndpoints::get_version(&version.id).await.map_err(|e| anyhow!("unable to load version {}: {}", version.id, e))?;
+                    let custom_mod = ModrinthApiEndpoints::install_mod_version(&version, game_version, loader, &Vec::new()).await.map_err(|e| anyhow!("unable to install {}: {}", file.path, e))?;
+                    mods.push(custom_mod);
+                    continue;
+                }
+            }
+        }
+
+        let relative = Path::new(&file.path);
+        if relative.is_absolute() || relative.components().any(|component| matches!(component, std::path::Component::ParentDir)) {
+            bail!("modpack file {} points outside of the game dir", file.path);
+        }
+        let url = file.downloads.first().ok_or_else(|| anyhow!("modpack file {} has no download", file.path))?;
+        debug!("Staging modpack file {}", file.path);
+        let content_file = ContentFile {
+            url: url.clone(),
+            file_name: file.path.clone(),
+            sha1,
+            size: file.file_size,
+            primary: true,
+        };
+        provider.download(&content_file, &long_path(&staging.overrides().join(relative))).await?;
+    }
+
+    task.set_phase(TaskPhase::Extracting);
+    for (folder_idx, folder) in OVERRIDE_FOLDERS.iter().enumerate() {
+        zip_extract_folder(fs::File::open(path).await?, folder, &staging.overrides(), |entry, entries| {
+            task.set_progress((folder_idx as f64 + entry as f64 / entries as f64) / OVERRIDE_FOLDERS.len() as f64);
+        }).await?;
+    }
+
+    // not part of the format, but exported by several launchers
+    if let Some(icon) = zip_read_file(fs::File::open(path).await?, ICON_FILE).await? {
+        fs::write(staging.dir.join(ICON_FILE), icon).await?;
+    }
+
+    info!("Read modpack {} for {} {} with {} mods", index.name, loader, game_version, mods.len());
+    let game_version = game_version.to_string();
+    Ok(ImportedPack { name: index.name, version: index.version_id, game_version, mods })
+}
+
+/// Reads a `.noriskpack`, a profile export with the files of its game dir, the custom mods of its branch and its icon
+pub async fn import_noriskpack(path: &Path, staging: &ImportStaging, task: &TaskHandle) -> Result<ExportedProfile> {
+    task.set_phase(TaskPhase::Scanning);
+    let profile = zip_read_file(fs::File::open(path).await?, "profile.json").await?
+        .ok_or_else(|| anyhow!("{:?} is not a NoRisk pack", path))?;
+    let profile: ExportedProfile = serde_json::from_slice(&profile)?;
+    staging.estimate_path_lengths(&zip_folder_entries(fs::File::open(path).await?, "overrides").await?);
+    task.set_phase(TaskPhase::Extracting);
+    zip_extract_folder(fs::File::open(path).await?, "overrides", &staging.overrides(), |entry, entries| {
+        task.set_progress(entry as f64 / entries as f64);
+    }).await?;
+    zip_extract_folder(fs::File::open(path).await?, "custom_mods", &staging.custom_mods(), |_, _| {}).await?;
+    if let Some(icon) = zip_read_file(fs::File::open(path).await?, ICON_FILE).await? {
+        fs::write(staging.dir.join(ICON_FILE), icon).await?;
+    }
+
+    Ok(profile)
+}
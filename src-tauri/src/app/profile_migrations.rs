@@ -0,0 +1,49 @@
+use log::{info, warn};
+use serde_json::{json, Value};
+
+/// Schema version of launcher_profiles.json written by this launcher
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Migration from the version at its index to the next one
+const MIGRATIONS: [fn(&mut Value); CURRENT_SCHEMA_VERSION as usize] = [
+    fill_missing_collections,
+];
+
+/// Launchers before schema versions didn't always write every list, which made the whole file unreadable
+fn fill_missing_collections(launcher_profiles: &mut Value) {
+    for key in ["mainProfiles", "experimentalProfiles"] {
+        let profiles = launcher_profiles.as_object_mut().unwrap().entry(key).or_insert_with(|| json!([]));
+        for profile in profiles.as_array_mut().into_iter().flatten() {
+            if let Some(profile) = profile.as_object_mut() {
+                profile.entry("mods").or_insert_with(|| json!([]));
+            }
+        }
+    }
+    for key in ["selectedMainProfiles", "selectedExperimentalProfiles", "addons"] {
+        launcher_profiles.as_object_mut().unwrap().entry(key).or_insert_with(|| json!({}));
+    }
+}
+
+pub fn schema_version(launcher_profiles: &Value) -> u32 {
+    launcher_profiles.get("schemaVersion").and_then(Value::as_u64).unwrap_or_default() as u32
+}
+
+/// Brings launcher_profiles.json up to the current schema.
+/// Files written by a newer launcher are left untouched, their unknown fields are preserved when they are stored again.
+pub fn migrate(launcher_profiles: &mut Value) {
+    if !launcher_profiles.is_object() {
+        return;
+    }
+
+    let version = schema_version(launcher_profiles);
+    if version > CURRENT_SCHEMA_VERSION {
+        warn!("launcher_profiles.json has schema version {}, this launcher only knows up to {}", version, CURRENT_SCHEMA_VERSION);
+        return;
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        info!("Migrating launcher profiles from schema version {} to {}", from, from + 1);
+        migration(launcher_profiles);
+    }
+    launcher_profiles["schemaVersion"] = json!(CURRENT_SCHEMA_VERSION);
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+/// Windows refuses files with these names, whatever their extension is
+const RESERVED_NAMES: [&str; 22] = [
+    "con", "prn", "aux", "nul",
+    "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9",
+    "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Used when nothing of a name is left after transliterating it, like for a name made of emoji only
+const FALLBACK_SEGMENT: &str = "profile";
+
+/// Whether every OS, zip tool and mod handles the name as file or folder name
+fn is_safe_file_name(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.'))
+        && !segment.starts_with(['.', ' '])
+        && !segment.ends_with(['.', ' '])
+        && !RESERVED_NAMES.contains(&segment.split('.').next().unwrap_or_default().to_ascii_lowercase().as_str())
+}
+
+/// Transliterates the name to ASCII with words joined by dashes, `Café Mods` becomes `Cafe-Mods` and CJK is romanized
+pub fn ascii_segment(name: &str) -> String {
+    let transliterated = deunicode::deunicode_with_tofu(name, " ");
+    let words = transliterated
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+        .map(|word| word.trim_matches('.'))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+    let segment = words.join("-");
+    if is_safe_file_name(&segment) { segment } else { format!("{}-{}", FALLBACK_SEGMENT, segment).trim_end_matches('-').to_string() }
+}
+
+/// File name for a profile in the folder that no file with the extension has yet. The profile keeps its name for display,
+/// only the file gets the ASCII version of it if `transliterate` is set.
+pub fn find_unique_profile_segment(folder: &Path, name: &str, extension: &str, transliterate: bool) -> String {
+    let base = if transliterate { ascii_segment(name) } else { sanitize_filename::sanitize(name) };
+    let base = if base.is_empty() { FALLBACK_SEGMENT.to_string() } else { base };
+
+    let mut segment = base.clone();
+    let mut counter = 2;
+    while folder.join(format!("{}.{}", segment, extension)).exists() {
+        segment = format!("{}-{}", base, counter);
+        counter += 1;
+    }
+    segment
+}
@@ -0,0 +1,200 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, error};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{Mutex, OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+
+use crate::app::app_data::{LauncherProfile, LauncherProfiles};
+use crate::LAUNCHER_DIRECTORY;
+
+/// Changes within this delay are written to launcher_profiles.json in a single batch
+const SAVE_DELAY: Duration = Duration::from_millis(750);
+
+static STATE: Lazy<Mutex<ProfileState>> = Lazy::new(|| Mutex::new(ProfileState::default()));
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+static PROFILE_LOCKS: Lazy<std::sync::Mutex<HashMap<String, Arc<RwLock<()>>>>> = Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// Start of the error returned for changes based on an outdated revision of a profile, the frontend reloads the profile then
+pub const PROFILE_CONFLICT: &str = "profile conflict";
+
+#[derive(Default)]
+struct ProfileState {
+    profiles: Option<LauncherProfiles>,
+    /// Last stored or broadcast fields of every profile, changes are diffed against them
+    known_fields: HashMap<String, serde_json::Map<String, serde_json::Value>>,
+    /// Fields of every profile changed since the last save
+    dirty: HashMap<String, BTreeSet<String>>,
+    /// Raised by every update, also the ones that only change the profile lists or the selected profiles
+    generation: u64,
+    saved_generation: u64,
+}
+
+///
+/// Section of the launcher profiles that changed, sent to the frontend as `launcher-profiles-updated`
+/// so it doesn't have to reload every profile with all of its mods.
+///
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "section")]
+pub enum ProfileChange {
+    /// Reported by updaters for every profile they changed, only sent as a whole for profiles the frontend doesn't know yet
+    #[serde(rename = "profile")]
+    Profile { profile: LauncherProfile },
+    /// Fields of a known profile that changed, like only its mods when a mod was toggled
+    #[serde(rename = "fields")]
+    Fields {
+        #[serde(rename = "profileId")]
+        profile_id: String,
+        fields: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+pub fn init(app_handle: AppHandle) {
+    let _ = APP_HANDLE.set(app_handle);
+}
+
+fn profile_lock(profile_id: &str) -> Arc<RwLock<()>> {
+    PROFILE_LOCKS.lock().unwrap().entry(profile_id.to_string()).or_default().clone()
+}
+
+/// Waits until no one else reads or changes the profile and keeps them waiting until the guard is dropped
+pub async fn lock_profile(profile_id: &str) -> OwnedRwLockWriteGuard<()> {
+    profile_lock(profile_id).write_owned().await
+}
+
+/// Locks several profiles at once, always in the same order so two callers can't deadlock each other
+pub async fn lock_profiles<'a>(profile_ids: impl IntoIterator<Item = &'a str>) -> Vec<OwnedRwLockWriteGuard<()>> {
+    let mut profile_ids: Vec<&str> = profile_ids.into_iter().collect();
+    profile_ids.sort_unstable();
+    profile_ids.dedup();
+    let mut guards = Vec::with_capacity(profile_ids.len());
+    for profile_id in profile_ids {
+        guards.push(lock_profile(profile_id).await);
+    }
+    guards
+}
+
+/// Keeps the profile from being changed until the guard is dropped, e.g. while installing its mods
+pub async fn read_profile(profile_id: &str) -> OwnedRwLockReadGuard<()> {
+    profile_lock(profile_id).read_owned().await
+}
+
+/// Refuses changes the frontend made to an older revision of the profile, `None` skips the check
+pub fn check_revision(profile: &LauncherProfile, expected_revision: Option<u64>) -> Result<(), String> {
+    match expected_revision {
+        Some(revision) if revision != profile.revision => {
+            Err(format!("{}: profile {} was changed elsewhere (revision {}, expected {})", PROFILE_CONFLICT, profile.id, profile.revision, revision))
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn loaded(state: &mut ProfileState) -> &mut LauncherProfiles {
+    if state.profiles.is_none() {
+        let launcher_profiles = LauncherProfiles::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+        for profile in launcher_profiles.main_profiles.iter().chain(launcher_profiles.experimental_profiles.iter()) {
+            if let Some(fields) = profile_fields(profile) {
+                state.known_fields.insert(profile.id.clone(), fields);
+            }
+        }
+        state.profiles = Some(launcher_profiles);
+    }
+    state.profiles.as_mut().unwrap()
+}
+
+fn profile_fields(profile: &LauncherProfile) -> Option<serde_json::Map<String, serde_json::Value>> {
+    match serde_json::to_value(profile) {
+        Ok(serde_json::Value::Object(fields)) => Some(fields),
+        _ => None,
+    }
+}
+
+/// Records the fields the change touched as dirty and narrows it down to them if the profile is already known
+fn track_fields(state: &mut ProfileState, change: ProfileChange) -> Option<ProfileChange> {
+    let ProfileChange::Profile { profile } = change else { return Some(change) };
+    let Some(fields) = profile_fields(&profile) else { return Some(ProfileChange::Profile { profile }) };
+
+    let Some(known) = state.known_fields.insert(profile.id.clone(), fields.clone()) else {
+        state.dirty.entry(profile.id.clone()).or_default().extend(fields.keys().cloned());
+        return Some(ProfileChange::Profile { profile });
+    };
+    let changed: serde_json::Map<String, serde_json::Value> = fields.into_iter()
+        .filter(|(field, value)| known.get(field) != Some(value))
+        .collect();
+    if changed.is_empty() {
+        return None;
+    }
+    state.dirty.entry(profile.id.clone()).or_default().extend(changed.keys().cloned());
+    Some(ProfileChange::Fields { profile_id: profile.id, fields: changed })
+}
+
+/// Returns the current launcher profiles including changes that haven't been saved yet
+pub async fn profiles() -> LauncherProfiles {
+    let mut state = STATE.lock().await;
+    loaded(&mut state).await.clone()
+}
+
+/// Applies a change to the launcher profiles, broadcasts the fields the returned profiles changed and schedules a save.
+/// Every profile of the returned changes gets its next revision.
+pub async fn update<F: FnOnce(&mut LauncherProfiles) -> Vec<ProfileChange>>(updater: F) {
+    let changes = {
+        let mut state = STATE.lock().await;
+        let launcher_profiles = loaded(&mut state).await;
+        let mut changes = updater(launcher_profiles);
+        for change in changes.iter_mut() {
+            let ProfileChange::Profile { profile } = change else { continue };
+            let stored = launcher_profiles.main_profiles.iter_mut().chain(launcher_profiles.experimental_profiles.iter_mut()).find(|stored| stored.id == profile.id);
+            if let Some(stored) = stored {
+                stored.revision += 1;
+                profile.revision = stored.revision;
+            }
+        }
+        let changes: Vec<ProfileChange> = changes.into_iter().filter_map(|change| track_fields(&mut state, change)).collect();
+        state.generation += 1;
+        schedule_save(state.generation);
+        changes
+    };
+
+    if let Some(app_handle) = APP_HANDLE.get() {
+        for change in changes {
+            if let Err(err) = app_handle.emit_all("launcher-profiles-updated", change) {
+                error!("Failed to emit profile change: {:?}", err);
+            }
+        }
+    }
+}
+
+fn schedule_save(generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+
+        // a newer change will save everything once its delay is over
+        if STATE.lock().await.generation != generation {
+            return;
+        }
+        flush().await;
+    });
+}
+
+/// Writes pending changes to launcher_profiles.json immediately
+pub async fn flush() {
+    let mut state = STATE.lock().await;
+    if state.generation == state.saved_generation {
+        return;
+    }
+
+    if let Some(launcher_profiles) = &state.profiles {
+        match launcher_profiles.store(LAUNCHER_DIRECTORY.config_dir()).await {
+            Ok(_) => debug!("Saved launcher profiles, changed fields: {:?}", state.dirty),
+            Err(err) => {
+                error!("Failed to save launcher profiles: {:?}", err);
+                return;
+            }
+        }
+    }
+    state.dirty.clear();
+    state.saved_generation = state.generation;
+}
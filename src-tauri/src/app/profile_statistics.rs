@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use log::{debug, error};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::JsonStore;
+use crate::LAUNCHER_DIRECTORY;
+
+/// Logged by Minecraft once the game window has been created
+const GAME_WINDOW_MARKER: &str = "Backend library:";
+
+/// Start times of the launches still waiting for their game window by profile, launches of other branches can overlap
+static PENDING_STARTUPS: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// Held while the statistics file is read and written again, so concurrent updates don't drop each other
+static STORE_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProfileStatistics {
+    /// Launches that got as far as starting the game process, whether the game crashed later or not
+    #[serde(alias = "successfulLaunches", default)]
+    pub launches: u64,
+    /// Launches whose game exited with an error, failed installs don't count
+    #[serde(default)]
+    pub crashes: u64,
+    #[serde(rename = "startupSamples", default)]
+    pub startup_samples: u64,
+    #[serde(rename = "averageStartupMs", default)]
+    pub average_startup_ms: u64,
+    #[serde(rename = "lastStartupMs", default)]
+    pub last_startup_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ProfileStatisticsStore {
+    pub profiles: HashMap<String, ProfileStatistics>,
+}
+
+impl JsonStore for ProfileStatisticsStore {
+    const FILE_NAME: &'static str = "profile_statistics.json";
+}
+
+impl ProfileStatisticsStore {
+    pub fn get(&self, profile_id: &str) -> ProfileStatistics {
+        self.profiles.get(profile_id).cloned().unwrap_or_default()
+    }
+}
+
+async fn update(profile_id: &str, updater: impl FnOnce(&mut ProfileStatistics)) {
+    let _lock = STORE_LOCK.lock().await;
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut store = ProfileStatisticsStore::load(config_dir).await.unwrap_or_default();
+    updater(store.profiles.entry(profile_id.to_string()).or_default());
+    if let Err(err) = store.store(config_dir).await {
+        error!("Failed to store profile statistics: {:?}", err);
+    }
+}
+
+/// Starts measuring the startup time of the given profile, called before the installation begins
+pub fn launch_started(profile_id: &str) {
+    PENDING_STARTUPS.lock().unwrap().insert(profile_id.to_string(), Instant::now());
+}
+
+/// Checks the game output of a profile for the game window being created and records the startup time of its launch
+pub fn check_game_output(profile_id: &str, output: &str) {
+    if !output.contains(GAME_WINDOW_MARKER) {
+        return;
+    }
+
+    let Some(started) = PENDING_STARTUPS.lock().unwrap().remove(profile_id) else { return };
+    let profile_id = profile_id.to_string();
+    let startup_ms = started.elapsed().as_millis() as u64;
+    debug!("Profile {} took {} ms to start", profile_id, startup_ms);
+
+    tauri::async_runtime::spawn(async move {
+        update(&profile_id, |statistics| {
+            statistics.average_startup_ms = (statistics.average_startup_ms * statistics.startup_samples + startup_ms) / (statistics.startup_samples + 1);
+            statistics.startup_samples += 1;
+            statistics.last_startup_ms = Some(startup_ms);
+        }).await;
+    });
+}
+
+/// Records the launch of the profile once its game process is running.
+/// Launches without keeping the launcher open exit the launcher with the game, so this can't wait for the exit.
+pub fn game_spawned(profile_id: &str) {
+    let profile_id = profile_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        update(&profile_id, |statistics| statistics.launches += 1).await;
+    });
+}
+
+/// Records a crash if the game exited with an error
+pub async fn launch_finished(profile_id: &str, crashed: bool) {
+    PENDING_STARTUPS.lock().unwrap().remove(profile_id);
+    if crashed {
+        update(profile_id, |statistics| statistics.crashes += 1).await;
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use log::debug;
+use tauri::{AppHandle, LogicalSize, Manager};
+
+use crate::minecraft::progress::ProgressUpdate;
+
+const WINDOW_LABEL: &str = "progress-overlay";
+
+/// Opens the small always-on-top window showing the launch progress of the given profile
+pub fn open(app_handle: &AppHandle, profile_id: &str) -> Result<()> {
+    if let Some(window) = app_handle.get_window(WINDOW_LABEL) {
+        window.set_focus()?;
+        return Ok(());
+    }
+
+    let url = format!("index.html?progressOverlay={}", urlencoding::encode(profile_id));
+    let window = tauri::WindowBuilder::new(app_handle, WINDOW_LABEL, tauri::WindowUrl::App(url.into()))
+        .title("NoRiskClient")
+        .decorations(false)
+        .always_on_top(true)
+        .resizable(false)
+        .skip_taskbar(true)
+        .build()?;
+    let _ = window.set_size(LogicalSize::new(360, 110));
+
+    debug!("Opened progress overlay for profile {}", profile_id);
+    Ok(())
+}
+
+/// Sends a progress update to the overlay, if it is open
+pub fn forward(app_handle: &AppHandle, progress_update: &ProgressUpdate) {
+    if let Some(window) = app_handle.get_window(WINDOW_LABEL) {
+        let _ = window.emit("progress-overlay-update", progress_update);
+    }
+}
+
+pub fn close(app_handle: &AppHandle) -> Result<()> {
+    if let Some(window) = app_handle.get_window(WINDOW_LABEL) {
+        window.close()?;
+    }
+    Ok(())
+}
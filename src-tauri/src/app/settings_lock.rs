@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+
+use crate::app::app_data::LauncherOptions;
+
+/// How long entering the PIN unlocks the protected settings
+const SESSION_DURATION: Duration = Duration::from_secs(10 * 60);
+/// Wrong PINs that can be entered before further attempts have to wait
+const FREE_ATTEMPTS: u32 = 3;
+/// Wait after the free attempts, doubled with every further wrong PIN
+const ATTEMPT_DELAY: Duration = Duration::from_secs(5);
+const MAX_ATTEMPT_DELAY: Duration = Duration::from_secs(15 * 60);
+
+///
+/// Sessions issued by `unlock_settings`, they only live in memory so a restart locks the settings again
+///
+#[derive(Default)]
+pub struct SettingsSessions {
+    sessions: Mutex<HashMap<String, Instant>>,
+}
+
+impl SettingsSessions {
+    pub fn issue(&self) -> String {
+        let token = uuid::Uuid::new_v4().to_string();
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, expires| *expires > Instant::now());
+        sessions.insert(token.clone(), Instant::now() + SESSION_DURATION);
+        debug!("Unlocked settings until {:?}", SESSION_DURATION);
+        token
+    }
+
+    pub fn is_valid(&self, token: Option<&str>) -> bool {
+        let Some(token) = token else { return false };
+        self.sessions.lock().unwrap().get(token).map_or(false, |expires| *expires > Instant::now())
+    }
+
+    /// Fails if the settings are locked and the token doesn't belong to an unexpired session
+    pub fn ensure_unlocked(&self, options: &LauncherOptions, token: Option<&str>, action: &str) -> Result<(), String> {
+        if options.settings_lock.enabled && !self.is_valid(token) {
+            return Err(format!("{} requires unlocking the settings with the PIN", action));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+struct PinAttemptState {
+    /// Wrong PINs in a row
+    failed: u32,
+    /// Unix time in milliseconds before which no further attempt is allowed
+    #[serde(rename = "nextAttempt")]
+    next_attempt: Option<i64>,
+}
+
+///
+/// Wrong PINs entered so far, so a PIN can't be guessed by trying all of them.
+/// They are kept in a file, restarting the launcher doesn't allow further attempts.
+///
+pub struct PinAttempts {
+    path: PathBuf,
+    state: Mutex<PinAttemptState>,
+}
+
+impl PinAttempts {
+    pub fn open(path: PathBuf) -> Self {
+        let state = std::fs::read(&path).ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        PinAttempts { path, state: Mutex::new(state) }
+    }
+
+    fn write(&self, state: &PinAttemptState) {
+        let written = serde_json::to_vec_pretty(state).map_err(anyhow::Error::from)
+            .and_then(|data| std::fs::write(&self.path, data).map_err(anyhow::Error::from));
+        if let Err(err) = written {
+            error!("Failed to store PIN attempts: {:?}", err);
+        }
+    }
+
+    /// Fails while attempts have to wait after too many wrong PINs
+    pub fn ensure_allowed(&self) -> Result<(), String> {
+        let next_attempt = self.state.lock().unwrap().next_attempt;
+        let now = Utc::now().timestamp_millis();
+        match next_attempt {
+            Some(next_attempt) if next_attempt > now => {
+                Err(format!("too many wrong PINs, try again in {} seconds", (next_attempt - now) / 1000 + 1))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn failed(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.failed += 1;
+        if state.failed >= FREE_ATTEMPTS {
+            let delay = ATTEMPT_DELAY.saturating_mul(1 << (state.failed - FREE_ATTEMPTS).min(16)).min(MAX_ATTEMPT_DELAY);
+            state.next_attempt = Some(Utc::now().timestamp_millis() + delay.as_millis() as i64);
+            debug!("Wrong PIN {} times, next attempt in {:?}", state.failed, delay);
+        }
+        self.write(&state);
+    }
+
+    pub fn succeeded(&self) {
+        let mut state = self.state.lock().unwrap();
+        *state = PinAttemptState::default();
+        self.write(&state);
+    }
+}
+
+/// Whether storing the options would change more than what the launcher itself keeps in them (selected account,
+/// signed in accounts, latest branches and the theme). Removing an account counts as change.
+pub fn changes_settings(stored: &LauncherOptions, options: &LauncherOptions) -> bool {
+    let removes_account = stored.accounts.iter()
+        .any(|account| !options.accounts.iter().any(|other| other.uuid == account.uuid));
+    if removes_account {
+        return true;
+    }
+
+    let mut unlocked = stored.clone();
+    unlocked.current_uuid = options.current_uuid.clone();
+    unlocked.accounts = options.accounts.clone();
+    unlocked.latest_branch = options.latest_branch.clone();
+    unlocked.latest_dev_branch = options.latest_dev_branch.clone();
+    unlocked.theme = options.theme.clone();
+    serde_json::to_value(&unlocked).ok() != serde_json::to_value(options).ok()
+}
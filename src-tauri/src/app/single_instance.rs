@@ -0,0 +1,105 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::app::jump_list;
+use crate::LAUNCHER_DIRECTORY;
+
+/// Answer of the running launcher once it took over a start
+const ACCEPTED: &str = "ok";
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+///
+/// Where the running launcher listens for further starts, like the ones of jump list entries.
+/// Only the user can read the config folder, so only their starts know the token.
+///
+#[derive(Serialize, Deserialize)]
+struct RunningInstance {
+    port: u16,
+    token: String,
+}
+
+fn instance_file() -> PathBuf {
+    LAUNCHER_DIRECTORY.config_dir().join("instance.json")
+}
+
+/// Hands the profile this start should launch to an already running launcher.
+/// Returns whether one took it over, the start then has nothing left to do.
+pub fn forward_to_running() -> bool {
+    let Ok(data) = std::fs::read(instance_file()) else { return false };
+    let Ok(running) = serde_json::from_slice::<RunningInstance>(&data) else { return false };
+
+    let forwarded = || -> std::io::Result<bool> {
+        let mut stream = TcpStream::connect_timeout(&SocketAddr::from((Ipv4Addr::LOCALHOST, running.port)), TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        let profile_id = jump_list::launch_profile_from_args(std::env::args()).unwrap_or_default();
+        stream.write_all(format!("{}\n{}\n", running.token, profile_id).as_bytes())?;
+
+        // the port might belong to another program by now, if the launcher didn't quit cleanly
+        let mut answer = String::new();
+        BufReader::new(stream).read_line(&mut answer)?;
+        Ok(answer.trim() == ACCEPTED)
+    };
+    forwarded().unwrap_or(false)
+}
+
+/// Listens for further starts of the launcher, they focus this one and launch their profile here
+pub fn listen(app_handle: &AppHandle) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("Unable to listen for further launcher starts: {:?}", err);
+            return;
+        }
+    };
+    let running = RunningInstance {
+        port: listener.local_addr().map(|address| address.port()).unwrap_or_default(),
+        token: uuid::Uuid::new_v4().to_string(),
+    };
+    let written = serde_json::to_vec(&running).map_err(anyhow::Error::from)
+        .and_then(|data| std::fs::write(instance_file(), data).map_err(anyhow::Error::from));
+    if let Err(err) = written {
+        error!("Unable to store the running launcher: {:?}", err);
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().filter_map(|stream| stream.ok()) {
+            if let Err(err) = accept(&app_handle, stream, &running.token) {
+                warn!("Unable to accept a further launcher start: {:?}", err);
+            }
+        }
+    });
+}
+
+fn accept(app_handle: &AppHandle, mut stream: TcpStream, token: &str) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    let mut lines = BufReader::new(stream.try_clone()?).lines();
+    if lines.next().transpose()?.as_deref() != Some(token) {
+        return Ok(());
+    }
+    let profile_id = lines.next().transpose()?.unwrap_or_default();
+    stream.write_all(format!("{}\n", ACCEPTED).as_bytes())?;
+
+    info!("Launcher was started again");
+    if profile_id.is_empty() {
+        if let Some(window) = app_handle.get_window("main") {
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+    } else {
+        jump_list::request_launch(app_handle, profile_id);
+    }
+    Ok(())
+}
+
+/// Forgets the running launcher when it quits, so the next start doesn't try to reach it
+pub fn release() {
+    let _ = std::fs::remove_file(instance_file());
+}
@@ -0,0 +1,144 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime};
+
+use log::{debug, error, info, warn};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::app::app_data::LauncherOptions;
+use crate::app::content_blacklist::ContentBlacklist;
+use crate::app::{data_dir, i18n, jump_list, profile_state};
+use crate::minecraft::natives;
+use crate::utils::WINE;
+use crate::LAUNCHER_DIRECTORY;
+
+static PROCESS_START: Lazy<Instant> = Lazy::new(Instant::now);
+static TIMINGS: Lazy<Mutex<Vec<StartupPhase>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Subsystems whose `state-ready` was sent already, for a frontend that starts listening late
+static READY: Lazy<Mutex<Vec<StateReady>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+///
+/// Duration of one phase of the launcher startup, to notice when the startup gets slower
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct StartupPhase {
+    pub name: String,
+    /// Milliseconds between the start of the process and the start of this phase
+    #[serde(rename = "startedAtMs")]
+    pub started_at_ms: u64,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+}
+
+///
+/// Sent as `state-ready` once a subsystem has finished loading in the background
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct StateReady {
+    pub subsystem: String,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+}
+
+///
+/// Sent as `compatibility-warning` when the launcher runs in an environment known to cause problems
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct CompatibilityWarning {
+    pub kind: String,
+    pub message: String,
+}
+
+/// Remembers when the process started, has to be called first thing in main
+pub fn mark_process_start() {
+    Lazy::force(&PROCESS_START);
+}
+
+fn record(name: &str, started: Instant) -> u64 {
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let started_at_ms = started.duration_since(*PROCESS_START).as_millis() as u64;
+    debug!("Startup phase {} took {} ms", name, duration_ms);
+    TIMINGS.lock().unwrap().push(StartupPhase { name: name.to_string(), started_at_ms, duration_ms });
+    duration_ms
+}
+
+pub fn measure<T, F: FnOnce() -> T>(name: &str, phase: F) -> T {
+    let started = Instant::now();
+    let result = phase();
+    record(name, started);
+    result
+}
+
+/// Loads a subsystem and tells the frontend that it is ready
+async fn load_subsystem<F: Future<Output = ()>>(app_handle: &AppHandle, subsystem: &str, load: F) {
+    let started = Instant::now();
+    load.await;
+    let duration_ms = record(subsystem, started);
+
+    let ready = StateReady { subsystem: subsystem.to_string(), duration_ms };
+    READY.lock().unwrap().push(ready.clone());
+    if let Err(err) = app_handle.emit_all("state-ready", ready) {
+        error!("Failed to emit state ready: {:?}", err);
+    }
+}
+
+/// Loads everything the launcher needs concurrently in the background, so the window shows up
+/// without waiting for it and the first commands using them don't have to load them either
+pub fn warm_up(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        if *WINE {
+            warn!("Running under Wine, some features may not work as expected");
+            let warning = CompatibilityWarning { kind: "wine".to_string(), message: "The launcher is running under Wine or Proton. Consider using the native Linux build if you run into problems.".to_string() };
+            if let Err(err) = app_handle.emit_all("compatibility-warning", warning) {
+                error!("Failed to emit compatibility warning: {:?}", err);
+            }
+        }
+        let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+        tokio::join!(
+            load_subsystem(&app_handle, "dataFolder", async {
+                let status = data_dir::check(&options).await;
+                if !status.issues.is_empty() {
+                    warn!("Problems with the data folder {:?}: {:?}", status.path, status.issues);
+                    if let Err(err) = app_handle.emit_all("data-dir-issues", status) {
+                        error!("Failed to emit data folder issues: {:?}", err);
+                    }
+                }
+            }),
+            load_subsystem(&app_handle, "profiles", async {
+                profile_state::profiles().await;
+            }),
+            load_subsystem(&app_handle, "contentBlacklist", async {
+                ContentBlacklist::get().await;
+            }),
+            load_subsystem(&app_handle, "translations", async {
+                i18n::translations(&i18n::selected_locale(&options).await).await;
+            }),
+        );
+        // needs the profiles, which are loaded by now
+        load_subsystem(&app_handle, "jumpList", jump_list::refresh()).await;
+        info!("Launcher state is ready after {} ms", PROCESS_START.elapsed().as_millis());
+        record("warmUp", started);
+
+        // pending and jump list launches may already be linking natives, what they touched since the start is kept
+        let data = options.data_path_buf();
+        let started_at = SystemTime::now() - PROCESS_START.elapsed();
+        if data.join("natives").exists() {
+            if let Err(err) = natives::remove_stale(&data, started_at).await {
+                warn!("Failed to remove stale natives: {:?}", err);
+            }
+        }
+    });
+}
+
+/// All startup phases recorded so far, in the order they finished
+pub fn timings() -> Vec<StartupPhase> {
+    TIMINGS.lock().unwrap().clone()
+}
+
+/// Subsystems that have finished loading so far
+pub fn ready_subsystems() -> Vec<StateReady> {
+    READY.lock().unwrap().clone()
+}
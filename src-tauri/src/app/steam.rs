@@ -0,0 +1,321 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+use log::{debug, info};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sysinfo::{ProcessExt, ProcessRefreshKind, RefreshKind, System, SystemExt};
+use tokio::fs;
+
+use crate::app::app_data::LauncherProfile;
+use crate::app::jump_list::LAUNCH_PROFILE_ARG;
+use crate::app::profile_icons;
+use crate::utils::file_utils;
+
+const MAP_START: u8 = 0x00;
+const STRING: u8 = 0x01;
+const INT: u8 = 0x02;
+const MAP_END: u8 = 0x08;
+
+static ICON: &[u8] = include_bytes!("../../icons/icon.png");
+
+/// Background of the library artwork, the one of the launcher
+const ARTWORK_BACKGROUND: Rgba<u8> = Rgba([0x1a, 0x1a, 0x1a, 0xff]);
+/// Suffix of the grid file name, width and height of the artwork Steam shows in its library
+const ARTWORK: [(&str, u32, u32); 3] = [("p", 600, 900), ("", 920, 430), ("_hero", 1920, 620)];
+
+/// Library paths in the text VDF format of libraryfolders.vdf
+static LIBRARY_PATH: Lazy<Regex> = Lazy::new(|| Regex::new(r#""path"\s+"([^"]+)""#).unwrap());
+
+///
+/// Value of Steam's binary VDF format which is used by shortcuts.vdf
+///
+#[derive(Debug, Clone)]
+pub enum VdfValue {
+    Map(Vec<(String, VdfValue)>),
+    String(String),
+    Int(u32),
+}
+
+impl VdfValue {
+    fn get(&self, key: &str) -> Option<&VdfValue> {
+        match self {
+            VdfValue::Map(entries) => entries.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn parse_map(data: &[u8], pos: &mut usize) -> Result<Vec<(String, VdfValue)>> {
+        let mut entries = Vec::new();
+        loop {
+            let Some(&kind) = data.get(*pos) else { bail!("unexpected end of vdf data") };
+            *pos += 1;
+            if kind == MAP_END {
+                return Ok(entries);
+            }
+            let key = Self::read_string(data, pos)?;
+            let value = match kind {
+                MAP_START => VdfValue::Map(Self::parse_map(data, pos)?),
+                STRING => VdfValue::String(Self::read_string(data, pos)?),
+                INT => {
+                    let Some(bytes) = data.get(*pos..*pos + 4) else { bail!("unexpected end of vdf data") };
+                    *pos += 4;
+                    VdfValue::Int(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                }
+                _ => bail!("unknown vdf type {}", kind),
+            };
+            entries.push((key, value));
+        }
+    }
+
+    fn read_string(data: &[u8], pos: &mut usize) -> Result<String> {
+        let end = data[*pos..].iter().position(|&b| b == 0).ok_or_else(|| anyhow::anyhow!("unterminated vdf string"))?;
+        let value = String::from_utf8_lossy(&data[*pos..*pos + end]).to_string();
+        *pos += end + 1;
+        Ok(value)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<VdfValue> {
+        let mut pos = 0;
+        Ok(VdfValue::Map(Self::parse_map(data, &mut pos)?))
+    }
+
+    fn write_entries(entries: &[(String, VdfValue)], out: &mut Vec<u8>) {
+        for (key, value) in entries {
+            match value {
+                VdfValue::Map(children) => {
+                    out.push(MAP_START);
+                    Self::write_string(key, out);
+                    Self::write_entries(children, out);
+                }
+                VdfValue::String(string) => {
+                    out.push(STRING);
+                    Self::write_string(key, out);
+                    Self::write_string(string, out);
+                }
+                VdfValue::Int(int) => {
+                    out.push(INT);
+                    Self::write_string(key, out);
+                    out.extend_from_slice(&int.to_le_bytes());
+                }
+            }
+        }
+        out.push(MAP_END);
+    }
+
+    fn write_string(value: &str, out: &mut Vec<u8>) {
+        out.extend_from_slice(value.as_bytes());
+        out.push(0);
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if let VdfValue::Map(entries) = self {
+            Self::write_entries(entries, &mut out);
+        }
+        out
+    }
+}
+
+/// Steam's id for non-steam games, see https://github.com/boppreh/steamgrid
+fn shortcut_app_id(exe: &str, app_name: &str) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+    for byte in exe.bytes().chain(app_name.bytes()) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    !crc | 0x80000000
+}
+
+#[cfg(target_os = "windows")]
+fn registry_string(root: windows::Win32::System::Registry::HKEY, sub_key: windows::core::PCWSTR, value: windows::core::PCWSTR) -> Option<String> {
+    use windows::Win32::System::Registry::{RegGetValueW, RRF_RT_REG_SZ};
+
+    let mut size = 0u32;
+    unsafe {
+        RegGetValueW(root, sub_key, value, RRF_RT_REG_SZ, None, None, Some(&mut size as *mut u32)).ok().ok()?;
+        let mut buffer = vec![0u16; size as usize / 2];
+        RegGetValueW(root, sub_key, value, RRF_RT_REG_SZ, None, Some(buffer.as_mut_ptr() as *mut std::ffi::c_void), Some(&mut size as *mut u32)).ok().ok()?;
+        let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        Some(String::from_utf16_lossy(&buffer[..len]))
+    }
+}
+
+/// Folders Steam may be installed in, the registry knows the install folder on Windows
+fn steam_install_candidates() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::core::w;
+        use windows::Win32::System::Registry::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+
+        [
+            registry_string(HKEY_CURRENT_USER, w!("Software\\Valve\\Steam"), w!("SteamPath")),
+            registry_string(HKEY_LOCAL_MACHINE, w!("SOFTWARE\\WOW6432Node\\Valve\\Steam"), w!("InstallPath")),
+            registry_string(HKEY_LOCAL_MACHINE, w!("SOFTWARE\\Valve\\Steam"), w!("InstallPath")),
+        ].into_iter().flatten().map(PathBuf::from).collect()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let Some(base_dirs) = directories::BaseDirs::new() else { return Vec::new() };
+        vec![base_dirs.data_dir().join("Steam")]
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let Some(base_dirs) = directories::BaseDirs::new() else { return Vec::new() };
+        vec![
+            base_dirs.home_dir().join(".steam").join("root"),
+            base_dirs.home_dir().join(".steam").join("steam"),
+            base_dirs.data_dir().join("Steam"),
+            base_dirs.home_dir().join(".var").join("app").join("com.valvesoftware.Steam").join("data").join("Steam"),
+        ]
+    }
+}
+
+/// Library folders listed in the libraryfolders.vdf of a Steam install, the first one is the install Steam runs from
+fn library_folders(steam: &Path) -> Vec<PathBuf> {
+    let Ok(content) = std::fs::read_to_string(steam.join("steamapps").join("libraryfolders.vdf")) else { return Vec::new() };
+    LIBRARY_PATH.captures_iter(&content)
+        .map(|captures| PathBuf::from(captures[1].replace("\\\\", "\\")))
+        .collect()
+}
+
+fn steam_userdata_dir() -> Option<PathBuf> {
+    let userdata = steam_install_candidates().into_iter()
+        .flat_map(|steam| std::iter::once(steam.clone()).chain(library_folders(&steam)))
+        .map(|steam| steam.join("userdata"))
+        .find(|path| path.exists());
+    debug!("Steam userdata folder: {:?}", userdata);
+    userdata
+}
+
+/// Steam writes its shortcuts back when it quits, which would drop a shortcut added while it runs
+fn steam_running() -> bool {
+    System::new_with_specifics(RefreshKind::new().with_processes(ProcessRefreshKind::new()))
+        .processes()
+        .values()
+        .any(|process| matches!(process.name().to_lowercase().as_str(), "steam" | "steam.exe" | "steam_osx"))
+}
+
+/// Program and arguments Steam starts the launcher with. AppImages are mounted to another folder on every start
+/// and Flatpaks can only be started through flatpak.
+fn launch_target() -> Result<(PathBuf, Vec<String>)> {
+    if let Some(app_image) = std::env::var_os("APPIMAGE") {
+        return Ok((PathBuf::from(app_image), Vec::new()));
+    }
+    if *file_utils::IS_SANDBOXED {
+        if let Ok(flatpak_id) = std::env::var("FLATPAK_ID") {
+            return Ok((PathBuf::from("/usr/bin/flatpak"), vec!["run".to_string(), flatpak_id]));
+        }
+    }
+    Ok((std::env::current_exe()?, Vec::new()))
+}
+
+/// Icon and library artwork of the shortcut as png, showing the profile icon or the launcher icon on the launcher background
+fn render_artwork(profile_icon: Option<PathBuf>) -> Result<Vec<(String, Vec<u8>)>> {
+    let icon = match profile_icon.filter(|icon| icon.is_file()) {
+        Some(icon) => image::open(icon)?,
+        None => image::load_from_memory(ICON)?,
+    };
+    let encode = |image: &DynamicImage| -> Result<Vec<u8>> {
+        let mut png = Vec::new();
+        image.write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)?;
+        Ok(png)
+    };
+
+    let mut artwork = vec![
+        ("_icon".to_string(), encode(&icon)?),
+        ("_logo".to_string(), encode(&icon.resize(256, 256, FilterType::Lanczos3))?),
+    ];
+    for (suffix, width, height) in ARTWORK {
+        let mut canvas = RgbaImage::from_pixel(width, height, ARTWORK_BACKGROUND);
+        let size = width.min(height) / 2;
+        let scaled = icon.resize(size, size, FilterType::Lanczos3);
+        imageops::overlay(&mut canvas, &scaled, ((width - scaled.width()) / 2) as i64, ((height - scaled.height()) / 2) as i64);
+        artwork.push((suffix.to_string(), encode(&DynamicImage::ImageRgba8(canvas))?));
+    }
+    Ok(artwork)
+}
+
+/// Adds a non-Steam game entry launching the given profile to every Steam user found on this machine
+pub async fn create_shortcut(profile: &LauncherProfile) -> Result<Vec<PathBuf>> {
+    let Some(userdata) = steam_userdata_dir() else { bail!("unable to find steam installation") };
+    if steam_running() {
+        bail!("Steam is running, close it first so it doesn't overwrite the shortcut");
+    }
+
+    let (exe_path, mut launch_arguments) = launch_target()?;
+    let exe = format!("\"{}\"", exe_path.to_string_lossy());
+    let start_dir = format!("\"{}\"", exe_path.parent().map(Path::to_path_buf).unwrap_or_default().to_string_lossy());
+    let app_name = format!("NoRiskClient - {}", profile.name);
+    let app_id = shortcut_app_id(&exe, &app_name);
+    let profile_launch = format!("{} {}", LAUNCH_PROFILE_ARG, profile.id);
+    launch_arguments.push(profile_launch.clone());
+    let launch_options = launch_arguments.join(" ");
+
+    let profile_icon = profile.icon.as_deref().and_then(profile_icons::icon_path);
+    let artwork = tokio::task::spawn_blocking(move || render_artwork(profile_icon)).await??;
+
+    let mut written = Vec::new();
+    let mut users = fs::read_dir(&userdata).await?;
+    while let Some(user) = users.next_entry().await? {
+        if !user.file_type().await?.is_dir() {
+            continue;
+        }
+
+        let config_dir = user.path().join("config");
+        let grid_dir = config_dir.join("grid");
+        fs::create_dir_all(&grid_dir).await?;
+        for (suffix, png) in &artwork {
+            fs::write(grid_dir.join(format!("{}{}.png", app_id, suffix)), png).await?;
+        }
+        let icon_path = grid_dir.join(format!("{}_icon.png", app_id));
+
+        let shortcuts_path = config_dir.join("shortcuts.vdf");
+        let mut root = if shortcuts_path.exists() {
+            VdfValue::parse(&fs::read(&shortcuts_path).await?)?
+        } else {
+            VdfValue::Map(vec![("shortcuts".to_string(), VdfValue::Map(Vec::new()))])
+        };
+
+        let VdfValue::Map(root_entries) = &mut root else { bail!("invalid shortcuts.vdf") };
+        let Some((_, VdfValue::Map(shortcuts))) = root_entries.iter_mut().find(|(key, _)| key.eq_ignore_ascii_case("shortcuts")) else { bail!("invalid shortcuts.vdf") };
+
+        // replace an existing shortcut for the same profile
+        shortcuts.retain(|(_, shortcut)| !matches!(shortcut.get("LaunchOptions"), Some(VdfValue::String(options)) if options.ends_with(&profile_launch)));
+        shortcuts.push((String::new(), VdfValue::Map(vec![
+            ("appid".to_string(), VdfValue::Int(app_id)),
+            ("AppName".to_string(), VdfValue::String(app_name.clone())),
+            ("Exe".to_string(), VdfValue::String(exe.clone())),
+            ("StartDir".to_string(), VdfValue::String(start_dir.clone())),
+            ("icon".to_string(), VdfValue::String(icon_path.to_string_lossy().to_string())),
+            ("ShortcutPath".to_string(), VdfValue::String(String::new())),
+            ("LaunchOptions".to_string(), VdfValue::String(launch_options.clone())),
+            ("IsHidden".to_string(), VdfValue::Int(0)),
+            ("AllowDesktopConfig".to_string(), VdfValue::Int(1)),
+            ("AllowOverlay".to_string(), VdfValue::Int(1)),
+            ("OpenVR".to_string(), VdfValue::Int(0)),
+            ("Devkit".to_string(), VdfValue::Int(0)),
+            ("DevkitGameID".to_string(), VdfValue::String(String::new())),
+            ("DevkitOverrideAppID".to_string(), VdfValue::Int(0)),
+            ("LastPlayTime".to_string(), VdfValue::Int(0)),
+            ("FlatpakAppID".to_string(), VdfValue::String(String::new())),
+            ("tags".to_string(), VdfValue::Map(Vec::new())),
+        ])));
+        for (index, (key, _)) in shortcuts.iter_mut().enumerate() {
+            *key = index.to_string();
+        }
+
+        fs::write(&shortcuts_path, root.serialize()).await?;
+        info!("Added steam shortcut for profile {} to {:?}", profile.name, shortcuts_path);
+        written.push(shortcuts_path);
+    }
+
+    Ok(written)
+}
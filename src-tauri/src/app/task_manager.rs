@@ -0,0 +1,332 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use log::{debug, info};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{oneshot, Notify};
+
+use crate::app::app_data::LauncherOptions;
+use crate::LAUNCHER_DIRECTORY;
+
+/// Finished tasks that are kept so the activity panel can still show their result
+const FINISHED_TASK_HISTORY: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum TaskKind {
+    #[serde(rename = "install")]
+    Install,
+    #[serde(rename = "export")]
+    Export,
+    #[serde(rename = "launch")]
+    Launch,
+}
+
+impl TaskKind {
+    /// Installs and launches download a lot of files, so they have to wait for the install queue
+    fn uses_install_queue(&self) -> bool {
+        matches!(self, TaskKind::Install | TaskKind::Launch)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum TaskStatus {
+    #[serde(rename = "queued")]
+    Queued,
+    #[serde(rename = "running")]
+    Running,
+    #[serde(rename = "completed")]
+    Completed,
+    #[serde(rename = "failed")]
+    Failed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+///
+/// Step of a task with several steps, the progress of the task is the progress of its current phase
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum TaskPhase {
+    #[serde(rename = "scanning")]
+    Scanning,
+    #[serde(rename = "compressing")]
+    Compressing,
+    #[serde(rename = "extracting")]
+    Extracting,
+    #[serde(rename = "resolvingMods")]
+    ResolvingMods,
+    #[serde(rename = "writing")]
+    Writing,
+}
+
+///
+/// Long-running operation as shown to the frontend, sent as `task-updated` whenever it changes
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct TaskInfo {
+    pub id: String,
+    pub kind: TaskKind,
+    pub title: String,
+    /// Between 0 and 1, `None` while the progress is unknown
+    pub progress: Option<f64>,
+    /// Only set by tasks with several steps
+    pub phase: Option<TaskPhase>,
+    pub status: TaskStatus,
+    /// Position in the install queue starting at 1, only set while the task is queued
+    #[serde(rename = "queuePosition")]
+    pub queue_position: Option<usize>,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    #[serde(rename = "startedAt")]
+    pub started_at: i64,
+    #[serde(rename = "finishedAt")]
+    pub finished_at: Option<i64>,
+}
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancel: Option<oneshot::Sender<()>>,
+}
+
+///
+/// Registry of every long-running operation (installs, exports, ...) with its progress and result
+///
+#[derive(Default)]
+pub struct TaskManager {
+    tasks: Mutex<HashMap<String, TaskEntry>>,
+    install_queue: Mutex<InstallQueue>,
+    install_queue_changed: Notify,
+    app_handle: OnceCell<AppHandle>,
+}
+
+#[derive(Default)]
+struct InstallQueue {
+    running: usize,
+    waiting: VecDeque<String>,
+}
+
+///
+/// Place of a task in the install queue, frees the place (or leaves the queue) when dropped
+///
+struct InstallSlot {
+    task_id: String,
+    manager: Arc<TaskManager>,
+    acquired: bool,
+}
+
+impl Drop for InstallSlot {
+    fn drop(&mut self) {
+        {
+            let mut queue = self.manager.install_queue.lock().unwrap();
+            if self.acquired {
+                queue.running -= 1;
+            } else {
+                queue.waiting.retain(|id| *id != self.task_id);
+            }
+        }
+        self.manager.install_queue_changed.notify_waiters();
+        self.manager.update_queue_positions();
+    }
+}
+
+///
+/// Passed to a running task so it can report its progress
+///
+#[derive(Clone)]
+pub struct TaskHandle {
+    id: String,
+    manager: Arc<TaskManager>,
+    install_slot: Arc<Mutex<Option<InstallSlot>>>,
+}
+
+impl TaskHandle {
+    pub fn set_progress(&self, progress: f64) {
+        self.manager.modify(&self.id, |info| info.progress = Some(progress.clamp(0.0, 1.0)));
+    }
+
+    /// Starts the next phase, its progress is unknown until it is set
+    pub fn set_phase(&self, phase: TaskPhase) {
+        self.manager.modify(&self.id, |info| {
+            info.phase = Some(phase);
+            info.progress = None;
+        });
+    }
+
+    /// Whether the task was cancelled, for work that keeps running on other threads after the task's future was dropped
+    pub fn is_cancelled(&self) -> bool {
+        self.manager.tasks.lock().unwrap().get(&self.id).map_or(true, |entry| entry.info.status == TaskStatus::Cancelled)
+    }
+
+    /// Lets the next queued task start before this one has finished, e.g. once the game is installed and running
+    pub fn release_install_slot(&self) {
+        self.install_slot.lock().unwrap().take();
+    }
+}
+
+impl TaskManager {
+    pub fn init(&self, app_handle: AppHandle) {
+        let _ = self.app_handle.set(app_handle);
+    }
+
+    fn emit(&self, info: &TaskInfo) {
+        if let Some(app_handle) = self.app_handle.get() {
+            let _ = app_handle.emit_all("task-updated", info);
+        }
+    }
+
+    fn modify<F: FnOnce(&mut TaskInfo)>(&self, id: &str, modifier: F) {
+        let info = {
+            let mut tasks = self.tasks.lock().unwrap();
+            let Some(entry) = tasks.get_mut(id) else { return };
+            modifier(&mut entry.info);
+            entry.info.clone()
+        };
+        self.emit(&info);
+    }
+
+    /// Registers a task, runs it until it finishes or gets cancelled and records its result
+    pub async fn run<T, F, Fut>(self: &Arc<Self>, kind: TaskKind, title: String, task: F) -> Result<T, String>
+        where T: Serialize,
+              F: FnOnce(TaskHandle) -> Fut,
+              Fut: Future<Output = Result<T, String>> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let info = TaskInfo {
+            id: id.clone(),
+            kind,
+            title,
+            progress: None,
+            phase: None,
+            status: if kind.uses_install_queue() { TaskStatus::Queued } else { TaskStatus::Running },
+            queue_position: None,
+            result: None,
+            error: None,
+            started_at: Utc::now().timestamp_millis(),
+            finished_at: None,
+        };
+        debug!("Starting task {} ({})", info.title, id);
+        self.tasks.lock().unwrap().insert(id.clone(), TaskEntry { info: info.clone(), cancel: Some(cancel_tx) });
+        self.emit(&info);
+
+        let run = async {
+            let install_slot = if kind.uses_install_queue() {
+                Some(self.install_slot(&id).await)
+            } else {
+                None
+            };
+            self.modify(&id, |info| {
+                info.status = TaskStatus::Running;
+                info.queue_position = None;
+            });
+
+            let handle = TaskHandle { id: id.clone(), manager: self.clone(), install_slot: Arc::new(Mutex::new(install_slot)) };
+            task(handle).await
+        };
+        let result = tokio::select! {
+            result = run => result,
+            _ = cancel_rx => Err("task was cancelled".to_string()),
+        };
+
+        self.modify(&id, |info| {
+            info.finished_at = Some(Utc::now().timestamp_millis());
+            match &result {
+                Ok(value) => {
+                    info.status = TaskStatus::Completed;
+                    info.progress = Some(1.0);
+                    info.result = serde_json::to_value(value).ok();
+                }
+                Err(err) if info.status == TaskStatus::Cancelled => {
+                    info.error = Some(err.clone());
+                }
+                Err(err) => {
+                    info.status = TaskStatus::Failed;
+                    info.error = Some(err.clone());
+                }
+            }
+        });
+        self.prune();
+        result
+    }
+
+    /// Waits until the install queue allows another task to run.
+    /// The queue can be turned off and its concurrency changed in the launcher options.
+    async fn install_slot(self: &Arc<Self>, task_id: &str) -> InstallSlot {
+        let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+        let concurrency = if options.queue_installs { options.install_concurrency.max(1) as usize } else { usize::MAX };
+
+        self.install_queue.lock().unwrap().waiting.push_back(task_id.to_string());
+        let mut slot = InstallSlot { task_id: task_id.to_string(), manager: self.clone(), acquired: false };
+        self.update_queue_positions();
+
+        loop {
+            let changed = self.install_queue_changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            {
+                let mut queue = self.install_queue.lock().unwrap();
+                if queue.running < concurrency && queue.waiting.front().map_or(false, |id| id == task_id) {
+                    queue.waiting.pop_front();
+                    queue.running += 1;
+                    slot.acquired = true;
+                }
+            }
+            if slot.acquired {
+                self.update_queue_positions();
+                return slot;
+            }
+
+            changed.await;
+        }
+    }
+
+    fn update_queue_positions(&self) {
+        let waiting: Vec<String> = self.install_queue.lock().unwrap().waiting.iter().cloned().collect();
+        for (index, id) in waiting.iter().enumerate() {
+            self.modify(id, |info| info.queue_position = Some(index + 1));
+        }
+    }
+
+    /// Drops the oldest finished tasks once there are more than `FINISHED_TASK_HISTORY`
+    fn prune(&self) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let mut finished: Vec<(i64, String)> = tasks.values()
+            .filter_map(|entry| entry.info.finished_at.map(|finished_at| (finished_at, entry.info.id.clone())))
+            .collect();
+        if finished.len() <= FINISHED_TASK_HISTORY {
+            return;
+        }
+        finished.sort();
+        for (_, id) in finished.iter().take(finished.len() - FINISHED_TASK_HISTORY) {
+            tasks.remove(id);
+        }
+    }
+
+    /// Returns all running and recently finished tasks, oldest first
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let mut tasks: Vec<TaskInfo> = self.tasks.lock().unwrap().values().map(|entry| entry.info.clone()).collect();
+        tasks.sort_by_key(|info| info.started_at);
+        tasks
+    }
+
+    /// Cancels a running task, returns false if there is no running task with this id
+    pub fn cancel(&self, id: &str) -> bool {
+        let info = {
+            let mut tasks = self.tasks.lock().unwrap();
+            let Some(entry) = tasks.get_mut(id) else { return false };
+            let Some(cancel) = entry.cancel.take() else { return false };
+            if cancel.send(()).is_err() {
+                return false;
+            }
+            entry.info.status = TaskStatus::Cancelled;
+            entry.info.clone()
+        };
+        info!("Cancelled task {} ({})", info.title, id);
+        self.emit(&info);
+        true
+    }
+}
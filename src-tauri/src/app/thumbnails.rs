@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use async_zip::read::seek::ZipFileReader;
+use image::imageops::FilterType;
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tauri::{AppHandle, Manager};
+use walkdir::WalkDir;
+
+use crate::utils::sha1sum;
+use crate::LAUNCHER_DIRECTORY;
+
+/// Thumbnails are pruned, least recently used first, once the cache is bigger than this
+const MAX_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+/// Remembers that an archive has no icon, so it isn't searched again
+const NO_ICON_MARKER: &str = "none";
+/// Touched when the thumbnails of an archive are used for the first time since the start, its modification time is the last use
+const LAST_USED_MARKER: &str = "used";
+
+/// Hashes of the archives by their path, with the size and modification time they had when they were hashed
+static ARCHIVE_HASHES: Lazy<Mutex<HashMap<PathBuf, (u64, SystemTime, String)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// Last use of the thumbnails of an archive by its hash since the start, later uses aren't written to disk
+static LAST_USED: Lazy<Mutex<HashMap<String, SystemTime>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn thumbnails_dir() -> PathBuf {
+    LAUNCHER_DIRECTORY.cache_dir().join("thumbnails")
+}
+
+/// Lets the webview load the thumbnails through the asset protocol, the cache dir is only known at runtime
+pub fn init(app_handle: &AppHandle) {
+    if let Err(err) = app_handle.asset_protocol_scope().allow_directory(thumbnails_dir(), true) {
+        warn!("Unable to allow the thumbnails for the asset protocol: {:?}", err);
+    }
+}
+
+/// Hash of an archive, only hashed again once its size or modification time changed
+async fn archive_hash(archive: &Path) -> Result<String> {
+    let metadata = fs::metadata(archive).await?;
+    let (len, modified) = (metadata.len(), metadata.modified()?);
+    if let Some((_, _, hash)) = ARCHIVE_HASHES.lock().unwrap().get(archive).filter(|(hashed_len, hashed_modified, _)| *hashed_len == len && *hashed_modified == modified) {
+        return Ok(hash.clone());
+    }
+
+    let path = archive.to_path_buf();
+    let hash = tokio::task::spawn_blocking(move || sha1sum(&path)).await??;
+    ARCHIVE_HASHES.lock().unwrap().insert(archive.to_path_buf(), (len, modified, hash.clone()));
+    Ok(hash)
+}
+
+/// Remembers that the thumbnails of an archive were used, so pruning keeps them over ones that weren't used for longer
+async fn mark_used(hash: &str, dir: &Path) {
+    let first_use = LAST_USED.lock().unwrap().insert(hash.to_string(), SystemTime::now()).is_none();
+    if first_use {
+        if let Err(err) = fs::write(dir.join(LAST_USED_MARKER), "").await {
+            debug!("Unable to mark thumbnails of {} as used: {:?}", hash, err);
+        }
+    }
+}
+
+/// Path of the icon inside a mod jar, resource pack, datapack or shader pack
+async fn icon_path<R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin>(reader: &mut ZipFileReader<R>) -> Result<Option<String>> {
+    let names: Vec<String> = reader.file().entries().iter().map(|entry| entry.entry().filename().to_string()).collect();
+
+    for metadata in ["fabric.mod.json", "quilt.mod.json"] {
+        let Some(index) = names.iter().position(|name| name == metadata) else { continue };
+        let json: Value = serde_json::from_slice(&read_entry(reader, index).await?).unwrap_or_default();
+        let icon = match metadata {
+            "fabric.mod.json" => &json["icon"],
+            _ => &json["quilt_loader"]["metadata"]["icon"],
+        };
+        // either a single path or paths by size
+        let icon = match icon {
+            Value::String(path) => Some(path.clone()),
+            Value::Object(sizes) => sizes.iter()
+                .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or_default())
+                .and_then(|(_, path)| path.as_str().map(str::to_string)),
+            _ => None,
+        };
+        if icon.is_some() {
+            return Ok(icon);
+        }
+    }
+
+    Ok(names.into_iter().find(|name| name == "pack.png" || name == "logo.png"))
+}
+
+async fn read_entry<R: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin>(reader: &mut ZipFileReader<R>, index: usize) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.entry(index).await?.read_to_end(&mut data).await?;
+    Ok(data)
+}
+
+/// Extracts the icon of an archive and stores it scaled to fit into `size`x`size` pixels.
+/// Returns the path of the stored thumbnail or `None` if the archive has no icon.
+pub async fn thumbnail(archive: &Path, size: u32) -> Result<Option<PathBuf>> {
+    let hash = archive_hash(archive).await?;
+    let dir = thumbnails_dir().join(&hash);
+    let target = dir.join(format!("{}.png", size));
+    if target.exists() {
+        mark_used(&hash, &dir).await;
+        return Ok(Some(target));
+    }
+    if dir.join(NO_ICON_MARKER).exists() {
+        mark_used(&hash, &dir).await;
+        return Ok(None);
+    }
+    fs::create_dir_all(&dir).await?;
+    mark_used(&hash, &dir).await;
+
+    let mut reader = ZipFileReader::new(fs::File::open(archive).await?).await?;
+    let icon = match icon_path(&mut reader).await? {
+        Some(icon_path) => {
+            let icon_path = icon_path.trim_start_matches('/');
+            match reader.file().entries().iter().position(|entry| entry.entry().filename() == icon_path) {
+                Some(index) => Some(read_entry(&mut reader, index).await?),
+                None => None,
+            }
+        }
+        None => None,
+    };
+    let Some(icon) = icon else {
+        debug!("{:?} has no icon", archive);
+        fs::write(dir.join(NO_ICON_MARKER), "").await?;
+        return Ok(None);
+    };
+
+    let thumbnail_path = target.clone();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let mut image = image::load_from_memory(&icon)?;
+        if image.width() > size || image.height() > size {
+            image = image.resize(size, size, FilterType::Lanczos3);
+        }
+        image.save_with_format(&thumbnail_path, image::ImageFormat::Png)?;
+        Ok(())
+    }).await??;
+
+    prune().await?;
+    Ok(Some(target))
+}
+
+/// Deletes the thumbnails of the least recently used archives until the cache is small enough again
+async fn prune() -> Result<()> {
+    let dir = thumbnails_dir();
+    let last_used = LAST_USED.lock().unwrap().clone();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let mut archives: Vec<(SystemTime, u64, PathBuf)> = Vec::new();
+        for entry in std::fs::read_dir(&dir)?.filter_map(|entry| entry.ok()) {
+            let size = WalkDir::new(entry.path()).into_iter()
+                .filter_map(|file| file.ok())
+                .filter_map(|file| file.metadata().ok())
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len())
+                .sum();
+            let hash = entry.file_name().to_string_lossy().to_string();
+            let used = match last_used.get(&hash) {
+                Some(used) => *used,
+                None => std::fs::metadata(entry.path().join(LAST_USED_MARKER)).or_else(|_| entry.metadata())?.modified()?,
+            };
+            archives.push((used, size, entry.path()));
+        }
+
+        let mut total: u64 = archives.iter().map(|(_, size, _)| size).sum();
+        if total <= MAX_CACHE_BYTES {
+            return Ok(());
+        }
+
+        archives.sort();
+        for (_, size, path) in archives {
+            if total <= MAX_CACHE_BYTES / 4 * 3 {
+                break;
+            }
+            std::fs::remove_dir_all(&path)?;
+            if let Some(hash) = path.file_name() {
+                LAST_USED.lock().unwrap().remove(hash.to_string_lossy().as_ref());
+            }
+            total -= size;
+        }
+        info!("Pruned thumbnail cache to {} bytes", total);
+        Ok(())
+    }).await?
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::app::modrinth_api::{ModUpdate, VersionChangelog};
+use crate::utils::JsonStore;
+use crate::LAUNCHER_DIRECTORY;
+
+///
+/// What changes with the pending update of a single mod
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModChangelog {
+    pub slug: String,
+    pub title: String,
+    #[serde(rename = "currentVersion")]
+    pub current_version: String,
+    #[serde(rename = "latestVersion")]
+    pub latest_version: String,
+    /// Newest first
+    pub versions: Vec<VersionChangelog>,
+}
+
+///
+/// Changelogs of the updates found by the last update check of a profile
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PendingChangelogs {
+    /// Unix timestamp of the update check
+    #[serde(rename = "checkedAt")]
+    pub checked_at: i64,
+    pub mods: Vec<ModChangelog>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ChangelogStore {
+    pub profiles: HashMap<String, PendingChangelogs>,
+}
+
+impl JsonStore for ChangelogStore {
+    const FILE_NAME: &'static str = "update_changelogs.json";
+}
+
+/// Replaces the cached changelogs of the profile with those of the updates just found
+pub async fn cache(profile_id: &str, updates: &[ModUpdate]) {
+    let config_dir = LAUNCHER_DIRECTORY.config_dir();
+    let mut store = ChangelogStore::load(config_dir).await.unwrap_or_default();
+    let mods = updates.iter().map(|update| ModChangelog {
+        slug: update.slug.clone(),
+        title: update.title.clone(),
+        current_version: update.current_version.clone(),
+        latest_version: update.latest_version.clone(),
+        versions: update.changelogs.clone(),
+    }).collect();
+    store.profiles.insert(profile_id.to_string(), PendingChangelogs { checked_at: Utc::now().timestamp(), mods });
+    if let Err(err) = store.store(config_dir).await {
+        error!("Failed to store update changelogs: {:?}", err);
+    }
+}
+
+/// Changelogs of the profile's pending updates, empty until its updates were checked
+pub async fn pending(profile_id: &str) -> PendingChangelogs {
+    ChangelogStore::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default()
+        .profiles.remove(profile_id)
+        .unwrap_or_default()
+}
@@ -0,0 +1,98 @@
+use serde::Serialize;
+
+use crate::app::app_data::ModVersionFallback;
+
+///
+/// Featured mod that is offered for another Minecraft version than the one of the branch
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct FallbackMod {
+    pub title: String,
+    #[serde(rename = "gameVersion")]
+    pub game_version: String,
+}
+
+///
+/// Sent as `featured-mods-filtered` when featured mods don't support the Minecraft version of the branch,
+/// so users know why there are fewer of them on new releases
+///
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct FeaturedModsFiltered {
+    #[serde(rename = "mcVersion")]
+    pub mc_version: String,
+    /// Offered for the nearest supported version instead
+    pub fallback: Vec<FallbackMod>,
+    /// Titles of the mods that aren't offered at all
+    pub dropped: Vec<String>,
+}
+
+impl FeaturedModsFiltered {
+    pub fn is_empty(&self) -> bool {
+        self.fallback.is_empty() && self.dropped.is_empty()
+    }
+}
+
+/// Numeric parts of a release version, `None` for snapshots and pre-releases
+fn release_parts(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// The supported version closest to the target within its major version (the first two parts, like 1.21).
+/// Older versions win over newer ones at the same distance, a mod usually keeps working on the next patch.
+pub fn nearest_compatible(rule: ModVersionFallback, target: &str, supported: &[String]) -> Option<String> {
+    if rule == ModVersionFallback::Exact {
+        return None;
+    }
+    let target_parts = release_parts(target)?;
+    let patch = |parts: &[u32]| parts.get(2).copied().unwrap_or(0) as i64;
+
+    supported.iter()
+        .filter_map(|version| release_parts(version).map(|parts| (version, parts)))
+        .filter(|(_, parts)| parts.len() >= 2 && parts[..2] == target_parts[..2.min(target_parts.len())])
+        .min_by_key(|(_, parts)| {
+            let distance = patch(parts) - patch(&target_parts);
+            (distance.abs(), distance > 0)
+        })
+        .map(|(version, _)| version.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(versions: &[&str]) -> Vec<String> {
+        versions.iter().map(|version| version.to_string()).collect()
+    }
+
+    #[test]
+    fn exact_never_falls_back() {
+        assert_eq!(nearest_compatible(ModVersionFallback::Exact, "1.21.3", &versions(&["1.21.1", "1.21.2"])), None);
+        assert_eq!(ModVersionFallback::default(), ModVersionFallback::Exact);
+    }
+
+    #[test]
+    fn picks_the_nearest_patch() {
+        let supported = versions(&["1.21", "1.21.1", "1.21.5"]);
+        assert_eq!(nearest_compatible(ModVersionFallback::NearestInMajor, "1.21.3", &supported), Some("1.21.1".to_string()));
+        assert_eq!(nearest_compatible(ModVersionFallback::NearestInMajor, "1.21.4", &supported), Some("1.21.5".to_string()));
+    }
+
+    #[test]
+    fn prefers_the_older_version_at_the_same_distance() {
+        let supported = versions(&["1.21.4", "1.21.2"]);
+        assert_eq!(nearest_compatible(ModVersionFallback::NearestInMajor, "1.21.3", &supported), Some("1.21.2".to_string()));
+    }
+
+    #[test]
+    fn stays_within_the_major_version() {
+        let supported = versions(&["1.20.6", "1.22"]);
+        assert_eq!(nearest_compatible(ModVersionFallback::NearestInMajor, "1.21.3", &supported), None);
+    }
+
+    #[test]
+    fn ignores_snapshots() {
+        let supported = versions(&["24w14a", "1.21-pre1", "1.21"]);
+        assert_eq!(nearest_compatible(ModVersionFallback::NearestInMajor, "1.21.1", &supported), Some("1.21".to_string()));
+        assert_eq!(nearest_compatible(ModVersionFallback::NearestInMajor, "24w14a", &supported), None);
+    }
+}
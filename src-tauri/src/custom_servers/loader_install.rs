@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{bail, Result};
+use log::{debug, info, warn};
+use tauri::Window;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use walkdir::WalkDir;
+
+use crate::utils::sha1sum;
+use crate::LAUNCHER_DIRECTORY;
+
+/// Output of the loader installer, kept in the folder of the server it installed.
+/// Installers only run for custom servers, client profiles get their loader from the launch manifest.
+pub const LOADER_INSTALL_LOG: &str = "loader-install.log";
+
+/// Lines containing one of these are sent to the frontend as `loader-install-output`, the rest only ends up in the log
+const KEY_LINE_MARKERS: [&str; 7] = ["processor", "error", "exception", "failed", "downloading", "installing", "successfully"];
+
+/// Files of the installer output the user is meant to edit, a reinstall from the cache keeps their version
+const USER_EDITABLE_FILES: [&str; 3] = ["user_jvm_args.txt", "run.sh", "run.bat"];
+
+/// Loaders kept in the cache, the least recently used ones are removed beyond that
+const MAX_CACHED_LOADERS: usize = 5;
+
+/// File of a cache entry with the time it was last installed from
+const LAST_USED_FILE: &str = ".last-used";
+
+#[derive(serde::Serialize, Clone, Debug)]
+struct LoaderInstallOutput {
+    server_id: String,
+    line: String,
+    stderr: bool,
+}
+
+///
+/// Loader version an installer installs, the files it produced are cached under it
+///
+pub struct LoaderKey<'a> {
+    pub loader: &'a str,
+    pub mc_version: &'a str,
+    pub loader_version: &'a str,
+}
+
+fn cache_folder() -> PathBuf {
+    LAUNCHER_DIRECTORY.data_dir().join("custom_servers").join("installers").join("cache")
+}
+
+/// Cache entry of a loader, the installer hash keeps a re-released installer of the same version from using old files
+fn cache_entry(key: &LoaderKey, installer_jar: &Path) -> Result<PathBuf> {
+    let installer_sha1 = sha1sum(&installer_jar.to_path_buf())?;
+    Ok(cache_folder().join(format!("{}-{}-{}-{}", key.loader, key.mc_version, key.loader_version, &installer_sha1[..12])))
+}
+
+/// Files of the folder relative to it
+fn relative_files(folder: &Path) -> HashSet<PathBuf> {
+    WalkDir::new(folder).into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(folder).ok().map(Path::to_path_buf))
+        .filter(|file| file.as_os_str() != LAST_USED_FILE)
+        .collect()
+}
+
+/// Files of an installer output that are copied into the server folder, the user's changes to the editable ones are kept
+fn files_to_restore(cached: &Path, server_dir: &Path) -> Vec<PathBuf> {
+    relative_files(cached).into_iter()
+        .filter(|file| !(USER_EDITABLE_FILES.iter().any(|editable| file.as_os_str() == *editable) && server_dir.join(file).exists()))
+        .collect()
+}
+
+async fn mark_used(cached: &Path) {
+    if let Err(err) = fs::write(cached.join(LAST_USED_FILE), chrono::Utc::now().timestamp().to_string()).await {
+        warn!("Failed to mark {:?} as used: {:?}", cached, err);
+    }
+}
+
+/// Removes the least recently used loaders beyond `MAX_CACHED_LOADERS` and installs that were interrupted
+async fn prune_cache() -> Result<()> {
+    let mut entries = Vec::new();
+    let mut read_dir = fs::read_dir(cache_folder()).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        if entry.file_name().to_string_lossy().contains(".partial-") {
+            // installs that are still running are younger than a day
+            let stale = entry.metadata().await?.modified()?.elapsed().map_or(false, |age| age.as_secs() > 24 * 60 * 60);
+            if stale {
+                fs::remove_dir_all(entry.path()).await?;
+            }
+            continue;
+        }
+        let last_used = fs::read_to_string(entry.path().join(LAST_USED_FILE)).await.ok()
+            .and_then(|last_used| last_used.trim().parse::<i64>().ok())
+            .unwrap_or_default();
+        entries.push((last_used, entry.path()));
+    }
+
+    entries.sort_by(|(a, _), (b, _)| b.cmp(a));
+    for (_, entry) in entries.into_iter().skip(MAX_CACHED_LOADERS) {
+        info!("Removing cached loader {:?}", entry);
+        fs::remove_dir_all(entry).await?;
+    }
+    Ok(())
+}
+
+async fn copy_files(from: &Path, to: &Path, files: impl IntoIterator<Item = PathBuf>) -> Result<usize> {
+    let mut copied = 0;
+    for file in files {
+        let target = to.join(&file);
+        fs::create_dir_all(target.parent().unwrap()).await?;
+        fs::copy(from.join(&file), &target).await?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+/// Installs the loader into the server folder, from the cache if the same installer ran before.
+/// The processors of the installer patch the server jar for minutes, their output is the same for every server.
+pub async fn install_cached(java_bin: &Path, installer_jar: &Path, key: LoaderKey<'_>, server_id: &str, window: &Arc<Mutex<Window>>) -> Result<()> {
+    let server_dir = LAUNCHER_DIRECTORY.data_dir().join("custom_servers").join(server_id);
+    let cached = cache_entry(&key, installer_jar)?;
+    if cached.exists() {
+        let restored = copy_files(&cached, &server_dir, files_to_restore(&cached, &server_dir)).await?;
+        mark_used(&cached).await;
+        fs::write(log_path(server_id), format!("> restored {} files of {:?} instead of running the installer\n", restored, cached)).await?;
+        info!("Restored {} {} {} for server {} from the cache", key.loader, key.mc_version, key.loader_version, server_id);
+        return Ok(());
+    }
+
+    // the installer runs in an empty folder next to the cache entry, so its whole output is cached even on a reinstall
+    // into an already populated server folder, and an interrupted install is never used.
+    // Every install gets a folder of its own, as the same loader can be installed for two servers at once
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let partial = cache_folder().join(format!("{}.partial-{}", cached.file_name().unwrap().to_string_lossy(), &id[..8]));
+    fs::create_dir_all(&partial).await?;
+    if let Err(err) = run_installer(java_bin, installer_jar, &partial, server_id, window).await {
+        let _ = fs::remove_dir_all(&partial).await;
+        return Err(err);
+    }
+    if let Err(err) = copy_files(&partial, &server_dir, files_to_restore(&partial, &server_dir)).await {
+        let _ = fs::remove_dir_all(&partial).await;
+        return Err(err);
+    }
+
+    match fs::rename(&partial, &cached).await {
+        Ok(()) => {
+            debug!("Cached the installed loader in {:?}", cached);
+            mark_used(&cached).await;
+        }
+        Err(err) => {
+            // another install of the same loader may have finished first
+            warn!("Failed to cache the installed loader: {:?}", err);
+            let _ = fs::remove_dir_all(&partial).await;
+        }
+    }
+    if let Err(err) = prune_cache().await {
+        warn!("Failed to prune the loader cache: {:?}", err);
+    }
+    Ok(())
+}
+
+fn log_path(server_id: &str) -> PathBuf {
+    LAUNCHER_DIRECTORY.data_dir().join("custom_servers").join(server_id).join(LOADER_INSTALL_LOG)
+}
+
+fn is_key_line(line: &str) -> bool {
+    let line = line.to_lowercase();
+    KEY_LINE_MARKERS.iter().any(|marker| line.contains(marker))
+}
+
+/// Runs a Forge or NeoForge installer jar in the given folder. Its output is written to `loader-install.log` of the server, and a
+/// failed install reports the last error line of the installer instead of failing silently.
+pub async fn run_installer(java_bin: &Path, installer_jar: &Path, install_dir: &Path, server_id: &str, window: &Arc<Mutex<Window>>) -> Result<()> {
+    let server_dir = LAUNCHER_DIRECTORY.data_dir().join("custom_servers").join(server_id);
+    fs::create_dir_all(&server_dir).await?;
+    let mut log = File::create(log_path(server_id)).await?;
+    log.write_all(format!("> {:?} -jar {:?} --installServer\n", java_bin, installer_jar).as_bytes()).await?;
+
+    info!("Running loader installer {:?} for server {}", installer_jar, server_id);
+    let mut child = Command::new(java_bin)
+        .current_dir(install_dir)
+        .arg("-jar").arg(installer_jar)
+        .arg("--installServer")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut stderr = BufReader::new(child.stderr.take().unwrap()).lines();
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    // installers print warnings to stderr as well, lines naming an error are what made the install fail
+    let (mut last_error, mut last_stderr) = (None, None);
+    while stdout_open || stderr_open {
+        let (line, from_stderr) = tokio::select! {
+            line = stdout.next_line(), if stdout_open => (line?, false),
+            line = stderr.next_line(), if stderr_open => (line?, true),
+        };
+        let Some(line) = line else {
+            if from_stderr { stderr_open = false } else { stdout_open = false }
+            continue;
+        };
+
+        log.write_all(format!("{}{}\n", if from_stderr { "[stderr] " } else { "" }, line).as_bytes()).await?;
+        let lowercase = line.to_lowercase();
+        if lowercase.contains("error") || lowercase.contains("exception") {
+            last_error = Some(line.clone());
+        } else if from_stderr {
+            last_stderr = Some(line.clone());
+        }
+        if is_key_line(&line) || from_stderr {
+            debug!("Loader installer: {}", line);
+            let output = LoaderInstallOutput { server_id: server_id.to_string(), line, stderr: from_stderr };
+            if let Err(err) = window.lock().unwrap().emit("loader-install-output", output) {
+                warn!("Failed to emit loader install output: {:?}", err);
+            }
+        }
+    }
+
+    let status = child.wait().await?;
+    log.write_all(format!("> exited with {}\n", status).as_bytes()).await?;
+    log.flush().await?;
+    if !status.success() {
+        bail!("the loader installer failed ({}): {}, see {}", status, last_error.or(last_stderr).unwrap_or_else(|| "no error output".to_string()), LOADER_INSTALL_LOG);
+    }
+    info!("Installed loader of server {}", server_id);
+    Ok(())
+}
+
+/// Output of the last loader install of the server, `None` if its loader was never installed
+pub async fn read_log(server_id: &str) -> Result<Option<String>> {
+    let path = log_path(server_id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(path).await?))
+}
@@ -2,12 +2,12 @@ use std::{path::PathBuf, sync::{Arc, Mutex}};
 
 use anyhow::{Ok, Result};
 use log::{debug, error, info};
-use tauri::Window;
+use tauri::{Manager, Window};
 use tokio::{fs, process::Child};
 
-use crate::{app::{api::ApiEndpoints, app_data::LauncherOptions}, custom_servers::forwarding_manager::GetTokenResponse, minecraft::{java::{find_java_binary, jre_downloader, JavaRuntime}, progress::ProgressUpdate}, LAUNCHER_DIRECTORY};
+use crate::{app::{api::ApiEndpoints, app_data::LauncherOptions, log_windows}, custom_servers::forwarding_manager::GetTokenResponse, minecraft::{java::{find_java_binary, jre_downloader, JavaRuntime}, progress::ProgressUpdate}, LAUNCHER_DIRECTORY};
 
-use super::{models::{CustomServer, CustomServerType}, providers::{forge::ForgeProvider, vanilla::VanillaProvider}};
+use super::{loader_install, models::{CustomServer, CustomServerType}, providers::{forge::ForgeProvider, vanilla::VanillaProvider}};
 
 
 pub struct CustomServerManager {}
@@ -24,8 +24,18 @@ impl CustomServerManager {
             CustomServerType::FORGE => {
                 let label = ProgressUpdate::SetLabel("Downloading installer jar...".to_owned());
                 let _ = Self::handle_progress(&window, &server.id, label);
-                let _ = ForgeProvider::download_installer_jar(&server).await;
+                ForgeProvider::download_installer_jar(&server).await?;
                 let _ = Self::create_eula_file(&server).await;
+
+                let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+                let java_bin = Self::find_java(&options, window, &server.id).await?;
+                let label = ProgressUpdate::SetLabel("Installing Forge...".to_owned());
+                let _ = Self::handle_progress(&window, &server.id, label);
+                let loader_version = server.loader_version.clone().unwrap_or_default();
+                let installer_jar = LAUNCHER_DIRECTORY.data_dir().join("custom_servers").join("installers")
+                    .join(format!("forge-{}-{}.jar", server.mc_version, loader_version));
+                let key = loader_install::LoaderKey { loader: "forge", mc_version: &server.mc_version, loader_version: &loader_version };
+                loader_install::install_cached(&java_bin, &installer_jar, key, &server.id, window).await?;
             },
             CustomServerType::FABRIC => todo!(),
             CustomServerType::NEO_FORGE => todo!(),
@@ -47,20 +57,21 @@ impl CustomServerManager {
         Ok(())
     }
 
-    pub async fn run_server(custom_server: CustomServer, options: LauncherOptions, token: String, window_mutex: Arc<Mutex<Window>>) -> Result<Child> {
+    /// Java the servers run with, downloaded if necessary
+    async fn find_java(options: &LauncherOptions, window_mutex: &Arc<Mutex<Window>>, server_id: &str) -> Result<PathBuf> {
         // JRE download
         let runtimes_folder = options.data_path_buf().join("runtimes");
         if !runtimes_folder.exists() {
             fs::create_dir(&runtimes_folder).await?;
         }
 
-        let custom_java_path = if !options.custom_java_path.is_empty() { Some(options.custom_java_path) } else { None };
+        let custom_java_path = if !options.custom_java_path.is_empty() { Some(options.custom_java_path.clone()) } else { None };
 
         let java_bin = match &custom_java_path {
             Some(path) => PathBuf::from(path),
             None => {
                 info!("Checking for JRE...");
-                let _ = Self::handle_progress(&window_mutex, &custom_server.id, ProgressUpdate::SetLabel("Checking for JRE...".to_owned()))?;
+                let _ = Self::handle_progress(window_mutex, server_id, ProgressUpdate::SetLabel("Checking for JRE...".to_owned()))?;
                 
                 match find_java_binary(&runtimes_folder, 17).await {
                     Result::Ok(jre) => jre, // Fix: Wrap the value in a tuple variant
@@ -68,15 +79,20 @@ impl CustomServerManager {
                         error!("Failed to find JRE: {}", e);
                         
                         info!("Download JRE...");
-                        let _ = Self::handle_progress(&window_mutex, &custom_server.id, ProgressUpdate::SetLabel("Download JRE...".to_owned()))?;
+                        let _ = Self::handle_progress(window_mutex, server_id, ProgressUpdate::SetLabel("Download JRE...".to_owned()))?;
                         jre_downloader::jre_download(&runtimes_folder, 17, |a, b| {
-                            let _ = Self::handle_progress(&window_mutex, &custom_server.id, ProgressUpdate::SetProgress((a / b) * 100));
+                            let _ = Self::handle_progress(window_mutex, server_id, ProgressUpdate::SetProgress((a / b) * 100));
                         }).await?
                     }
                 }
             }
         };
         debug!("Java binary: {}", java_bin.to_str().unwrap());
+        Ok(java_bin)
+    }
+
+    pub async fn run_server(custom_server: CustomServer, options: LauncherOptions, token: String, window_mutex: Arc<Mutex<Window>>) -> Result<Child> {
+        let java_bin = Self::find_java(&options, &window_mutex, &custom_server.id).await?;
         // Game
         let java_runtime = JavaRuntime::new(java_bin);
 
@@ -100,7 +116,9 @@ impl CustomServerManager {
         }
     
         info!("{}", data);
-        window.lock().unwrap().emit("custom-server-process-output", CustomServerEventPayload { server_id: server_id.to_owned(), data: data })?;
+        let window = window.lock().unwrap();
+        log_windows::forward(&window.app_handle(), server_id, &data);
+        window.emit("custom-server-process-output", CustomServerEventPayload { server_id: server_id.to_owned(), data: data })?;
         Ok(())
     }
     
@@ -111,7 +129,9 @@ impl CustomServerManager {
         }
     
         error!("{}", data);
-        window.lock().unwrap().emit("custom-server-process-output", CustomServerEventPayload { server_id: server_id.to_owned(), data: data })?;
+        let window = window.lock().unwrap();
+        log_windows::forward(&window.app_handle(), server_id, &data);
+        window.emit("custom-server-process-output", CustomServerEventPayload { server_id: server_id.to_owned(), data: data })?;
         Ok(())
     }
     
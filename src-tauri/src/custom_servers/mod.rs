@@ -1,3 +1,4 @@
+pub mod loader_install;
 pub mod manager;
 pub mod models;
 pub mod prelauncher;
@@ -0,0 +1,99 @@
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+/// Environment variable that overrides the data folder of the options, set per OS user
+const DATA_DIR_OVERRIDE_VAR: &str = "NORISKCLIENT_DATA_DIR";
+
+/// Starts the launcher in portable mode
+const PORTABLE_ARG: &str = "--portable";
+
+/// Placing this file beside the executable starts the launcher in portable mode
+const PORTABLE_FLAG_FILE: &str = "portable.txt";
+
+/// Folder beside the executable that holds everything in portable mode
+const PORTABLE_FOLDER: &str = "NoRiskClient";
+
+///
+/// Folders the launcher keeps its data, config and cache in. These are the folders of the OS user profile,
+/// or a folder beside the executable in portable mode, so the launcher can run from a USB stick.
+/// The data folder can be moved with `NORISKCLIENT_DATA_DIR`, see `overridden_data_dir`.
+///
+pub struct LauncherDirectory {
+    data_dir: PathBuf,
+    config_dir: PathBuf,
+    cache_dir: PathBuf,
+    portable: bool,
+}
+
+impl LauncherDirectory {
+    pub fn resolve() -> Self {
+        if let Some(root) = test_root() {
+            return LauncherDirectory {
+                data_dir: root.join("data"),
+                config_dir: root.join("config"),
+                cache_dir: root.join("cache"),
+                portable: false,
+            };
+        }
+        if let Some(root) = portable_root() {
+            return LauncherDirectory {
+                data_dir: root.join("data"),
+                config_dir: root.join("config"),
+                cache_dir: root.join("cache"),
+                portable: true,
+            };
+        }
+        match ProjectDirs::from("gg", "norisk", "NoRiskClient") {
+            Some(proj_dirs) => LauncherDirectory {
+                // every lookup of the data folder goes through here, so the override applies to all of them
+                data_dir: overridden_data_dir().unwrap_or_else(|| proj_dirs.data_dir().to_path_buf()),
+                config_dir: proj_dirs.config_dir().to_path_buf(),
+                cache_dir: proj_dirs.cache_dir().to_path_buf(),
+                portable: false,
+            },
+            None => panic!("no application directory")
+        }
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// Whether everything lives beside the executable, nothing may be written to the OS user profile then
+    pub fn is_portable(&self) -> bool {
+        self.portable
+    }
+}
+
+/// Data folder set for the current OS user with `NORISKCLIENT_DATA_DIR`
+pub fn overridden_data_dir() -> Option<PathBuf> {
+    std::env::var_os(DATA_DIR_OVERRIDE_VAR)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Tests never touch the folders of an installed launcher, everything goes to the temporary folder of the test process
+#[cfg(feature = "testing")]
+fn test_root() -> Option<PathBuf> {
+    Some(crate::testing::launcher_root())
+}
+
+#[cfg(not(feature = "testing"))]
+fn test_root() -> Option<PathBuf> {
+    None
+}
+
+fn portable_root() -> Option<PathBuf> {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let requested = std::env::args().any(|arg| arg == PORTABLE_ARG) || exe_dir.join(PORTABLE_FLAG_FILE).exists();
+    requested.then(|| exe_dir.join(PORTABLE_FOLDER))
+}
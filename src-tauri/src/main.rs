@@ -5,7 +5,6 @@ windows_subsystem = "windows"
 )]
 
 use std::fs;
-use directories::ProjectDirs;
 use log::{info, LevelFilter};
 use log4rs::{
     append::{
@@ -26,15 +25,16 @@ pub mod minecraft;
 pub mod custom_servers;
 
 mod error;
+mod launcher_directory;
 mod utils;
+#[cfg(feature = "testing")]
+mod testing;
+
+use launcher_directory::LauncherDirectory;
+use utils::InstrumentedClient;
 
 const LAUNCHER_VERSION: &str = env!("CARGO_PKG_VERSION");
-static LAUNCHER_DIRECTORY: Lazy<ProjectDirs> = Lazy::new(|| {
-    match ProjectDirs::from("gg", "norisk", "NoRiskClient") {
-        Some(proj_dirs) => proj_dirs,
-        None => panic!("no application directory")
-    }
-});
+static LAUNCHER_DIRECTORY: Lazy<LauncherDirectory> = Lazy::new(LauncherDirectory::resolve);
 
 static APP_USER_AGENT: &str = concat!(
     env!("CARGO_PKG_NAME"),
@@ -42,14 +42,14 @@ static APP_USER_AGENT: &str = concat!(
     env!("CARGO_PKG_VERSION"),
 );
 
-/// HTTP Client with launcher agent
-static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
+/// HTTP Client with launcher agent, records the timing of every request
+static HTTP_CLIENT: Lazy<InstrumentedClient> = Lazy::new(|| {
     let client = reqwest::ClientBuilder::new()
         .user_agent(APP_USER_AGENT)
         .build()
         .unwrap_or_else(|_| Client::new());
 
-    client
+    InstrumentedClient::new(client)
 });
 
 const TRIGGER_FILE_SIZE: u64 = 2 * 1024 * 1000;
@@ -58,6 +58,13 @@ const TRIGGER_FILE_SIZE: u64 = 2 * 1024 * 1000;
 const LOG_FILE_COUNT: u32 = 10;
 
 pub fn main() -> Result<()> {
+    minecraft::fake_process::run_if_requested();
+    // jump list entries start the launcher again, the running one launches their profile
+    if app::single_instance::forward_to_running() {
+        return Ok(());
+    }
+    app::startup::mark_process_start();
+
     let log_folder = LAUNCHER_DIRECTORY.data_dir().join("logs");
     let latest_log = log_folder.join("latest.log");
     let archive_folder = log_folder.join("archive").join("launcher.{}.log");
@@ -104,7 +111,7 @@ pub fn main() -> Result<()> {
     // This means you can change the default log level to trace
     // if you are trying to debug an issue and need more logs on then turn it off
     // once you are done.
-    let _handle = log4rs::init_config(config)?;
+    let _handle = app::startup::measure("logging", || log4rs::init_config(config))?;
 
     info!("###############################");
     info!("");
@@ -114,11 +121,17 @@ pub fn main() -> Result<()> {
     info!("");
     info!("###############################");
 
+    if LAUNCHER_DIRECTORY.is_portable() {
+        info!("Running in portable mode from {:?}", LAUNCHER_DIRECTORY.data_dir().parent());
+    }
 
     // application directory
     info!("Creating launcher directories...");
-    fs::create_dir_all(LAUNCHER_DIRECTORY.data_dir())?;
-    fs::create_dir_all(LAUNCHER_DIRECTORY.config_dir())?;
+    app::startup::measure("directories", || -> Result<()> {
+        fs::create_dir_all(LAUNCHER_DIRECTORY.data_dir())?;
+        fs::create_dir_all(LAUNCHER_DIRECTORY.config_dir())?;
+        Ok(())
+    })?;
 
     // app
     app::gui::gui_main();
@@ -1,2 +1,112 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use keyring::Entry as KeyringEntry;
+use log::error;
+use once_cell::sync::Lazy;
+
+use crate::LAUNCHER_DIRECTORY;
+
 /// The client ID of the Azure app used for authentication
 pub(crate) const AZURE_CLIENT_ID: &str = "5a2085e1-422b-4205-b7ca-1c3fff75dd42";
+
+///
+/// Storage for secrets like account tokens, keyed by name
+///
+pub trait SecretStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&self, key: &str, value: &str) -> Result<()>;
+    fn delete(&self, key: &str);
+}
+
+///
+/// Stores secrets in the keychain of the system: Windows Credential Manager, macOS Keychain or the Secret Service on Linux
+///
+pub struct KeyringSecretStore {
+    service: &'static str,
+}
+
+impl KeyringSecretStore {
+    pub const fn new(service: &'static str) -> Self {
+        KeyringSecretStore { service }
+    }
+}
+
+impl SecretStore for KeyringSecretStore {
+    fn get(&self, key: &str) -> Option<String> {
+        KeyringEntry::new(self.service, key).ok()?.get_password().ok()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        KeyringEntry::new(self.service, key)?.set_password(value)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) {
+        if let Ok(entry) = KeyringEntry::new(self.service, key) {
+            if let Err(err) = entry.delete_password() {
+                if !matches!(err, keyring::Error::NoEntry) {
+                    error!("Failed to delete secret {}: {:?}", key, err);
+                }
+            }
+        }
+    }
+}
+
+///
+/// Stores secrets in a file in the config folder, used in portable mode where the keychain of the system
+/// belongs to whichever computer the launcher is plugged into
+///
+pub struct FileSecretStore {
+    path: PathBuf,
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+impl FileSecretStore {
+    pub fn open(path: PathBuf) -> Self {
+        let secrets = fs::read(&path).ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        FileSecretStore { path, secrets: Mutex::new(secrets) }
+    }
+
+    fn write(&self, secrets: &HashMap<String, String>) -> Result<()> {
+        fs::write(&self.path, serde_json::to_vec_pretty(secrets)?)?;
+        Ok(())
+    }
+}
+
+impl SecretStore for FileSecretStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.secrets.lock().unwrap().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        let mut secrets = self.secrets.lock().unwrap();
+        secrets.insert(key.to_string(), value.to_string());
+        self.write(&secrets)
+    }
+
+    fn delete(&self, key: &str) {
+        let mut secrets = self.secrets.lock().unwrap();
+        if secrets.remove(key).is_some() {
+            if let Err(err) = self.write(&secrets) {
+                error!("Failed to delete secret {}: {:?}", key, err);
+            }
+        }
+    }
+}
+
+static KEYRING: KeyringSecretStore = KeyringSecretStore::new("noriskclient-launcher");
+static PORTABLE_SECRETS: Lazy<FileSecretStore> = Lazy::new(|| FileSecretStore::open(LAUNCHER_DIRECTORY.config_dir().join("secrets.json")));
+
+/// The secret store account tokens are kept in
+pub fn secret_store() -> &'static dyn SecretStore {
+    if LAUNCHER_DIRECTORY.is_portable() {
+        return &*PORTABLE_SECRETS;
+    }
+    &KEYRING
+}
@@ -0,0 +1,174 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use gix::bstr::BString;
+use gix::objs::tree::{Entry, EntryKind};
+use log::{debug, info};
+use serde::Serialize;
+
+/// Revisions listed at most, the history itself is never pruned
+const MAX_REVISIONS: usize = 200;
+
+///
+/// Committed state of the `config/` folder of a game directory
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigRevision {
+    pub id: String,
+    pub message: String,
+    /// Unix timestamp of the commit
+    pub time: i64,
+}
+
+/// Folder of the game dir the history is kept in. It is a bare repository outside of `config/`,
+/// so mods and everything walking the configs never see it.
+const HISTORY_FOLDER: &str = ".config_history";
+
+fn config_dir(game_dir: &Path) -> PathBuf {
+    game_dir.join("config")
+}
+
+fn history_dir(game_dir: &Path) -> PathBuf {
+    game_dir.join(HISTORY_FOLDER)
+}
+
+fn open_or_init(history_dir: &Path) -> Result<gix::Repository> {
+    if history_dir.exists() {
+        return Ok(gix::open(history_dir)?);
+    }
+    info!("Initializing config history in {:?}", history_dir);
+    Ok(gix::init_bare(history_dir)?)
+}
+
+/// Writes the folder as tree, `None` if it contains no files
+fn write_tree(repo: &gix::Repository, dir: &Path) -> Result<Option<gix::ObjectId>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let filename: BString = entry.file_name().to_string_lossy().as_ref().into();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if let Some(oid) = write_tree(repo, &entry.path())? {
+                entries.push(Entry { mode: EntryKind::Tree.into(), filename, oid });
+            }
+        } else if file_type.is_file() {
+            let oid = repo.write_blob(fs::read(entry.path())?)?.detach();
+            entries.push(Entry { mode: EntryKind::Blob.into(), filename, oid });
+        }
+    }
+    if entries.is_empty() {
+        return Ok(None);
+    }
+    // git requires the entries of a tree in its own order, which `Entry` implements
+    entries.sort();
+    Ok(Some(repo.write_object(&gix::objs::Tree { entries })?.detach()))
+}
+
+fn commit(history_dir: &Path, config_dir: &Path, message: &str) -> Result<Option<String>> {
+    if !config_dir.exists() {
+        return Ok(None);
+    }
+    let repo = open_or_init(history_dir)?;
+    let Some(tree) = write_tree(&repo, config_dir)? else { return Ok(None) };
+
+    let head = repo.head_commit().ok();
+    if let Some(head) = &head {
+        if head.tree_id()? == tree {
+            debug!("Configs in {:?} didn't change since the last revision", config_dir);
+            return Ok(None);
+        }
+    }
+
+    let signature = gix::actor::Signature {
+        name: "NoRiskClient Launcher".into(),
+        email: "launcher@norisk.gg".into(),
+        time: gix::date::Time::now_local_or_utc(),
+    };
+    let id = repo.commit_as(signature.to_ref(), signature.to_ref(), "HEAD", message, tree, head.map(|head| head.id))?;
+    debug!("Committed configs in {:?} as {}", config_dir, id);
+    Ok(Some(id.to_string()))
+}
+
+fn list(history_dir: &Path) -> Result<Vec<ConfigRevision>> {
+    if !history_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let repo = gix::open(history_dir)?;
+    let Ok(head) = repo.head_id() else { return Ok(Vec::new()) };
+
+    let mut revisions = Vec::new();
+    for info in head.ancestors().all()?.take(MAX_REVISIONS) {
+        let commit = info?.object()?;
+        revisions.push(ConfigRevision {
+            id: commit.id.to_string(),
+            message: commit.message()?.summary().to_string(),
+            time: commit.time()?.seconds,
+        });
+    }
+    Ok(revisions)
+}
+
+/// Writes the files of the tree into the folder
+fn write_out(repo: &gix::Repository, tree: gix::Tree<'_>, dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    for entry in tree.decode()?.entries.iter() {
+        let filename = entry.filename.to_string();
+        if filename.contains(['/', '\\']) || filename == ".." {
+            bail!("invalid file name {} in config revision", filename);
+        }
+        let path = dir.join(filename);
+        let object = repo.find_object(entry.oid)?;
+        if entry.mode.is_tree() {
+            write_out(repo, object.into_tree(), &path)?;
+        } else if entry.mode.is_blob() {
+            fs::write(&path, &object.data)?;
+        }
+    }
+    Ok(())
+}
+
+fn checkout(history_dir: &Path, config_dir: &Path, revision: &str) -> Result<()> {
+    let repo = gix::open(history_dir)?;
+    let id = gix::ObjectId::from_hex(revision.as_bytes())?;
+    let tree = repo.find_object(id)?.try_into_commit()?.tree()?;
+
+    // the current configs were committed before, so nothing is lost by clearing the folder
+    for entry in fs::read_dir(config_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    write_out(&repo, tree, config_dir)
+}
+
+/// Commits the `config/` folder unless it didn't change, returns the new revision
+pub async fn snapshot(game_dir: &Path, message: String) -> Result<Option<String>> {
+    let (history_dir, config_dir) = (history_dir(game_dir), config_dir(game_dir));
+    tokio::task::spawn_blocking(move || commit(&history_dir, &config_dir, &message)).await?
+}
+
+/// Revisions of the `config/` folder, newest first
+pub async fn revisions(game_dir: &Path) -> Result<Vec<ConfigRevision>> {
+    let history_dir = history_dir(game_dir);
+    tokio::task::spawn_blocking(move || list(&history_dir)).await?
+}
+
+/// Restores the `config/` folder to the revision, the current configs are committed first so the restore can be undone
+pub async fn restore(game_dir: &Path, revision: &str) -> Result<()> {
+    let (history_dir, config_dir) = (history_dir(game_dir), config_dir(game_dir));
+    if !history_dir.exists() {
+        bail!("no config history in {:?}", game_dir);
+    }
+    let revision = revision.to_string();
+    tokio::task::spawn_blocking(move || {
+        let short: String = revision.chars().take(8).collect();
+        commit(&history_dir, &config_dir, &format!("Before restoring {}", short))?;
+        checkout(&history_dir, &config_dir, &revision)?;
+        info!("Restored configs in {:?} to {}", config_dir, revision);
+        Ok(())
+    }).await?
+}
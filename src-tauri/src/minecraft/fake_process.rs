@@ -0,0 +1,80 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::process::{Child, Command};
+
+/// Starts the launcher executable as a fake game process that plays the script in the following file
+const FAKE_PROCESS_ARG: &str = "--fake-process";
+
+///
+/// Line the fake game process prints
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FakeLine {
+    pub text: String,
+    /// Printed to stderr instead of stdout
+    #[serde(default)]
+    pub stderr: bool,
+    /// Waits this long before printing the line
+    #[serde(rename = "delayMs", default)]
+    pub delay_ms: u64,
+}
+
+///
+/// Output and exit code of a fake game process, so the console and crash screens can be built without installing Minecraft
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FakeScript {
+    #[serde(default)]
+    pub lines: Vec<FakeLine>,
+    #[serde(rename = "exitCode", default)]
+    pub exit_code: i32,
+}
+
+/// Spawns the launcher executable as fake game process, its output and exit code go through the same handling as the game's
+pub async fn spawn(script: &FakeScript, data: &Path) -> Result<Child> {
+    let script_folder = data.join("fake-processes");
+    fs::create_dir_all(&script_folder).await?;
+    let script_file = script_folder.join(format!("{}.json", uuid::Uuid::new_v4()));
+    fs::write(&script_file, serde_json::to_vec(script)?).await?;
+
+    let child = Command::new(std::env::current_exe()?)
+        .arg(FAKE_PROCESS_ARG)
+        .arg(&script_file)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    Ok(child)
+}
+
+/// Plays the script and exits if the launcher was started as fake game process, only development builds do this
+pub fn run_if_requested() {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let mut args = std::env::args().skip_while(|arg| arg != FAKE_PROCESS_ARG).skip(1);
+    let Some(script_file) = args.next() else { return };
+
+    let script = std::fs::read(&script_file).ok()
+        .and_then(|script| serde_json::from_slice::<FakeScript>(&script).ok())
+        .unwrap_or_default();
+    let _ = std::fs::remove_file(&script_file);
+
+    for line in script.lines {
+        std::thread::sleep(Duration::from_millis(line.delay_ms));
+        if line.stderr {
+            let _ = writeln!(std::io::stderr(), "{}", line.text);
+        } else {
+            let mut stdout = std::io::stdout();
+            let _ = writeln!(stdout, "{}", line.text);
+            let _ = stdout.flush();
+        }
+    }
+    std::process::exit(script.exit_code);
+}
@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::Utc;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::app::api::{LoaderSubsystem, NoRiskLaunchManifest};
+use crate::minecraft::mod_store::hash_stamp;
+use crate::minecraft::mod_sync::ModSyncReport;
+use crate::utils::sha1sum;
+
+/// Written to the game dir of a branch after every successful install
+const INSTALL_MANIFEST_FILE: &str = "last_install.json";
+
+///
+/// A file that was part of a successful install
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InstalledFile {
+    /// Relative to the libraries folder for libraries, to the mods folder for mods and to the data folder otherwise
+    pub path: String,
+    pub sha1: Option<String>,
+    pub size: u64,
+    /// Size and modification time the sha1 of a mod was computed at, see `hash_stamp`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stamp: Option<String>,
+}
+
+impl InstalledFile {
+    pub async fn new(path: String, sha1: Option<String>, file: &Path) -> Result<Self> {
+        Ok(InstalledFile { path, sha1, size: fs::metadata(file).await?.len(), stamp: None })
+    }
+
+    /// Whether the file still exists with the size it was installed with
+    pub async fn is_present(&self, folder: &Path) -> bool {
+        fs::metadata(folder.join(&self.path)).await.map_or(false, |metadata| metadata.len() == self.size)
+    }
+}
+
+///
+/// The exact set of files the last successful install of a branch resolved to.
+/// Later launches compare against it to skip phases that didn't change, and repairs restore this state.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstallManifest {
+    #[serde(rename = "installedAt")]
+    pub installed_at: i64,
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+    /// Loader and its version, like `fabric 0.15.11`
+    pub loader: String,
+    pub client: Option<InstalledFile>,
+    pub libraries: Vec<InstalledFile>,
+    /// Mods the launcher placed into the mods folder
+    pub mods: Vec<InstalledFile>,
+    /// Mode of the NoRisk assets, if they were installed
+    #[serde(rename = "noriskAssetsExperimental", default)]
+    pub norisk_assets_experimental: Option<bool>,
+    /// Launch manifest the install was made for, a profile prepared for offline play launches with it when the API is unreachable
+    #[serde(rename = "launchManifest", default)]
+    pub launch_manifest: Option<serde_json::Value>,
+}
+
+/// Loader of a launch manifest and its version
+pub fn loader_of(manifest: &NoRiskLaunchManifest) -> String {
+    match &manifest.subsystem {
+        LoaderSubsystem::Fabric { .. } => format!("fabric {}", manifest.build.fabric_loader_version),
+        LoaderSubsystem::Forge { manifest: loader_manifest, .. } => format!("forge {}", loader_manifest),
+    }
+}
+
+impl InstallManifest {
+    pub fn new(version_id: String, loader: String) -> Self {
+        InstallManifest {
+            installed_at: Utc::now().timestamp(),
+            version_id,
+            loader,
+            client: None,
+            libraries: Vec::new(),
+            mods: Vec::new(),
+            norisk_assets_experimental: None,
+            launch_manifest: None,
+        }
+    }
+
+    fn path(game_dir: &Path) -> PathBuf {
+        game_dir.join(INSTALL_MANIFEST_FILE)
+    }
+
+    pub async fn load(game_dir: &Path) -> Option<Self> {
+        let content = fs::read(Self::path(game_dir)).await.ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    pub async fn store(&self, game_dir: &Path) -> Result<()> {
+        fs::create_dir_all(game_dir).await?;
+        fs::write(Self::path(game_dir), serde_json::to_vec_pretty(self)?).await?;
+        Ok(())
+    }
+
+    /// Launch manifest of the last successful install of the game dir
+    pub async fn load_launch_manifest(game_dir: &Path) -> Option<NoRiskLaunchManifest> {
+        let launch_manifest = Self::load(game_dir).await?.launch_manifest?;
+        serde_json::from_value(launch_manifest).ok()
+    }
+
+    /// Records the mods the last mod sync placed into the mods folder with their hashes.
+    /// Mods that are unchanged since the previous install keep their recorded hash instead of being hashed again.
+    pub async fn record_mods(&mut self, game_dir: &Path, previous: Option<&InstallManifest>) -> Result<()> {
+        let mods_dir = game_dir.join("mods");
+        let Some(report) = ModSyncReport::load(game_dir).await? else { return Ok(()) };
+
+        let mut files = report.managed_files().into_iter().collect::<Vec<_>>();
+        files.sort();
+        for file in files {
+            let path = mods_dir.join(file);
+            let Ok(metadata) = fs::metadata(&path).await else { continue };
+            let stamp = hash_stamp(&metadata)?;
+            let known = previous
+                .and_then(|previous| previous.mods.iter().find(|installed| installed.path == file && installed.stamp.as_ref() == Some(&stamp)))
+                .and_then(|installed| installed.sha1.clone());
+            let sha1 = match known {
+                Some(sha1) => sha1,
+                None => sha1sum(&path)?,
+            };
+            self.mods.push(InstalledFile { path: file.to_string(), sha1: Some(sha1), size: metadata.len(), stamp: Some(stamp) });
+        }
+        Ok(())
+    }
+
+    /// Whether the given version resolves to the same files as this install
+    pub fn matches(&self, version_id: &str, loader: &str) -> bool {
+        self.version_id == version_id && self.loader == loader
+    }
+
+    /// Whether the resolved libraries, by path and sha1, are the ones of this install and all of them are still present,
+    /// so verifying them can be skipped
+    pub async fn libraries_unchanged(&self, libraries: &[(String, Option<String>)], libraries_folder: &Path) -> bool {
+        if libraries.len() != self.libraries.len() {
+            return false;
+        }
+        for library in &self.libraries {
+            let resolved = libraries.iter().any(|(path, sha1)| *path == library.path && *sha1 == library.sha1);
+            if !resolved || !library.is_present(libraries_folder).await {
+                debug!("Library {} changed since the last install", library.path);
+                return false;
+            }
+        }
+        true
+    }
+}
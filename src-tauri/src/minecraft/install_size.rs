@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+
+use crate::app::api::{LoaderMod, ModSource, NoRiskLaunchManifest};
+use crate::minecraft::version::VersionProfile;
+use crate::utils::{available_disk_space, get_maven_artifact_path};
+use crate::HTTP_CLIENT;
+
+/// Parallel requests for the sizes of mods
+const SIZE_REQUESTS: usize = 8;
+
+///
+/// Bytes that still have to be downloaded to launch a profile, files that already exist are not counted
+///
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct InstallSizeEstimate {
+    #[serde(rename = "clientBytes")]
+    pub client_bytes: u64,
+    #[serde(rename = "librariesBytes")]
+    pub libraries_bytes: u64,
+    #[serde(rename = "assetsBytes")]
+    pub assets_bytes: u64,
+    #[serde(rename = "modsBytes")]
+    pub mods_bytes: u64,
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+    /// Files whose size isn't known in advance, the total is a bit too small if there are any
+    #[serde(rename = "unknownSizeFiles")]
+    pub unknown_size_files: usize,
+    /// Free space on the drive of the data folder, `None` if it couldn't be determined
+    #[serde(rename = "availableBytes")]
+    pub available_bytes: Option<u64>,
+    #[serde(rename = "enoughSpace")]
+    pub enough_space: bool,
+}
+
+fn mod_download_url(manifest: &NoRiskLaunchManifest, source: &ModSource) -> Option<String> {
+    match source {
+        ModSource::Repository { repository, artifact, url } => match url {
+            Some(url) => Some(url.clone()),
+            None => {
+                let repository_url = manifest.repositories.get(repository)?;
+                Some(format!("{}{}", repository_url, get_maven_artifact_path(artifact).ok()?))
+            }
+        }
+    }
+}
+
+async fn download_size(url: &str) -> Option<u64> {
+    HTTP_CLIENT.head(url).send().await.ok()?.error_for_status().ok()?.content_length()
+}
+
+pub async fn estimate(data: &Path, manifest: &NoRiskLaunchManifest, version_profile: &VersionProfile, additional_mods: &[LoaderMod]) -> Result<InstallSizeEstimate> {
+    let mut estimate = InstallSizeEstimate::default();
+
+    let client_jar = data.join("versions").join(&version_profile.id).join(format!("{}.jar", &version_profile.id));
+    if !client_jar.exists() {
+        match version_profile.downloads.as_ref().and_then(|downloads| downloads.client.as_ref()) {
+            Some(client) => estimate.client_bytes = client.size as u64,
+            None => estimate.unknown_size_files += 1,
+        }
+    }
+
+    let libraries_folder = data.join("libraries");
+    for library in version_profile.applicable_libraries(&HashSet::new()) {
+        let download = library.get_library_download()?;
+        if libraries_folder.join(&download.path).exists() {
+            continue;
+        }
+        match download.size {
+            Some(size) => estimate.libraries_bytes += size as u64,
+            None => estimate.unknown_size_files += 1,
+        }
+    }
+
+    if let Some(asset_index_location) = &version_profile.asset_index_location {
+        let assets_folder = data.join("assets");
+        let index_path = assets_folder.join("indexes").join(format!("{}.json", asset_index_location.id));
+        if index_path.exists() {
+            // the objects that are still missing are known exactly
+            let asset_index = asset_index_location.load_asset_index(&assets_folder.join("indexes")).await?;
+            estimate.assets_bytes = asset_index.objects.values()
+                .filter(|object| !assets_folder.join("objects").join(&object.hash[..2]).join(&object.hash).exists())
+                .map(|object| object.size as u64)
+                .sum();
+        } else {
+            estimate.assets_bytes = asset_index_location.total_size as u64;
+        }
+    }
+
+    let mod_cache = data.join("mod_cache");
+    let missing_mods: Vec<&LoaderMod> = manifest.mods.iter()
+        .filter(|loader_mod| loader_mod.required || loader_mod.enabled)
+        .chain(additional_mods.iter())
+        .filter(|loader_mod| loader_mod.source.get_path().map_or(true, |path| !mod_cache.join(path).exists()))
+        .collect();
+    let sizes: Vec<Option<u64>> = stream::iter(missing_mods)
+        .map(|loader_mod| async move {
+            match mod_download_url(manifest, &loader_mod.source) {
+                Some(url) => download_size(&url).await,
+                None => None,
+            }
+        })
+        .buffer_unordered(SIZE_REQUESTS)
+        .collect()
+        .await;
+    for size in sizes {
+        match size {
+            Some(size) => estimate.mods_bytes += size,
+            None => estimate.unknown_size_files += 1,
+        }
+    }
+
+    estimate.total_bytes = estimate.client_bytes + estimate.libraries_bytes + estimate.assets_bytes + estimate.mods_bytes;
+    estimate.available_bytes = available_disk_space(data);
+    estimate.enough_space = estimate.available_bytes.map_or(true, |available| available > estimate.total_bytes);
+    Ok(estimate)
+}
@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{debug, error, warn};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::minecraft::install_manifest::InstallManifest;
+
+/// Written to the game dir of a branch whenever its install state changes
+const INSTALL_STATE_FILE: &str = "install_state.json";
+
+/// Game dirs an install of this launcher process is running in, installs persisted as running elsewhere were interrupted
+static RUNNING: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+
+///
+/// Part of an install that is running
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InstallPhase {
+    #[serde(rename = "client")]
+    Client,
+    #[serde(rename = "libraries")]
+    Libraries,
+    #[serde(rename = "natives")]
+    Natives,
+    #[serde(rename = "assets")]
+    Assets,
+    #[serde(rename = "noriskAssets")]
+    NoRiskAssets,
+    #[serde(rename = "finishing")]
+    Finishing,
+}
+
+///
+/// Install state of a branch, kept in its game dir so the frontend knows it after a restart.
+/// Installs that were interrupted by closing the launcher are reported as `NeedsRepair`.
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state")]
+pub enum InstallState {
+    #[serde(rename = "notInstalled")]
+    NotInstalled,
+    #[serde(rename = "installing")]
+    Installing { phase: InstallPhase },
+    /// `fingerprint` is the version and loader the install resolved to, see `fingerprint`
+    #[serde(rename = "installed")]
+    Installed { fingerprint: String },
+    #[serde(rename = "needsRepair")]
+    NeedsRepair { reason: String },
+    /// Replacing the install of another version or loader
+    #[serde(rename = "updating")]
+    Updating { phase: InstallPhase, from: String },
+}
+
+impl Default for InstallState {
+    fn default() -> Self {
+        InstallState::NotInstalled
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct InstallStateChange {
+    branch: String,
+    state: InstallState,
+}
+
+pub fn init(app_handle: AppHandle) {
+    let _ = APP_HANDLE.set(app_handle);
+}
+
+/// Version and loader an install resolves to, like `1.21 fabric 0.16.0`
+pub fn fingerprint(version_id: &str, loader: &str) -> String {
+    format!("{} {}", version_id, loader)
+}
+
+fn path(game_dir: &Path) -> PathBuf {
+    game_dir.join(INSTALL_STATE_FILE)
+}
+
+/// Current state of the branch installed in the game dir
+pub async fn load(game_dir: &Path) -> InstallState {
+    let stored = tokio::fs::read(path(game_dir)).await.ok()
+        .and_then(|content| serde_json::from_slice::<InstallState>(&content).ok());
+    match stored {
+        Some(InstallState::Installing { .. } | InstallState::Updating { .. }) if !RUNNING.lock().unwrap().contains(game_dir) => {
+            InstallState::NeedsRepair { reason: "The last install was interrupted".to_string() }
+        }
+        Some(state) => state,
+        // installs of launchers before the install state only left their install manifest
+        None => match InstallManifest::load(game_dir).await {
+            Some(install) => InstallState::Installed { fingerprint: fingerprint(&install.version_id, &install.loader) },
+            None => InstallState::NotInstalled,
+        },
+    }
+}
+
+/// Persists the state and sends it to the frontend as `install-state-updated`
+pub fn store(game_dir: &Path, state: InstallState) {
+    debug!("Install state of {:?}: {:?}", game_dir, state);
+    let written = serde_json::to_vec_pretty(&state).map_err(anyhow::Error::from)
+        .and_then(|content| std::fs::create_dir_all(game_dir).and_then(|_| std::fs::write(path(game_dir), content)).map_err(anyhow::Error::from));
+    if let Err(err) = written {
+        error!("Failed to store install state of {:?}: {:?}", game_dir, err);
+    }
+
+    if let Some(app_handle) = APP_HANDLE.get() {
+        let branch = game_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if let Err(err) = app_handle.emit_all("install-state-updated", InstallStateChange { branch, state }) {
+            error!("Failed to emit install state: {:?}", err);
+        }
+    }
+}
+
+///
+/// Install running in a game dir. Dropping it before `finish` marks the branch as needing a repair,
+/// which covers installs that failed or were cancelled halfway.
+///
+pub struct InstallTracker {
+    game_dir: PathBuf,
+    /// Fingerprint of the install being replaced
+    from: Option<String>,
+    finished: bool,
+}
+
+impl InstallTracker {
+    /// Starts tracking an install of the fingerprint, `None` if it is installed already and the launch only verifies it
+    pub async fn begin(game_dir: &Path, fingerprint: &str) -> Option<Self> {
+        let from = match load(game_dir).await {
+            InstallState::Installed { fingerprint: installed } if installed == fingerprint => return None,
+            InstallState::Installed { fingerprint: installed } => Some(installed),
+            InstallState::Updating { from, .. } => Some(from),
+            _ => None,
+        };
+        RUNNING.lock().unwrap().insert(game_dir.to_path_buf());
+        let tracker = InstallTracker { game_dir: game_dir.to_path_buf(), from, finished: false };
+        tracker.phase(InstallPhase::Client);
+        Some(tracker)
+    }
+
+    pub fn phase(&self, phase: InstallPhase) {
+        let state = match &self.from {
+            Some(from) => InstallState::Updating { phase, from: from.clone() },
+            None => InstallState::Installing { phase },
+        };
+        store(&self.game_dir, state);
+    }
+
+    /// Marks the install as complete once the install manifest is written
+    pub fn finish(mut self, fingerprint: String) {
+        self.finished = true;
+        store(&self.game_dir, InstallState::Installed { fingerprint });
+    }
+}
+
+impl Drop for InstallTracker {
+    fn drop(&mut self) {
+        RUNNING.lock().unwrap().remove(&self.game_dir);
+        if !self.finished {
+            warn!("Install in {:?} didn't finish", self.game_dir);
+            store(&self.game_dir, InstallState::NeedsRepair { reason: "The install didn't finish".to_string() });
+        }
+    }
+}
+
+/// Tracks an optional install, so launches of installed branches don't have to check for one
+pub fn phase(tracker: &Option<InstallTracker>, phase: InstallPhase) {
+    if let Some(tracker) = tracker {
+        tracker.phase(phase);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::minecraft::install_manifest::InstallManifest;
+use crate::minecraft::library_cache::VerifiedLibraries;
+use crate::minecraft::mod_store::ModStore;
+use crate::minecraft::version::VersionProfile;
+use crate::utils::sha1sum;
+
+///
+/// Files a repair checks, a scoped repair only takes seconds compared to checking everything
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum RepairScope {
+    #[default]
+    #[serde(rename = "all")]
+    All,
+    /// Client jar and libraries
+    #[serde(rename = "loader")]
+    Loader,
+    #[serde(rename = "assets")]
+    Assets,
+    /// Mods of the last install
+    #[serde(rename = "mods")]
+    Mods,
+}
+
+impl RepairScope {
+    fn includes(&self, scope: RepairScope) -> bool {
+        *self == RepairScope::All || *self == scope
+    }
+}
+
+///
+/// Result of checking the files of a profile, broken files are removed so the next launch downloads them again
+///
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct IntegrityReport {
+    #[serde(rename = "fullCheck")]
+    pub full_check: bool,
+    pub scope: RepairScope,
+    #[serde(rename = "checkedFiles")]
+    pub checked_files: usize,
+    /// Files that had to be hashed, because a full check was requested or the quick check flagged them
+    #[serde(rename = "hashedFiles")]
+    pub hashed_files: usize,
+    #[serde(rename = "brokenFiles")]
+    pub broken_files: Vec<String>,
+}
+
+struct ExpectedFile {
+    /// Key of the file in the verified libraries
+    key: String,
+    path: PathBuf,
+    size: Option<u64>,
+    sha1: Option<String>,
+}
+
+enum QuickCheck {
+    Valid,
+    Broken,
+    /// Size matches, but the file changed since it was last verified
+    Flagged,
+}
+
+impl IntegrityReport {
+    async fn check(&mut self, file: ExpectedFile, verified: Option<&VerifiedLibraries>, full: bool) -> Result<()> {
+        self.checked_files += 1;
+
+        let quick = match fs::metadata(&file.path).await {
+            Err(_) => QuickCheck::Broken,
+            Ok(metadata) if file.size.map_or(false, |size| size != metadata.len()) => QuickCheck::Broken,
+            Ok(_) => match (&file.sha1, verified) {
+                (Some(sha1), Some(verified)) if !verified.is_verified(&file.key, &file.path, sha1) => QuickCheck::Flagged,
+                _ => QuickCheck::Valid,
+            }
+        };
+
+        let broken = match (quick, &file.sha1) {
+            (QuickCheck::Broken, _) => true,
+            (QuickCheck::Valid, _) if !full => false,
+            (_, None) => false,
+            (_, Some(sha1)) => {
+                self.hashed_files += 1;
+                let matches = sha1sum(&file.path)? == *sha1;
+                if matches {
+                    if let Some(verified) = verified {
+                        verified.mark_verified(&file.key, &file.path, sha1);
+                    }
+                }
+                !matches
+            }
+        };
+
+        if broken {
+            debug!("{:?} is broken", file.path);
+            if file.path.exists() {
+                // mods might be read-only links into the mod store
+                ModStore::remove(&file.path).await?;
+            }
+            self.broken_files.push(file.key);
+        }
+        Ok(())
+    }
+}
+
+/// Checks the client jar, libraries and assets of a version and the mods of the last install of the game dir.
+/// By default only size and modification time are compared and files that changed since their last verification get hashed,
+/// `full` hashes every file. The scope limits the check to one part of the install.
+pub async fn verify(data: &Path, game_dir: &Path, version_profile: &VersionProfile, full: bool, scope: RepairScope) -> Result<IntegrityReport> {
+    let mut report = IntegrityReport { full_check: full, scope, ..Default::default() };
+
+    if scope.includes(RepairScope::Loader) {
+        let libraries_folder = data.join("libraries");
+        let verified_libraries = VerifiedLibraries::load(&libraries_folder, &version_profile.id).await;
+
+        if let Some(client) = version_profile.downloads.as_ref().and_then(|downloads| downloads.client.as_ref()) {
+            let key = format!("versions/{0}/{0}.jar", &version_profile.id);
+            let file = ExpectedFile { path: data.join(&key), key, size: Some(client.size as u64), sha1: Some(client.sha1.clone()) };
+            report.check(file, Some(&verified_libraries), full).await?;
+        }
+
+        for library in version_profile.applicable_libraries(&HashSet::new()) {
+            let download = library.get_library_download()?;
+            let file = ExpectedFile {
+                path: libraries_folder.join(&download.path),
+                size: download.size.map(|size| size as u64),
+                sha1: download.sha1.map(|sha1| sha1.trim().to_lowercase()),
+                key: download.path,
+            };
+            report.check(file, Some(&verified_libraries), full).await?;
+        }
+        verified_libraries.store().await;
+    }
+
+    if let Some(asset_index_location) = version_profile.asset_index_location.as_ref().filter(|_| scope.includes(RepairScope::Assets)) {
+        let indexes_folder = data.join("assets").join("indexes");
+        fs::create_dir_all(&indexes_folder).await?;
+        let asset_index = asset_index_location.load_asset_index(&indexes_folder).await?;
+        let objects_folder = data.join("assets").join("objects");
+        // assets are named by their hash and never change, the quick check only compares their size
+        for object in asset_index.objects.into_values() {
+            let file = ExpectedFile {
+                path: objects_folder.join(&object.hash[..2]).join(&object.hash),
+                key: format!("assets/objects/{}/{}", &object.hash[..2], &object.hash),
+                size: Some(object.size as u64),
+                sha1: Some(object.hash),
+            };
+            report.check(file, None, full).await?;
+        }
+    }
+
+    // removed mods are copied from the mod cache again by the next launch
+    let install = if scope.includes(RepairScope::Mods) { InstallManifest::load(game_dir).await } else { None };
+    if let Some(install) = install.filter(|install| install.version_id == version_profile.id) {
+        let mods_folder = game_dir.join("mods");
+        for installed_mod in install.mods {
+            let file = ExpectedFile {
+                path: mods_folder.join(&installed_mod.path),
+                key: format!("mods/{}", installed_mod.path),
+                size: Some(installed_mod.size),
+                sha1: installed_mod.sha1,
+            };
+            report.check(file, None, full).await?;
+        }
+    }
+
+    info!("Checked {} files of {} ({:?}), hashed {}, {} broken", report.checked_files, version_profile.id, scope, report.hashed_files, report.broken_files.len());
+    Ok(report)
+}
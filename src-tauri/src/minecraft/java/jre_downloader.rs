@@ -6,7 +6,7 @@ use path_absolutize::Absolutize;
 use tokio::fs;
 use crate::app::api::ApiEndpoints;
 
-use crate::utils::{download_file, tar_gz_extract, zip_extract, ARCHITECTURE, OperatingSystem, OS};
+use crate::utils::{clear_quarantine, download_file, tar_gz_extract, zip_extract, ARCHITECTURE, OperatingSystem, OS};
 
 /// Find java binary in JRE folder
 pub async fn find_java_binary(runtimes_folder: &Path, jre_version: u32) -> Result<PathBuf> {
@@ -76,6 +76,7 @@ pub async fn jre_download<F>(runtimes_folder: &Path, jre_version: u32, on_progre
         OperatingSystem::LINUX | OperatingSystem::OSX => tar_gz_extract(cursor, runtime_path.as_path()).await?,
         _ => bail!("Unsupported OS")
     }
+    clear_quarantine(&runtime_path).await;
 
     // Find JRE afterwards
     find_java_binary(runtimes_folder, jre_version).await
@@ -10,6 +10,7 @@ use tokio::io::AsyncReadExt;
 use log::debug;
 use crate::custom_servers::forwarding_manager::{start_forwarding, GetTokenResponse};
 use crate::custom_servers::models::CustomServer;
+use crate::utils::{simplified_argument, simplified_path, WINE};
 
 pub struct JavaRuntime(PathBuf);
 
@@ -19,19 +20,83 @@ impl JavaRuntime {
         JavaRuntime(path)
     }
 
-    pub async fn execute(&self, arguments: Vec<String>, game_dir: &Path) -> Result<Child> {
-        let mut command = Command::new(&self.0);
-        command.current_dir(game_dir);
-        command.args(arguments);
+    pub async fn execute(&self, arguments: Vec<String>, game_dir: &Path, isolated_home: Option<&Path>, preferred_gpu: &str) -> Result<Child> {
+        let mut command = self.gpu_command(preferred_gpu);
+        if *WINE {
+            // Wine can't resolve the `\\?\` prefix of canonicalized paths, not even those inside the arguments
+            command.current_dir(simplified_path(game_dir));
+            command.args(arguments.iter().map(|argument| simplified_argument(argument)));
+        } else {
+            command.current_dir(game_dir);
+            command.args(arguments);
+        }
+
+        // native code and child processes don't see user.home, so the environment points to the isolated home as well
+        if let Some(home) = isolated_home {
+            command.env("HOME", home);
+            #[cfg(target_os = "windows")]
+            {
+                command.env("USERPROFILE", home);
+                command.env("APPDATA", home.join("AppData").join("Roaming"));
+                command.env("LOCALAPPDATA", home.join("AppData").join("Local"));
+            }
+            #[cfg(target_os = "linux")]
+            {
+                command.env("XDG_CONFIG_HOME", home.join(".config"));
+                command.env("XDG_DATA_HOME", home.join(".local").join("share"));
+            }
+        }
 
+        // cancelling the launch task drops the child, which has to stop the game as well
         command
             .stderr(Stdio::piped())
-            .stdout(Stdio::piped());
+            .stdout(Stdio::piped())
+            .kill_on_drop(true);
 
         let child = command.spawn()?;
         Ok(child)
     }
 
+    /// Creates the game command with the workarounds needed to start on the preferred gpu ("DEFAULT", "DEDICATED" or "INTEGRATED")
+    fn gpu_command(&self, preferred_gpu: &str) -> Command {
+        #[cfg(target_os = "windows")]
+        {
+            // Windows picks the gpu per executable based on the graphics settings stored in the registry
+            if let Err(err) = set_windows_gpu_preference(&self.0, preferred_gpu) {
+                debug!("Failed to set gpu preference for {:?}: {:?}", self.0, err);
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            match preferred_gpu {
+                "DEDICATED" => {
+                    // prime-run handles nvidia offloading, DRI_PRIME covers mesa drivers
+                    if let Some(prime_run) = find_in_path("prime-run") {
+                        debug!("Launching through {:?}", prime_run);
+                        let mut command = Command::new(prime_run);
+                        command.arg(&self.0);
+                        return command;
+                    }
+                    let mut command = Command::new(&self.0);
+                    command.env("DRI_PRIME", "1");
+                    return command;
+                }
+                "INTEGRATED" => {
+                    let mut command = Command::new(&self.0);
+                    command.env("DRI_PRIME", "0");
+                    return command;
+                }
+                _ => {}
+            }
+        }
+
+        if *WINE {
+            return Command::new(simplified_path(&self.0));
+        }
+        Command::new(&self.0)
+    }
+
     pub async fn run_server(&self, max_ram: u64, min_ram: u64, server_dir: &Path) -> Result<Child> {
         let mut command = Command::new(&self.0);
         command.current_dir(server_dir);
@@ -122,4 +187,73 @@ impl JavaRuntime {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(target_os = "linux")]
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| std::env::split_paths(&paths).map(|dir| dir.join(binary)).find(|path| path.is_file()))
+}
+
+/// Preferences Windows had for java binaries before the launcher replaced them, restored once the preference is back to "DEFAULT"
+#[cfg(target_os = "windows")]
+const GPU_PREFERENCE_BACKUP: &str = "gpu_preference_backup.json";
+
+#[cfg(target_os = "windows")]
+fn set_windows_gpu_preference(java_bin: &Path, preferred_gpu: &str) -> Result<()> {
+    use std::collections::HashMap;
+    use windows::core::{HSTRING, w};
+    use windows::Win32::System::Registry::{RegDeleteKeyValueW, RegGetValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ, RRF_RT_REG_SZ};
+
+    let sub_key = w!("Software\\Microsoft\\DirectX\\UserGpuPreferences");
+    let value_name = HSTRING::from(java_bin.as_os_str());
+    let backup_path = crate::LAUNCHER_DIRECTORY.config_dir().join(GPU_PREFERENCE_BACKUP);
+    // `None` means there was no preference before the launcher set one
+    let mut backups = std::fs::read(&backup_path).ok()
+        .and_then(|content| serde_json::from_slice::<HashMap<String, Option<String>>>(&content).ok())
+        .unwrap_or_default();
+    let key = java_bin.to_string_lossy().to_string();
+
+    let write = |value: &str| -> Result<()> {
+        let data = value.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+        unsafe {
+            RegSetKeyValueW(HKEY_CURRENT_USER, sub_key, &value_name, REG_SZ.0, Some(data.as_ptr() as *const _), (data.len() * 2) as u32).ok()?;
+        }
+        Ok(())
+    };
+
+    // 1 = power saving, 2 = high performance
+    let preference = match preferred_gpu {
+        "DEDICATED" => 2,
+        "INTEGRATED" => 1,
+        _ => {
+            // only preferences the launcher replaced are touched, the user's own settings stay
+            let Some(previous) = backups.remove(&key) else { return Ok(()) };
+            match previous {
+                Some(previous) => write(&previous)?,
+                None => unsafe {
+                    let _ = RegDeleteKeyValueW(HKEY_CURRENT_USER, sub_key, &value_name);
+                },
+            }
+            std::fs::write(&backup_path, serde_json::to_vec_pretty(&backups)?)?;
+            return Ok(());
+        }
+    };
+
+    if !backups.contains_key(&key) {
+        let mut size = 0u32;
+        let previous = unsafe {
+            if RegGetValueW(HKEY_CURRENT_USER, sub_key, &value_name, RRF_RT_REG_SZ, None, None, Some(&mut size as *mut u32)).is_ok() {
+                let mut buffer = vec![0u16; size as usize / 2];
+                RegGetValueW(HKEY_CURRENT_USER, sub_key, &value_name, RRF_RT_REG_SZ, None, Some(buffer.as_mut_ptr() as *mut std::ffi::c_void), Some(&mut size as *mut u32)).ok()?;
+                let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+                Some(String::from_utf16_lossy(&buffer[..len]))
+            } else {
+                None
+            }
+        };
+        backups.insert(key, previous);
+        std::fs::write(&backup_path, serde_json::to_vec_pretty(&backups)?)?;
+    }
+
+    write(&format!("GpuPreference={};", preference))
+}
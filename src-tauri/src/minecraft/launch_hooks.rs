@@ -0,0 +1,140 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use log::{error, info};
+use once_cell::sync::Lazy;
+use tokio::fs;
+
+use crate::app::api::NoRiskLaunchManifest;
+use crate::minecraft::launch_hotfixes::LaunchHotfixHook;
+use crate::minecraft::launcher::{LaunchArguments, LaunchingParameter};
+
+/// Marks which mode the NoRisk assets in a folder belong to
+pub(crate) const NORISK_ASSETS_MODE_FILE: &str = ".mode";
+
+///
+/// What a launch hook gets to know about the launch it runs in
+///
+pub struct LaunchContext<'a> {
+    pub data: &'a Path,
+    pub game_dir: PathBuf,
+    pub manifest: &'a NoRiskLaunchManifest,
+    pub parameters: &'a LaunchingParameter,
+}
+
+impl<'a> LaunchContext<'a> {
+    pub fn new(data: &'a Path, manifest: &'a NoRiskLaunchManifest, parameters: &'a LaunchingParameter) -> Self {
+        LaunchContext { data, game_dir: data.join("gameDir").join(&manifest.build.branch), manifest, parameters }
+    }
+}
+
+///
+/// Step that runs at fixed points of every launch, so client specific steps like injecting agents or tweakers
+/// don't have to be hardcoded into the install. Every phase does nothing unless the hook overrides it.
+///
+pub trait LaunchHook: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Runs before anything is downloaded or installed, failing aborts the launch
+    fn pre_install<'a>(&'a self, _context: &'a LaunchContext<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Runs once everything is installed, right before the game starts. Can add JVM and game arguments.
+    fn pre_launch<'a>(&'a self, _context: &'a LaunchContext<'a>, _arguments: &'a mut LaunchArguments) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Runs after the game exited, `crashed` if it didn't exit cleanly
+    fn post_exit<'a>(&'a self, _context: &'a LaunchContext<'a>, _crashed: bool) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+///
+/// The NoRisk assets differ between experimental and production mode. When the mode changed since the last launch,
+/// the assets of the previous mode are moved aside and those of the current mode are restored,
+/// so neither mode ends up with a mix of both and switching back doesn't download everything again.
+///
+struct NoRiskAssetsModeHook;
+
+impl NoRiskAssetsModeHook {
+    async fn switch_mode(norisk_asset_dir: &Path, experimental_mode: bool) -> Result<()> {
+        let mode = if experimental_mode { "experimental" } else { "production" };
+        let mode_file = norisk_asset_dir.join(NORISK_ASSETS_MODE_FILE);
+        let parent = norisk_asset_dir.parent().unwrap();
+
+        if norisk_asset_dir.exists() {
+            // assets of launchers before the separation belong to production mode
+            let previous_mode = fs::read_to_string(&mode_file).await.unwrap_or_else(|_| "production".to_string());
+            let previous_mode = previous_mode.trim();
+            if previous_mode == mode {
+                return Ok(());
+            }
+
+            let stash = parent.join(format!("assets-{}", previous_mode));
+            if stash.exists() {
+                fs::remove_dir_all(&stash).await?;
+            }
+            fs::write(&mode_file, previous_mode).await?;
+            fs::rename(norisk_asset_dir, &stash).await?;
+            info!("Moved {} NoRisk assets to {:?}", previous_mode, stash);
+        }
+
+        let stash = parent.join(format!("assets-{}", mode));
+        if stash.exists() {
+            fs::rename(&stash, norisk_asset_dir).await?;
+            info!("Restored {} NoRisk assets from {:?}", mode, stash);
+        }
+        fs::create_dir_all(norisk_asset_dir).await?;
+        fs::write(&mode_file, mode).await?;
+        Ok(())
+    }
+}
+
+impl LaunchHook for NoRiskAssetsModeHook {
+    fn name(&self) -> &'static str {
+        "noriskAssetsMode"
+    }
+
+    fn pre_install<'a>(&'a self, context: &'a LaunchContext<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(Self::switch_mode(&context.game_dir.join("NoRiskClient").join("assets"), context.parameters.experimental_mode))
+    }
+}
+
+static HOOKS: Lazy<RwLock<Vec<Arc<dyn LaunchHook>>>> = Lazy::new(|| RwLock::new(vec![Arc::new(NoRiskAssetsModeHook), Arc::new(LaunchHotfixHook)]));
+
+/// Adds a hook that runs on all following launches, after the hooks registered before it
+pub fn register(hook: Arc<dyn LaunchHook>) {
+    info!("Registered launch hook {}", hook.name());
+    HOOKS.write().unwrap().push(hook);
+}
+
+fn hooks() -> Vec<Arc<dyn LaunchHook>> {
+    HOOKS.read().unwrap().clone()
+}
+
+pub async fn pre_install(context: &LaunchContext<'_>) -> Result<()> {
+    for hook in hooks() {
+        hook.pre_install(context).await.map_err(|err| err.context(format!("launch hook {} failed", hook.name())))?;
+    }
+    Ok(())
+}
+
+pub async fn pre_launch(context: &LaunchContext<'_>, arguments: &mut LaunchArguments) -> Result<()> {
+    for hook in hooks() {
+        hook.pre_launch(context, arguments).await.map_err(|err| err.context(format!("launch hook {} failed", hook.name())))?;
+    }
+    Ok(())
+}
+
+/// The game already exited, so failing hooks are only logged
+pub async fn post_exit(context: &LaunchContext<'_>, crashed: bool) {
+    for hook in hooks() {
+        if let Err(err) = hook.post_exit(context, crashed).await {
+            error!("Launch hook {} failed after the game exited: {:?}", hook.name(), err);
+        }
+    }
+}
@@ -8,20 +8,27 @@ use std::sync::atomic::{AtomicU64, Ordering};
 
 use anyhow::Result;
 use futures::stream::{self, StreamExt};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use rand::seq::SliceRandom;
+use serde::Serialize;
 
 use path_absolutize::*;
-use tokio::{fs, fs::OpenOptions};
+use tokio::fs;
 use walkdir::WalkDir;
 
-use crate::{LAUNCHER_VERSION, utils::{OS, OS_VERSION}, app::api::ApiEndpoints, minecraft::version::AssetObject};
-use crate::app::api::NoRiskLaunchManifest;
+use crate::{LAUNCHER_VERSION, utils::{OS, OS_VERSION}, minecraft::version::AssetObject};
+use crate::app::api::{is_access_denied, NoRiskLaunchManifest, SharedNoRiskToken};
+use crate::app::app_data::{AssetVerification, AssetVerificationMode};
 use crate::error::LauncherError;
 use crate::minecraft::progress::{get_max, get_progress, ProgressReceiver, ProgressUpdate, ProgressUpdateSteps};
-use crate::minecraft::rule_interpreter;
 use crate::minecraft::java::{find_java_binary, JavaRuntime, jre_downloader};
-use crate::minecraft::version::LibraryDownloadInfo;
-use crate::utils::{download_file, sha1sum, zip_extract};
+use crate::minecraft::library_cache::VerifiedLibraries;
+use crate::minecraft::natives;
+use crate::minecraft::install_manifest::{self, InstalledFile, InstallManifest};
+use crate::minecraft::install_state::{self, InstallPhase, InstallTracker};
+use crate::minecraft::launch_hooks::{self, LaunchContext, NORISK_ASSETS_MODE_FILE};
+use crate::minecraft::version::{Library, LibraryDownloadInfo};
+use crate::utils::{download_file, download_tracker, expect_downloads, sha1sum, DownloadConcurrency};
 
 use super::version::VersionProfile;
 
@@ -29,6 +36,10 @@ pub struct LauncherData<D: Send + Sync> {
     pub(crate) on_stdout: fn(&D, &[u8]) -> Result<()>,
     pub(crate) on_stderr: fn(&D, &[u8]) -> Result<()>,
     pub(crate) on_progress: fn(&D, ProgressUpdate) -> Result<()>,
+    /// Called once everything is downloaded, right before the game gets started
+    pub(crate) on_installed: fn(&D),
+    /// Called once the game process is running
+    pub(crate) on_spawned: fn(&D),
     pub(crate) data: Box<D>,
     pub(crate) terminator: tokio::sync::oneshot::Receiver<()>,
 }
@@ -36,6 +47,9 @@ pub struct LauncherData<D: Send + Sync> {
 impl<D: Send + Sync> ProgressReceiver for LauncherData<D> {
     fn progress_update(&self, progress_update: ProgressUpdate) {
         let _ = (self.on_progress)(&self.data, progress_update);
+        if let Some(bytes) = download_tracker().and_then(|tracker| tracker.snapshot()) {
+            let _ = (self.on_progress)(&self.data, ProgressUpdate::SetBytes(bytes));
+        }
     }
 }
 
@@ -77,8 +91,24 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
     // Launch class path for JRE
     let mut class_path = String::new();
 
+    let game_dir = data.join("gameDir").join(manifest.build.branch.clone());
+    let loader = install_manifest::loader_of(&manifest);
+    let last_install = InstallManifest::load(&game_dir).await.filter(|last_install| last_install.matches(&version_profile.id, &loader));
+    let fingerprint = install_state::fingerprint(&version_profile.id, &loader);
+    let install_tracker = InstallTracker::begin(&game_dir, &fingerprint).await;
+    let mut install = InstallManifest::new(version_profile.id.clone(), loader);
+    install.launch_manifest = serde_json::to_value(manifest).ok();
+    // files of the last successful install are trusted instead of verified
+    let quick_launch = launching_parameter.quick_launch && last_install.is_some();
+    if quick_launch {
+        info!("Quick launch, trusting the installed files of {}", version_profile.id);
+    }
+
     // Client
     let versions_folder = data.join("versions");
+    let libraries_folder = data.join("libraries");
+    // the client jar is recorded next to the libraries, so the quick integrity check doesn't have to hash it
+    let verified_libraries = Arc::new(VerifiedLibraries::load(&libraries_folder, &version_profile.id).await);
 
     // Check if json has client download (or doesn't require one)
     if let Some(client_download) = version_profile.downloads.as_ref().and_then(|x| x.client.as_ref()) {
@@ -91,10 +121,20 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
         write!(class_path, "{}{}", &client_jar.absolutize().unwrap().to_str().unwrap(), OS.get_path_separator()?)?;
 
         // Download client jar
+        let client_key = format!("versions/{0}/{0}.jar", &version_profile.id);
+        let unchanged = match last_install.as_ref().and_then(|last_install| last_install.client.as_ref()) {
+            Some(client) if client.path == client_key && client.sha1.as_ref() == Some(&client_download.sha1) => client.is_present(data).await,
+            _ => false,
+        };
         let requires_download = if !client_jar.exists() {
             true
+        } else if quick_launch || unchanged || verified_libraries.is_verified(&client_key, &client_jar, &client_download.sha1) {
+            false
         } else {
             let hash = sha1sum(&client_jar)?;
+            if hash == client_download.sha1 {
+                verified_libraries.mark_verified(&client_key, &client_jar, &client_download.sha1);
+            }
             hash != client_download.sha1
         };
 
@@ -112,21 +152,28 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
             if hash != client_download.sha1 {
                 anyhow::bail!("Client JAR download failed. SHA1 mismatch.");
             }
+            verified_libraries.mark_verified(&client_key, &client_jar, &client_download.sha1);
         }
+        install.client = Some(InstalledFile::new(client_key, Some(client_download.sha1.clone()), &client_jar).await?);
     } else {
         return Err(LauncherError::InvalidVersionProfile("No client JAR downloads were specified.".to_string()).into());
     }
 
     // Libraries
-    let libraries_folder = data.join("libraries");
-    let natives_folder = data.join("natives");
-    let natives_path = natives_folder.as_path();
-    if natives_folder.exists() {
-        fs::remove_dir_all(&natives_folder).await?;
-    }
-    fs::create_dir_all(&natives_folder).await?;
-
-    let libraries_to_download = version_profile.libraries.iter().map(|x| x.to_owned()).collect::<Vec<_>>();
+    install_state::phase(&install_tracker, InstallPhase::Libraries);
+    let native_jars = &Mutex::new(Vec::new());
+
+    let concurrency = &DownloadConcurrency::new(launching_parameter.concurrent_downloads, launching_parameter.adaptive_downloads);
+    let libraries_to_download = version_profile.applicable_libraries(&features);
+    let resolved_libraries = libraries_to_download.iter()
+        .filter_map(|library| library_download(library).transpose())
+        .map(|download| download.map(|download| (download.path, download.sha1.map(|sha1| sha1.trim().to_lowercase()))))
+        .collect::<Result<Vec<_>>>()?;
+    // libraries are only verified when they changed since the last install
+    let trust_libraries = quick_launch || match &last_install {
+        Some(last_install) => last_install.libraries_unchanged(&resolved_libraries, &libraries_folder).await,
+        None => false,
+    };
     // let libraries_downloaded = Arc::new(AtomicU64::new(0));
     let libraries_max = libraries_to_download.len() as u64;
 
@@ -134,56 +181,51 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
     launcher_data_arc.progress_update(ProgressUpdate::set_for_step(ProgressUpdateSteps::DownloadLibraries, 0, libraries_max));
 
     let class_paths: Vec<Result<Option<String>>> = stream::iter(
-        libraries_to_download.into_iter().filter_map(|library| {
+        libraries_to_download.into_iter().map(|library| {
             // let download_count = libraries_downloaded.clone();
             let data_clone = launcher_data_arc.clone();
             let folder_clone = libraries_folder.to_path_buf();
+            let verified_clone = verified_libraries.clone();
 
-            if !rule_interpreter::check_condition(&library.rules, &features).unwrap_or(false) {
-                return None;
-            }
-
-            Some(async move {
-                if let Some(natives) = &library.natives {
-                    if let Some(required_natives) = natives.get(OS.get_simple_name()?) {
-                        if let Some(classifiers) = library.downloads.as_ref().and_then(|x| x.classifiers.as_ref()) {
-                            if let Some(artifact) = classifiers.get(required_natives).map(LibraryDownloadInfo::from) {
-                                let path = artifact.download(library.name, folder_clone.as_path(), data_clone).await?;
-
-                                info!("Natives zip extract: {:?}", path);
-                                let file = OpenOptions::new().read(true).open(path).await?;
-                                zip_extract(file, natives_path).await?;
-                            }
-                        } else {
-                            return Err(LauncherError::InvalidVersionProfile("missing classifiers, but natives required.".to_string()).into());
-                        }
-                    }
+            async move {
+                let Some(artifact) = library_download(&library)? else { return Ok(None) };
+                let path = concurrency.run(artifact.download(library.name, folder_clone.as_path(), &verified_clone, trust_libraries, data_clone)).await?;
 
+                // Natives are not included in the classpath
+                if library.natives.is_some() {
+                    native_jars.lock().unwrap().push(path);
                     return Ok(None);
                 }
 
-                // Download regular artifact
-                let artifact = library.get_library_download()?;
-                let path = artifact.download(library.name, folder_clone.as_path(), data_clone).await?;
-
-                // Natives are not included in the classpath
-                return if library.natives.is_none() {
-                    return Ok(path.absolutize()?.to_str().map(|x| x.to_string()));
-                } else {
-                    Ok(None)
-                };
-            })
+                Ok(path.absolutize()?.to_str().map(|x| x.to_string()))
+            }
         })
-    ).buffer_unordered(launching_parameter.concurrent_downloads as usize).collect().await;
+    ).buffer_unordered(concurrency.max()).collect().await;
+    verified_libraries.store().await;
+
+    // natives are extracted once per jar and linked into the folder of the version
+    install_state::phase(&install_tracker, InstallPhase::Natives);
+    let native_jars = std::mem::take(&mut *native_jars.lock().unwrap());
+    let mut native_sources = Vec::new();
+    for jar in native_jars {
+        native_sources.push(natives::extract_cached(data, &jar).await?);
+    }
+    natives::link_version(data, &version_profile.id, &native_sources).await?;
+
     for x in class_paths {
         if let Some(library_path) = x? {
             write!(class_path, "{}{}", &library_path, OS.get_path_separator()?)?;
         }
     }
+    for (path, sha1) in resolved_libraries {
+        let library_path = libraries_folder.join(&path);
+        install.libraries.push(InstalledFile::new(path, sha1, &library_path).await?);
+    }
 
     launcher_data_arc.progress_update(ProgressUpdate::set_for_step(ProgressUpdateSteps::DownloadLibraries, libraries_max, libraries_max));
 
     // Minecraft Assets
+    install_state::phase(&install_tracker, InstallPhase::Assets);
     let assets_folder = data.join("assets");
     let indexes_folder: PathBuf = assets_folder.join("indexes");
     let objects_folder: PathBuf = assets_folder.join("objects");
@@ -194,8 +236,20 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
     let asset_index_location = version_profile.asset_index_location.as_ref().ok_or_else(|| LauncherError::InvalidVersionProfile("Asset index unspecified".to_string()))?;
     let asset_index = asset_index_location.load_asset_index(&indexes_folder).await?;
     let asset_objects_to_download = asset_index.objects.values().map(|x| x.to_owned()).collect::<Vec<_>>();
+    // the last install already verified its assets
+    if !quick_launch && launching_parameter.asset_verification.mode != AssetVerificationMode::None {
+        launcher_data_arc.progress_update(ProgressUpdate::set_label("Verifying Minecraft assets..."));
+        let removed = verify_assets(&objects_folder, &asset_objects_to_download, launching_parameter.asset_verification).await?;
+        if removed > 0 {
+            warn!("Removed {} corrupt assets, they are downloaded again", removed);
+        }
+    }
     let assets_downloaded = Arc::new(AtomicU64::new(0));
     let asset_max = asset_objects_to_download.len() as u64;
+    expect_downloads(asset_objects_to_download.iter()
+        .filter(|asset_object| !objects_folder.join(&asset_object.hash[0..2]).join(&asset_object.hash).exists())
+        .map(|asset_object| asset_object.size as u64)
+        .sum());
 
     launcher_data_arc.progress_update(ProgressUpdate::set_label("Checking Minecraft assets..."));
     launcher_data_arc.progress_update(ProgressUpdate::set_for_step(ProgressUpdateSteps::DownloadAssets, 0, asset_max));
@@ -208,7 +262,7 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
 
             async move {
                 let hash = asset_object.hash.clone();
-                match asset_object.download_destructing(folder_clone, data_clone.clone()).await {
+                match concurrency.run(asset_object.download_destructing(folder_clone, data_clone.clone())).await {
                     Ok(downloaded) => {
                         let curr = download_count.fetch_add(1, Ordering::Relaxed);
 
@@ -224,25 +278,44 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
                 Ok(())
             }
         })
-    ).buffer_unordered(launching_parameter.concurrent_downloads as usize).collect().await;
+    ).buffer_unordered(concurrency.max()).collect().await;
 
     launcher_data_arc.progress_update(ProgressUpdate::set_for_step(ProgressUpdateSteps::DownloadAssets, asset_max, asset_max));
 
-    let game_dir = data.join("gameDir").join(manifest.build.branch.clone());
+    if let Some(legacy_folder) = asset_index.legacy_folder(&asset_index_location.id, &assets_folder, &game_dir) {
+        launcher_data_arc.progress_update(ProgressUpdate::set_label("Mapping legacy assets..."));
+        let placed = asset_index.reconstruct(&objects_folder, &legacy_folder).await?;
+        info!("Placed {} legacy assets in {:?}", placed, legacy_folder);
+    }
 
     // Norisk Assets
+    install_state::phase(&install_tracker, InstallPhase::NoRiskAssets);
     let norisk_asset_dir = game_dir.join("NoRiskClient").join("assets");
     fs::create_dir_all(&norisk_asset_dir).await?;
 
-    let json_data = ApiEndpoints::norisk_assets(manifest.build.branch.clone(), norisk_token, uuid).await;
+    // shared by the parallel asset downloads, so an expired token is only refreshed once
+    let shared_token = &SharedNoRiskToken::new(norisk_token, uuid, launching_parameter.experimental_mode);
+    let norisk_assets_installed = last_install.as_ref().and_then(|last_install| last_install.norisk_assets_experimental) == Some(launching_parameter.experimental_mode);
+    let skip_norisk_assets = launching_parameter.skip_norisk_assets || (quick_launch && norisk_assets_installed);
 
-    let norisk_asset_objects_to_download: HashMap<String, AssetObject> = match json_data {
-        Ok(norisk_assets) => norisk_assets.objects,
-        Err(err) => {
-            info!("Error fetching norisk_assets: {}", err);
-            HashMap::new()
+    let norisk_asset_objects_to_download: HashMap<String, AssetObject> = if skip_norisk_assets {
+        info!("Skipping Norisk assets");
+        HashMap::new()
+    } else {
+        match shared_token.norisk_assets(&manifest.build.branch).await {
+            Ok(norisk_assets) => norisk_assets.objects,
+            Err(err) if is_access_denied(&err) => {
+                warn!("This account may not download the NoRisk assets of {}, launching without them: {}", manifest.build.branch, err);
+                HashMap::new()
+            }
+            Err(err) => {
+                info!("Error fetching norisk_assets: {}", err);
+                HashMap::new()
+            }
         }
     };
+    // only recorded as installed if every asset made it, so a later quick launch doesn't skip missing ones
+    let mut norisk_assets_complete = false;
 
     if norisk_asset_objects_to_download.len() > 0 {
         let norisk_assets_downloaded = Arc::new(AtomicU64::new(0));
@@ -251,7 +324,7 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
         launcher_data_arc.progress_update(ProgressUpdate::set_label("Checking Norisk assets..."));
         launcher_data_arc.progress_update(ProgressUpdate::set_for_step(ProgressUpdateSteps::DownloadNoRiskAssets, 0, norisk_asset_max));
 
-        let _: Vec<Result<()>> = stream::iter(
+        let results: Vec<Result<()>> = stream::iter(
             norisk_asset_objects_to_download.clone().into_iter().map(|asset_object| {
                 let download_count = norisk_assets_downloaded.clone();
                 let data_clone = launcher_data_arc.clone();
@@ -261,7 +334,7 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
                 async move {
                     let hash = asset_object.1.hash.clone();
 
-                    match asset_object.1.download_norisk_cosmetic_destructing(branch_clone, asset_object.0, norisk_token.to_string(), folder_clone, data_clone.clone()).await {
+                    match concurrency.run(asset_object.1.download_norisk_cosmetic_destructing(branch_clone, asset_object.0, shared_token, folder_clone, data_clone.clone())).await {
                         Ok(downloaded) => {
                             let curr = download_count.fetch_add(1, Ordering::Relaxed);
 
@@ -271,13 +344,17 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
                                 data_clone.progress_update(ProgressUpdate::set_label(format!("Downloaded Norisk asset {}", hash)));
                             }
                         }
-                        Err(err) => error!("Unable to download Norisk asset {}: {:?}", hash, err)
+                        Err(err) => {
+                            error!("Unable to download Norisk asset {}: {:?}", hash, err);
+                            return Err(err);
+                        }
                     }
 
                     Ok(())
                 }
             })
-        ).buffer_unordered(launching_parameter.concurrent_downloads as usize).collect().await;
+        ).buffer_unordered(concurrency.max()).collect().await;
+        norisk_assets_complete = results.iter().all(|result| result.is_ok());
 
         launcher_data_arc.progress_update(ProgressUpdate::set_for_step(ProgressUpdateSteps::DownloadNoRiskAssets, norisk_asset_max, norisk_asset_max));
 
@@ -289,27 +366,155 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
     // Game
     let java_runtime = JavaRuntime::new(java_bin);
 
-    let mut command_arguments = Vec::new();
+    // the token may have been refreshed while downloading the assets
+    let mut arguments = resolve_arguments(&shared_token.current(), &version_profile, &launching_parameter, &features, data, &game_dir, &class_path)?;
+
+    launcher_data_arc.progress_update(ProgressUpdate::set_label("Launching..."));
+    launcher_data_arc.progress_update(ProgressUpdate::set_to_max());
+
+    install_state::phase(&install_tracker, InstallPhase::Finishing);
+    if norisk_assets_complete || (skip_norisk_assets && norisk_assets_installed) {
+        install.norisk_assets_experimental = Some(launching_parameter.experimental_mode);
+    }
+    if let Err(err) = install.record_mods(&game_dir, last_install.as_ref()).await {
+        error!("Failed to record installed mods: {:?}", err);
+    }
+    match install.store(&game_dir).await {
+        Ok(()) => if let Some(install_tracker) = install_tracker {
+            install_tracker.finish(fingerprint);
+        },
+        Err(err) => {
+            error!("Failed to store install manifest: {:?}", err);
+            drop(install_tracker);
+        }
+    }
+    if launching_parameter.prepare_only {
+        info!("Prepared {} for playing offline", manifest.build.branch);
+        return Ok(());
+    }
+    let hook_context = LaunchContext::new(data, &manifest, &launching_parameter);
+    launch_hooks::pre_launch(&hook_context, &mut arguments).await?;
+    let mapped = arguments.into_command_arguments();
+
+    (launcher_data_arc.on_installed)(&launcher_data_arc.data);
+    let home = if launching_parameter.isolated {
+        let home = isolated_home(&game_dir, launching_parameter.profile_id.as_deref());
+        fs::create_dir_all(&home).await?;
+        Some(home)
+    } else {
+        None
+    };
+    let mut running_task = java_runtime.execute(mapped, &game_dir, home.as_deref(), &launching_parameter.preferred_gpu).await?;
+    (launcher_data_arc.on_spawned)(&launcher_data_arc.data);
+
+    if !launching_parameter.keep_launcher_open {
+        // Hide launcher window
+        window.lock().unwrap().hide().unwrap();
+    }
 
-    // JVM Args
-    version_profile.arguments.add_jvm_args_to_vec(norisk_token, &mut command_arguments, &launching_parameter, &features)?;
+    let launcher_data = Arc::try_unwrap(launcher_data_arc)
+        .unwrap_or_else(|_| panic!());
+    let terminator = launcher_data.terminator;
+    let data = launcher_data.data;
 
-    // Main class
-    command_arguments.push(version_profile.main_class.as_ref().ok_or_else(|| LauncherError::InvalidVersionProfile("Main class unspecified".to_string()))?.to_owned());
+    let game_exit = java_runtime.handle_io(&mut running_task, launcher_data.on_stdout, launcher_data.on_stderr, terminator, &data).await;
+    launch_hooks::post_exit(&hook_context, game_exit.is_err()).await;
+    game_exit?;
 
-    // Game args
-    version_profile.arguments.add_game_args_to_vec(&mut command_arguments, &features)?;
+    if !launching_parameter.keep_launcher_open {
+        // Hide launcher window
+        exit(0);
+    }
 
-    let mut mapped: Vec<String> = Vec::with_capacity(command_arguments.len());
+    Ok(())
+}
 
-    for x in command_arguments.iter() {
-        mapped.push(
+///
+/// Arguments of the game process with all templates resolved
+///
+pub struct LaunchArguments {
+    pub jvm_args: Vec<String>,
+    pub main_class: String,
+    pub game_args: Vec<String>,
+}
+
+impl LaunchArguments {
+    pub fn into_command_arguments(self) -> Vec<String> {
+        let mut command_arguments = self.jvm_args;
+        command_arguments.push(self.main_class);
+        command_arguments.extend(self.game_args);
+        command_arguments
+    }
+}
+
+///
+/// Launch command assembled without downloading or spawning anything
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchCommandPreview {
+    #[serde(rename = "javaPath")]
+    pub java_path: Option<String>,
+    #[serde(rename = "jvmArgs")]
+    pub jvm_args: Vec<String>,
+    #[serde(rename = "mainClass")]
+    pub main_class: String,
+    #[serde(rename = "classPath")]
+    pub class_path: Vec<String>,
+    #[serde(rename = "missingClassPath")]
+    pub missing_class_path: Vec<String>,
+    #[serde(rename = "gameArgs")]
+    pub game_args: Vec<String>,
+}
+
+/// Folder of the files that belong to one profile, the game dir itself is shared by every profile of the branch
+pub fn profile_dir(game_dir: &Path, profile_id: &str) -> PathBuf {
+    game_dir.join("profiles").join(profile_id)
+}
+
+/// Home folder of an isolated launch. Mods that write to the user's home or the shared `.minecraft` write here instead,
+/// so they can't affect other profiles. Launches without a profile share the home of the branch.
+pub fn isolated_home(game_dir: &Path, profile_id: Option<&str>) -> PathBuf {
+    match profile_id {
+        Some(profile_id) => profile_dir(game_dir, profile_id).join("home"),
+        None => game_dir.join("home"),
+    }
+}
+
+/// Replaces secrets in previews and exported scripts
+pub const REDACTED: &str = "<redacted>";
+
+fn resolve_arguments(norisk_token: &str, version_profile: &VersionProfile, launching_parameter: &LaunchingParameter, features: &HashSet<String>, data: &Path, game_dir: &Path, class_path: &str) -> Result<LaunchArguments> {
+    let assets_folder = data.join("assets");
+    let natives_folder = natives::version_folder(data, &version_profile.id);
+    let asset_index_location = version_profile.asset_index_location.as_ref().ok_or_else(|| LauncherError::InvalidVersionProfile("Asset index unspecified".to_string()))?;
+
+    let mut jvm_args = Vec::new();
+    version_profile.arguments.add_jvm_args_to_vec(norisk_token, &mut jvm_args, launching_parameter, features)?;
+    if launching_parameter.isolated {
+        let home = isolated_home(game_dir, launching_parameter.profile_id.as_deref());
+        jvm_args.push(format!("-Duser.home={}", home.absolutize()?.to_string_lossy()));
+        jvm_args.push(format!("-Dminecraft.applet.TargetDirectory={}", game_dir.absolutize()?.to_string_lossy()));
+    }
+
+    let main_class = version_profile.main_class.as_ref().ok_or_else(|| LauncherError::InvalidVersionProfile("Main class unspecified".to_string()))?.to_owned();
+
+    let mut game_args = Vec::new();
+    version_profile.arguments.add_game_args_to_vec(&mut game_args, features)?;
+
+    let resolve = |arguments: Vec<String>| -> Result<Vec<String>> {
+        arguments.iter().map(|x| {
             process_templates(x, |output, param| {
                 match param {
                     "auth_player_name" => output.push_str(&launching_parameter.auth_player_name),
                     "version_name" => output.push_str(&version_profile.id),
                     "game_directory" => output.push_str(game_dir.absolutize().unwrap().to_str().unwrap()),
                     "assets_root" => output.push_str(assets_folder.absolutize().unwrap().to_str().unwrap()),
+                    // legacy versions read their assets by name from the virtual folder
+                    "game_assets" => {
+                        let virtual_folder = assets_folder.join("virtual").join(&asset_index_location.id);
+                        let game_assets = if virtual_folder.exists() { virtual_folder } else { assets_folder.clone() };
+                        output.push_str(game_assets.absolutize().unwrap().to_str().unwrap())
+                    }
                     "assets_index_name" => output.push_str(&asset_index_location.id),
                     "auth_uuid" => output.push_str(&launching_parameter.auth_uuid),
                     "auth_access_token" => output.push_str(&launching_parameter.auth_access_token),
@@ -318,7 +523,7 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
                     "natives_directory" => output.push_str(natives_folder.absolutize().unwrap().to_str().unwrap()),
                     "launcher_name" => output.push_str("NoRiskClient"),
                     "launcher_version" => output.push_str(LAUNCHER_VERSION),
-                    "classpath" => output.push_str(&class_path),
+                    "classpath" => output.push_str(class_path),
                     "user_properties" => output.push_str("{}"),
                     "clientid" => output.push_str(&launching_parameter.clientid),
                     "auth_xuid" => output.push_str(&launching_parameter.auth_xuid),
@@ -326,34 +531,124 @@ pub async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, data: &Path,
                 };
 
                 Ok(())
-            })?
-        );
+            })
+        }).collect()
+    };
+
+    Ok(LaunchArguments {
+        jvm_args: resolve(jvm_args)?,
+        main_class,
+        game_args: resolve(game_args)?,
+    })
+}
+
+/// Class path entries of the client jar and all libraries, without downloading them
+pub(crate) fn class_path_entries(data: &Path, version_profile: &VersionProfile, features: &HashSet<String>) -> Result<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    entries.push(data.join("versions").join(&version_profile.id).join(format!("{}.jar", &version_profile.id)).absolutize()?.to_path_buf());
+
+    let libraries_folder = data.join("libraries");
+    for library in version_profile.applicable_libraries(features) {
+        // Natives are not included in the classpath
+        if library.natives.is_some() {
+            continue;
+        }
+
+        let artifact = library.get_library_download()?;
+        entries.push(libraries_folder.join(&artifact.path).absolutize()?.to_path_buf());
     }
 
-    launcher_data_arc.progress_update(ProgressUpdate::set_label("Launching..."));
-    launcher_data_arc.progress_update(ProgressUpdate::set_to_max());
+    Ok(entries)
+}
 
-    let mut running_task = java_runtime.execute(mapped, &game_dir).await?;
+/// Resolves java binary, class path and arguments like [launch] does, without downloading or spawning anything
+async fn assemble_launch_command(data: &Path, manifest: &NoRiskLaunchManifest, version_profile: &VersionProfile, launching_parameter: &LaunchingParameter, norisk_token: &str) -> Result<(Option<PathBuf>, Vec<PathBuf>, String, LaunchArguments)> {
+    let features: HashSet<String> = HashSet::new();
 
-    if !launching_parameter.keep_launcher_open {
-        // Hide launcher window
-        window.lock().unwrap().hide().unwrap();
+    let java_bin = match &launching_parameter.custom_java_path {
+        Some(path) => Some(PathBuf::from(path)),
+        None => find_java_binary(&data.join("runtimes"), manifest.build.jre_version).await.ok(),
+    };
+
+    let class_path = class_path_entries(data, version_profile, &features)?;
+    let mut joined_class_path = String::new();
+    for entry in &class_path {
+        write!(joined_class_path, "{}{}", entry.to_string_lossy(), OS.get_path_separator()?)?;
     }
 
-    let launcher_data = Arc::try_unwrap(launcher_data_arc)
-        .unwrap_or_else(|_| panic!());
-    let terminator = launcher_data.terminator;
-    let data = launcher_data.data;
+    let game_dir = data.join("gameDir").join(&manifest.build.branch);
+    let arguments = resolve_arguments(norisk_token, version_profile, launching_parameter, &features, data, &game_dir, &joined_class_path)?;
 
-    java_runtime.handle_io(&mut running_task, launcher_data.on_stdout, launcher_data.on_stderr, terminator, &data)
-        .await?;
+    Ok((java_bin, class_path, joined_class_path, arguments))
+}
 
-    if !launching_parameter.keep_launcher_open {
-        // Hide launcher window
-        exit(0);
+/// Runs the full parameter assembly of [launch] without downloading or spawning anything. Tokens are redacted.
+pub async fn launch_command_preview(data: &Path, manifest: &NoRiskLaunchManifest, version_profile: &VersionProfile, mut launching_parameter: LaunchingParameter) -> Result<LaunchCommandPreview> {
+    launching_parameter.auth_access_token = REDACTED.to_string();
+    let (java_bin, class_path, joined_class_path, arguments) = assemble_launch_command(data, manifest, version_profile, &launching_parameter, REDACTED).await?;
+
+    Ok(LaunchCommandPreview {
+        java_path: java_bin.map(|path| path.to_string_lossy().to_string()),
+        // the class path is listed separately, it would make the jvm args unreadable
+        jvm_args: arguments.jvm_args.into_iter().map(|arg| if arg == joined_class_path { format!("<{} class path entries>", class_path.len()) } else { arg }).collect(),
+        main_class: arguments.main_class,
+        missing_class_path: class_path.iter().filter(|entry| !entry.exists()).map(|entry| entry.to_string_lossy().to_string()).collect(),
+        class_path: class_path.iter().map(|entry| entry.to_string_lossy().to_string()).collect(),
+        game_args: arguments.game_args,
+    })
+}
+
+/// Environment variables the exported launch script reads the tokens from
+const SCRIPT_ACCESS_TOKEN_VAR: &str = "MC_ACCESS_TOKEN";
+const SCRIPT_NORISK_TOKEN_VAR: &str = "NORISK_TOKEN";
+const ACCESS_TOKEN_PLACEHOLDER: &str = "@@MC_ACCESS_TOKEN@@";
+const NORISK_TOKEN_PLACEHOLDER: &str = "@@NORISK_TOKEN@@";
+
+/// Builds a standalone batch (windows) or shell script reproducing the exact launch command.
+/// The tokens are not included, they have to be provided via environment variables.
+pub async fn launch_script(data: &Path, manifest: &NoRiskLaunchManifest, version_profile: &VersionProfile, mut launching_parameter: LaunchingParameter, batch: bool) -> Result<String> {
+    launching_parameter.auth_access_token = ACCESS_TOKEN_PLACEHOLDER.to_string();
+    let (java_bin, _, _, arguments) = assemble_launch_command(data, manifest, version_profile, &launching_parameter, NORISK_TOKEN_PLACEHOLDER).await?;
+    let java_bin = java_bin.ok_or_else(|| LauncherError::InvalidVersionProfile(format!("Java {} is not installed yet, launch the profile once first", manifest.build.jre_version)))?;
+    let game_dir = data.join("gameDir").join(&manifest.build.branch).absolutize()?.to_path_buf();
+
+    let mut command = vec![java_bin.to_string_lossy().to_string()];
+    command.extend(arguments.into_command_arguments());
+
+    let mut script = String::new();
+    if batch {
+        let quote = |arg: &str| format!("\"{}\"", arg.replace('%', "%%").replace('"', "\"\""))
+            .replace(ACCESS_TOKEN_PLACEHOLDER, &format!("%{}%", SCRIPT_ACCESS_TOKEN_VAR))
+            .replace(NORISK_TOKEN_PLACEHOLDER, &format!("%{}%", SCRIPT_NORISK_TOKEN_VAR));
+
+        writeln!(script, "@echo off")?;
+        writeln!(script, "rem NoRiskClient {} launch script, set {} and {} before running it", manifest.build.branch, SCRIPT_ACCESS_TOKEN_VAR, SCRIPT_NORISK_TOKEN_VAR)?;
+        writeln!(script, "cd /d {}", quote(&game_dir.to_string_lossy()))?;
+        writeln!(script, "{}", command.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" "))?;
+    } else {
+        let quote = |arg: &str| format!("'{}'", arg.replace('\'', "'\\''"))
+            .replace(ACCESS_TOKEN_PLACEHOLDER, &format!("'\"${{{}}}\"'", SCRIPT_ACCESS_TOKEN_VAR))
+            .replace(NORISK_TOKEN_PLACEHOLDER, &format!("'\"${{{}}}\"'", SCRIPT_NORISK_TOKEN_VAR));
+
+        writeln!(script, "#!/bin/sh")?;
+        writeln!(script, "# NoRiskClient {} launch script, set {} and {} before running it", manifest.build.branch, SCRIPT_ACCESS_TOKEN_VAR, SCRIPT_NORISK_TOKEN_VAR)?;
+        writeln!(script, ": \"${{{}:?}}\" \"${{{}:?}}\"", SCRIPT_ACCESS_TOKEN_VAR, SCRIPT_NORISK_TOKEN_VAR)?;
+        writeln!(script, "cd {} || exit 1", quote(&game_dir.to_string_lossy()))?;
+        writeln!(script, "exec {}", command.iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" \\\n    "))?;
     }
 
-    Ok(())
+    Ok(script)
+}
+
+/// The file to download for a library, natives only exist for some systems
+fn library_download(library: &Library) -> Result<Option<LibraryDownloadInfo>> {
+    if let Some(natives) = &library.natives {
+        let Some(required_natives) = natives.get(OS.get_simple_name()?) else { return Ok(None) };
+        let classifiers = library.downloads.as_ref().and_then(|x| x.classifiers.as_ref())
+            .ok_or_else(|| LauncherError::InvalidVersionProfile("missing classifiers, but natives required.".to_string()))?;
+        return Ok(classifiers.get(required_natives).map(LibraryDownloadInfo::from));
+    }
+    library.get_library_download().map(Some)
 }
 
 async fn verify_norisk_assets<D: Send + Sync>(dir: &Path, asset_objetcs: HashMap<String, AssetObject>, launcher_data_arc: Arc<LauncherData<D>>) {
@@ -366,6 +661,7 @@ async fn verify_norisk_assets<D: Send + Sync>(dir: &Path, asset_objetcs: HashMap
         }
     }
     keys_vec.push(".DS_Store");
+    keys_vec.push(NORISK_ASSETS_MODE_FILE);
     let file_names: &[&str] = &keys_vec;
     let mut verified: u64 = 0;
 
@@ -392,10 +688,50 @@ async fn verify_norisk_assets<D: Send + Sync>(dir: &Path, asset_objetcs: HashMap
     launcher_data_arc.progress_update(ProgressUpdate::set_for_step(ProgressUpdateSteps::VerifyNoRiskAssets, file_names.len() as u64, file_names.len() as u64));
 }
 
+/// Hashes the existing assets that the verification picks and deletes the ones that don't match, so they are downloaded again.
+/// A sample containing a corrupt asset escalates to hashing every asset, one broken file is rarely alone.
+async fn verify_assets(objects_folder: &Path, asset_objects: &[AssetObject], verification: AssetVerification) -> Result<usize> {
+    let existing = asset_objects.iter()
+        .map(|asset_object| (objects_folder.join(&asset_object.hash[0..2]).join(&asset_object.hash), asset_object.hash.clone()))
+        .filter(|(path, _)| path.exists())
+        .collect::<Vec<_>>();
+
+    tokio::task::spawn_blocking(move || -> Result<usize> {
+        let remove_corrupt = |assets: &[&(PathBuf, String)]| -> Result<usize> {
+            let mut removed = 0;
+            for (path, hash) in assets {
+                if sha1sum(path).map_or(true, |sha1| &sha1 != hash) {
+                    debug!("Asset {} is corrupt", hash);
+                    std::fs::remove_file(path)?;
+                    removed += 1;
+                }
+            }
+            Ok(removed)
+        };
+
+        if verification.mode == AssetVerificationMode::Sample {
+            let sample_size = (existing.len() * verification.sample_percent.clamp(1, 100) as usize).div_ceil(100);
+            let sample = existing.choose_multiple(&mut rand::thread_rng(), sample_size).collect::<Vec<_>>();
+            let removed = remove_corrupt(&sample)?;
+            info!("Verified {} sampled assets, {} corrupt", sample.len(), removed);
+            if removed == 0 {
+                return Ok(0);
+            }
+            warn!("Sampled assets were corrupt, verifying all assets");
+        }
+
+        let removed = remove_corrupt(&existing.iter().filter(|(path, _)| path.exists()).collect::<Vec<_>>())?;
+        info!("Verified all {} assets, {} corrupt", existing.len(), removed);
+        Ok(removed)
+    }).await?
+}
+
+#[derive(Clone)]
 pub struct LaunchingParameter {
     pub dev_mode: bool,
     pub force_server: Option<String>,
     pub memory: i64,
+    pub min_memory: Option<i64>,
     pub data_path: PathBuf,
     pub custom_java_path: Option<String>,
     pub custom_java_args: String,
@@ -407,6 +743,27 @@ pub struct LaunchingParameter {
     pub user_type: String,
     pub keep_launcher_open: bool,
     pub concurrent_downloads: i32,
+    /// Adjusts the concurrent downloads to the connection, see `DownloadConcurrency`
+    pub adaptive_downloads: bool,
+    pub preferred_gpu: String,
+    pub link_mods: bool,
+    pub ignore_content_blacklist: bool,
+    /// Only the mods of the NoRisk pack are loaded, see `RestrictedMode`
+    pub restricted_mode: bool,
+    /// Experimental and production mode keep separate NoRisk assets
+    pub experimental_mode: bool,
+    /// Trusts the files of the last successful install of the version instead of verifying them, see `InstallManifest`
+    pub quick_launch: bool,
+    /// Neither downloads nor verifies the NoRisk assets
+    pub skip_norisk_assets: bool,
+    /// Redirects the home and app data folders into the game dir, see `isolated_home`
+    pub isolated: bool,
+    /// Profile that is launched, `None` when the branch is launched without one
+    pub profile_id: Option<String>,
+    /// Stops once everything is downloaded instead of starting the game, see `prepare_profile_offline`
+    pub prepare_only: bool,
+    /// How many existing assets are hashed before launching, see `verify_assets`
+    pub asset_verification: AssetVerification,
 }
 
 fn process_templates<F: Fn(&mut String, &str) -> Result<()>>(input: &String, retriever: F) -> Result<String> {
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use log::{debug, error};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+struct VerifiedLibrary {
+    sha1: String,
+    size: u64,
+    modified: u64,
+}
+
+impl VerifiedLibrary {
+    fn of(library_path: &Path, sha1: &str) -> Option<Self> {
+        let metadata = std::fs::metadata(library_path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(VerifiedLibrary { sha1: sha1.to_string(), size: metadata.len(), modified })
+    }
+}
+
+///
+/// Libraries whose sha1 has already been verified for a version, so they don't have to be hashed again on every launch.
+/// An entry is only trusted as long as size and modification time of the file are unchanged.
+///
+pub struct VerifiedLibraries {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, VerifiedLibrary>>,
+}
+
+impl VerifiedLibraries {
+    pub async fn load(libraries_folder: &Path, version_id: &str) -> Self {
+        let path = libraries_folder.join(".verified").join(format!("{}.json", version_id));
+        let entries = match fs::read(&path).await {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        };
+
+        VerifiedLibraries { path, entries: Mutex::new(entries) }
+    }
+
+    pub fn is_verified(&self, key: &str, library_path: &Path, sha1: &str) -> bool {
+        let Some(current) = VerifiedLibrary::of(library_path, sha1) else { return false };
+        self.entries.lock().unwrap().get(key) == Some(&current)
+    }
+
+    pub fn mark_verified(&self, key: &str, library_path: &Path, sha1: &str) {
+        if let Some(verified) = VerifiedLibrary::of(library_path, sha1) {
+            self.entries.lock().unwrap().insert(key.to_string(), verified);
+        }
+    }
+
+    pub async fn store(&self) {
+        let data = match serde_json::to_vec(&*self.entries.lock().unwrap()) {
+            Ok(data) => data,
+            Err(err) => {
+                error!("Failed to serialize verified libraries: {:?}", err);
+                return;
+            }
+        };
+
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent).await;
+        }
+        match fs::write(&self.path, data).await {
+            Ok(_) => debug!("Stored verified libraries to {:?}", self.path),
+            Err(err) => error!("Failed to store verified libraries: {:?}", err),
+        }
+    }
+}
@@ -1,7 +1,20 @@
 pub mod launcher;
+pub mod fake_process;
+pub mod launch_hooks;
+pub mod launch_hotfixes;
 pub mod version;
 mod rule_interpreter;
 pub mod auth;
 pub mod prelauncher;
 pub mod progress;
-pub mod java;
\ No newline at end of file
+pub mod java;
+pub mod library_cache;
+pub mod natives;
+pub mod config_history;
+pub mod mod_configs;
+pub mod mod_store;
+pub mod mod_sync;
+pub mod install_size;
+pub mod integrity;
+pub mod install_manifest;
+pub mod install_state;
@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use log::{debug, error, info};
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+use tokio::fs;
+use walkdir::WalkDir;
+
+/// Copies of the configs as the mods first generated them
+const DEFAULTS_FOLDER: &str = ".config_defaults";
+/// Configs that were replaced by a reset, one folder per reset
+const BACKUPS_FOLDER: &str = ".config_backups";
+
+/// Diffs that take longer than this are reported less precisely
+const DIFF_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How often the config directory is checked for generated configs while the game is loading
+const GENERATED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Generated configs that didn't change for this long are considered completely written
+const GENERATED_SETTLE_TIME: Duration = Duration::from_secs(10);
+/// Mods generate their configs while the game loads, configs that still change this long after the first one showed up
+/// may already be changed by the user
+const GENERATED_WATCH_LIMIT: Duration = Duration::from_secs(300);
+
+///
+/// Config files in the `config/` directory of a game directory which belong to the same mod
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct ModConfig {
+    #[serde(rename = "mod")]
+    pub mod_id: String,
+    pub files: Vec<String>,
+    /// Whether any file differs from the default the mod generated
+    pub modified: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigLineChange {
+    pub kind: ConfigLineChangeKind,
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub enum ConfigLineChangeKind {
+    #[serde(rename = "added")]
+    Added,
+    #[serde(rename = "removed")]
+    Removed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigFileDiff {
+    pub file: String,
+    /// `false` if there is no default for this file to compare against
+    #[serde(rename = "hasDefault")]
+    pub has_default: bool,
+    pub changes: Vec<ConfigLineChange>,
+}
+
+/// Mod a config file belongs to, e.g. `sodium` for `sodium-options.json` or `fabric` for `fabric/indigo-renderer.properties`
+fn mod_id(relative_path: &Path) -> String {
+    let first = relative_path.components().next().map(|component| component.as_os_str().to_string_lossy().to_string()).unwrap_or_default();
+    first.split(|c| c == '-' || c == '_' || c == '.').next().unwrap_or(&first).to_lowercase()
+}
+
+fn config_files(config_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(config_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(config_dir).ok().map(Path::to_path_buf))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Configs that are in the game dir before a launch, see `snapshot_generated`
+pub fn existing_configs(game_dir: &Path) -> HashSet<PathBuf> {
+    config_files(&game_dir.join("config")).into_iter().collect()
+}
+
+/// Remembers the configs the mods generated during a launch as their defaults.
+/// Configs that were there before the launch may already be changed by the user, they never become defaults.
+pub async fn snapshot_generated(game_dir: &Path, existing: &HashSet<PathBuf>) -> Result<()> {
+    let config_dir = game_dir.join("config");
+    let defaults_dir = game_dir.join(DEFAULTS_FOLDER);
+
+    for file in config_files(&config_dir).into_iter().filter(|file| !existing.contains(file)) {
+        let default = defaults_dir.join(&file);
+        if default.exists() {
+            continue;
+        }
+        fs::create_dir_all(default.parent().unwrap()).await?;
+        fs::copy(config_dir.join(&file), &default).await?;
+        debug!("Stored default config {:?}", file);
+    }
+    Ok(())
+}
+
+/// Waits until the mods of a running game generated their configs and remembers them as defaults, before the user
+/// gets to change them. Meant to run next to the install and the game, the launcher may exit together with the game.
+pub async fn snapshot_when_generated(game_dir: PathBuf, existing: HashSet<PathBuf>) {
+    let config_dir = game_dir.join("config");
+    let mut first_generated: Option<Instant> = None;
+    let mut generated = BTreeMap::new();
+    let mut unchanged_since = Instant::now();
+
+    while first_generated.map_or(true, |first_generated| first_generated.elapsed() < GENERATED_WATCH_LIMIT) {
+        tokio::time::sleep(GENERATED_POLL_INTERVAL).await;
+        let current: BTreeMap<PathBuf, u64> = config_files(&config_dir)
+            .into_iter()
+            .filter(|file| !existing.contains(file))
+            .map(|file| {
+                let size = std::fs::metadata(config_dir.join(&file)).map(|metadata| metadata.len()).unwrap_or_default();
+                (file, size)
+            })
+            .collect();
+        if current != generated {
+            first_generated.get_or_insert_with(Instant::now);
+            generated = current;
+            unchanged_since = Instant::now();
+        } else if !generated.is_empty() && unchanged_since.elapsed() >= GENERATED_SETTLE_TIME {
+            break;
+        }
+    }
+
+    if let Err(err) = snapshot_generated(&game_dir, &existing).await {
+        error!("Failed to store default configs: {:?}", err);
+    }
+}
+
+/// Remembers the configs a modpack ships as the defaults of their mods, replacing older defaults
+pub async fn store_shipped_defaults(shipped_config_dir: &Path, game_dir: &Path) -> Result<()> {
+    let defaults_dir = game_dir.join(DEFAULTS_FOLDER);
+
+    for file in config_files(shipped_config_dir) {
+        let default = defaults_dir.join(&file);
+        fs::create_dir_all(default.parent().unwrap()).await?;
+        fs::copy(shipped_config_dir.join(&file), &default).await?;
+    }
+    Ok(())
+}
+
+pub async fn list(game_dir: &Path) -> Result<Vec<ModConfig>> {
+    let config_dir = game_dir.join("config");
+    let defaults_dir = game_dir.join(DEFAULTS_FOLDER);
+
+    let mut configs: BTreeMap<String, ModConfig> = BTreeMap::new();
+    for file in config_files(&config_dir) {
+        let default = defaults_dir.join(&file);
+        let modified = !default.exists() || fs::read(&default).await? != fs::read(config_dir.join(&file)).await?;
+
+        let mod_id = mod_id(&file);
+        let config = configs.entry(mod_id.clone()).or_insert_with(|| ModConfig { mod_id, files: Vec::new(), modified: false });
+        config.files.push(file.to_string_lossy().replace('\\', "/"));
+        config.modified |= modified;
+    }
+    Ok(configs.into_values().collect())
+}
+
+/// Line based diff between the default and the current version of every config file of a mod
+pub async fn diff(game_dir: &Path, mod_id: &str) -> Result<Vec<ConfigFileDiff>> {
+    let config_dir = game_dir.join("config");
+    let defaults_dir = game_dir.join(DEFAULTS_FOLDER);
+
+    let mut diffs = Vec::new();
+    for file in config_files(&config_dir).into_iter().filter(|file| self::mod_id(file) == mod_id) {
+        let current = String::from_utf8_lossy(&fs::read(config_dir.join(&file)).await?).to_string();
+        let default = defaults_dir.join(&file);
+        let (has_default, default) = match fs::read(&default).await {
+            Ok(default) => (true, String::from_utf8_lossy(&default).to_string()),
+            Err(_) => (false, String::new()),
+        };
+
+        diffs.push(ConfigFileDiff {
+            file: file.to_string_lossy().replace('\\', "/"),
+            has_default,
+            changes: diff_lines(&default, &current),
+        });
+    }
+    Ok(diffs)
+}
+
+fn diff_lines(old: &str, new: &str) -> Vec<ConfigLineChange> {
+    TextDiff::configure()
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(old, new)
+        .iter_all_changes()
+        .filter_map(|change| {
+            let (kind, index) = match change.tag() {
+                ChangeTag::Insert => (ConfigLineChangeKind::Added, change.new_index()?),
+                ChangeTag::Delete => (ConfigLineChangeKind::Removed, change.old_index()?),
+                ChangeTag::Equal => return None,
+            };
+            Some(ConfigLineChange { kind, line: index + 1, text: change.value().trim_end_matches(|c| c == '\r' || c == '\n').to_string() })
+        })
+        .collect()
+}
+
+/// Restores the default configs of a mod after backing up the current ones.
+/// Files without a default are removed, so the mod generates them again. Returns the backup folder.
+pub async fn reset(game_dir: &Path, mod_id: &str) -> Result<PathBuf> {
+    let config_dir = game_dir.join("config");
+    let defaults_dir = game_dir.join(DEFAULTS_FOLDER);
+    let backup_dir = game_dir.join(BACKUPS_FOLDER).join(format!("{}-{}", mod_id, Utc::now().format("%Y-%m-%d_%H-%M-%S")));
+
+    let files: Vec<PathBuf> = config_files(&config_dir).into_iter().filter(|file| self::mod_id(file) == mod_id).collect();
+    if files.is_empty() {
+        bail!("there are no configs of {}", mod_id);
+    }
+
+    for file in &files {
+        let backup = backup_dir.join(file);
+        fs::create_dir_all(backup.parent().unwrap()).await?;
+        fs::copy(config_dir.join(file), &backup).await?;
+    }
+
+    for file in &files {
+        let default = defaults_dir.join(file);
+        if default.exists() {
+            fs::copy(&default, config_dir.join(file)).await?;
+        } else {
+            fs::remove_file(config_dir.join(file)).await?;
+        }
+    }
+
+    info!("Reset configs of {}, backup is at {:?}", mod_id, backup_dir);
+    Ok(backup_dir)
+}
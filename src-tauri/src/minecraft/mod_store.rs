@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::Result;
+use chrono::Utc;
+use log::debug;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tokio::sync::Mutex;
+
+use crate::utils::sha1sum;
+
+/// Written to the mod cache, maps the sha1 of every cached mod to where it came from
+const INDEX_FILE: &str = "index.json";
+
+///
+/// Where a cached mod came from
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModCacheEntry {
+    /// Repository of the pack the mod was downloaded from
+    pub source: String,
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    /// Relative to the mod cache
+    pub path: String,
+    #[serde(rename = "lastUsed")]
+    pub last_used: i64,
+}
+
+///
+/// Index of the mod cache by sha1, so mods are found by their hash instead of their exact file name
+///
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ModCacheIndex {
+    #[serde(default)]
+    pub entries: HashMap<String, ModCacheEntry>,
+}
+
+impl ModCacheIndex {
+    /// Index of the mod cache, empty if it doesn't have one yet
+    pub async fn load(mod_cache: &Path) -> Self {
+        let Ok(content) = fs::read(mod_cache.join(INDEX_FILE)).await else { return Self::default() };
+        serde_json::from_slice(&content).unwrap_or_else(|err| {
+            debug!("Ignoring broken mod cache index: {:?}", err);
+            Self::default()
+        })
+    }
+
+    /// Written to a temporary file first, an interrupted write doesn't lose the whole index
+    pub async fn store(&self, mod_cache: &Path) -> Result<()> {
+        fs::create_dir_all(mod_cache).await?;
+        let temp_path = mod_cache.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&temp_path, serde_json::to_vec_pretty(self)?).await?;
+        fs::rename(&temp_path, mod_cache.join(INDEX_FILE)).await?;
+        Ok(())
+    }
+
+    /// Adds entries, an entry of the same hash is only replaced by one used more recently
+    fn merge(&mut self, entries: HashMap<String, ModCacheEntry>) {
+        for (sha1, entry) in entries {
+            match self.entries.get(&sha1) {
+                Some(existing) if existing.last_used > entry.last_used => {}
+                _ => { self.entries.insert(sha1, entry); }
+            }
+        }
+    }
+}
+
+/// Size and modification time of a file, a hash recorded with them is valid as long as they don't change
+pub fn hash_stamp(metadata: &std::fs::Metadata) -> Result<String> {
+    let modified = metadata.modified()?.duration_since(UNIX_EPOCH).map(|modified| modified.as_millis()).unwrap_or_default();
+    Ok(format!("{} {}", metadata.len(), modified))
+}
+
+/// Held while the index file is read and written, launches running at the same time would otherwise drop each other's entries
+static INDEX_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+///
+/// Content-addressed storage for mod jars. Every profile links to the same stored file
+/// instead of keeping its own copy, which saves a lot of space with many similar profiles.
+///
+pub struct ModStore {
+    mod_cache: PathBuf,
+    objects: PathBuf,
+    link: bool,
+    index: Mutex<ModCacheIndex>,
+    /// Entries recorded by this launch, merged into the index file by `flush`
+    recorded: Mutex<HashMap<String, ModCacheEntry>>,
+}
+
+impl ModStore {
+    pub async fn load(data: &Path, link: bool) -> Self {
+        let mod_cache = data.join("mod_cache");
+        let index = ModCacheIndex::load(&mod_cache).await;
+        ModStore { objects: mod_cache.join(".objects"), mod_cache, link, index: Mutex::new(index), recorded: Mutex::new(HashMap::new()) }
+    }
+
+    /// Cached file with the given sha1 and its hash, for mods whose download path changed since they were cached.
+    /// Without a known sha1 a cached file of the same project version is used. The hash is the one the file was
+    /// recorded with, callers verify their copy against it.
+    pub async fn find(&self, sha1: Option<&str>, project_id: &str, version_id: &str) -> Option<(String, PathBuf)> {
+        let index = self.index.lock().await;
+        match sha1 {
+            Some(sha1) => {
+                let sha1 = sha1.to_lowercase();
+                let path = self.mod_cache.join(&index.entries.get(&sha1)?.path);
+                path.exists().then_some((sha1, path))
+            }
+            None => index.entries.iter()
+                .filter(|(_, entry)| entry.project_id.eq_ignore_ascii_case(project_id) && entry.version_id == version_id)
+                .map(|(sha1, entry)| (sha1.clone(), self.mod_cache.join(&entry.path)))
+                .find(|(_, path)| path.exists()),
+        }
+    }
+
+    /// Records where a cached mod came from and that it was just used
+    pub async fn record(&self, sha1: &str, source: String, project_id: String, version_id: String, path: &Path) {
+        let Ok(relative) = path.strip_prefix(&self.mod_cache) else { return };
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let entry = ModCacheEntry {
+            source,
+            project_id,
+            version_id,
+            file_name,
+            path: relative.to_string_lossy().replace('\\', "/"),
+            last_used: Utc::now().timestamp(),
+        };
+        self.index.lock().await.entries.insert(sha1.to_string(), entry.clone());
+        self.recorded.lock().await.insert(sha1.to_string(), entry);
+    }
+
+    /// Merges the recorded entries into the index of the mod cache as it is on disk now
+    pub async fn flush(&self) -> Result<()> {
+        let _lock = INDEX_LOCK.lock().await;
+        let mut index = ModCacheIndex::load(&self.mod_cache).await;
+        index.merge(self.recorded.lock().await.clone());
+        index.store(&self.mod_cache).await
+    }
+
+    /// Sha1 of a cached file, remembered next to it with the size and modification time it was hashed at.
+    /// A file that changed since then is hashed again, so a corrupted cache file doesn't keep its old hash.
+    pub async fn hash(path: &Path) -> Result<String> {
+        let hash_path = PathBuf::from(format!("{}.sha1", path.to_string_lossy()));
+        let stamp = hash_stamp(&fs::metadata(path).await?)?;
+        if let Ok(content) = fs::read_to_string(&hash_path).await {
+            if let Some((hash, recorded)) = content.trim().split_once(' ') {
+                if recorded == stamp {
+                    return Ok(hash.to_string());
+                }
+            }
+        }
+
+        let hash = sha1sum(&path.to_path_buf())?;
+        fs::write(&hash_path, format!("{} {}", hash, stamp)).await?;
+        Ok(hash)
+    }
+
+    /// Adds a cached file to the store and returns the path of the stored object.
+    /// Objects are read-only, a profile editing a linked mod in place would change it for every profile.
+    async fn store(&self, path: &Path) -> Result<PathBuf> {
+        let hash = Self::hash(path).await?;
+        let object = self.objects.join(&hash[..2]).join(format!("{}.jar", hash));
+        if !object.exists() {
+            fs::create_dir_all(object.parent().unwrap()).await?;
+            if fs::hard_link(path, &object).await.is_err() {
+                fs::copy(path, &object).await?;
+            }
+        }
+        let mut permissions = fs::metadata(&object).await?.permissions();
+        if !permissions.readonly() {
+            permissions.set_readonly(true);
+            fs::set_permissions(&object, permissions).await?;
+        }
+        Ok(object)
+    }
+
+    /// Removes a mod placed by `install`. Windows refuses to delete read-only files, which linked mods are.
+    pub async fn remove(path: &Path) -> Result<()> {
+        #[cfg(windows)]
+        {
+            if let Ok(metadata) = fs::symlink_metadata(path).await {
+                if metadata.is_file() && metadata.permissions().readonly() {
+                    let mut permissions = metadata.permissions();
+                    #[allow(clippy::permissions_set_readonly_false)]
+                    permissions.set_readonly(false);
+                    fs::set_permissions(path, permissions).await?;
+                }
+            }
+        }
+        fs::remove_file(path).await?;
+        Ok(())
+    }
+
+    /// Places a cached mod at the target path. It is hardlinked (or symlinked) to the stored object when linking
+    /// is enabled and the link is read-only. Otherwise, and whenever the file system doesn't support linking, the mod is copied.
+    pub async fn install(&self, path: &Path, target: &Path) -> Result<()> {
+        if target.symlink_metadata().is_ok() {
+            Self::remove(target).await?;
+        }
+
+        if self.link {
+            match self.link_object(path, target).await {
+                Ok(()) if fs::metadata(target).await.map_or(false, |metadata| metadata.permissions().readonly()) => return Ok(()),
+                Ok(()) => {
+                    debug!("Linked {:?} is writable, copying it instead", target);
+                    Self::remove(target).await?;
+                }
+                Err(err) => debug!("Unable to link {:?}, copying it instead: {:?}", target, err),
+            }
+        }
+
+        fs::copy(path, target).await?;
+        // copies take over the permissions of the read-only cache file, but belong to the profile alone
+        let mut permissions = fs::metadata(target).await?.permissions();
+        if permissions.readonly() {
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            fs::set_permissions(target, permissions).await?;
+        }
+        Ok(())
+    }
+
+    async fn link_object(&self, path: &Path, target: &Path) -> Result<()> {
+        let object = self.store(path).await?;
+        if fs::hard_link(&object, target).await.is_ok() {
+            return Ok(());
+        }
+
+        #[cfg(unix)]
+        fs::symlink(&object, target).await?;
+        #[cfg(windows)]
+        fs::symlink_file(&object, target).await?;
+        Ok(())
+    }
+}
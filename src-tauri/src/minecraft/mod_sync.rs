@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::Utc;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::minecraft::mod_store::ModStore;
+
+const REPORT_FILE: &str = "mod_sync_report.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SyncAction {
+    #[serde(rename = "added")]
+    Added,
+    #[serde(rename = "removed")]
+    Removed,
+    #[serde(rename = "kept")]
+    Kept,
+    #[serde(rename = "skipped")]
+    Skipped,
+}
+
+///
+/// Why a file was (or wasn't) placed into the mods folder
+///
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SyncReason {
+    /// Part of the NoRiskClient pack of the branch
+    #[serde(rename = "pack")]
+    Pack,
+    /// Added to the profile by the user
+    #[serde(rename = "profile")]
+    Profile,
+    /// Dropped into the custom mods folder
+    #[serde(rename = "custom")]
+    Custom,
+    /// Optional mod that is disabled in the profile
+    #[serde(rename = "disabled")]
+    Disabled,
+    /// Pack mod replaced by a mod of the profile
+    #[serde(rename = "replaced")]
+    Replaced,
+    /// Same mod was already installed from another source
+    #[serde(rename = "duplicate")]
+    Duplicate,
+    /// Profile mod that would override a mod the pack requires, like the anticheat relevant NoRisk mods
+    #[serde(rename = "required")]
+    Required,
+    /// Not part of the last sync anymore
+    #[serde(rename = "outdated")]
+    Outdated,
+    /// Put into the mods folder by the user, the launcher never deletes these
+    #[serde(rename = "unknown")]
+    Unknown,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModSyncEntry {
+    pub file: String,
+    pub action: SyncAction,
+    pub reason: SyncReason,
+}
+
+///
+/// What the last launch did to the mods folder, to help finding out why a mod is missing in game
+///
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ModSyncReport {
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "mcVersion", default)]
+    pub mc_version: String,
+    pub entries: Vec<ModSyncEntry>,
+}
+
+impl ModSyncReport {
+    fn path(game_dir: &Path) -> PathBuf {
+        game_dir.join(REPORT_FILE)
+    }
+
+    pub async fn load(game_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path(game_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&fs::read(path).await?)?))
+    }
+
+    /// Files the launcher placed into the mods folder itself
+    pub fn managed_files(&self) -> HashSet<&str> {
+        self.entries.iter()
+            .filter(|entry| matches!(entry.action, SyncAction::Added | SyncAction::Kept) && entry.reason != SyncReason::Unknown)
+            .map(|entry| entry.file.as_str())
+            .collect()
+    }
+
+    pub fn unknown_files(&self) -> Vec<&str> {
+        self.entries.iter()
+            .filter(|entry| entry.reason == SyncReason::Unknown)
+            .map(|entry| entry.file.as_str())
+            .collect()
+    }
+}
+
+/// Moves the unknown jars of the last sync from the mods folder into the custom mods folder,
+/// so they are managed by the launcher from now on. Returns the names of the adopted files.
+pub async fn adopt_unknown_mods(report: &ModSyncReport, game_dir: &Path, custom_mods_dir: &Path) -> Result<Vec<String>> {
+    let mods_dir = game_dir.join("mods");
+    fs::create_dir_all(custom_mods_dir).await?;
+
+    let mut adopted = Vec::new();
+    for file in report.unknown_files() {
+        if !file.ends_with(".jar") || !mods_dir.join(file).exists() {
+            continue;
+        }
+        if adopt_file(&mods_dir, file, custom_mods_dir).await? {
+            adopted.push(file.to_string());
+        }
+    }
+    Ok(adopted)
+}
+
+/// Moves a single file from the mods folder into the custom mods folder, unless there already is a custom mod with its name
+pub async fn adopt_file(mods_dir: &Path, file: &str, custom_mods_dir: &Path) -> Result<bool> {
+    let source = mods_dir.join(file);
+    let target = custom_mods_dir.join(file);
+    if target.exists() {
+        debug!("Not adopting {}, there already is a custom mod with this name", file);
+        return Ok(false);
+    }
+
+    fs::create_dir_all(custom_mods_dir).await?;
+    if fs::rename(&source, &target).await.is_err() {
+        // the custom mods folder might be on another drive
+        fs::copy(&source, &target).await?;
+        ModStore::remove(&source).await?;
+    }
+    info!("Adopted {} as custom mod", file);
+    Ok(true)
+}
+
+///
+/// Collects the changes to the mods folder while mods are being synced
+///
+#[derive(Default)]
+pub struct ModSyncRecorder {
+    previous: HashSet<String>,
+    installed: Vec<(String, SyncReason)>,
+    skipped: Vec<(String, SyncReason)>,
+    unknown: Vec<String>,
+}
+
+impl ModSyncRecorder {
+    /// Remembers the files that were in the mods folder before it got cleared
+    pub fn previous(&mut self, file: String) {
+        self.previous.insert(file);
+    }
+
+    pub fn installed(&mut self, file: String, reason: SyncReason) {
+        self.installed.push((file, reason));
+    }
+
+    pub fn skipped(&mut self, file: String, reason: SyncReason) {
+        self.skipped.push((file, reason));
+    }
+
+    /// Remembers a file the user put into the mods folder, which was left untouched
+    pub fn unknown(&mut self, file: String) {
+        self.unknown.push(file);
+    }
+
+    pub async fn save(self, game_dir: &Path, mc_version: &str) -> Result<()> {
+        let installed_files: HashSet<&String> = self.installed.iter().map(|(file, _)| file).collect();
+
+        let mut entries: Vec<ModSyncEntry> = self.installed.iter().map(|(file, reason)| ModSyncEntry {
+            file: file.clone(),
+            action: if self.previous.contains(file) { SyncAction::Kept } else { SyncAction::Added },
+            reason: *reason,
+        }).collect();
+        // files that were skipped this time got removed for the same reason
+        entries.extend(self.previous.iter().filter(|file| !installed_files.contains(file)).map(|file| ModSyncEntry {
+            file: file.clone(),
+            action: SyncAction::Removed,
+            reason: self.skipped.iter().find(|(skipped, _)| skipped == file).map_or(SyncReason::Outdated, |(_, reason)| *reason),
+        }));
+        entries.extend(self.skipped.iter().filter(|(file, _)| !self.previous.contains(file)).map(|(file, reason)| ModSyncEntry {
+            file: file.clone(),
+            action: SyncAction::Skipped,
+            reason: *reason,
+        }));
+
+        entries.extend(self.unknown.iter().filter(|file| !installed_files.contains(file)).map(|file| ModSyncEntry {
+            file: file.clone(),
+            action: SyncAction::Kept,
+            reason: SyncReason::Unknown,
+        }));
+
+        let report = ModSyncReport { created_at: Utc::now().timestamp(), mc_version: mc_version.to_string(), entries };
+        fs::write(ModSyncReport::path(game_dir), serde_json::to_string_pretty(&report)?).await?;
+        Ok(())
+    }
+}
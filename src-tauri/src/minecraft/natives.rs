@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use tokio::fs::{self, OpenOptions};
+use walkdir::WalkDir;
+
+use crate::utils::{clear_quarantine, sha1sum, zip_extract};
+
+/// Natives extracted once per native jar, named by the sha1 of the jar
+const CACHE_FOLDER: &str = "cache";
+
+/// Lists the cache entries a version folder was linked from
+const SOURCES_FILE: &str = ".sources";
+
+/// Natives folder of a version, the game loads its natives from here
+pub fn version_folder(data: &Path, version_id: &str) -> PathBuf {
+    data.join("natives").join(version_id)
+}
+
+fn cache_folder(data: &Path) -> PathBuf {
+    data.join("natives").join(CACHE_FOLDER)
+}
+
+/// Extracts a native jar into the cache unless a jar with the same content was extracted before, returns its sha1
+pub async fn extract_cached(data: &Path, jar: &Path) -> Result<String> {
+    let sha1 = sha1sum(&jar.to_path_buf())?;
+    let cached = cache_folder(data).join(&sha1);
+    if cached.exists() {
+        return Ok(sha1);
+    }
+
+    // extracted next to the cache entry first, so an interrupted extraction never looks complete
+    let partial = cache_folder(data).join(format!("{}.partial", sha1));
+    if partial.exists() {
+        fs::remove_dir_all(&partial).await?;
+    }
+    fs::create_dir_all(&partial).await?;
+    info!("Natives zip extract: {:?}", jar);
+    zip_extract(OpenOptions::new().read(true).open(jar).await?, &partial).await?;
+    // only once per extraction, the links of the version folders share the cleared and signed files
+    clear_quarantine(&partial).await;
+    fs::rename(&partial, &cached).await?;
+    Ok(sha1)
+}
+
+/// Hard links a file, copying it if the file system doesn't support links
+async fn link_or_copy(source: &Path, target: &Path) -> Result<()> {
+    if fs::hard_link(source, target).await.is_err() {
+        fs::copy(source, target).await?;
+    }
+    Ok(())
+}
+
+/// Fills the natives folder of the version with links to the cached natives of its jars
+pub async fn link_version(data: &Path, version_id: &str, sources: &[String]) -> Result<PathBuf> {
+    let version_folder = version_folder(data, version_id);
+    if version_folder.exists() {
+        fs::remove_dir_all(&version_folder).await?;
+    }
+    fs::create_dir_all(&version_folder).await?;
+
+    for sha1 in sources {
+        let cached = cache_folder(data).join(sha1);
+        for entry in WalkDir::new(&cached).into_iter().filter_map(|entry| entry.ok()).filter(|entry| entry.file_type().is_file()) {
+            let target = version_folder.join(entry.path().strip_prefix(&cached)?);
+            fs::create_dir_all(target.parent().unwrap()).await?;
+            // jars may contain the same file, the last one wins like it did when all jars were extracted into one folder
+            if target.exists() {
+                fs::remove_file(&target).await?;
+            }
+            link_or_copy(entry.path(), &target).await?;
+        }
+    }
+    fs::write(version_folder.join(SOURCES_FILE), sources.join("\n")).await?;
+    Ok(version_folder)
+}
+
+/// Deletes the natives of versions that were removed and the cache entries no version links to anymore.
+/// Natives extracted by launchers before the cache existed lie loose in the natives folder and are deleted as well.
+/// Anything changed after `started_at` may belong to a launch that is linking its natives right now, it is left alone,
+/// and the cache isn't cleaned up at all then since that launch's `.sources` may not be written yet.
+pub async fn remove_stale(data: &Path, started_at: SystemTime) -> Result<()> {
+    let natives_folder = data.join("natives");
+    let versions_folder = data.join("versions");
+    let mut used = Vec::new();
+    let mut installing = false;
+
+    let mut entries = fs::read_dir(&natives_folder).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == CACHE_FOLDER {
+            continue;
+        }
+        let path = entry.path();
+        if entry.metadata().await?.modified().map_or(true, |modified| modified >= started_at) {
+            debug!("Keeping natives {:?}, they are being installed", path);
+            installing = true;
+            continue;
+        }
+        let is_version = entry.file_type().await?.is_dir() && path.join(SOURCES_FILE).exists();
+        if is_version && versions_folder.join(&name).exists() {
+            let sources = fs::read_to_string(path.join(SOURCES_FILE)).await.unwrap_or_default();
+            used.extend(sources.lines().map(str::to_string));
+            continue;
+        }
+
+        debug!("Removing stale natives {:?}", path);
+        let removed = if entry.file_type().await?.is_dir() { fs::remove_dir_all(&path).await } else { fs::remove_file(&path).await };
+        if let Err(err) = removed {
+            warn!("Failed to remove stale natives {:?}: {:?}", path, err);
+        }
+    }
+    if installing {
+        return Ok(());
+    }
+
+    let Ok(mut entries) = fs::read_dir(cache_folder(data)).await else { return Ok(()) };
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // extracted for a launch that hasn't created its version folder yet
+        let extracting = entry.metadata().await?.modified().map_or(true, |modified| modified >= started_at);
+        if !used.contains(&name) && !extracting {
+            debug!("Removing unused cached natives {}", name);
+            if let Err(err) = fs::remove_dir_all(entry.path()).await {
+                warn!("Failed to remove cached natives {:?}: {:?}", entry.path(), err);
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,43 +1,100 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{Mutex, Arc};
 
-use anyhow::{Ok, Result};
-use log::{debug, info};
+use anyhow::{bail, Ok, Result};
+use log::{debug, error, info, warn};
+use once_cell::sync::Lazy;
 use tokio::fs;
 
 use crate::app::api::{LoaderSubsystem, ModSource, LoaderMod, NoRiskLaunchManifest};
+use crate::app::content_blacklist::{self, ContentBlacklist};
 use crate::app::modrinth_api::{Datapack, ResourcePack, Shader};
+use crate::app::profile_import;
 use crate::error::LauncherError;
+use crate::minecraft::launch_hooks::{self, LaunchContext};
 use crate::minecraft::launcher;
 use crate::minecraft::launcher::{LauncherData, LaunchingParameter};
+use crate::minecraft::mod_configs;
+use crate::minecraft::mod_store::ModStore;
+use crate::minecraft::mod_sync::{self, ModSyncRecorder, ModSyncReport, SyncReason};
 use crate::minecraft::progress::{get_max, get_progress, ProgressReceiver, ProgressUpdate, ProgressUpdateSteps};
 use crate::minecraft::version::{VersionManifest, VersionProfile};
-use crate::utils::{download_file, get_maven_artifact_path};
+use crate::utils::{download_file, get_maven_artifact_path, track_downloads};
+
+/// Version profiles with their inherited version already merged in, by the url of their manifest
+static RESOLVED_VERSION_PROFILES: Lazy<Mutex<HashMap<String, VersionProfile>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
 ///
 /// Prelaunching client
 ///
 pub(crate) async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, launch_manifest: NoRiskLaunchManifest, launching_parameter: LaunchingParameter, additional_mods: Vec<LoaderMod>, shaders: Vec<Shader>, resourcepacks: Vec<ResourcePack>, datapacks: Vec<Datapack>, progress: LauncherData<D>, window: Arc<Mutex<tauri::Window>>) -> Result<()> {
-    info!("Loading minecraft version manifest...");
-    let mc_version_manifest = VersionManifest::download().await?;
-
-    let build = &launch_manifest.build;
-    let subsystem = &launch_manifest.subsystem;
+    track_downloads(install_and_launch(norisk_token, uuid, launch_manifest, launching_parameter, additional_mods, shaders, resourcepacks, datapacks, progress, window)).await
+}
 
+async fn install_and_launch<D: Send + Sync>(norisk_token: &str, uuid: &str, launch_manifest: NoRiskLaunchManifest, launching_parameter: LaunchingParameter, additional_mods: Vec<LoaderMod>, shaders: Vec<Shader>, resourcepacks: Vec<ResourcePack>, datapacks: Vec<Datapack>, progress: LauncherData<D>, window: Arc<Mutex<tauri::Window>>) -> Result<()> {
     progress.progress_update(ProgressUpdate::set_max());
     progress.progress_update(ProgressUpdate::SetProgress(0));
 
     let data_directory = launching_parameter.data_path.clone();
+    launch_hooks::pre_install(&LaunchContext::new(&data_directory, &launch_manifest, &launching_parameter)).await?;
+
+    // the files of the pack the profile was imported from
+    let game_dir = data_directory.join("gameDir").join(&launch_manifest.build.branch);
+    if let Some(profile_id) = &launching_parameter.profile_id {
+        profile_import::apply_overrides(&game_dir, profile_id).await?;
+    }
 
     // Copy retrieve and copy mods from manifest
-    clear_mods(&data_directory, &launch_manifest).await?;
-    retrieve_and_copy_mods(&data_directory, &launch_manifest, &launch_manifest.mods, &additional_mods, &progress).await?;
-    retrieve_and_copy_mods(&data_directory, &launch_manifest, &additional_mods, &additional_mods, &progress).await?;
-    retrieve_shaders(&data_directory, &launch_manifest, &shaders, &progress).await?;
-    retrieve_resourcepacks(&data_directory, &launch_manifest, &resourcepacks, &progress).await?;
-    retrieve_datapacks(&data_directory, &launch_manifest, &datapacks, &progress).await?;
+    let mut sync_recorder = ModSyncRecorder::default();
+    let synced: Result<()> = async {
+        clear_mods(&data_directory, &launch_manifest, launching_parameter.restricted_mode, &mut sync_recorder).await?;
+        let mod_store = ModStore::load(&data_directory, launching_parameter.link_mods).await;
+        let blacklist = ContentBlacklist::get().await;
+        // restricted mode only allows the mods of the NoRisk pack
+        let additional_mods = if launching_parameter.restricted_mode { Vec::new() } else { additional_mods };
+        let mut retrieved = retrieve_and_copy_mods(&data_directory, &launch_manifest, &launch_manifest.mods, &additional_mods, &mod_store, &blacklist, launching_parameter.ignore_content_blacklist, SyncReason::Pack, &mut sync_recorder, &progress).await;
+        if retrieved.is_ok() {
+            retrieved = retrieve_and_copy_mods(&data_directory, &launch_manifest, &additional_mods, &additional_mods, &mod_store, &blacklist, launching_parameter.ignore_content_blacklist, SyncReason::Profile, &mut sync_recorder, &progress).await;
+        }
+        // the mods cached before a failure are recorded as well
+        if let Err(err) = mod_store.flush().await {
+            error!("Failed to store mod cache index: {:?}", err);
+        }
+        retrieved?;
+        retrieve_shaders(&data_directory, &launch_manifest, &shaders, &progress).await?;
+        retrieve_resourcepacks(&data_directory, &launch_manifest, &resourcepacks, &progress).await?;
+        retrieve_datapacks(&data_directory, &launch_manifest, &datapacks, &progress).await?;
+
+        if !launching_parameter.restricted_mode {
+            copy_custom_mods(&data_directory, &launch_manifest, &mut sync_recorder, &progress).await?;
+        }
+        Ok(())
+    }.await;
+    // a failed sync is what the report is needed for the most, it's saved before the error is returned
+    if let Err(err) = sync_recorder.save(&game_dir, &launch_manifest.build.mc_version).await {
+        error!("Failed to save mod sync report: {:?}", err);
+    }
+    synced?;
+
+    let version = load_version_profile(&launch_manifest).await?;
+
+    info!("Launching {}...", launch_manifest.build.branch);
 
-    copy_custom_mods(&data_directory, &launch_manifest, &progress).await?;
+    // configs that show up while the game loads were just generated by their mods
+    let config_snapshot = tokio::spawn(mod_configs::snapshot_when_generated(game_dir.clone(), mod_configs::existing_configs(&game_dir)));
+    let result = launcher::launch(norisk_token, uuid, &data_directory, launch_manifest, version, launching_parameter, progress, window).await;
+    config_snapshot.abort();
+    result
+}
+
+///
+/// Loads the version profile of the launch manifest's loader merged with its inherited minecraft version.
+/// Resolved profiles are cached until `invalidate_version_profiles` is called.
+///
+pub(crate) async fn load_version_profile(launch_manifest: &NoRiskLaunchManifest) -> Result<VersionProfile> {
+    let build = &launch_manifest.build;
+    let subsystem = &launch_manifest.subsystem;
 
     info!("Loading version profile...");
     let manifest_url = match subsystem {
@@ -46,47 +103,75 @@ pub(crate) async fn launch<D: Send + Sync>(norisk_token: &str, uuid: &str, launc
             .replace("{FABRIC_LOADER_VERSION}", &build.fabric_loader_version),
         LoaderSubsystem::Forge { manifest, .. } => manifest.clone()
     };
+    if let Some(version) = RESOLVED_VERSION_PROFILES.lock().unwrap().get(&manifest_url) {
+        debug!("Using cached version profile {}", manifest_url);
+        return Ok(version.clone());
+    }
+
     let mut version = VersionProfile::load(&manifest_url).await?;
 
     if let Some(inherited_version) = &version.inherits_from {
-        let url = mc_version_manifest.versions
-            .iter()
-            .find(|x| &x.id == inherited_version)
-            .map(|x| &x.url)
-            .ok_or_else(|| LauncherError::InvalidVersionProfile(format!("unable to find inherited version manifest {}", inherited_version)))?;
+        info!("Loading minecraft version manifest...");
+        let url = VersionManifest::version_url(inherited_version).await?;
 
         debug!("Determined {}'s download url to be {}", inherited_version, url);
         info!("Downloading inherited version {}...", inherited_version);
 
-        let parent_version = VersionProfile::load(url).await?;
+        let parent_version = VersionProfile::load(&url).await?;
 
         version.merge(parent_version)?;
     }
 
-    info!("Launching {}...", launch_manifest.build.branch);
+    RESOLVED_VERSION_PROFILES.lock().unwrap().insert(manifest_url, version.clone());
+    Ok(version)
+}
 
-    launcher::launch(norisk_token, uuid, &data_directory, launch_manifest, version, launching_parameter, progress, window).await?;
-    Ok(())
+/// Drops the cached version profiles, so the next launch loads them again
+pub(crate) fn invalidate_version_profiles() {
+    RESOLVED_VERSION_PROFILES.lock().unwrap().clear();
 }
 
-pub(crate) async fn clear_mods(data: &Path, manifest: &NoRiskLaunchManifest) -> Result<()> {
+pub(crate) async fn clear_mods(data: &Path, manifest: &NoRiskLaunchManifest, restricted_mode: bool, sync_recorder: &mut ModSyncRecorder) -> Result<()> {
     let mods_path = data.join("gameDir").join(&manifest.build.branch).join("mods");
 
     if !mods_path.exists() {
         return Ok(());
     }
 
-    // Clear mods directory
+    // Files that the launcher didn't place there itself were added by the user and must not be deleted.
+    // Without a report of the last sync every file is assumed to be from the launcher.
+    let last_report = ModSyncReport::load(&data.join("gameDir").join(&manifest.build.branch)).await.unwrap_or_default();
+    let managed_files = last_report.as_ref().map(|report| report.managed_files());
+
+    // Clear mods directory, mods might be symlinks into the mod store
     let mut mods_read = fs::read_dir(&mods_path).await?;
     while let Some(entry) = mods_read.next_entry().await? {
-        if entry.file_type().await?.is_file() {
-            fs::remove_file(entry.path()).await?;
+        let file_type = entry.file_type().await?;
+        if file_type.is_file() || file_type.is_symlink() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if managed_files.as_ref().map_or(false, |managed_files| !managed_files.contains(file_name.as_str())) {
+                if restricted_mode {
+                    // moved out of the way instead of deleted, custom mods aren't loaded in restricted mode
+                    let custom_mods_path = data.join("custom_mods").join(format!("{}-{}", manifest.build.branch, manifest.build.mc_version));
+                    if mod_sync::adopt_file(&mods_path, &file_name, &custom_mods_path).await? {
+                        sync_recorder.previous(file_name);
+                        continue;
+                    }
+                    bail!("unable to remove {} from the mods folder, which is required in restricted mode", file_name);
+                }
+                info!("Keeping unknown file {} in mods folder", file_name);
+                sync_recorder.unknown(file_name);
+                continue;
+            }
+
+            sync_recorder.previous(file_name);
+            ModStore::remove(&entry.path()).await?;
         }
     }
     Ok(())
 }
 
-pub async fn retrieve_and_copy_mods(data: &Path, manifest: &NoRiskLaunchManifest, mods: &Vec<LoaderMod>, additional_mods: &Vec<LoaderMod>, progress: &impl ProgressReceiver) -> Result<()> {
+pub async fn retrieve_and_copy_mods(data: &Path, manifest: &NoRiskLaunchManifest, mods: &Vec<LoaderMod>, additional_mods: &Vec<LoaderMod>, mod_store: &ModStore, blacklist: &ContentBlacklist, ignore_blacklist: bool, reason: SyncReason, sync_recorder: &mut ModSyncRecorder, progress: &impl ProgressReceiver) -> Result<()> {
     let mod_cache_path = data.join("mod_cache");
     let mods_path = data.join("gameDir").join(&manifest.build.branch).join("mods");
 
@@ -99,8 +184,25 @@ pub async fn retrieve_and_copy_mods(data: &Path, manifest: &NoRiskLaunchManifest
     let max = get_max(mods.len());
 
     for (mod_idx, current_mod) in mods.iter().enumerate() {
+        let file_name = format!("{}.jar", current_mod.name.replace(".jar",""));
+
         // Skip mods that are not needed
-        if (!current_mod.required && !current_mod.enabled) || additional_mods.iter().any(|m| m.source.get_slug() == current_mod.source.get_slug() && m.source.get_repository() == "PLACEHOLDER") {
+        if !current_mod.required && !current_mod.enabled {
+            sync_recorder.skipped(file_name, SyncReason::Disabled);
+            continue;
+        }
+        if additional_mods.iter().any(|m| m.source.get_slug() == current_mod.source.get_slug() && m.source.get_repository() == "PLACEHOLDER") {
+            if current_mod.required {
+                warn!("{} is required by the pack and can't be disabled", current_mod.name);
+            } else {
+                sync_recorder.skipped(file_name, SyncReason::Replaced);
+                continue;
+            }
+        }
+        // required pack mods can't be overridden with another version by the profile
+        if reason == SyncReason::Profile && manifest.mods.iter().any(|pack_mod| pack_mod.required && pack_mod.is_same_slug(current_mod)) {
+            warn!("Skipping {} of the profile, the pack requires its own version", current_mod.name);
+            sync_recorder.skipped(file_name, SyncReason::Required);
             continue;
         }
 
@@ -111,6 +213,7 @@ pub async fn retrieve_and_copy_mods(data: &Path, manifest: &NoRiskLaunchManifest
                 return loader_mod.is_same_slug(current_mod);
             }).unwrap();
             info!("Skipping Mod {:?} cuz {:?} is already installed",current_mod,already_installed);
+            sync_recorder.skipped(file_name, SyncReason::Duplicate);
             continue;
         }
 
@@ -118,6 +221,20 @@ pub async fn retrieve_and_copy_mods(data: &Path, manifest: &NoRiskLaunchManifest
 
         let current_mod_path = mod_cache_path.join(current_mod.source.get_path()?);
 
+        // Reuse the same version cached under another path
+        if !current_mod_path.exists() {
+            if let Some((sha1, cached)) = mod_store.find(current_mod.sha1.as_deref(), &current_mod.source.get_slug(), &current_mod.source.get_version()).await {
+                debug!("Reusing cached {:?} for {}", cached, current_mod.name);
+                fs::create_dir_all(&current_mod_path.parent().unwrap()).await?;
+                fs::copy(&cached, &current_mod_path).await?;
+                // a cached file that changed since it was recorded is downloaded again
+                if ModStore::hash(&current_mod_path).await? != sha1 {
+                    warn!("Cached {:?} doesn't match its recorded sha1, downloading {} again", cached, current_mod.name);
+                    ModStore::remove(&current_mod_path).await?;
+                }
+            }
+        }
+
         // Do we need to download the mod?
         if !current_mod_path.exists() {
             // Make sure that the parent directory exists
@@ -144,8 +261,18 @@ pub async fn retrieve_and_copy_mods(data: &Path, manifest: &NoRiskLaunchManifest
             }
         }
 
-        // Copy the mod.
-        fs::copy(&current_mod_path, mods_path.join(format!("{}.jar", current_mod.name.replace(".jar","")))).await?;
+        // Refuse mods that are known to be broken or malicious
+        let sha1 = ModStore::hash(&current_mod_path).await?;
+        let ModSource::Repository { repository, .. } = &current_mod.source;
+        mod_store.record(&sha1, repository.clone(), current_mod.source.get_slug(), current_mod.source.get_version(), &current_mod_path).await;
+        let blocked = blacklist.find(Some(&sha1), &[&current_mod.source.get_slug()], &[&current_mod.source.get_version()]);
+        if let Some(err) = content_blacklist::refuse(blocked, &current_mod.name, ignore_blacklist) {
+            bail!(err);
+        }
+
+        // Link or copy the mod.
+        mod_store.install(&current_mod_path, &mods_path.join(&file_name)).await?;
+        sync_recorder.installed(file_name, reason);
 
         info!("Installed Mod {:?}",current_mod);
         installed_mods.push(current_mod.clone())
@@ -310,7 +437,7 @@ pub async fn retrieve_datapacks(data: &Path, manifest: &NoRiskLaunchManifest, da
     Ok(())
 }
 
-pub async fn copy_custom_mods(data: &Path, manifest: &NoRiskLaunchManifest, progress: &impl ProgressReceiver) -> Result<()> {
+pub async fn copy_custom_mods(data: &Path, manifest: &NoRiskLaunchManifest, sync_recorder: &mut ModSyncRecorder, progress: &impl ProgressReceiver) -> Result<()> {
     let mod_cache_path = data.join("custom_mods").join(format!("{}-{}", manifest.build.branch, manifest.build.mc_version));
     let mods_path = data.join("gameDir").join(&manifest.build.branch).join("mods");
 
@@ -323,6 +450,7 @@ pub async fn copy_custom_mods(data: &Path, manifest: &NoRiskLaunchManifest, prog
         if entry.file_type().await?.is_file() {
             progress.progress_update(ProgressUpdate::set_label(format!("Copied custom mod {}", entry.file_name().to_str().unwrap_or_default())));
             fs::copy(entry.path(), mods_path.join(entry.file_name())).await?;
+            sync_recorder.installed(entry.file_name().to_string_lossy().to_string(), SyncReason::Custom);
         }
     }
 
@@ -2,6 +2,8 @@ use core::convert::AsRef;
 
 use serde::Serialize;
 
+use crate::utils::DownloadBytes;
+
 #[derive(Debug)]
 pub enum ProgressUpdateSteps {
     DownloadNoRiskClientMods,
@@ -60,6 +62,8 @@ pub enum ProgressUpdate {
     SetProgress(u64),
     #[serde(rename = "label")] 
     SetLabel(String),
+    #[serde(rename = "bytes")]
+    SetBytes(DownloadBytes),
 }
 
 const PER_STEP: u64 = 1024;
@@ -2,19 +2,34 @@ use std::{collections::HashMap, fmt, marker::PhantomData, path::{Path, PathBuf},
 
 use anyhow::Result;
 use tokio::fs;
-use serde::{Deserialize, Deserializer, de::{self, MapAccess, Visitor}};
+use reqwest::StatusCode;
+use serde::{Deserialize, Deserializer, Serialize, de::{self, MapAccess, Visitor}};
+use sha1::{Digest, Sha1};
 use void::Void;
 use std::collections::HashSet;
-use crate::{error::LauncherError, HTTP_CLIENT, LAUNCHER_DIRECTORY, utils::{download_file_untracked, download_private_file_untracked, Architecture}};
+use crate::{error::LauncherError, HTTP_CLIENT, LAUNCHER_DIRECTORY, utils::{download_file_untracked, download_private_file_untracked, expect_downloads, lock_file, Architecture}};
 use crate::utils::{get_maven_artifact_path, sha1sum};
 use std::sync::Arc;
-use log::{debug, info};
-use crate::app::api::get_api_base;
-use crate::app::app_data::LauncherOptions;
+use log::{debug, info, warn};
+use crate::app::api::{get_api_base, is_unauthorized, SharedNoRiskToken};
 use crate::minecraft::launcher::LaunchingParameter;
+use crate::minecraft::library_cache::VerifiedLibraries;
 use crate::minecraft::progress::{ProgressReceiver, ProgressUpdate};
 
-// https://launchermeta.mojang.com/mc/game/version_manifest.json
+const VERSION_MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+
+/// The cached version manifest is used without asking Mojang for this long, then it's refreshed in the background
+const VERSION_MANIFEST_MAX_AGE_SECS: i64 = 10 * 60;
+
+///
+/// ETag and time of the last check of the cached version manifest
+///
+#[derive(Default, Serialize, Deserialize)]
+struct VersionManifestCacheInfo {
+    etag: Option<String>,
+    #[serde(rename = "checkedAt")]
+    checked_at: i64,
+}
 
 #[derive(Deserialize)]
 pub struct VersionManifest {
@@ -22,13 +37,81 @@ pub struct VersionManifest {
 }
 
 impl VersionManifest {
-    pub async fn download() -> Result<Self> {
-        let response = HTTP_CLIENT.get("https://launchermeta.mojang.com/mc/game/version_manifest.json")
-            .send().await?
-            .error_for_status()?;
-        let manifest = response.json::<VersionManifest>().await?;
+    fn cache_paths() -> (PathBuf, PathBuf) {
+        let cache_dir = LAUNCHER_DIRECTORY.cache_dir();
+        (cache_dir.join("version_manifest_v2.json"), cache_dir.join("version_manifest_v2.info.json"))
+    }
 
-        Ok(manifest)
+    async fn read_cache() -> Result<Self> {
+        let (manifest_path, _) = Self::cache_paths();
+        Ok(serde_json::from_slice(&fs::read(manifest_path).await?)?)
+    }
+
+    /// Asks Mojang for the manifest, only downloads it if it changed since the cached one
+    async fn refresh() -> Result<()> {
+        let (manifest_path, info_path) = Self::cache_paths();
+        let mut info = fs::read(&info_path).await.ok()
+            .and_then(|info| serde_json::from_slice::<VersionManifestCacheInfo>(&info).ok())
+            .filter(|_| manifest_path.exists())
+            .unwrap_or_default();
+
+        let mut request = HTTP_CLIENT.get(VERSION_MANIFEST_URL);
+        if let Some(etag) = &info.etag {
+            request = request.header("If-None-Match", etag);
+        }
+        let response = request.send().await?.error_for_status()?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            debug!("Version manifest is unchanged");
+        } else {
+            info.etag = response.headers().get("ETag").and_then(|etag| etag.to_str().ok()).map(str::to_string);
+            let manifest = response.bytes().await?;
+            // only cache what can be read later
+            serde_json::from_slice::<VersionManifest>(&manifest)?;
+            fs::create_dir_all(manifest_path.parent().unwrap()).await?;
+            fs::write(&manifest_path, &manifest).await?;
+            info!("Downloaded version manifest");
+        }
+        info.checked_at = chrono::Utc::now().timestamp();
+        fs::write(&info_path, serde_json::to_vec(&info)?).await?;
+        Ok(())
+    }
+
+    /// The cached version manifest, refreshed in the background once it's older than a few minutes.
+    /// Without a cache it's downloaded, and if Mojang can't be reached the cache is used no matter its age.
+    pub async fn load() -> Result<Self> {
+        let (_, info_path) = Self::cache_paths();
+        let checked_at = fs::read(&info_path).await.ok()
+            .and_then(|info| serde_json::from_slice::<VersionManifestCacheInfo>(&info).ok())
+            .map(|info| info.checked_at);
+
+        if let Ok(cached) = Self::read_cache().await {
+            if checked_at.map_or(true, |checked_at| chrono::Utc::now().timestamp() - checked_at > VERSION_MANIFEST_MAX_AGE_SECS) {
+                tokio::spawn(async {
+                    if let Err(err) = Self::refresh().await {
+                        warn!("Failed to refresh version manifest: {:?}", err);
+                    }
+                });
+            }
+            return Ok(cached);
+        }
+
+        Self::refresh().await?;
+        Self::read_cache().await
+    }
+
+    /// Download url of a version's profile. Versions missing from the cached manifest refresh it first,
+    /// they were probably released since it was downloaded.
+    pub async fn version_url(id: &str) -> Result<String> {
+        let find = |manifest: &VersionManifest| manifest.versions.iter().find(|version| version.id == id).map(|version| version.url.clone());
+        if let Some(url) = find(&Self::load().await?) {
+            return Ok(url);
+        }
+
+        if let Err(err) = Self::refresh().await {
+            warn!("Failed to refresh version manifest: {:?}", err);
+        }
+        find(&Self::read_cache().await?)
+            .ok_or_else(|| LauncherError::InvalidVersionProfile(format!("unable to find inherited version manifest {}", id)).into())
     }
 }
 
@@ -43,7 +126,7 @@ pub struct ManifestVersion {
     pub release_time: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct VersionProfile {
     pub id: String,
     #[serde(rename = "assetIndex")]
@@ -100,6 +183,38 @@ impl VersionProfile {
         Ok(())
     }
 
+    /// Libraries that apply to this system. When the loader and minecraft provide different versions of the same
+    /// maven artifact only the higher version is kept, having both on the class path causes `NoSuchMethodError`s.
+    pub(crate) fn applicable_libraries(&self, features: &HashSet<String>) -> Vec<Library> {
+        let mut libraries: Vec<Library> = Vec::new();
+
+        for library in &self.libraries {
+            if !crate::minecraft::rule_interpreter::check_condition(&library.rules, features).unwrap_or(false) {
+                continue;
+            }
+
+            let Some((key, version)) = library.coordinate() else {
+                libraries.push(library.clone());
+                continue;
+            };
+
+            match libraries.iter_mut().find(|existing| existing.natives.is_some() == library.natives.is_some() && existing.coordinate().map_or(false, |(existing_key, _)| existing_key == key)) {
+                Some(existing) => {
+                    let existing_version = existing.coordinate().map(|(_, version)| version).unwrap_or_default();
+                    if compare_versions(&version, &existing_version).is_gt() {
+                        info!("Dropping library {} in favor of {}", existing.name, library.name);
+                        *existing = library.clone();
+                    } else {
+                        info!("Dropping library {} in favor of {}", library.name, existing.name);
+                    }
+                }
+                None => libraries.push(library.clone()),
+            }
+        }
+
+        libraries
+    }
+
     fn merge_options<T>(a: &mut Option<T>, b: Option<T>) {
         if !a.is_some() {
             *a = b;
@@ -117,7 +232,7 @@ impl VersionProfile {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 #[serde(untagged)] // TODO: Might guess from minimum_launcher_version just to be sure.
 pub enum ArgumentDeclaration {
     /// V21 describes the new version json used by versions above 1.13.
@@ -129,6 +244,9 @@ pub enum ArgumentDeclaration {
 impl ArgumentDeclaration {
     pub(crate) fn add_jvm_args_to_vec(&self, norisk_token: &str, command_arguments: &mut Vec<String>, parameter: &LaunchingParameter, features: &HashSet<String>) -> Result<()> {
         command_arguments.push(format!("-Xmx{}M", parameter.memory));
+        if let Some(min_memory) = parameter.min_memory {
+            command_arguments.push(format!("-Xms{}M", min_memory));
+        }
         command_arguments.push("-XX:+UnlockExperimentalVMOptions".to_string());
         command_arguments.push("-XX:+UseG1GC".to_string());
         command_arguments.push("-XX:G1NewSizePercent=20".to_string());
@@ -194,28 +312,46 @@ impl ArgumentDeclaration {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct V14ArgumentDeclaration {
     #[serde(rename = "minecraftArguments")]
     pub minecraft_arguments: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct V21ArgumentDeclaration {
     pub arguments: Arguments,
 }
 
 impl VersionProfile {
+    /// Downloads the profile and caches it, the cached profile is used if it can't be downloaded
     pub async fn load(url: &String) -> Result<Self> {
-        dbg!(url);
-        Ok(HTTP_CLIENT.get(url).send().await?.error_for_status()?.json::<VersionProfile>().await?)
+        let cached = LAUNCHER_DIRECTORY.cache_dir().join("version_profiles").join(format!("{}.json", base16ct::lower::encode_string(&Sha1::digest(url.as_bytes()))));
+        let downloaded = async {
+            let profile = HTTP_CLIENT.get(url).send().await?.error_for_status()?.bytes().await?;
+            serde_json::from_slice::<VersionProfile>(&profile)?;
+            Ok::<_, anyhow::Error>(profile)
+        }.await;
+
+        match downloaded {
+            Ok(profile) => {
+                fs::create_dir_all(cached.parent().unwrap()).await?;
+                fs::write(&cached, &profile).await?;
+                Ok(serde_json::from_slice(&profile)?)
+            }
+            Err(err) if cached.exists() => {
+                warn!("Failed to download version profile {}, using the cached one: {:?}", url, err);
+                Ok(serde_json::from_slice(&fs::read(&cached).await?)?)
+            }
+            Err(err) => Err(err),
+        }
     }
 }
 
 // Parsing the arguments was pain, please mojang. What in the hell did you do?
 // https://github.com/serde-rs/serde/issues/723 That's why I've done a workaround using vec_argument
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct Arguments {
     #[serde(default)]
     #[serde(deserialize_with = "vec_argument")]
@@ -225,13 +361,13 @@ pub struct Arguments {
     pub jvm: Vec<Argument>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct Argument {
     pub rules: Option<Vec<Rule>>,
     pub value: ArgumentValue,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 #[serde(untagged)]
 pub enum ArgumentValue {
     SINGLE(String),
@@ -293,7 +429,7 @@ fn string_or_struct<'de, T, D>(deserializer: D) -> Result<T, D::Error>
     deserializer.deserialize_any(StringOrStruct(PhantomData))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct AssetIndexLocation {
     pub id: String,
     pub sha1: String,
@@ -307,6 +443,7 @@ impl AssetIndexLocation {
     pub async fn load_asset_index(&self, assets_root: &PathBuf) -> Result<AssetIndex> {
         let asset_index = assets_root.join(format!("{}.json", &self.id));
 
+        let _lock = lock_file(&asset_index).await;
         if !asset_index.exists() {
             info!("Downloading assets index of {}", self.id);
             download_file_untracked(&self.url, &asset_index).await?;
@@ -321,12 +458,60 @@ impl AssetIndexLocation {
 #[derive(Deserialize)]
 pub struct AssetIndex {
     pub objects: HashMap<String, AssetObject>,
+    /// Legacy indexes, their assets are looked up by name in `assets/virtual/<index>`
+    #[serde(rename = "virtual", default)]
+    pub is_virtual: bool,
+    /// Pre-1.6 indexes, their assets are looked up by name in the `resources` folder of the game dir
+    #[serde(default)]
+    pub map_to_resources: bool,
+}
+
+impl AssetIndex {
+    /// Folder a legacy version looks up its assets by name in, modern versions read the objects by hash
+    pub fn legacy_folder(&self, index_id: &str, assets_folder: &Path, game_dir: &Path) -> Option<PathBuf> {
+        if self.map_to_resources {
+            Some(game_dir.join("resources"))
+        } else if self.is_virtual {
+            Some(assets_folder.join("virtual").join(index_id))
+        } else {
+            None
+        }
+    }
+
+    /// Places every object under its name in the legacy folder, skipping those that are already there.
+    /// Returns how many objects were placed.
+    pub async fn reconstruct(&self, objects_folder: &Path, legacy_folder: &Path) -> Result<usize> {
+        let mut placed = 0;
+        for (name, object) in &self.objects {
+            let target = legacy_folder.join(name);
+            if fs::metadata(&target).await.map_or(false, |metadata| metadata.len() == object.size as u64) {
+                continue;
+            }
+
+            let source = objects_folder.join(&object.hash[0..2]).join(&object.hash);
+            if !source.exists() {
+                debug!("Legacy asset {} is missing its object {}", name, object.hash);
+                continue;
+            }
+            fs::create_dir_all(target.parent().unwrap()).await?;
+            let _ = fs::remove_file(&target).await;
+            // a hard link saves the space of a copy, but doesn't work across drives
+            if fs::hard_link(&source, &target).await.is_err() {
+                fs::copy(&source, &target).await?;
+            }
+            placed += 1;
+        }
+        Ok(placed)
+    }
 }
 
 #[derive(Deserialize, Clone)]
 pub struct AssetObject {
     pub hash: String,
     pub size: i64,
+    /// Pre-signed download url of NoRisk assets, if the api provides one
+    #[serde(default)]
+    pub url: Option<String>,
 }
 
 impl AssetObject {
@@ -339,6 +524,7 @@ impl AssetObject {
         }
 
         let asset_path = asset_folder.join(&self.hash);
+        let _lock = lock_file(&asset_path).await;
 
         return if !asset_path.exists() {
             progress.progress_update(ProgressUpdate::set_label(format!("Downloading asset object {}", self.hash)));
@@ -353,8 +539,7 @@ impl AssetObject {
         };
     }
 
-    pub async fn download_norisk_cosmetic(&self, branch: String, file_path: String, norisk_token: String, assets_objects_folder: impl AsRef<Path>, progress: Arc<impl ProgressReceiver>) -> Result<bool> {
-        let options = LauncherOptions::load(LAUNCHER_DIRECTORY.config_dir()).await.unwrap_or_default();
+    pub async fn download_norisk_cosmetic(&self, branch: String, file_path: String, norisk_token: &SharedNoRiskToken, assets_objects_folder: impl AsRef<Path>, progress: Arc<impl ProgressReceiver>) -> Result<bool> {
         let assets_objects_folder = assets_objects_folder.as_ref().to_owned();
 
         let mut path_parts: Vec<&str> = file_path.split("/").collect();
@@ -375,6 +560,7 @@ impl AssetObject {
             fs::create_dir_all(&asset_path).await?;
         }
 
+        let _lock = lock_file(&asset_file_path).await;
         let mut download = false;
 
         if asset_file_path.exists() {
@@ -395,7 +581,8 @@ impl AssetObject {
             progress.progress_update(ProgressUpdate::set_label(format!("Downloading asset object {}", self.hash)));
 
             info!("Downloading {}", self.hash);
-            download_private_file_untracked(&*format!("{}/launcher/assets/{}/{}/{}", get_api_base(options.experimental_mode), branch, &self.hash[0..2], &self.hash), norisk_token, asset_file_path).await?;
+            expect_downloads(self.size as u64);
+            self.download_norisk_file(&branch, norisk_token, &asset_file_path).await?;
             info!("Downloaded {}", self.hash);
 
             Ok(true)
@@ -404,16 +591,36 @@ impl AssetObject {
         };
     }
 
+    /// Downloads from the pre-signed url if there is one, falling back to the api, which gets a refreshed token when the current one expired
+    async fn download_norisk_file(&self, branch: &str, norisk_token: &SharedNoRiskToken, path: &Path) -> Result<()> {
+        if let Some(url) = &self.url {
+            match download_file_untracked(url, path).await {
+                Ok(()) => return Ok(()),
+                Err(err) => warn!("Signed url of Norisk asset {} failed, downloading it from the api: {:?}", self.hash, err),
+            }
+        }
+
+        let url = format!("{}/launcher/assets/{}/{}/{}", get_api_base(norisk_token.is_experimental()), branch, &self.hash[0..2], &self.hash);
+        let token = norisk_token.current();
+        match download_private_file_untracked(&url, token.clone(), path).await {
+            Err(err) if is_unauthorized(&err) => {
+                let token = norisk_token.refresh(&token).await?;
+                download_private_file_untracked(&url, token, path).await
+            }
+            result => result,
+        }
+    }
+
     pub async fn download_destructing(self, assets_objects_folder: impl AsRef<Path>, progress: Arc<impl ProgressReceiver>) -> Result<bool> {
         return self.download(assets_objects_folder, progress).await;
     }
 
-    pub async fn download_norisk_cosmetic_destructing(self, branch: String, file_path: String, norisk_token: String, assets_objects_folder: impl AsRef<Path>, progress: Arc<impl ProgressReceiver>) -> Result<bool> {
+    pub async fn download_norisk_cosmetic_destructing(self, branch: String, file_path: String, norisk_token: &SharedNoRiskToken, assets_objects_folder: impl AsRef<Path>, progress: Arc<impl ProgressReceiver>) -> Result<bool> {
         return self.download_norisk_cosmetic(branch, file_path, norisk_token, assets_objects_folder, progress).await;
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct Downloads {
     pub client: Option<Download>,
     pub client_mappings: Option<Download>,
@@ -423,7 +630,7 @@ pub struct Downloads {
 }
 
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct Download {
     pub sha1: String,
     pub size: i64,
@@ -448,7 +655,87 @@ pub struct Library {
     pub url: Option<String>,
 }
 
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum VersionPart {
+    /// Qualifiers like `SNAPSHOT` or `rc` by their rank, see `qualifier_rank`
+    Qualifier(u8, String),
+    /// Numbers are newer than any qualifier, `1.0.1` is newer than `1.0-rc`
+    Number(u64),
+}
+
+/// Rank of a maven qualifier, pre-releases come before the release and unknown qualifiers after it
+fn qualifier_rank(qualifier: &str) -> u8 {
+    match qualifier {
+        "alpha" | "a" => 0,
+        "beta" | "b" => 1,
+        "milestone" | "m" => 2,
+        "rc" | "cr" | "pre" => 3,
+        "snapshot" => 4,
+        "" | "ga" | "final" | "release" => 5,
+        "sp" => 6,
+        _ => 7,
+    }
+}
+
+/// Splits a maven version at separators and where digits and letters meet, `1.0-rc1` becomes `1`, `0`, `rc`, `1`
+fn version_parts(version: &str) -> Vec<VersionPart> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut previous_digit = None;
+    for c in version.chars() {
+        if c == '.' || c == '-' || c == '+' || c == '_' {
+            previous_digit = None;
+            continue;
+        }
+        match tokens.last_mut() {
+            Some(token) if previous_digit == Some(c.is_ascii_digit()) => token.push(c),
+            _ => tokens.push(c.to_string()),
+        }
+        previous_digit = Some(c.is_ascii_digit());
+    }
+
+    tokens.into_iter().map(|token| match token.parse::<u64>() {
+        Ok(number) => VersionPart::Number(number),
+        Err(_) => {
+            let qualifier = token.to_ascii_lowercase();
+            VersionPart::Qualifier(qualifier_rank(&qualifier), qualifier)
+        }
+    }).collect()
+}
+
+/// Compares maven versions part by part like maven does, missing parts count as `0` or as the release.
+/// So `1.0-SNAPSHOT` < `1.0` = `1.0.0` < `1.0.1`.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let (parts_a, parts_b) = (version_parts(a), version_parts(b));
+    let missing = |other: &VersionPart| match other {
+        VersionPart::Number(_) => VersionPart::Number(0),
+        VersionPart::Qualifier(..) => VersionPart::Qualifier(qualifier_rank(""), String::new()),
+    };
+
+    for index in 0..parts_a.len().max(parts_b.len()) {
+        let ordering = match (parts_a.get(index), parts_b.get(index)) {
+            (Some(part_a), Some(part_b)) => part_a.cmp(part_b),
+            (Some(part_a), None) => part_a.cmp(&missing(part_a)),
+            (None, Some(part_b)) => missing(part_b).cmp(part_b),
+            (None, None) => std::cmp::Ordering::Equal,
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    std::cmp::Ordering::Equal
+}
+
 impl Library {
+    /// Maven coordinate without the version (`group:artifact[:classifier]`) and the version
+    fn coordinate(&self) -> Option<(String, String)> {
+        let parts: Vec<&str> = self.name.split(':').collect();
+        match parts.as_slice() {
+            [group, artifact, version] => Some((format!("{}:{}", group, artifact), version.to_string())),
+            [group, artifact, version, classifier] => Some((format!("{}:{}:{}", group, artifact, classifier), version.to_string())),
+            _ => None,
+        }
+    }
+
     pub fn get_library_download(&self) -> Result<LibraryDownloadInfo> {
         if let Some(artifact) = self.downloads.as_ref().and_then(|x| x.artifact.as_ref()) {
             return Ok(artifact.into());
@@ -533,13 +820,19 @@ impl LibraryDownloadInfo {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
-    pub async fn download(&self, name: String, libraries_folder: &Path, progress: Arc<impl ProgressReceiver>) -> Result<PathBuf> {
+    /// Downloads the library unless it already exists and matches. With `trust_existing` existing libraries aren't checked.
+    pub async fn download(&self, name: String, libraries_folder: &Path, verified_libraries: &VerifiedLibraries, trust_existing: bool, progress: Arc<impl ProgressReceiver>) -> Result<PathBuf> {
         info!("Downloading library {}, sha1: {:?}, size: {:?}", name, &self.sha1, &self.size);
         debug!("Library download url: {}", &self.url);
 
         let path = libraries_folder.to_path_buf();
         let library_path = path.join(&self.path);
 
+        if trust_existing && library_path.exists() {
+            debug!("Library {} already exists, trusting it.", name);
+            return Ok(library_path);
+        }
+
         // Create parent directories
         fs::create_dir_all(&library_path.parent().unwrap()).await?;
 
@@ -569,30 +862,43 @@ impl LibraryDownloadInfo {
             }
         };
 
+        let sha1 = sha1.map(|sha1| sha1.trim().to_lowercase());
+
+        // held until the library is verified, another install would otherwise check or remove it in the meantime
+        let _lock = lock_file(&library_path).await;
+
         // Check if library already exists
         if library_path.exists() {
-            // Check if sha1 matches
-            let hash = sha1sum(&library_path)?;
-
             if let Some(sha1) = &sha1 {
+                // Skip hashing if the library was already verified and did not change since then
+                if verified_libraries.is_verified(&self.path, &library_path, sha1) {
+                    debug!("Library {} already verified.", name);
+                    return Ok(library_path);
+                }
+
+                // Check if sha1 matches
+                let hash = sha1sum(&library_path)?;
                 if hash == *sha1 {
                     // If sha1 matches, return
                     info!("Library {} already exists and matches sha1.", name);
+                    verified_libraries.mark_verified(&self.path, &library_path, sha1);
                     return Ok(library_path);
                 }
-            } else {
-                // If sha1 is not available, assume it matches
+            } else if self.size.map_or(true, |size| fs::metadata(&library_path).await.map_or(false, |metadata| metadata.len() as i64 == size)) {
+                // If sha1 is not available, only the size can be checked
                 info!("Library {} already exists.", name);
                 return Ok(library_path);
             }
 
-            // If sha1 doesn't match, remove the file
-            info!("Library {} already exists but sha1 doesn't match, redownloading", name);
+            // If sha1 or size doesn't match, remove the file
+            info!("Library {} already exists but doesn't match, redownloading", name);
             fs::remove_file(&library_path).await?;
         }
 
         // Download library
         progress.progress_update(ProgressUpdate::set_label(format!("Downloading library {}", name)));
+        // sizes of libraries are only known once they have been checked
+        expect_downloads(self.size.unwrap_or_default() as u64);
 
         download_file_untracked(&self.url, &library_path).await?;
         info!("Downloaded {}", self.url);
@@ -603,13 +909,37 @@ impl LibraryDownloadInfo {
             if hash != *sha1 {
                 anyhow::bail!("sha1 of downloaded library {} doesn't match", name);
             }
+            verified_libraries.mark_verified(&self.path, &library_path, sha1);
         }
 
         Ok(library_path)
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct Logging {
     // TODO: Add logging configuration
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::*;
+
+    #[test]
+    fn snapshots_and_pre_releases_come_before_the_release() {
+        assert_eq!(compare_versions("1.0-SNAPSHOT", "1.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0-rc1", "1.0-SNAPSHOT"), Ordering::Less);
+        assert_eq!(compare_versions("1.0-beta", "1.0-rc1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0-rc1", "1.0-rc2"), Ordering::Less);
+    }
+
+    #[test]
+    fn missing_parts_count_as_zero() {
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.1", "1.0"), Ordering::Greater);
+        assert_eq!(compare_versions("9.2", "10.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0-sp1", "1.0"), Ordering::Greater);
+    }
+}
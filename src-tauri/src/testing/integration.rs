@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+
+use serde_json::json;
+use sha1::{Digest, Sha1};
+
+use crate::app::api::NoRiskLaunchManifest;
+use crate::app::app_data::{AssetVerification, LauncherProfile, LauncherProfiles};
+use crate::app::profile_export::noriskpack_entries;
+use crate::app::profile_import::{apply_overrides, import_mrpack, import_noriskpack, profile_overrides, ImportStaging};
+use crate::app::profile_state::{self, ProfileChange};
+use crate::app::task_manager::TaskKind;
+use crate::minecraft::launcher::{launch_command_preview, LaunchingParameter, REDACTED};
+use crate::minecraft::version::VersionProfile;
+use crate::utils::{create_zip_archive, ZipEntry, ZipSource};
+
+use super::Harness;
+
+const MC_VERSION: &str = "1.21";
+
+fn sha1_hex(bytes: &[u8]) -> String {
+    base16ct::lower::encode_string(&Sha1::digest(bytes))
+}
+
+/// Modrinth version of a mod with a single file
+fn modrinth_version(id: &str, project_id: &str, version_number: &str, file_url: &str, file_name: &str, sha1: &str) -> serde_json::Value {
+    json!({
+        "id": id,
+        "project_id": project_id,
+        "project_type": "mod",
+        "author_id": "author",
+        "featured": false,
+        "name": version_number,
+        "version_number": version_number,
+        "changelog": "",
+        "changelog_url": null,
+        "date_published": "2024-06-13T00:00:00Z",
+        "downloads": 0,
+        "version_type": "release",
+        "status": "listed",
+        "requested_status": null,
+        "files": [{
+            "hashes": { "sha512": "", "sha1": sha1 },
+            "url": file_url,
+            "filename": file_name,
+            "primary": true,
+            "size": 0,
+            "file_type": null
+        }],
+        "dependencies": [],
+        "game_versions": [MC_VERSION],
+        "loaders": ["fabric"]
+    })
+}
+
+fn launching_parameter(data_path: PathBuf) -> LaunchingParameter {
+    LaunchingParameter {
+        dev_mode: false,
+        force_server: None,
+        memory: 2048,
+        min_memory: None,
+        data_path,
+        custom_java_path: Some("java".to_string()),
+        custom_java_args: String::new(),
+        auth_player_name: "Player".to_string(),
+        auth_uuid: "00000000-0000-0000-0000-000000000000".to_string(),
+        auth_access_token: "secret-access-token".to_string(),
+        auth_xuid: "x".to_string(),
+        clientid: "client".to_string(),
+        user_type: "msa".to_string(),
+        keep_launcher_open: false,
+        concurrent_downloads: 4,
+        adaptive_downloads: false,
+        preferred_gpu: String::new(),
+        link_mods: false,
+        ignore_content_blacklist: false,
+        restricted_mode: false,
+        experimental_mode: false,
+        quick_launch: false,
+        skip_norisk_assets: true,
+        isolated: false,
+        profile_id: None,
+        prepare_only: false,
+        asset_verification: AssetVerification::default(),
+    }
+}
+
+#[tokio::test]
+async fn mrpack_profile_launch_command() {
+    let harness = Harness::start().await;
+    let data = harness.data_dir().to_path_buf();
+
+    // modrinth knows the mod of the pack by its hash, the config file is only a download
+    let mod_jar = b"sodium mod jar".to_vec();
+    let mod_sha1 = sha1_hex(&mod_jar);
+    let mod_url = harness.mock_file("/files/sodium-0.5.8.jar", mod_jar).await;
+    let config = b"renderDistance=12".to_vec();
+    let config_sha1 = sha1_hex(&config);
+    let config_url = harness.mock_file("/files/sodium-options.txt", config).await;
+    let version = modrinth_version("vErSiOn1", "AANobbMI", "0.5.8", &mod_url, "sodium-0.5.8.jar", &mod_sha1);
+    harness.mock_json(&format!("/modrinth/v2/version_file/{}", mod_sha1), version.clone()).await;
+    harness.mock_json("/modrinth/v2/version/vErSiOn1", version).await;
+    harness.mock_json("/modrinth/v2/project/AANobbMI", json!({ "slug": "sodium", "title": "Sodium", "icon_url": "" })).await;
+
+    let index = json!({
+        "formatVersion": 1,
+        "game": "minecraft",
+        "name": "Test Pack",
+        "versionId": "1.0.0",
+        "files": [
+            { "path": "mods/sodium-0.5.8.jar", "hashes": { "sha1": mod_sha1 }, "downloads": [mod_url] },
+            { "path": "config/sodium-options.txt", "hashes": { "sha1": config_sha1 }, "downloads": [config_url] },
+            { "path": "mods/server-only.jar", "hashes": { "sha1": "0" }, "env": { "client": "unsupported" }, "downloads": [] }
+        ],
+        "dependencies": { "minecraft": MC_VERSION, "fabric-loader": "0.16.0" }
+    });
+    let pack_path = data.join("test-pack.mrpack");
+    create_zip_archive(&pack_path, vec![
+        ZipEntry { name: "modrinth.index.json".to_string(), source: ZipSource::Bytes(serde_json::to_vec(&index).unwrap()) },
+        ZipEntry { name: "overrides/options.txt".to_string(), source: ZipSource::Bytes(b"fov:90".to_vec()) },
+    ], |_, _| true).await.unwrap();
+
+    // mrpack import and mod resolution
+    let staging = ImportStaging::new(&data).await.unwrap();
+    let (name, mods) = harness.task_manager().run(TaskKind::Install, "Import Test Pack".to_string(), |task| {
+        let (pack_path, staging) = (&pack_path, &staging);
+        async move {
+            let pack = import_mrpack(pack_path, staging, &task).await.map_err(|e| e.to_string())?;
+            Ok((pack.name, pack.mods))
+        }
+    }).await.unwrap();
+    assert_eq!(name, "Test Pack");
+    assert_eq!(mods.len(), 1);
+    assert_eq!(mods[0].value.source.get_slug(), "sodium");
+    assert_eq!(mods[0].value.name, "sodium-0.5.8.jar");
+
+    // the pack's files stay with the profile until it is launched, the game dir is shared by the branch
+    let game_dir = data.join("gameDir").join("TEST");
+    staging.commit(&game_dir, "test-pack").await.unwrap();
+    assert!(!game_dir.join("options.txt").exists());
+    apply_overrides(&game_dir, "test-pack").await.unwrap();
+    assert_eq!(tokio::fs::read_to_string(game_dir.join("options.txt")).await.unwrap(), "fov:90");
+    assert_eq!(tokio::fs::read_to_string(game_dir.join("config").join("sodium-options.txt")).await.unwrap(), "renderDistance=12");
+
+    // profile creation
+    let profile: LauncherProfile = serde_json::from_value(json!({ "id": "test-pack", "branch": "TEST", "name": name, "mods": mods })).unwrap();
+    profile_state::update(|profiles| {
+        profiles.main_profiles.push(profile.clone());
+        vec![ProfileChange::Profile { profile }]
+    }).await;
+    profile_state::flush().await;
+    let stored = LauncherProfiles::load(harness.config_dir()).await.unwrap();
+    let stored_profile = stored.main_profiles.iter().find(|profile| profile.id == "test-pack").unwrap();
+    assert_eq!(stored_profile.mods.len(), 1);
+
+    // noriskpack export and import round trip
+    let custom_mods_dir = data.join("custom_mods").join(format!("TEST-{}", MC_VERSION));
+    tokio::fs::create_dir_all(&custom_mods_dir).await.unwrap();
+    tokio::fs::write(custom_mods_dir.join("local.jar"), b"local").await.unwrap();
+    let entries = noriskpack_entries(stored_profile, Some(MC_VERSION.to_string()), &game_dir, Some(&custom_mods_dir), false).unwrap();
+    let export_path = data.join("test-pack.noriskpack");
+    create_zip_archive(&export_path, entries, |_, _| true).await.unwrap();
+    tokio::fs::remove_dir_all(&custom_mods_dir).await.unwrap();
+
+    let staging = ImportStaging::new(&data).await.unwrap();
+    let exported = harness.task_manager().run(TaskKind::Install, "Import Test Pack".to_string(), |task| {
+        let (export_path, staging) = (&export_path, &staging);
+        async move { import_noriskpack(export_path, staging, &task).await.map_err(|e| e.to_string()) }
+    }).await.unwrap();
+    assert_eq!(exported.mc_version(), Some(MC_VERSION));
+    let imported = exported.into_profile();
+    assert_eq!(imported.name, name);
+    assert_eq!(imported.mods.len(), 1);
+    staging.commit(&game_dir, &imported.id).await.unwrap();
+    staging.commit_custom_mods(&custom_mods_dir).await.unwrap();
+    assert_eq!(tokio::fs::read_to_string(profile_overrides(&game_dir, &imported.id).join("options.txt")).await.unwrap(), "fov:90");
+    assert_eq!(tokio::fs::read(custom_mods_dir.join("local.jar")).await.unwrap(), b"local");
+
+    // launch parameter assembly
+    let manifest: NoRiskLaunchManifest = serde_json::from_value(json!({
+        "build": { "branch": "TEST", "mc_version": MC_VERSION, "jre_version": 21, "fabric_loader_version": "0.16.0" },
+        "server": "",
+        "subsystem": { "name": "fabric", "manifest": "", "mod_directory": "" },
+        "mods": [],
+        "repositories": {}
+    })).unwrap();
+    let version_profile: VersionProfile = serde_json::from_value(json!({
+        "id": "fabric-loader-0.16.0-1.21",
+        "assetIndex": { "id": "17", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+        "assets": "17",
+        "libraries": [],
+        "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+        "type": "release",
+        "minecraftArguments": "--username ${auth_player_name} --version ${version_name} --gameDir ${game_directory} --assetIndex ${assets_index_name} --accessToken ${auth_access_token}"
+    })).unwrap();
+    let preview = launch_command_preview(&data, &manifest, &version_profile, launching_parameter(data.clone())).await.unwrap();
+
+    assert_eq!(preview.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+    assert!(preview.jvm_args.contains(&"-Xmx2048M".to_string()));
+    assert!(preview.game_args.windows(2).any(|pair| pair == ["--username", "Player"]));
+    assert!(preview.game_args.windows(2).any(|pair| pair == ["--accessToken", REDACTED]));
+    assert!(!preview.game_args.iter().any(|arg| arg.contains("secret-access-token")));
+}
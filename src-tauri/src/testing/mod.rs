@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use tempfile::TempDir;
+use tokio::sync::MutexGuard;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::app::task_manager::TaskManager;
+use crate::LAUNCHER_DIRECTORY;
+
+#[cfg(test)]
+mod integration;
+
+/// Launcher folder of the test process, created when `LAUNCHER_DIRECTORY` is first resolved and kept until the process exits
+static ROOT: Lazy<TempDir> = Lazy::new(|| tempfile::tempdir().expect("unable to create the launcher folder of the test"));
+
+/// Url of the mock server the NoRisk and Modrinth API requests go to
+static API_BASE: RwLock<Option<String>> = RwLock::new(None);
+
+/// The API base and the launcher folder are shared by the whole process, so harnesses run one after another
+static HARNESS_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+pub fn launcher_root() -> PathBuf {
+    ROOT.path().to_path_buf()
+}
+
+pub fn api_base_override() -> Option<String> {
+    API_BASE.read().unwrap().clone()
+}
+
+///
+/// Mock APIs and a headless launcher state for integration tests. NoRisk API requests go to `/norisk/api/v1`
+/// and Modrinth API requests to `/modrinth/v2` of the mock server, the launcher folder is a temporary folder.
+///
+pub struct Harness {
+    pub server: MockServer,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        let guard = HARNESS_LOCK.lock().await;
+        for dir in [LAUNCHER_DIRECTORY.data_dir(), LAUNCHER_DIRECTORY.config_dir(), LAUNCHER_DIRECTORY.cache_dir()] {
+            tokio::fs::create_dir_all(dir).await.expect("unable to create the launcher folder of the test");
+        }
+
+        let server = MockServer::start().await;
+        *API_BASE.write().unwrap() = Some(server.uri());
+        Harness { server, _guard: guard }
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        LAUNCHER_DIRECTORY.data_dir()
+    }
+
+    pub fn config_dir(&self) -> &Path {
+        LAUNCHER_DIRECTORY.config_dir()
+    }
+
+    /// Task manager without a window, tasks run the same way but nothing is emitted
+    pub fn task_manager(&self) -> Arc<TaskManager> {
+        Arc::new(TaskManager::default())
+    }
+
+    pub fn url(&self, route: &str) -> String {
+        format!("{}{}", self.server.uri(), route)
+    }
+
+    /// Answers GET requests to the route with the JSON body
+    pub async fn mock_json(&self, route: &str, body: Value) {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Serves the bytes as a file, returns its url
+    pub async fn mock_file(&self, route: &str, bytes: Vec<u8>) -> String {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(bytes))
+            .mount(&self.server)
+            .await;
+        self.url(route)
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        *API_BASE.write().unwrap() = None;
+    }
+}
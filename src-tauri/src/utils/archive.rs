@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use async_zip::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use log::debug;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+
+/// Extensions of files that are compressed already, deflating them again costs time without making the archive smaller
+const COMPRESSED_EXTENSIONS: [&str; 8] = ["jar", "zip", "png", "jpg", "ogg", "gz", "mrpack", "noriskpack"];
+
+/// Bytes read from a file before the progress is reported
+const CHUNK_SIZE: usize = 256 * 1024;
+
+///
+/// Content of an entry of a ZIP archive that is being created
+///
+pub enum ZipSource {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+pub struct ZipEntry {
+    /// Path inside the archive, separated by `/`
+    pub name: String,
+    pub source: ZipSource,
+}
+
+impl ZipEntry {
+    async fn size(&self) -> u64 {
+        match &self.source {
+            ZipSource::File(path) => fs::metadata(path).await.map(|metadata| metadata.len()).unwrap_or_default(),
+            ZipSource::Bytes(bytes) => bytes.len() as u64,
+        }
+    }
+
+    fn compression(&self) -> Compression {
+        let extension = self.name.rsplit_once('.').map(|(_, extension)| extension.to_lowercase()).unwrap_or_default();
+        if COMPRESSED_EXTENSIONS.contains(&extension.as_str()) { Compression::Stored } else { Compression::Deflate }
+    }
+}
+
+async fn write_entries<P: Fn(u64, u64) -> bool>(destination: &Path, entries: Vec<ZipEntry>, total: u64, on_progress: &P) -> Result<()> {
+    let mut file = BufWriter::new(File::create(destination).await?);
+    let mut writer = ZipFileWriter::new(&mut file);
+    let mut written = 0;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    for entry in entries {
+        let builder = ZipEntryBuilder::new(entry.name.clone(), entry.compression());
+        let mut entry_writer = writer.write_entry_stream(builder).await?;
+        match &entry.source {
+            ZipSource::File(path) => {
+                let mut source = File::open(path).await?;
+                loop {
+                    let read = source.read(&mut buffer).await?;
+                    if read == 0 {
+                        break;
+                    }
+                    entry_writer.write_all(&buffer[..read]).await?;
+                    written += read as u64;
+                    if !on_progress(written, total) {
+                        bail!("archive creation was cancelled");
+                    }
+                }
+            }
+            ZipSource::Bytes(bytes) => {
+                entry_writer.write_all(bytes).await?;
+                written += bytes.len() as u64;
+                if !on_progress(written, total) {
+                    bail!("archive creation was cancelled");
+                }
+            }
+        }
+        entry_writer.close().await?;
+    }
+    writer.close().await?;
+    file.flush().await?;
+    Ok(())
+}
+
+/// Creates a ZIP archive on a thread of the blocking pool, so compressing big files never stalls the async runtime.
+/// Files that are compressed already, like mods and resource packs, are stored as they are.
+/// `on_progress` is called with the bytes read so far and the total bytes, returning `false` cancels the creation.
+pub async fn create_zip_archive<P>(destination: &Path, entries: Vec<ZipEntry>, on_progress: P) -> Result<()>
+    where P: Fn(u64, u64) -> bool + Send + Sync + 'static {
+    let destination = destination.to_path_buf();
+    let runtime = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || runtime.block_on(async {
+        let mut total = 0;
+        for entry in &entries {
+            total += entry.size().await;
+        }
+
+        let result = write_entries(&destination, entries, total, &on_progress).await;
+        if result.is_err() {
+            let _ = fs::remove_file(&destination).await;
+        }
+        debug!("Created {:?} from {} bytes", destination, total);
+        result
+    })).await?
+}
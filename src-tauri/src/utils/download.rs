@@ -1,33 +1,190 @@
-use std::path::Path;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use reqwest::Response;
+use serde::Serialize;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Notify, OwnedMutexGuard};
 use log::debug;
 use anyhow::Result;
 
 use crate::HTTP_CLIENT;
 
-/// Download file using HTTP_CLIENT without any progress tracking
-pub async fn download_file_untracked(url: &str, path: impl AsRef<Path>) -> Result<()> {
+/// Snapshots are taken at most this often, so the speed doesn't jump around
+const SPEED_WINDOW: Duration = Duration::from_millis(500);
+
+tokio::task_local! {
+    /// Tracker of the install the current task runs, see `track_downloads`
+    static TRACKER: Arc<DownloadTracker>;
+}
+/// Locks of the files installs are checking or downloading, see `lock_file`
+static FILE_LOCKS: Lazy<DashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>> = Lazy::new(DashMap::new);
+/// Numbers the temporary files of downloads, so two downloads of the same file never write to the same one
+static TEMP_FILES: AtomicU64 = AtomicU64::new(0);
+
+///
+/// Bytes of the current install, sent to the frontend to show the speed and remaining time
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct DownloadBytes {
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+    #[serde(rename = "transferredBytes")]
+    pub transferred_bytes: u64,
+    /// Bytes per second
+    pub speed: u64,
+}
+
+///
+/// Counts the bytes of every download, shared by all downloads running for one install
+///
+#[derive(Default)]
+pub struct DownloadTracker {
+    expected: AtomicU64,
+    transferred: AtomicU64,
+    /// Time and transferred bytes of the last speed sample, and the speed measured then
+    sample: Mutex<Option<(Instant, u64, u64)>>,
+}
+
+impl DownloadTracker {
+    /// Adds bytes that are going to be downloaded
+    pub fn expect(&self, bytes: u64) {
+        self.expected.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn add_transferred(&self, bytes: u64) {
+        self.transferred.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Current byte counts and speed, `None` if the last snapshot was taken less than the speed window ago
+    pub fn snapshot(&self) -> Option<DownloadBytes> {
+        let now = Instant::now();
+        let transferred_bytes = self.transferred.load(Ordering::Relaxed);
+        let mut sample = self.sample.lock().unwrap();
+
+        let speed = match *sample {
+            Some((time, _, _)) if now.duration_since(time) < SPEED_WINDOW => return None,
+            Some((time, bytes, last_speed)) => {
+                let measured = (transferred_bytes.saturating_sub(bytes) as f64 / now.duration_since(time).as_secs_f64()) as u64;
+                // averaged with the last speed, single samples vary a lot with many small files
+                if last_speed == 0 { measured } else { (last_speed + measured) / 2 }
+            }
+            None => 0,
+        };
+        *sample = Some((now, transferred_bytes, speed));
+
+        Some(DownloadBytes {
+            // downloads without a known size can make the transferred bytes exceed the expected ones
+            total_bytes: self.expected.load(Ordering::Relaxed).max(transferred_bytes),
+            transferred_bytes,
+            speed,
+        })
+    }
+}
+
+/// Runs an install with a download tracker of its own, so installs running at the same time don't mix up their speed and remaining time
+pub async fn track_downloads<F: Future>(install: F) -> F::Output {
+    TRACKER.scope(Arc::new(DownloadTracker::default()), install).await
+}
+
+/// Tracker of the install the current task runs, `None` outside of `track_downloads`
+pub fn download_tracker() -> Option<Arc<DownloadTracker>> {
+    TRACKER.try_with(Arc::clone).ok()
+}
+
+/// Adds bytes the current install is going to download
+pub fn expect_downloads(bytes: u64) {
+    if let Some(tracker) = download_tracker() {
+        tracker.expect(bytes);
+    }
+}
+
+fn add_transferred(bytes: u64) {
+    if let Some(tracker) = download_tracker() {
+        tracker.add_transferred(bytes);
+    }
+}
+
+fn transferred_bytes() -> u64 {
+    download_tracker().map_or(0, |tracker| tracker.transferred.load(Ordering::Relaxed))
+}
+
+///
+/// Keeps other installs from checking or downloading a file until it is dropped
+///
+pub struct FileLock {
+    path: PathBuf,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        self.guard.take();
+        // the last install holding the file removes its lock
+        FILE_LOCKS.remove_if(&self.path, |_, lock| Arc::strong_count(lock) == 1);
+    }
+}
+
+/// Locks a file for the whole check and download of an install. Otherwise a second install using the same library
+/// could take the file for complete, or delete it as corrupt, while the first one is still downloading it.
+pub async fn lock_file(path: impl AsRef<Path>) -> FileLock {
     let path = path.as_ref().to_owned();
+    let lock = FILE_LOCKS.entry(path.clone()).or_default().clone();
+    let guard = lock.lock_owned().await;
+    FileLock { path, guard: Some(guard) }
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}-{}.part", std::process::id(), TEMP_FILES.fetch_add(1, Ordering::Relaxed)));
+    path.with_file_name(file_name)
+}
+
+/// Writes a response to a temporary file chunk by chunk, counting the bytes, and moves it to the path once it is complete.
+/// A file at the path is therefore never partial, even if the download fails or another download of it runs at the same time.
+async fn write_response(mut response: Response, path: &Path) -> Result<()> {
+    let temp_path = temp_path(path);
+    let result: Result<()> = async {
+        let mut file = fs::File::create(&temp_path).await?;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+            add_transferred(chunk.len() as u64);
+        }
+        file.flush().await?;
+        // closed before the rename, Windows can't move open files
+        drop(file);
+        fs::rename(&temp_path, path).await?;
+        Ok(())
+    }.await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path).await;
+    }
+    result
+}
+
+/// Download file using HTTP_CLIENT without any progress tracking, only the bytes are counted by the download tracker
+pub async fn download_file_untracked(url: &str, path: impl AsRef<Path>) -> Result<()> {
     let response = HTTP_CLIENT.get(url)
         .send().await?
         .error_for_status()?;
-    
-    let content = response.bytes().await?;
-    fs::write(path, content).await?;
-    Ok(())
+
+    write_response(response, path.as_ref()).await
 }
 
 pub async fn download_private_file_untracked(url: &str, norisk_token: String, path: impl AsRef<Path>) -> Result<()> {
-    let path = path.as_ref().to_owned();
     let response = HTTP_CLIENT.get(url)
         .header("Authorization", format!("Bearer {}", norisk_token))
         .send().await?
         .error_for_status()?;
 
-    let content = response.bytes().await?;
-    fs::write(path, content).await?;
-    Ok(())
+    write_response(response, path.as_ref()).await
 }
 
 pub async fn download_file<F>(url: &str, on_progress: F) -> Result<Vec<u8>> where F : Fn(u64, u64) {
@@ -42,6 +199,8 @@ pub async fn download_file<F>(url: &str, on_progress: F) -> Result<Vec<u8>> wher
     let max_len = response.content_length().unwrap_or(0);
     let mut output = Vec::with_capacity(max_len as usize);
     let mut curr_len = 0;
+    // sizes of these downloads aren't known in advance
+    expect_downloads(max_len);
 
     on_progress(0, max_len);
 
@@ -49,9 +208,125 @@ pub async fn download_file<F>(url: &str, on_progress: F) -> Result<Vec<u8>> wher
     while let Some(data) = response.chunk().await? {
         output.extend_from_slice(&data);
         curr_len += data.len();
+        add_transferred(data.len() as u64);
         on_progress(curr_len as u64, max_len);
     }
 
     debug!("Downloaded file");
     Ok(output)
-}
\ No newline at end of file
+}
+/// Adaptive concurrency is judged on windows of this length
+const CONCURRENCY_WINDOW: Duration = Duration::from_secs(2);
+/// Share of failed downloads in a window above which the concurrency is halved
+const MAX_ERROR_RATE: f64 = 0.1;
+/// Upper bound of adaptive concurrency, unless more concurrent downloads are configured
+const MAX_ADAPTIVE_DOWNLOADS: usize = 64;
+
+struct ConcurrencyState {
+    limit: usize,
+    active: usize,
+    window_start: Instant,
+    window_transferred: u64,
+    completed: usize,
+    failed: usize,
+    last_throughput: f64,
+}
+
+///
+/// Limits how many downloads run at once. With adaptive concurrency the limit starts at the configured value
+/// and is adjusted after every window: halved when many downloads fail, raised while more downloads increase
+/// the throughput and lowered when the throughput drops.
+///
+pub struct DownloadConcurrency {
+    adaptive: bool,
+    max: usize,
+    state: Mutex<ConcurrencyState>,
+    released: Notify,
+}
+
+impl DownloadConcurrency {
+    pub fn new(concurrent_downloads: i32, adaptive: bool) -> Self {
+        let concurrent_downloads = concurrent_downloads.max(1) as usize;
+        DownloadConcurrency {
+            adaptive,
+            max: if adaptive { concurrent_downloads.max(MAX_ADAPTIVE_DOWNLOADS) } else { concurrent_downloads },
+            state: Mutex::new(ConcurrencyState {
+                limit: concurrent_downloads,
+                active: 0,
+                window_start: Instant::now(),
+                window_transferred: transferred_bytes(),
+                completed: 0,
+                failed: 0,
+                last_throughput: 0.0,
+            }),
+            released: Notify::new(),
+        }
+    }
+
+    /// Most downloads that can ever run at once, the buffer size of download streams
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Runs a download once the limit allows it
+    pub async fn run<T>(&self, download: impl Future<Output = Result<T>>) -> Result<T> {
+        if !self.adaptive {
+            return download.await;
+        }
+
+        loop {
+            let released = self.released.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.active < state.limit {
+                    state.active += 1;
+                    break;
+                }
+            }
+            released.await;
+        }
+
+        let result = download.await;
+        self.complete(result.is_err());
+        result
+    }
+
+    fn complete(&self, failed: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.active -= 1;
+        state.completed += 1;
+        if failed {
+            state.failed += 1;
+        }
+
+        let elapsed = state.window_start.elapsed();
+        if elapsed >= CONCURRENCY_WINDOW {
+            let transferred = transferred_bytes();
+            let throughput = transferred.saturating_sub(state.window_transferred) as f64 / elapsed.as_secs_f64();
+            let error_rate = state.failed as f64 / state.completed as f64;
+
+            let limit = if error_rate > MAX_ERROR_RATE {
+                state.limit / 2
+            } else if throughput > state.last_throughput * 1.05 {
+                state.limit + 1
+            } else if throughput < state.last_throughput * 0.8 {
+                state.limit - 1
+            } else {
+                state.limit
+            }.clamp(1, self.max);
+
+            if limit != state.limit {
+                debug!("Adjusting concurrent downloads from {} to {} ({:.0} B/s, {:.0}% failed)", state.limit, limit, throughput, error_rate * 100.0);
+            }
+            state.limit = limit;
+            state.window_start = Instant::now();
+            state.window_transferred = transferred;
+            state.completed = 0;
+            state.failed = 0;
+            state.last_throughput = throughput;
+        }
+        drop(state);
+
+        self.released.notify_waiters();
+    }
+}
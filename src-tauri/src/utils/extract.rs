@@ -6,6 +6,8 @@ use tokio::fs::{create_dir_all, OpenOptions};
 use tokio::io;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, BufReader};
 
+use super::long_path;
+
 /// Extracts everything from the ZIP archive to the output directory
 ///
 /// Taken from https://github.com/Majored/rs-async-zip/blob/main/examples/file_extraction.rs
@@ -49,6 +51,57 @@ pub async fn zip_extract<R>(archive: R, out_dir: &Path) -> Result<()>
     Ok(())
 }
 
+/// Extracts the entries below a folder of the ZIP archive to the output directory, like the overrides of a modpack.
+/// `on_progress` is called with the entries of the archive looked at so far and the number of entries.
+pub async fn zip_extract_folder<R, P>(archive: R, folder: &str, out_dir: &Path, mut on_progress: P) -> Result<()>
+    where R: AsyncRead + AsyncSeek + Unpin, P: FnMut(usize, usize) {
+    let prefix = format!("{}/", folder.trim_end_matches('/'));
+    let mut reader = ZipFileReader::new(archive).await?;
+    let entries = reader.file().entries().len();
+    for index in 0..entries {
+        on_progress(index, entries);
+        let file_name = reader.file().entries().get(index).unwrap().entry().filename().replace('\\', "/");
+        let Some(relative) = file_name.strip_prefix(&prefix) else { continue };
+        if relative.is_empty() || relative.ends_with('/') {
+            continue;
+        }
+
+        // nested overrides of modpacks easily exceed the path limit of Windows
+        let path = long_path(&out_dir.join(sanitize_file_path(relative)));
+        create_dir_all(path.parent().unwrap()).await?;
+        let mut entry_reader = reader.entry(index).await?;
+        let mut writer = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path).await
+            .context("Failed to create extracted file")?;
+        io::copy(&mut entry_reader, &mut writer).await?;
+    }
+    Ok(())
+}
+
+/// Paths of the files below a folder of the ZIP archive relative to it, as `zip_extract_folder` extracts them
+pub async fn zip_folder_entries<R>(archive: R, folder: &str) -> Result<Vec<PathBuf>>
+    where R: AsyncRead + AsyncSeek + Unpin {
+    let prefix = format!("{}/", folder.trim_end_matches('/'));
+    let reader = ZipFileReader::new(archive).await?;
+    Ok(reader.file().entries().iter()
+        .map(|entry| entry.entry().filename().replace('\\', "/"))
+        .filter_map(|file_name| file_name.strip_prefix(&prefix).filter(|relative| !relative.is_empty() && !relative.ends_with('/')).map(sanitize_file_path))
+        .collect())
+}
+
+/// Reads a single file of the ZIP archive, `None` if the archive doesn't contain it
+pub async fn zip_read_file<R>(archive: R, name: &str) -> Result<Option<Vec<u8>>>
+    where R: AsyncRead + AsyncSeek + Unpin {
+    let mut reader = ZipFileReader::new(archive).await?;
+    let Some(index) = reader.file().entries().iter().position(|entry| entry.entry().filename() == name) else { return Ok(None) };
+    let mut data = Vec::new();
+    reader.entry(index).await?.read_to_end(&mut data).await?;
+    Ok(Some(data))
+}
+
 pub async fn tar_gz_extract<R>(archive: R, out_dir: &Path) -> Result<()>
     where R: AsyncRead + AsyncSeek + Unpin {
     let mut decoder = GzipDecoder::new(BufReader::new(archive));
@@ -68,4 +121,43 @@ fn sanitize_file_path(path: &str) -> PathBuf {
         .split('/')
         .map(sanitize_filename::sanitize)
         .collect()
-}
\ No newline at end of file
+}
+/// Removes the quarantine attribute from extracted files and ad-hoc signs libraries and executables without a valid signature,
+/// otherwise Gatekeeper refuses to load them and reports them as damaged. Does nothing on other systems.
+pub async fn clear_quarantine(dir: &Path) {
+    #[cfg(target_os = "macos")]
+    {
+        use log::{debug, warn};
+        use tokio::process::Command;
+
+        match Command::new("xattr").arg("-dr").arg("com.apple.quarantine").arg(dir).output().await {
+            Ok(output) if output.status.success() => debug!("Removed quarantine attribute from {:?}", dir),
+            Ok(output) => debug!("xattr failed on {:?}: {}", dir, String::from_utf8_lossy(&output.stderr)),
+            Err(err) => warn!("Failed to run xattr on {:?}: {:?}", dir, err),
+        }
+
+        let binaries = walkdir::WalkDir::new(dir).into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                let is_library = path.extension().map_or(false, |extension| extension == "dylib" || extension == "jnilib");
+                let is_executable = path.parent().map_or(false, |parent| parent.ends_with("bin"));
+                is_library || is_executable
+            });
+        for binary in binaries {
+            let signed = Command::new("codesign").arg("--verify").arg(&binary).output().await
+                .map_or(true, |output| output.status.success());
+            if signed {
+                continue;
+            }
+            match Command::new("codesign").args(["--force", "--sign", "-"]).arg(&binary).output().await {
+                Ok(output) if output.status.success() => debug!("Ad-hoc signed {:?}", binary),
+                Ok(output) => warn!("Failed to sign {:?}: {}", binary, String::from_utf8_lossy(&output.stderr)),
+                Err(err) => warn!("Failed to run codesign on {:?}: {:?}", binary, err),
+            }
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = dir;
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use log::{debug, error};
+use once_cell::sync::Lazy;
+
+/// Whether the launcher runs inside a Flatpak sandbox, where direct file access and openers are restricted
+pub static IS_SANDBOXED: Lazy<bool> = Lazy::new(|| {
+    cfg!(target_os = "linux") && (Path::new("/.flatpak-info").exists() || std::env::var_os("FLATPAK_ID").is_some())
+});
+
+/// Folders modified more recently than this aren't cached, some file systems only store the modification time in seconds
+const FOLDER_SCAN_SETTLE_TIME: Duration = Duration::from_secs(2);
+
+static FOLDER_SCANS: Lazy<Mutex<HashMap<PathBuf, FolderScan>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct FolderScan {
+    modified: SystemTime,
+    files: Vec<String>,
+}
+
+/// Names of the files in a folder, sorted. The result is cached until the modification time of the folder changes,
+/// which happens whenever a file is added to, removed from or renamed in it.
+pub async fn list_file_names(folder: &Path) -> Result<Vec<String>> {
+    let modified = tokio::fs::metadata(folder).await?.modified()?;
+    if let Some(scan) = FOLDER_SCANS.lock().unwrap().get(folder) {
+        if scan.modified == modified {
+            return Ok(scan.files.clone());
+        }
+    }
+
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(folder).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        // mods in the mods folder might be symlinks
+        if !entry.file_type().await?.is_dir() {
+            files.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    files.sort();
+
+    let settled = SystemTime::now().duration_since(modified).map_or(false, |age| age > FOLDER_SCAN_SETTLE_TIME);
+    if settled {
+        FOLDER_SCANS.lock().unwrap().insert(folder.to_path_buf(), FolderScan { modified, files: files.clone() });
+    }
+    Ok(files)
+}
+
+/// Forgets the cached listing of a folder, for changes made by the launcher itself
+pub fn invalidate_folder_scan(folder: &Path) {
+    FOLDER_SCANS.lock().unwrap().remove(folder);
+}
+
+/// Lets the user choose a single file, using the XDG file chooser portal when sandboxed
+pub async fn pick_file(title: &str, filter_name: &str, extensions: &[&str]) -> Option<PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        if *IS_SANDBOXED {
+            return match portal::pick_file(title, filter_name, extensions).await {
+                Ok(path) => path,
+                Err(err) => {
+                    error!("File chooser portal failed: {:?}", err);
+                    None
+                }
+            };
+        }
+    }
+
+    use tauri::api::dialog::blocking::FileDialogBuilder;
+    FileDialogBuilder::new()
+        .set_title(title)
+        .add_filter(filter_name, extensions)
+        .pick_file()
+}
+
+/// Opens a file or folder with the default application, using the XDG open uri portal when sandboxed
+pub async fn open_path(path: &Path) -> Result<()> {
+    debug!("Opening path {:?}", path);
+
+    #[cfg(target_os = "linux")]
+    {
+        if *IS_SANDBOXED {
+            return portal::open_path(path).await;
+        }
+    }
+
+    open::that(path)?;
+    Ok(())
+}
+
+/// Files chosen through the document portal are exposed below `/run/user/<uid>/doc`.
+/// Returns the real location on the host so it can be shown to the user or stored.
+pub fn document_host_path(path: &Path) -> PathBuf {
+    #[cfg(target_os = "linux")]
+    {
+        if *IS_SANDBOXED && path.starts_with(format!("/run/user/{}/doc", current_uid())) {
+            if let Ok(Some(host_path)) = xattr::get(path, "user.document-portal.host-path") {
+                return PathBuf::from(String::from_utf8_lossy(&host_path).trim_end_matches('\0'));
+            }
+        }
+    }
+
+    path.to_path_buf()
+}
+
+#[cfg(target_os = "linux")]
+fn current_uid() -> u32 {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata("/proc/self").map(|metadata| metadata.uid()).unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+mod portal {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::Result;
+    use ashpd::desktop::file_chooser::{FileFilter, SelectedFiles};
+    use ashpd::desktop::open_uri::{OpenDirectoryRequest, OpenFileRequest};
+
+    pub async fn pick_file(title: &str, filter_name: &str, extensions: &[&str]) -> Result<Option<PathBuf>> {
+        let filter = extensions.iter().fold(FileFilter::new(filter_name), |filter, extension| filter.glob(&format!("*.{}", extension)));
+
+        let response = SelectedFiles::open_file()
+            .title(title)
+            .modal(true)
+            .multiple(false)
+            .filter(filter)
+            .send().await?
+            .response()?;
+
+        Ok(response.uris().first().and_then(|uri| uri.to_file_path().ok()))
+    }
+
+    pub async fn open_path(path: &Path) -> Result<()> {
+        let file = std::fs::File::open(path)?;
+        if path.is_dir() {
+            OpenDirectoryRequest::default().send(&file).await?;
+        } else {
+            OpenFileRequest::default().send_file(&file).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use reqwest::{Body, Client, IntoUrl, Method, RequestBuilder, Response};
+use serde::Serialize;
+
+static METRICS: Lazy<Mutex<HashMap<String, EndpointStats>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Default)]
+struct EndpointStats {
+    requests: u64,
+    failures: u64,
+    total_ms: u64,
+    max_ms: u64,
+    statuses: BTreeMap<u16, u64>,
+}
+
+///
+/// Timing and status codes of the requests to an endpoint since the launcher started
+///
+#[derive(Clone, Debug, Serialize)]
+pub struct EndpointMetrics {
+    /// Host and first path segment, like `api.modrinth.com/project`
+    pub endpoint: String,
+    pub requests: u64,
+    /// Requests that failed to connect or returned an error status
+    pub failures: u64,
+    /// Time until the response headers arrived
+    #[serde(rename = "averageMs")]
+    pub average_ms: u64,
+    #[serde(rename = "maxMs")]
+    pub max_ms: u64,
+    pub statuses: BTreeMap<u16, u64>,
+}
+
+/// Groups urls by host and their first path segment. API versions are skipped and segments containing
+/// digits (ids, hashes, versions) become `*`, so the metrics don't grow with every project or file.
+fn endpoint_of(url: &reqwest::Url) -> String {
+    let segment = url.path_segments()
+        .and_then(|mut segments| segments.find(|segment| !segment.is_empty() && *segment != "api" && !is_api_version(segment)))
+        .map(|segment| if segment.chars().any(|c| c.is_ascii_digit()) { "*" } else { segment })
+        .unwrap_or_default();
+    format!("{}/{}", url.host_str().unwrap_or_default(), segment)
+}
+
+fn is_api_version(segment: &str) -> bool {
+    segment.strip_prefix('v').map_or(false, |version| !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn record(endpoint: String, started: Instant, status: Option<u16>) {
+    let elapsed = started.elapsed().as_millis() as u64;
+    let mut metrics = METRICS.lock().unwrap();
+    let stats = metrics.entry(endpoint).or_default();
+    stats.requests += 1;
+    stats.total_ms += elapsed;
+    stats.max_ms = stats.max_ms.max(elapsed);
+    match status {
+        Some(status) => {
+            *stats.statuses.entry(status).or_default() += 1;
+            if status >= 400 {
+                stats.failures += 1;
+            }
+        }
+        None => stats.failures += 1,
+    }
+}
+
+/// Metrics of every endpoint, the slowest first
+pub fn network_metrics() -> Vec<EndpointMetrics> {
+    let metrics = METRICS.lock().unwrap();
+    let mut endpoints = metrics.iter().map(|(endpoint, stats)| EndpointMetrics {
+        endpoint: endpoint.clone(),
+        requests: stats.requests,
+        failures: stats.failures,
+        average_ms: stats.total_ms / stats.requests.max(1),
+        max_ms: stats.max_ms,
+        statuses: stats.statuses.clone(),
+    }).collect::<Vec<_>>();
+    endpoints.sort_by(|a, b| b.average_ms.cmp(&a.average_ms));
+    endpoints
+}
+
+///
+/// HTTP client that records the timing and status of every request, see `network_metrics`
+///
+pub struct InstrumentedClient(Client);
+
+impl InstrumentedClient {
+    pub fn new(client: Client) -> Self {
+        InstrumentedClient(client)
+    }
+
+    fn request<U: IntoUrl>(&self, method: Method, url: U) -> InstrumentedRequest {
+        InstrumentedRequest(self.0.request(method, url))
+    }
+
+    pub fn get<U: IntoUrl>(&self, url: U) -> InstrumentedRequest {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post<U: IntoUrl>(&self, url: U) -> InstrumentedRequest {
+        self.request(Method::POST, url)
+    }
+
+    pub fn delete<U: IntoUrl>(&self, url: U) -> InstrumentedRequest {
+        self.request(Method::DELETE, url)
+    }
+
+    pub fn head<U: IntoUrl>(&self, url: U) -> InstrumentedRequest {
+        self.request(Method::HEAD, url)
+    }
+}
+
+pub struct InstrumentedRequest(RequestBuilder);
+
+impl InstrumentedRequest {
+    pub fn header<K: AsRef<str>, V: AsRef<str>>(self, key: K, value: V) -> Self {
+        InstrumentedRequest(self.0.header(key.as_ref(), value.as_ref()))
+    }
+
+    pub fn bearer_auth<T: Display>(self, token: T) -> Self {
+        InstrumentedRequest(self.0.bearer_auth(token))
+    }
+
+    pub fn query<T: Serialize + ?Sized>(self, query: &T) -> Self {
+        InstrumentedRequest(self.0.query(query))
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        InstrumentedRequest(self.0.json(json))
+    }
+
+    pub fn body<T: Into<Body>>(self, body: T) -> Self {
+        InstrumentedRequest(self.0.body(body))
+    }
+
+    pub fn multipart(self, form: reqwest::multipart::Form) -> Self {
+        InstrumentedRequest(self.0.multipart(form))
+    }
+
+    pub async fn send(self) -> reqwest::Result<Response> {
+        let (client, request) = self.0.build_split();
+        let request = request?;
+        let endpoint = endpoint_of(request.url());
+        let started = Instant::now();
+        let response = client.execute(request).await;
+        record(endpoint, started, response.as_ref().ok().map(|response| response.status().as_u16()));
+        response
+    }
+}
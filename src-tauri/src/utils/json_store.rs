@@ -0,0 +1,31 @@
+use std::path::Path;
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::fs;
+
+///
+/// State of the launcher that is kept in a JSON file of the config dir
+///
+pub trait JsonStore: Serialize + DeserializeOwned + Send + 'static {
+    const FILE_NAME: &'static str;
+
+    /// Fails if the file is missing or can't be read, callers usually start over with the default then
+    fn load(app_data: &Path) -> BoxFuture<'static, Result<Self>> {
+        let path = app_data.join(Self::FILE_NAME);
+        Box::pin(async move {
+            Ok(serde_json::from_slice::<Self>(&fs::read(path).await?)?)
+        })
+    }
+
+    fn store(&self, app_data: &Path) -> BoxFuture<'static, Result<()>> {
+        let path = app_data.join(Self::FILE_NAME);
+        let data = serde_json::to_string_pretty(self);
+        Box::pin(async move {
+            fs::write(path, data?).await?;
+            Ok(())
+        })
+    }
+}
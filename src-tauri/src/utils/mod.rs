@@ -1,14 +1,21 @@
 mod sys;
 mod extract;
+mod archive;
 mod download;
 mod maven;
 mod checksum;
+mod http;
+mod json_store;
+pub mod file_utils;
 
 pub use {
     sys::*,
     extract::*,
+    archive::*,
     download::*,
     maven::*,
     checksum::*,
+    http::*,
+    json_store::*,
 };
 
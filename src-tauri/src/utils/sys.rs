@@ -2,7 +2,101 @@ use std::fmt::Display;
 use anyhow::{bail, Result};
 use once_cell::sync::Lazy;
 use serde::Deserialize;
-use sysinfo::{RefreshKind, System, SystemExt};
+use std::path::{Path, PathBuf};
+use sysinfo::{DiskExt, RefreshKind, System, SystemExt};
+
+/// Whether the Windows build runs under Wine or Proton, which handle some process flags and paths differently
+pub static WINE: Lazy<bool> = Lazy::new(|| {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::core::w;
+        use windows::Win32::System::Registry::{RegCloseKey, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, KEY_READ};
+
+        if std::env::var_os("WINEPREFIX").is_some() || std::env::var_os("WINELOADER").is_some() {
+            return true;
+        }
+        // every wine prefix has this key, real Windows installations don't
+        let mut key = HKEY::default();
+        unsafe {
+            if RegOpenKeyExW(HKEY_CURRENT_USER, w!("Software\\Wine"), 0, KEY_READ, &mut key).is_ok() {
+                let _ = RegCloseKey(key);
+                return true;
+            }
+        }
+    }
+    false
+});
+
+/// Hides the console window of a helper process.
+/// Wine doesn't connect the pipes of processes without a console, so the flag is left out there.
+#[cfg(target_os = "windows")]
+pub fn hide_console_window(command: &mut tokio::process::Command) {
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    if !*WINE {
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+}
+
+/// Removes the `\\?\` prefix Windows adds to canonicalized paths, Wine and most tools can't handle it
+pub fn simplified_path(path: &Path) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    match path_str.strip_prefix(r"\\?\") {
+        Some(stripped) if !stripped.starts_with("UNC") => PathBuf::from(stripped),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Removes the `\\?\` prefix from every path inside an argument like a class path or `-Dkey=path`, see `simplified_path`
+pub fn simplified_argument(argument: &str) -> String {
+    argument.replace(r"\\?\UNC\", r"\\").replace(r"\\?\", "")
+}
+
+/// Longest path Windows accepts without the `\\?\` prefix, including the terminating null
+pub const MAX_PATH: usize = 260;
+
+/// Adds the `\\?\` prefix to paths that are too long for Windows without it, the counterpart of `simplified_path`.
+/// Other systems and short paths are returned as they are.
+pub fn long_path(path: &Path) -> PathBuf {
+    // folders have to leave room for an 8.3 file name
+    if path.as_os_str().len() < MAX_PATH - 12 {
+        return path.to_path_buf();
+    }
+    prefixed_path(path)
+}
+
+/// Adds the `\\?\` prefix even to short paths, for folders whose content may exceed the limit.
+/// Walking such a folder from the prefixed path yields prefixed paths for all of its files.
+pub fn prefixed_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+    // prefixed paths are passed to the file system as they are, so they have to be absolute and use backslashes only
+    let absolute = path_absolutize::Absolutize::absolutize(path).map(|path| path.to_path_buf()).unwrap_or_else(|_| path.to_path_buf());
+    let path_str = absolute.to_string_lossy().replace('/', "\\");
+    if path_str.starts_with(r"\\?\") {
+        absolute
+    } else if let Some(share) = path_str.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{}", share))
+    } else {
+        PathBuf::from(format!(r"\\?\{}", path_str))
+    }
+}
+
+/// Value of a registry entry in the current user's hive as printed by `reg query`
+#[cfg(target_os = "windows")]
+pub async fn user_registry_value(key: &str, name: &str) -> Option<String> {
+    let mut command = tokio::process::Command::new("reg");
+    command.args(["query", &format!("HKCU\\{}", key), "/v", name]);
+    hide_console_window(&mut command);
+    let output = command.output().await.ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    stdout.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with(name))
+        .and_then(|line| line.split_whitespace().last())
+        .map(str::to_string)
+}
 
 /// Get the total memory of the system in bytes
 pub fn percentage_of_total_memory(memory_percentage: i32) -> i64 {
@@ -11,6 +105,25 @@ pub fn percentage_of_total_memory(memory_percentage: i32) -> i64 {
     ((sys.total_memory() / 1000000) as f64 * (memory_percentage as f64 / 100.0)) as i64
 }
 
+/// Get the total memory of the system in megabytes
+pub fn get_system_ram_mb() -> u64 {
+    let sys = System::new_with_specifics(RefreshKind::new().with_memory());
+
+    sys.total_memory() / 1000000
+}
+
+/// Free space in bytes on the disk the path is stored on
+pub fn available_disk_space(path: &Path) -> Option<u64> {
+    let sys = System::new_with_specifics(RefreshKind::new().with_disks_list());
+    let path = simplified_path(&path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+
+    // the most specific mount point contains the path
+    sys.disks().iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
 pub const OS: OperatingSystem = if cfg!(target_os = "windows") {
     OperatingSystem::WINDOWS
 } else if cfg!(target_os = "macos") {
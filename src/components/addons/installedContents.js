@@ -0,0 +1,12 @@
+import { invoke } from "@tauri-apps/api";
+
+// asks the backend which contents of a page of search results are installed in the profile, with one call for the whole page.
+// Returns the upper case slugs of the installed ones.
+export async function checkInstalled(profileId, contentType, contents, world = null) {
+  const queries = contents.filter(content => content?.slug).map(content => ({ contentType, slug: content.slug, world }));
+  if (queries.length === 0) {
+    return new Set();
+  }
+  const installed = await invoke("check_contents_installed", { profileId, queries });
+  return new Set(queries.filter((_, index) => installed[index]).map(query => query.slug.toUpperCase()));
+}
@@ -0,0 +1,20 @@
+import { derived, writable } from "svelte/store";
+import { invoke } from "@tauri-apps/api";
+
+export const locale = writable("en-US");
+export const translations = writable({});
+
+// falls back to the key itself while the translations are loading or if a key is missing,
+// placeholders like {name} are replaced with the values of the same name
+export const t = derived(translations, $translations => (key, values = {}) =>
+  ($translations[key] ?? key).replace(/\{(\w+)\}/g, (placeholder, name) => values[name] ?? placeholder));
+
+export async function loadTranslations() {
+  try {
+    const selectedLocale = await invoke("get_locale");
+    translations.set(await invoke("get_translations", { locale: selectedLocale }));
+    locale.set(selectedLocale);
+  } catch (e) {
+    console.error("Failed to load translations", e);
+  }
+}
@@ -0,0 +1,43 @@
+import { get, writable } from "svelte/store";
+import { invoke } from "@tauri-apps/api";
+import { listen } from "@tauri-apps/api/event";
+
+// subsystems the backend loads in the background on startup, see startup::warm_up
+export const SUBSYSTEMS = ["dataFolder", "profiles", "contentBlacklist", "translations", "jumpList"];
+
+// names of the subsystems that have finished loading
+export const readySubsystems = writable([]);
+
+function markReady(subsystem) {
+  readySubsystems.update(ready => ready.includes(subsystem) ? ready : [...ready, subsystem]);
+}
+
+// events sent before the listener was registered are caught up on through get_ready_subsystems
+export async function listenStateReady() {
+  const unlisten = await listen("state-ready", event => {
+    console.debug(`${event.payload.subsystem} is ready after ${event.payload.durationMs} ms`);
+    markReady(event.payload.subsystem);
+  });
+  try {
+    (await invoke("get_ready_subsystems")).forEach(ready => markReady(ready.subsystem));
+  } catch (e) {
+    console.error("Failed to load the ready subsystems", e);
+  }
+  return unlisten;
+}
+
+export function whenReady(subsystem) {
+  return new Promise(resolve => {
+    if (get(readySubsystems).includes(subsystem)) {
+      resolve();
+      return;
+    }
+    const unsubscribe = readySubsystems.subscribe(ready => {
+      if (ready.includes(subsystem)) {
+        // subscribe calls back synchronously, the unsubscribe function may not exist yet
+        setTimeout(() => unsubscribe());
+        resolve();
+      }
+    });
+  });
+}